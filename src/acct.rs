@@ -0,0 +1,204 @@
+
+//! Provides process accounting.
+//!
+//! While accounting is enabled, the kernel appends a record to the
+//! accounting file each time a process terminates. Both of the record
+//! formats that Linux can be configured to write are decoded here:
+//! version 2 (`struct acct`) and version 3 (`struct acct_v3`), each 64
+//! bytes long. Consult the man page (command `man 5 acct`) for
+//! details.
+
+use std::ffi::CString;
+use std::ptr;
+use process::WaitStatus;
+use fd::SysResult;
+use stat::Timestamp;
+
+/// A decoded process accounting record.
+///
+/// The times are in units of `AHZ` ticks per second.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AcctRecord {
+    /// The format of the record: 2 or 3.
+    pub version: u8,
+    /// The flags describing how the process ran.
+    pub flags: AcctFlags,
+    /// The command name: the first 15 or 16 bytes of the filename of
+    /// the last program executed, with any bytes that are not valid
+    /// UTF-8 replaced by U+FFFD.
+    pub command: String,
+    /// How the process terminated.
+    pub status: WaitStatus,
+    /// The real user ID.
+    pub uid: u32,
+    /// The real group ID.
+    pub gid: u32,
+    /// The process ID; only recorded in version 3.
+    pub pid: Option<u32>,
+    /// The parent process ID; only recorded in version 3.
+    pub ppid: Option<u32>,
+    /// The controlling terminal, as a device number; 0 for none.
+    pub tty: u16,
+    /// When the process started, in whole seconds.
+    pub start_time: Timestamp,
+    /// The real time elapsed from start to termination.
+    pub elapsed_time: u64,
+    /// The CPU time spent in user mode.
+    pub user_time: u64,
+    /// The CPU time spent in kernel mode.
+    pub system_time: u64,
+    /// The average memory usage, in kilobytes.
+    pub memory: u64,
+    /// The number of minor page faults.
+    pub minor_faults: u64,
+    /// The number of major page faults.
+    pub major_faults: u64,
+}
+
+/// The `acct()` system call.
+///
+/// Enables process accounting, appending records to the existing file
+/// `path`, or disables it if `path` is `None`. Requires privilege
+/// (`CAP_SYS_PACCT`).
+///
+/// Consult the man page (command `man 2 acct`) for further details.
+pub fn acct(path: Option<&str>) -> SysResult<()> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = path.map(|path| CString::new(path).unwrap());
+    let path_ptr = c_path.as_ref().map_or(ptr::null(), |p| p.as_ptr());
+    let status = unsafe { ::libc::acct(path_ptr) };
+    errno_check!(status, ())
+}
+
+/// Decodes a record read from an accounting file, or returns `None` if
+/// `bytes` isn't `ACCT_RECORD_SIZE` bytes long or the record is in an
+/// unknown format.
+pub fn parse_record(bytes: &[u8]) -> Option<AcctRecord> {
+    if bytes.len() != ACCT_RECORD_SIZE {
+        return None;
+    }
+
+    // Only little-endian records, as written on x86-64, are recognized;
+    // the top bit of the version byte is set in a big-endian one
+    match bytes[1] {
+        2 => Some(parse_v2(bytes)),
+        3 => Some(parse_v3(bytes)),
+        _ => None,
+    }
+}
+
+/// Converts a `comp_t` value, as used for the times and counts in
+/// accounting records, to an integer.
+///
+/// A `comp_t` is a floating-point number with a 3-bit base-8 exponent
+/// and a 13-bit mantissa.
+pub fn comp_t_to_u64(value: u16) -> u64 {
+    let exponent = (value >> 13) & 0x7;
+    let mantissa = value & 0x1fff;
+    (mantissa as u64) << (exponent * 3)
+}
+
+/// Helper function; decodes a version 2 record.
+fn parse_v2(bytes: &[u8]) -> AcctRecord {
+    // The IDs are split into a 16-bit field and a 32-bit field for
+    // compatibility with older readers; the latter is complete
+    AcctRecord {
+        version: 2,
+        flags: AcctFlags::from_bits_truncate(bytes[0]),
+        command: parse_command(&bytes[36..53]),
+        status: WaitStatus::from_raw(read_u32(bytes, 32) as i32),
+        uid: read_u32(bytes, 56),
+        gid: read_u32(bytes, 60),
+        pid: None,
+        ppid: None,
+        tty: read_u16(bytes, 6),
+        start_time: Timestamp { secs: read_u32(bytes, 8) as i64, nanos: 0 },
+        elapsed_time: read_comp_t(bytes, 16),
+        user_time: read_comp_t(bytes, 12),
+        system_time: read_comp_t(bytes, 14),
+        memory: read_comp_t(bytes, 18),
+        minor_faults: read_comp_t(bytes, 24),
+        major_faults: read_comp_t(bytes, 26),
+    }
+}
+
+/// Helper function; decodes a version 3 record.
+fn parse_v3(bytes: &[u8]) -> AcctRecord {
+    // The elapsed time is an IEEE single-precision value, in ticks
+    let elapsed = f32_from_bits(read_u32(bytes, 28));
+    AcctRecord {
+        version: 3,
+        flags: AcctFlags::from_bits_truncate(bytes[0]),
+        command: parse_command(&bytes[48..64]),
+        status: WaitStatus::from_raw(read_u32(bytes, 4) as i32),
+        uid: read_u32(bytes, 8),
+        gid: read_u32(bytes, 12),
+        pid: Some(read_u32(bytes, 16)),
+        ppid: Some(read_u32(bytes, 20)),
+        tty: read_u16(bytes, 2),
+        start_time: Timestamp { secs: read_u32(bytes, 24) as i64, nanos: 0 },
+        elapsed_time: elapsed as u64,
+        user_time: read_comp_t(bytes, 32),
+        system_time: read_comp_t(bytes, 34),
+        memory: read_comp_t(bytes, 36),
+        minor_faults: read_comp_t(bytes, 42),
+        major_faults: read_comp_t(bytes, 44),
+    }
+}
+
+/// Helper function; converts the null-padded command name.
+fn parse_command(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+/// Helper function; reads a little-endian `u16` at `offset`.
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    bytes[offset] as u16 | (bytes[offset + 1] as u16) << 8
+}
+
+/// Helper function; reads a little-endian `u32` at `offset`.
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let high = read_u16(bytes, offset + 2) as u32;
+    read_u16(bytes, offset) as u32 | high << 16
+}
+
+/// Helper function; reads and expands a `comp_t` at `offset`.
+fn read_comp_t(bytes: &[u8], offset: usize) -> u64 {
+    comp_t_to_u64(read_u16(bytes, offset))
+}
+
+/// Helper function; reinterprets the bits of an IEEE single-precision
+/// value.
+fn f32_from_bits(bits: u32) -> f32 {
+    let mut value = 0f32;
+    unsafe {
+        ptr::copy_nonoverlapping(
+            &bits as *const u32 as *const f32, &mut value, 1
+        )
+    };
+    value
+}
+
+bitflags! {
+    #[doc = "Flags in a process accounting record."]
+    #[doc = ""]
+    #[doc = "Consult `man 5 acct` for details on each flag."]
+    flags AcctFlags: u8 {
+        #[doc = "forked, but didn't execute a program"]
+        const AFORK = 0x01,
+        #[doc = "used superuser privileges"]
+        const ASU   = 0x02,
+        #[doc = "dumped core"]
+        const ACORE = 0x08,
+        #[doc = "killed by a signal"]
+        const AXSIG = 0x10,
+    }
+}
+
+/// The size of each record in an accounting file.
+pub const ACCT_RECORD_SIZE: usize = 64;
+
+/// The number of ticks per second used for the times in accounting
+/// records.
+pub const AHZ: u64 = 100;
@@ -0,0 +1,383 @@
+
+//! Provides POSIX asynchronous I/O.
+//!
+//! An operation is described by an `AioControlBlock`, which owns the
+//! buffer being read into or written from. Once submitted, the
+//! operation proceeds while the program does other work; its progress
+//! can be checked with `AioControlBlock::status()`, waited for with
+//! `aio_suspend()`, or reported by a `Notification`.
+
+use std::mem;
+use std::ptr;
+use std::time::Duration;
+use libc::{c_int, c_void, size_t, timespec, time_t, c_long};
+use err::{Errno, EINPROGRESS, EINVAL};
+use fd::{FileDescriptor, SysResult};
+use sigevent::{Notification, RawSigevent};
+
+/// A control block (`struct aiocb`) for an asynchronous read or
+/// write, together with its buffer.
+///
+/// The control block records the raw file descriptor, so the caller
+/// must keep the file open until the operation completes. If the
+/// control block is dropped while its operation is in progress, the
+/// operation is canceled and waited for, since the buffer can't be
+/// freed while it is in use.
+pub struct AioControlBlock {
+    /// The C control block; boxed since its address must not change
+    /// while the operation is in progress.
+    raw: Box<RawAiocb>,
+
+    /// The data read or to be written.
+    buffer: Vec<u8>,
+
+    /// Whether an operation has been submitted but its result not yet
+    /// retrieved with `aio_return()`.
+    in_flight: bool,
+}
+
+/// The state of an asynchronous operation, as reported by
+/// `aio_error()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AioStatus {
+    /// The operation has not completed yet.
+    InProgress,
+    /// The operation completed successfully; use
+    /// `AioControlBlock::aio_return()` to find out how many bytes were
+    /// transferred.
+    Completed,
+    /// The operation failed with the given error, which is `ECANCELED`
+    /// if it was canceled.
+    Failed(Errno),
+}
+
+/// How `lio_listio()` waits for the operations it submits.
+pub enum LioMode {
+    /// `LIO_WAIT`: return once all the operations have completed.
+    Wait,
+    /// `LIO_NOWAIT`: return at once; the given notification is
+    /// delivered once all the operations have completed.
+    NoWait(Notification),
+}
+
+impl AioControlBlock {
+
+    /// Creates a control block to read `len` bytes at `offset` in the
+    /// file `fd`.
+    pub fn new_read(
+        fd: &FileDescriptor, offset: u64, len: usize
+    ) -> AioControlBlock {
+        AioControlBlock::new(fd, offset, vec![0; len], LIO_READ)
+    }
+
+    /// Creates a control block to write `data` at `offset` in the
+    /// file `fd`. If the file was opened with `O_APPEND`, the data is
+    /// written at the end of the file instead.
+    pub fn new_write(
+        fd: &FileDescriptor, offset: u64, data: Vec<u8>
+    ) -> AioControlBlock {
+        AioControlBlock::new(fd, offset, data, LIO_WRITE)
+    }
+
+    /// The `aio_error()` library function.
+    ///
+    /// Returns the state of the submitted operation. Fails with
+    /// `EINVAL` if no operation is outstanding, i.e. none was
+    /// submitted, or its result was already retrieved.
+    ///
+    /// Consult the man page (command `man 3 aio_error`) for further
+    /// details.
+    pub fn status(&self) -> SysResult<AioStatus> {
+        if !self.in_flight {
+            return Err(EINVAL);
+        }
+
+        let error = unsafe { aio_error(&*self.raw) };
+        errno_check!(error, match error {
+            0 => AioStatus::Completed,
+            _ if error == EINPROGRESS.value() => AioStatus::InProgress,
+            _ => AioStatus::Failed(Errno::new(error)),
+        })
+    }
+
+    /// The `aio_return()` library function.
+    ///
+    /// Returns the number of bytes transferred by the completed
+    /// operation, or the error with which it failed. Can only be
+    /// called once per operation, after `status()` shows that it is no
+    /// longer in progress; fails with `EINVAL` otherwise.
+    ///
+    /// Consult the man page (command `man 3 aio_return`) for further
+    /// details.
+    pub fn aio_return(&mut self) -> SysResult<usize> {
+        let status = try!(self.status());
+        if let AioStatus::InProgress = status {
+            return Err(EINVAL);
+        }
+
+        // Called even for a failed operation, so that the control
+        // block is released; the error itself is known only from
+        // `aio_error()`, since errno isn't set
+        let count = unsafe { aio_return(&mut *self.raw) };
+        self.in_flight = false;
+        match status {
+            AioStatus::Failed(errno) => Err(errno),
+            _ => Ok(count as usize),
+        }
+    }
+
+    /// The buffer of the operation, or `None` while the operation is
+    /// still in progress.
+    ///
+    /// For a read, only the number of bytes returned by
+    /// `aio_return()` are meaningful.
+    pub fn buffer(&self) -> Option<&[u8]> {
+        match self.status() {
+            Ok(AioStatus::InProgress) => None,
+            _ => Some(&self.buffer),
+        }
+    }
+
+    /// Consumes the control block, returning its buffer; any
+    /// operation in progress is canceled first.
+    pub fn into_buffer(mut self) -> Vec<u8> {
+        self.finish();
+        mem::replace(&mut self.buffer, Vec::new())
+    }
+
+    /// Helper function; creates a control block for the operation
+    /// `opcode`.
+    fn new(
+        fd: &FileDescriptor, offset: u64, mut buffer: Vec<u8>, opcode: c_int
+    ) -> AioControlBlock {
+        let mut raw: Box<RawAiocb> = Box::new(unsafe { mem::zeroed() });
+        raw.fildes = fd.as_raw();
+        raw.lio_opcode = opcode;
+        raw.buf = buffer.as_mut_ptr() as *mut c_void;
+        raw.nbytes = buffer.len() as size_t;
+        raw.sigevent = Notification::Nothing.into_raw();
+        raw.offset = offset as i64;
+        AioControlBlock { raw: raw, buffer: buffer, in_flight: false }
+    }
+
+    /// Helper method; prepares to submit the operation, failing with
+    /// `EINVAL` if one is already outstanding.
+    fn prepare(&mut self, notification: Notification) -> SysResult<()> {
+        if self.in_flight {
+            return Err(EINVAL);
+        }
+        self.raw.sigevent = notification.into_raw();
+        Ok(())
+    }
+
+    /// Helper method; frees the notification of an operation that
+    /// couldn't be submitted.
+    fn discard_notification(&mut self) {
+        let raw = mem::replace(
+            &mut self.raw.sigevent, Notification::Nothing.into_raw()
+        );
+        unsafe { raw.free_closure() };
+    }
+
+    /// Helper method; cancels any operation in progress, and waits
+    /// for it to finish.
+    fn finish(&mut self) {
+        while let Ok(AioStatus::InProgress) = self.status() {
+            unsafe {
+                aio_cancel(self.raw.fildes, &mut *self.raw);
+                let list = [&*self.raw as *const RawAiocb];
+                aio_suspend_c(list.as_ptr(), 1, ptr::null());
+            }
+        }
+        self.in_flight = false;
+    }
+
+}
+
+impl Drop for AioControlBlock {
+
+    fn drop(&mut self) {
+        self.finish();
+    }
+
+}
+
+/// The `aio_read()` library function.
+///
+/// Starts the read described by `cb`, delivering `notification` when
+/// it completes. Fails with `EINVAL` if `cb` has an operation
+/// outstanding.
+///
+/// Consult the man page (command `man 3 aio_read`) for further
+/// details.
+pub fn aio_read(
+    cb: &mut AioControlBlock, notification: Notification
+) -> SysResult<()> {
+    try!(cb.prepare(notification));
+    let status = unsafe { aio_read_c(&mut *cb.raw) };
+    let result = errno_check!(status, { cb.in_flight = true; });
+    if result.is_err() {
+        cb.discard_notification();
+    }
+    result
+}
+
+/// The `aio_write()` library function.
+///
+/// Starts the write described by `cb`, delivering `notification`
+/// when it completes. Fails with `EINVAL` if `cb` has an operation
+/// outstanding.
+///
+/// Consult the man page (command `man 3 aio_write`) for further
+/// details.
+pub fn aio_write(
+    cb: &mut AioControlBlock, notification: Notification
+) -> SysResult<()> {
+    try!(cb.prepare(notification));
+    let status = unsafe { aio_write_c(&mut *cb.raw) };
+    let result = errno_check!(status, { cb.in_flight = true; });
+    if result.is_err() {
+        cb.discard_notification();
+    }
+    result
+}
+
+/// The `aio_suspend()` library function.
+///
+/// Blocks until at least one of the operations of `cbs` has
+/// completed, or `timeout` expires (failing with `EAGAIN`); `None`
+/// means wait indefinitely. Control blocks without an outstanding
+/// operation are ignored.
+///
+/// Consult the man page (command `man 3 aio_suspend`) for further
+/// details.
+pub fn aio_suspend(
+    cbs: &[&AioControlBlock], timeout: Option<Duration>
+) -> SysResult<()> {
+    let list: Vec<*const RawAiocb> = cbs.iter().map(|cb| {
+        if cb.in_flight { &*cb.raw as *const _ } else { ptr::null() }
+    }).collect();
+    let raw_timeout = timeout.map(|duration| timespec {
+        tv_sec: duration.as_secs() as time_t,
+        tv_nsec: duration.subsec_nanos() as c_long,
+    });
+    let timeout_ptr = match raw_timeout {
+        Some(ref value) => value as *const timespec,
+        None => ptr::null(),
+    };
+
+    let status = unsafe {
+        aio_suspend_c(list.as_ptr(), list.len() as c_int, timeout_ptr)
+    };
+    errno_check!(status, ())
+}
+
+/// The `lio_listio()` library function.
+///
+/// Submits all of the operations of `cbs` at once, waiting for them
+/// or not according to `mode`. No notifications are delivered for the
+/// individual operations. Fails with `EINVAL` if any of `cbs` has an
+/// operation outstanding.
+///
+/// If this function fails with `EAGAIN`, `EINTR` or `EIO`, some of
+/// the operations may have been started; check them with
+/// `AioControlBlock::status()`.
+///
+/// Consult the man page (command `man 3 lio_listio`) for further
+/// details.
+pub fn lio_listio(
+    mode: LioMode, cbs: &mut [&mut AioControlBlock]
+) -> SysResult<()> {
+    for cb in cbs.iter_mut() {
+        try!(cb.prepare(Notification::Nothing));
+    }
+
+    let (raw_mode, notification) = match mode {
+        LioMode::Wait => (LIO_WAIT, None),
+        LioMode::NoWait(notification) => {
+            (LIO_NOWAIT, Some(notification.into_raw()))
+        },
+    };
+    let sevp = match notification {
+        Some(ref raw) => raw as *const RawSigevent,
+        None => ptr::null(),
+    };
+
+    let list: Vec<*mut RawAiocb> = cbs.iter_mut().map(|cb| {
+        &mut *cb.raw as *mut _
+    }).collect();
+    let status = unsafe {
+        lio_listio_c(raw_mode, list.as_ptr(), list.len() as c_int, sevp)
+    };
+
+    // Statuses are meaningful even if some operations couldn't be
+    // started, so that outstanding ones are waited for when dropped
+    let result = errno_check!(status, ());
+    match result {
+        Err(errno) if errno == EINVAL => {
+            // Nothing was submitted, so the notification won't be
+            // delivered; after any other error, it still is, once the
+            // operations that were started complete
+            if let Some(raw) = notification {
+                unsafe { raw.free_closure() };
+            }
+        },
+        _ => {
+            for cb in cbs.iter_mut() {
+                cb.in_flight = true;
+            }
+        },
+    }
+    result
+}
+
+/// The C `struct aiocb`, laid out as on an x86-64 Linux system with
+/// glibc.
+#[repr(C)]
+struct RawAiocb {
+    fildes: c_int,
+    lio_opcode: c_int,
+    reqprio: c_int,
+    buf: *mut c_void,
+    nbytes: size_t,
+    sigevent: RawSigevent,
+    next_prio: *mut c_void,
+    abs_prio: c_int,
+    policy: c_int,
+    error_code: c_int,
+    return_value: isize,
+    offset: i64,
+    reserved: [u8; 32],
+}
+
+const LIO_READ: c_int = 0;
+const LIO_WRITE: c_int = 1;
+const LIO_WAIT: c_int = 0;
+const LIO_NOWAIT: c_int = 1;
+
+#[link(name = "rt")]
+extern {
+    #[link_name = "aio_read"]
+    fn aio_read_c(aiocbp: *mut RawAiocb) -> c_int;
+
+    #[link_name = "aio_write"]
+    fn aio_write_c(aiocbp: *mut RawAiocb) -> c_int;
+
+    fn aio_error(aiocbp: *const RawAiocb) -> c_int;
+
+    fn aio_return(aiocbp: *mut RawAiocb) -> isize;
+
+    #[link_name = "aio_suspend"]
+    fn aio_suspend_c(
+        list: *const *const RawAiocb, nitems: c_int,
+        timeout: *const timespec
+    ) -> c_int;
+
+    fn aio_cancel(fd: c_int, aiocbp: *mut RawAiocb) -> c_int;
+
+    #[link_name = "lio_listio"]
+    fn lio_listio_c(
+        mode: c_int, list: *const *mut RawAiocb, nitems: c_int,
+        sevp: *const RawSigevent
+    ) -> c_int;
+}
@@ -0,0 +1,29 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::acct::*;
+use tlpi_rust::err::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 2 || (argv.len() == 2 && argv[1] == "--help") {
+        return usage_err!("{} [file]", argv[0]);
+    }
+
+    // With no file, turn accounting off
+    let path = argv.get(1).map(|path| &path[..]);
+    try!(acct(path).or_else(|errno| err_exit!(errno, "acct")));
+
+    println!("Process accounting {}",
+             if path.is_some() { "enabled" } else { "disabled" });
+    Ok(())
+}
@@ -0,0 +1,79 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::acct::*;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::process::*;
+use tlpi_rust::users::*;
+
+const TIME_FORMAT: &'static str = "%Y-%m-%d %T";
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} file", argv[0]);
+    }
+
+    let contents = try!(read_whole_file(&argv[1]).or_else(|errno| {
+        err_exit!(errno, "reading {}", argv[1])
+    }));
+
+    println!("command  flags  term.  user     \
+              start time            CPU  elapsed");
+    println!("               status                                \
+              time     time");
+
+    for bytes in contents.chunks(ACCT_RECORD_SIZE) {
+        let record = match parse_record(bytes) {
+            Some(record) => record,
+            None => return fatal!("Unrecognized accounting record"),
+        };
+        let user = try!(user_name_from_id(record.uid).or_else(|errno| {
+            err_exit!(errno, "getpwuid")
+        }));
+
+        println!("{:<8} {:<5} {:>6}  {:<8} {} {:>5.2} {:>8.2}",
+                 record.command, flags_string(record.flags),
+                 status_string(record.status),
+                 user.unwrap_or(record.uid.to_string()),
+                 record.start_time.format_local(TIME_FORMAT),
+                 ticks_to_secs(record.user_time + record.system_time),
+                 ticks_to_secs(record.elapsed_time));
+    }
+    Ok(())
+}
+
+/// Shows each flag as a letter, or `-` if it isn't set.
+fn flags_string(flags: AcctFlags) -> String {
+    [(AFORK, 'F'), (ASU, 'S'), (AXSIG, 'X'), (ACORE, 'C')].iter()
+        .map(|&(flag, c)| if flags.contains(flag) { c } else { '-' })
+        .collect()
+}
+
+/// Shows an exit status in hexadecimal, as the raw value that
+/// `waitpid()` would report.
+fn status_string(status: WaitStatus) -> String {
+    let raw = match status {
+        WaitStatus::Exited(code) => code << 8,
+        WaitStatus::Signaled(sig, core_dumped) => {
+            sig.value() | if core_dumped { 0x80 } else { 0 }
+        },
+        WaitStatus::Stopped(sig) => sig.value() << 8 | 0x7f,
+        WaitStatus::Continued => 0xffff,
+    };
+    format!("{:#x}", raw)
+}
+
+fn ticks_to_secs(ticks: u64) -> f64 {
+    ticks as f64 / AHZ as f64
+}
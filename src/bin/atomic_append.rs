@@ -0,0 +1,68 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::process::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 3 || argv.len() > 4 || argv[1] == "--help" {
+        return usage_err!("{} file num-bytes [x]", argv[0]);
+    }
+    let path = &argv[1];
+    let num_bytes = match u64::from_str(&argv[2]) {
+        Ok(count) => count,
+        Err(_) => return cmd_line_err!("Invalid num-bytes: {}", argv[2]),
+    };
+    // With "x", each write is preceded by a separate seek to the end of
+    // the file, so other processes can append in between and have their
+    // data overwritten
+    let use_seek = match argv.get(3).map(|arg| &arg[..]) {
+        None => false,
+        Some("x") => true,
+        Some(arg) => return cmd_line_err!("Invalid argument: {}", arg),
+    };
+
+    let mut flags = O_WRONLY | O_CREAT;
+    if !use_seek {
+        flags = flags | O_APPEND;
+    }
+    let perms = S_IRUSR | S_IWUSR; // rw-------
+    let fd = try!(
+        FileDescriptor::open(path.clone(), flags, perms)
+            .or_else(|errno| err_exit!(errno, "open {}", path))
+    );
+
+    for _ in 0..num_bytes {
+        if use_seek {
+            try!(fd.lseek(0, OffsetBase::SeekEnd).or_else(|errno| {
+                err_exit!(errno, "lseek")
+            }));
+        }
+        match fd.write(b"x") {
+            Ok(1) => {},
+            Ok(_) => return fatal!("partial write"),
+            Err(errno) => return err_exit!(errno, "write"),
+        }
+    }
+
+    let size = try!(fd.lseek(0, OffsetBase::SeekEnd).or_else(|errno| {
+        err_exit!(errno, "lseek")
+    }));
+    println!("[PID {}] wrote {} bytes {}; file size is now {}",
+             getpid(), num_bytes,
+             if use_seek { "with lseek()" } else { "with O_APPEND" }, size);
+
+    fd.close().or_else(|errno| err_exit!(errno, "close"))
+}
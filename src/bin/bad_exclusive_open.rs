@@ -0,0 +1,55 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::thread;
+use std::time::Duration;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::process::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+/// Incorrect code to exclusively open a file; demonstrates the race
+/// that `O_EXCL` exists to prevent.
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv.len() > 3 || argv[1] == "--help" {
+        return usage_err!("{} file [sleep]", argv[0]);
+    }
+    let path = &argv[1];
+
+    match FileDescriptor::open(path.clone(), O_RDONLY, FilePerms::empty()) {
+        Ok(fd) => {
+            println!("[PID {}] File \"{}\" already exists", getpid(), path);
+            return fd.close().or_else(|errno| err_exit!(errno, "close"));
+        },
+        // Failed for an unexpected reason
+        Err(errno) if errno != ENOENT => return err_exit!(errno, "open"),
+        Err(_) => {},
+    }
+
+    println!("[PID {}] File \"{}\" doesn't exist yet", getpid(), path);
+    // Widen the window between the check and the create, so a second
+    // run can slip in
+    if argv.len() > 2 {
+        thread::sleep(Duration::from_secs(5));
+        println!("[PID {}] Done sleeping", getpid());
+    }
+
+    let perms = S_IRUSR | S_IWUSR; // rw-------
+    let fd = try!(
+        FileDescriptor::open(path.clone(), O_WRONLY | O_CREAT, perms)
+            .or_else(|errno| err_exit!(errno, "open"))
+    );
+    // MAY NOT BE TRUE!
+    println!("[PID {}] Created file \"{}\" exclusively", getpid(), path);
+
+    fd.close().or_else(|errno| err_exit!(errno, "close"))
+}
@@ -0,0 +1,40 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use tlpi_rust::err::*;
+use tlpi_rust::setjmp::*;
+
+/// Filled in by `x()`, whose frame is gone by the time of the jump.
+static mut ENV: JmpBuf = JMP_BUF_INIT;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+// Undefined behavior on purpose: the jump returns into the frame of
+// `x()`, which has already returned and whose stack space has been
+// reused by `y()`. The program may loop, print garbage, or crash.
+fn main_with_result() -> TlpiResult<()> {
+    x();
+    y();
+    println!("Back in main_with_result()");
+    Ok(())
+}
+
+#[inline(never)]
+fn x() {
+    let count = unsafe { setjmp(&mut ENV) };
+    println!("x(): setjmp() returned {}", count);
+}
+
+#[inline(never)]
+fn y() {
+    // Overwrite the stack space that `x()` used
+    let scratch = [0xffu8; 256];
+    println!("y(): scribbled over {} bytes; jumping into x()",
+             scratch.len());
+    unsafe { longjmp(&mut ENV, 1) };
+}
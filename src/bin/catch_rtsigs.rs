@@ -0,0 +1,118 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+extern crate libc;
+
+use std::env;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, ATOMIC_BOOL_INIT, Ordering};
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT};
+use std::thread;
+use std::time::Duration;
+use libc::{c_int, c_void};
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+use tlpi_rust::signal::*;
+
+/// How long the handler sleeps, in seconds, so that further signals
+/// queue up behind it.
+static HANDLER_SLEEP_TIME: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// How many signals have been caught.
+static SIG_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Set when `SIGINT` or `SIGTERM` is caught, to end the program.
+static ALL_DONE: AtomicBool = ATOMIC_BOOL_INIT;
+
+extern "C" fn handler(sig: c_int, info: &SigInfo, _ucontext: *mut c_void) {
+    // SIGINT or SIGTERM can be used to terminate the program
+    if sig == SIGINT.value() || sig == SIGTERM.value() {
+        ALL_DONE.store(true, Ordering::SeqCst);
+        return;
+    }
+
+    SIG_COUNT.fetch_add(1, Ordering::SeqCst);
+
+    // UNSAFE: `println!()` isn't async-signal-safe; the main thread
+    // only waits in `pause()` while handlers may run, though
+    let code = match info.code() {
+        SI_USER => "SI_USER",
+        SI_QUEUE => "SI_QUEUE",
+        _ => "other",
+    };
+    println!("caught signal {}", sig);
+    println!("    si_signo={}, si_code={} ({}), si_value={}",
+             info.signo(), info.code(), code, info.value());
+    println!("    si_pid={}, si_uid={}", info.pid(), info.uid());
+
+    let secs = HANDLER_SLEEP_TIME.load(Ordering::SeqCst) as u64;
+    thread::sleep(Duration::from_secs(secs));
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 1 && argv[1] == "--help" || argv.len() > 3 {
+        return usage_err!("{} [block-time [handler-sleep-time]]", argv[0]);
+    }
+
+    let block_time = match argv.get(1) {
+        Some(arg) => Some(try!(parse_secs(arg, "block-time"))),
+        None => None,
+    };
+    let sleep_time = match argv.get(2) {
+        Some(arg) => try!(parse_secs(arg, "handler-sleep-time")),
+        None => 1,
+    };
+    HANDLER_SLEEP_TIME.store(sleep_time as usize, Ordering::SeqCst);
+
+    println!("{}: PID is {}", argv[0], getpid());
+
+    // Block every signal while the handler runs, so that each one is
+    // delivered in turn; leave SIGTSTP and SIGQUIT alone, so that the
+    // program can still be suspended and killed from the terminal
+    let mut action = SigAction::new(SigHandler::InfoHandler(handler));
+    action.mask = SigSet::full();
+    for value in 1..NSIG {
+        let sig = Signal::new(value);
+        if sig != SIGTSTP && sig != SIGQUIT {
+            // SIGKILL and SIGSTOP can't be caught, so ignore failures
+            let _ = unsafe { sigaction(sig, &action) };
+        }
+    }
+
+    // Optionally block signals and sleep, allowing signals to be sent
+    // to us before they are unblocked and handled
+    if let Some(secs) = block_time {
+        let prev_mask = try!(
+            pthread_sigmask(SigmaskHow::SetMask, &SigSet::full())
+                .or_else(|errno| err_exit!(errno, "pthread_sigmask"))
+        );
+        println!("{}: signals blocked - sleeping {} seconds",
+                 argv[0], secs);
+        thread::sleep(Duration::from_secs(secs));
+        println!("{}: sleep complete", argv[0]);
+        try!(pthread_sigmask(SigmaskHow::SetMask, &prev_mask)
+             .or_else(|errno| err_exit!(errno, "pthread_sigmask")));
+    }
+
+    while !ALL_DONE.load(Ordering::SeqCst) {
+        pause();
+    }
+
+    println!("Caught {} signals", SIG_COUNT.load(Ordering::SeqCst));
+    Ok(())
+}
+
+fn parse_secs(arg: &str, name: &str) -> TlpiResult<u64> {
+    match u64::from_str(arg) {
+        Ok(secs) => Ok(secs),
+        Err(_) => cmd_line_err!("Invalid {}: {}", name, arg),
+    }
+}
@@ -0,0 +1,97 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::walk::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+/// Does the equivalent of `chmod -R a+rX` on each tree given.
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv[1] == "--help" {
+        return usage_err!("{} path...", argv[0]);
+    }
+
+    // Report each failure, but carry on with the rest of the trees
+    let mut result = Ok(());
+    for root in argv.iter().skip(1) {
+        if let Err(err) = add_read_exec_tree(root) {
+            result = Err(err);
+        }
+    }
+    result
+}
+
+/// Adds read and execute permissions throughout the tree at `root`.
+///
+/// A directory that couldn't be read is only reported after the walk
+/// has skipped its contents; once it has been given read permission,
+/// its subtree is walked in turn.
+fn add_read_exec_tree(root: &str) -> TlpiResult<()> {
+    let mut result = Ok(());
+    let mut now_readable = Vec::new();
+
+    // Symbolic links can't be given permissions, and following them
+    // could lead out of the tree
+    let walk_result = walk_tree(root, WALK_PHYSICAL, |entry| {
+        match add_read_exec(entry) {
+            Ok(true) if entry.kind == WalkKind::Unreadable => {
+                now_readable.push(String::from(entry.path));
+            },
+            Ok(_) => {},
+            Err(err) => result = Err(err),
+        }
+        WalkControl::Continue
+    });
+    if let Err(errno) = walk_result {
+        result = err_exit!(errno, "walking {}", root);
+    }
+
+    // Its permissions are already right when it's revisited, so a
+    // directory that is still unreadable isn't walked again
+    for dir in &now_readable {
+        if let Err(err) = add_read_exec_tree(dir) {
+            result = Err(err);
+        }
+    }
+    result
+}
+
+/// Gives everyone read permission for `entry`, and execute (search)
+/// permission too if it is a directory or anyone may already execute
+/// it. Returns whether its permissions were changed.
+fn add_read_exec(entry: &WalkEntry) -> TlpiResult<bool> {
+    let sb = match (entry.kind, entry.stat) {
+        (WalkKind::File, Some(sb)) |
+        (WalkKind::Directory, Some(sb)) |
+        (WalkKind::Unreadable, Some(sb)) => sb,
+        (WalkKind::NoStat, _) => {
+            return fatal!("Can't get the attributes of {}", entry.path)
+        },
+        _ => return Ok(false),
+    };
+
+    let mut perms = sb.perms | S_IRUSR | S_IRGRP | S_IROTH;
+    let any_exec = S_IXUSR | S_IXGRP | S_IXOTH;
+    if entry.kind != WalkKind::File || sb.perms.intersects(any_exec) {
+        perms = perms | any_exec;
+    }
+
+    if perms == sb.perms {
+        return Ok(false);
+    }
+    match chmod(entry.path, perms) {
+        Ok(()) => Ok(true),
+        Err(errno) => err_exit!(errno, "chmod {}", entry.path),
+    }
+}
@@ -0,0 +1,47 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::process::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 2 || (argv.len() == 2 && argv[1] == "--help") {
+        return usage_err!("{} [x]\n    \
+                           with any argument, set close-on-exec on stdout",
+                          argv[0]);
+    }
+
+    // With the flag set, `ls` finds no standard output, so it can only
+    // report the failure to write its listing on standard error
+    if argv.len() > 1 {
+        try!(STDOUT.set_close_on_exec(true).or_else(|errno| {
+            err_exit!(errno, "fcntl(F_SETFD)")
+        }));
+    }
+    let enabled = try!(STDOUT.close_on_exec().or_else(|errno| {
+        err_exit!(errno, "fcntl(F_GETFD)")
+    }));
+    display_flag(enabled);
+
+    let errno = execvp("ls", &["ls", "-l", "/proc/self/fd"]);
+    err_exit!(errno, "execvp")
+}
+
+/// Reports the state of the flag on standard error, which stays open
+/// across the exec either way.
+fn display_flag(enabled: bool) {
+    let message = format!("Close-on-exec flag on stdout: {}\n",
+                          if enabled { "set" } else { "clear" });
+    let _ = STDERR.write(message.as_bytes());
+}
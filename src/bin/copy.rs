@@ -1,32 +1,55 @@
 
-#![feature(libc)]
+#![feature(libc, slice_splits)]
 
 #[macro_use]
 extern crate tlpi_rust;
 
+extern crate getopts;
+use getopts::Options;
+
 use std::env;
+use std::io::{self, Write};
+use std::str::FromStr;
+use tlpi_rust::err::*;
 use tlpi_rust::fd::*;
 
-const BUF_SIZE: usize = 1024;
+/// Size of the buffer used for each `read()` and `write()`, if not
+/// given.
+const DEFAULT_BUF_SIZE: usize = 1024;
+
+/// Number of bytes copied between progress reports.
+const PROGRESS_INTERVAL: u64 = 1 << 20;
+
+/// Settings from the command line.
+struct Config {
+    /// The file to copy.
+    src_path: String,
+    /// The file to create or overwrite.
+    dst_path: String,
+    /// The size of the buffer for each `read()` and `write()`.
+    buf_size: usize,
+    /// Whether buffers of zero bytes become holes in the output.
+    sparse: bool,
+    /// Whether to report the bytes copied as the copy proceeds.
+    progress: bool,
+}
 
 fn main() {
     exit_with_status!(main_with_io());
 }
 
-fn main_with_io() -> tlpi_rust::err::TlpiResult<()> {
-    let argv: Vec<_> = env::args().collect();
-
-    if argv.len() != 3 || argv[1] == "--help" {
-        return usage_err!("{} old-file new-file", argv[0]);
-    }
+fn main_with_io() -> TlpiResult<()> {
+    let config = try!(parse_args());
 
     // Open input and output files
 
-    let src_path = argv[1].clone();
+    let src_path = config.src_path.clone();
     let empty_perms = FilePerms::empty();
     let input_fd = match FileDescriptor::open(src_path, O_RDONLY, empty_perms) {
         Ok(fd) => fd,
-        Err(errno) => return err_exit!(errno, "opening file {}", argv[1])
+        Err(errno) => {
+            return err_exit!(errno, "opening file {}", config.src_path)
+        },
     };
 
     let open_flags = O_CREAT | O_WRONLY | O_TRUNC;
@@ -34,29 +57,67 @@ fn main_with_io() -> tlpi_rust::err::TlpiResult<()> {
     // rw-rw-rw
     let file_perms = S_IRUSR | S_IWUSR | S_IRGRP | S_IWGRP | S_IROTH | S_IWOTH;
 
-    let dst_path = argv[2].clone();
+    let dst_path = config.dst_path.clone();
     let output_fd = match FileDescriptor::open(dst_path, open_flags, file_perms) {
         Ok(fd) => fd,
-        Err(errno) => return err_exit!(errno, "opening file {}", argv[2])
+        Err(errno) => {
+            return err_exit!(errno, "opening file {}", config.dst_path)
+        },
     };
 
     // Transfer data until we encounter end of input or an error
 
-    let mut buf = [0u8; BUF_SIZE];
+    let mut buf = vec![0u8; config.buf_size];
+    let mut total: u64 = 0;
+    let mut pending_hole = false;
     loop {
         let bytes_read = match input_fd.read(&mut buf[..]) {
             Ok(0) => break,
             Ok(bytes) => bytes,
-            Err(errno) => return err_exit!(errno, "reading file {}", argv[1])
+            Err(errno) => {
+                return err_exit!(errno, "reading file {}", config.src_path)
+            },
         };
+        let data = &buf[..bytes_read];
+
+        // In sparse mode, skip over buffers of zeros, leaving holes
+        if config.sparse && data.iter().all(|&byte| byte == 0) {
+            let offset = bytes_read as i64;
+            match output_fd.lseek(offset, OffsetBase::SeekCur) {
+                Ok(_) => pending_hole = true,
+                Err(errno) => return err_exit!(errno, "lseek"),
+            };
+        } else {
+            match output_fd.write(data) {
+                Ok(bytes_written) if bytes_read == bytes_written => {},
+                Ok(_) => return fatal!("couldn't write whole buffer"),
+                Err(errno) => {
+                    return err_exit!(errno, "writing file {}", config.dst_path)
+                },
+            };
+            pending_hole = false;
+        }
+
+        // Report progress each time another interval has been copied
+        let intervals = total / PROGRESS_INTERVAL;
+        total += bytes_read as u64;
+        if config.progress && total / PROGRESS_INTERVAL > intervals {
+            let _ = write!(io::stderr(), "\r{} bytes copied", total);
+        }
+    }
 
-        match output_fd.write(&buf[..bytes_read as usize]) {
-            Ok(bytes_written) if bytes_read == bytes_written => {},
-            Ok(_) => return fatal!("couldn't write whole buffer"),
-            Err(errno) => return err_exit!(errno, "writing file {}", argv[2])
+    // A hole at the end is only created by setting the file size
+    if pending_hole {
+        match output_fd.ftruncate(total as i64) {
+            Err(errno) => return err_exit!(errno, "ftruncate"),
+            _ => {}
         };
     }
 
+    if config.progress {
+        let _ = writeln!(io::stderr(), "\r{} bytes copied", total);
+    }
+
     // Clean up
 
     match input_fd.close() {
@@ -71,3 +132,61 @@ fn main_with_io() -> tlpi_rust::err::TlpiResult<()> {
 
     Ok(())
 }
+
+fn parse_args() -> TlpiResult<Config> {
+    let argv: Vec<_> = env::args().collect();
+    let opts = build_options();
+
+    let argv_tail = match argv.split_first() {
+        Some((_, tail)) => tail,
+        _ => return cmd_line_err!("No program name provided!?"),
+    };
+
+    // Mutable so we can move out the paths
+    let mut matches = match opts.parse(argv_tail) {
+        Ok(m) => m,
+        Err(f) => {
+            let usage = opts.usage(&f.to_string());
+            return cmd_line_err!("{}", usage)
+        },
+    };
+
+    if matches.opt_present("help") {
+        let usage = format!("{} [options] <old_file> <new_file>", argv[0]);
+        return usage_err!("{}", opts.usage(&usage));
+    }
+
+    let buf_size = match matches.opt_str("buffer-size") {
+        Some(arg) => match usize::from_str(&arg) {
+            Ok(size) if size > 0 => size,
+            _ => return cmd_line_err!("Invalid buffer size: {}", arg),
+        },
+        None => DEFAULT_BUF_SIZE,
+    };
+
+    if matches.free.len() == 2 {
+        let dst_path = matches.free.swap_remove(1);
+        Ok(Config {
+            src_path: matches.free.swap_remove(0),
+            dst_path: dst_path,
+            buf_size: buf_size,
+            sparse: matches.opt_present("sparse"),
+            progress: matches.opt_present("progress"),
+        })
+    } else {
+        let usage = opts.usage("Exactly two file arguments are required");
+        return cmd_line_err!("{}", usage)
+    }
+}
+
+fn build_options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "display this usage message");
+    opts.optopt(
+        "b", "buffer-size", "bytes to transfer with each read and write",
+        "SIZE"
+    );
+    opts.optflag("s", "sparse", "leave holes for buffers of zero bytes");
+    opts.optflag("p", "progress", "report the bytes copied on stderr");
+    opts
+}
@@ -1,12 +1,18 @@
 
-#![feature(libc)]
+#![feature(libc, slice_splits)]
 
 #[macro_use]
 extern crate tlpi_rust;
 
+extern crate getopts;
+use getopts::Options;
+
+use std::cmp;
 use std::env;
 use tlpi_rust::fd::*;
 use tlpi_rust::err::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::stat::*;
 use Region::*;
 
 /// Capacity of buffers for reading and writing file data.
@@ -17,16 +23,13 @@ fn main() {
 }
 
 fn main_with_io() -> TlpiResult<()> {
-    let argv: Vec<_> = env::args().collect();
-
-    if argv.len() != 3 || argv[1] == "--help" {
-        return usage_err!("{} old-file new-file", argv[0]);
-    }
+    let (scan_zeros, input_path, output_path) = try!(parse_args());
 
-    let input_fd = try!(open_input(&argv[1]));
-    let output_fd = try!(open_output(&argv[2]));
+    let input_fd = try!(open_input(&input_path));
+    let output_fd = try!(open_output(&output_path));
 
-    try!(copy_with_holes(&input_fd, &output_fd));
+    try!(copy_with_holes(&input_fd, &output_fd, scan_zeros));
+    try!(report_blocks(&input_fd, &output_fd));
 
     try!(clean_up(input_fd, "input"));
     try!(clean_up(output_fd, "output"));
@@ -34,6 +37,44 @@ fn main_with_io() -> TlpiResult<()> {
     Ok(())
 }
 
+fn parse_args() -> TlpiResult<(bool, String, String)> {
+    let argv: Vec<_> = env::args().collect();
+    let opts = build_options();
+
+    let argv_tail = match argv.split_first() {
+        Some((_, tail)) => tail,
+        _ => return cmd_line_err!("No program name provided!?"),
+    };
+
+    // Mutable so we can move out the paths
+    let mut matches = match opts.parse(argv_tail) {
+        Ok(m) => m,
+        Err(f) => {
+            let usage = opts.usage(&f.to_string());
+            return cmd_line_err!("{}", usage)
+        },
+    };
+
+    if matches.opt_present("help") || matches.free.len() != 2 {
+        let usage = format!("{} [options] old-file new-file", argv[0]);
+        return usage_err!("{}", opts.usage(&usage));
+    }
+
+    // Holes are found from the file's extents unless zero bytes are
+    // to be treated as holes
+    let scan_zeros = matches.opt_present("scan-zeros");
+    let output_path = matches.free.swap_remove(1);
+    let input_path = matches.free.swap_remove(0);
+    Ok((scan_zeros, input_path, output_path))
+}
+
+fn build_options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "display this usage message");
+    opts.optflag("z", "scan-zeros", "treat blocks of zero bytes as holes");
+    opts
+}
+
 fn open_input(path: &str) -> TlpiResult<FileDescriptor> {
     let empty_perms = FilePerms::empty();
     FileDescriptor::open(String::from(path), O_RDONLY, empty_perms)
@@ -41,17 +82,19 @@ fn open_input(path: &str) -> TlpiResult<FileDescriptor> {
 }
 
 fn open_output(path: &str) -> TlpiResult<FileDescriptor> {
-    let open_flags = O_CREAT | O_WRONLY | O_TRUNC;
+    // Not truncated, since `BulkWriter` either punches holes over any
+    // existing data or truncates the file itself
+    let open_flags = O_CREAT | O_WRONLY;
     let file_perms = S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH;
     FileDescriptor::open(String::from(path), open_flags, file_perms)
         .or_else(|errno| err_exit!(errno, "opening output file {}", path))
 }
 
 fn copy_with_holes(
-    input_fd: &FileDescriptor, output_fd: &FileDescriptor
+    input_fd: &FileDescriptor, output_fd: &FileDescriptor, scan_zeros: bool
 ) -> TlpiResult<()> {
-    let mut reader = RegionReader::attach(input_fd);
-    let mut writer = BulkWriter::attach(output_fd);
+    let mut reader = RegionReader::attach(input_fd, scan_zeros);
+    let mut writer = try!(BulkWriter::attach(output_fd));
 
     loop {
         let region = match try!(reader.read()) {
@@ -72,18 +115,42 @@ fn copy_with_holes(
 
 /// A contiguous, non-empty segment of a stream of bytes.
 enum Region<'a> {
-    /// Segment containing non-zero bytes.
+    /// Segment containing data; when scanning for zeros, only
+    /// non-zero bytes.
     Data(&'a [u8]),
 
-    /// Segment containing only zero bytes.
+    /// Segment containing only zero bytes: a hole in the file, or a
+    /// run of zeros when scanning for them.
     Hole(usize),
 }
 
+/// How `RegionReader` finds the holes in a file.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HoleDetection {
+    /// Map the file's data extents with `lseek()`, using `SeekData`
+    /// and `SeekHole`, so that holes are skipped without being read.
+    Extents,
+
+    /// Read the whole file, treating every run of zero bytes as a
+    /// hole.
+    ZeroScan,
+}
+
 /// Reads files as a sequence of `Region`s.
 struct RegionReader<'a> {
     /// The file to read from.
     fd: &'a FileDescriptor,
 
+    /// How holes are found.
+    detection: HoleDetection,
+
+    /// With `Extents`, the file offset of the next region.
+    offset: u64,
+
+    /// With `Extents`, the file offset where the current data extent
+    /// ends.
+    data_end: u64,
+
     /// The buffer to read into.
     buffer: [u8; BUF_SIZE],
 
@@ -98,10 +165,29 @@ impl<'a> RegionReader<'a> {
 
     /// Create an empty reader from an existing file descriptor.
     ///
-    /// The file offset of the descriptor is not changed.
-    fn attach(fd: &FileDescriptor) -> RegionReader {
+    /// Reading starts at the file offset of the descriptor. Holes are
+    /// found from the file's extents, unless `scan_zeros` is set or
+    /// the file doesn't support `SeekData` (for example, a pipe).
+    fn attach(fd: &FileDescriptor, scan_zeros: bool) -> RegionReader {
+        let offset = fd.lseek(0, OffsetBase::SeekCur).ok();
+        let detection = match offset {
+            Some(offset) if !scan_zeros => {
+                let probe = fd.lseek(offset as i64, OffsetBase::SeekData);
+                let _ = fd.lseek(offset as i64, OffsetBase::SeekSet);
+                match probe {
+                    Ok(_) => HoleDetection::Extents,
+                    // No data beyond the offset, which is still useful
+                    Err(errno) if errno == ENXIO => HoleDetection::Extents,
+                    Err(_) => HoleDetection::ZeroScan,
+                }
+            },
+            _ => HoleDetection::ZeroScan,
+        };
+
+        let offset = offset.unwrap_or(0);
         RegionReader {
-            fd: fd, buffer: [0; BUF_SIZE], next_index: 0, bytes_read: 0
+            fd: fd, detection: detection, offset: offset, data_end: offset,
+            buffer: [0; BUF_SIZE], next_index: 0, bytes_read: 0
         }
     }
 
@@ -112,6 +198,81 @@ impl<'a> RegionReader<'a> {
     ///
     /// Returns `Ok(None)` at end-of-file.
     fn read(&mut self) -> TlpiResult<Option<Region>> {
+        match self.detection {
+            HoleDetection::Extents => self.read_extent(),
+            HoleDetection::ZeroScan => self.scan_zeros(),
+        }
+    }
+
+    /// Helper method; `read()` by mapping the file's extents.
+    ///
+    /// Data is returned as it is read, zeros included; holes are
+    /// returned whole, without reading them.
+    fn read_extent(&mut self) -> TlpiResult<Option<Region>> {
+        // Have we reached the end of the current data extent?
+        if self.offset == self.data_end {
+            let next_data = self.seek(self.offset, OffsetBase::SeekData);
+            let data_start = match next_data {
+                Ok(start) => start,
+                // The rest of the file, if any, is a hole
+                Err(errno) if errno == ENXIO => {
+                    let size = try!(
+                        self.seek(0, OffsetBase::SeekEnd).or_else(|errno| {
+                            err_exit!(errno, "lseek to end of input file")
+                        })
+                    );
+                    if size <= self.offset {
+                        return Ok(None);
+                    }
+                    let hole = size - self.offset;
+                    self.offset = size;
+                    self.data_end = size;
+                    return Ok(Some(Hole(hole as usize)));
+                },
+                Err(errno) => {
+                    return err_exit!(errno, "lseek(SEEK_DATA) on input file");
+                },
+            };
+
+            self.data_end = try!(
+                self.seek(data_start, OffsetBase::SeekHole).or_else(|errno| {
+                    err_exit!(errno, "lseek(SEEK_HOLE) on input file")
+                })
+            );
+            try!(
+                self.seek(data_start, OffsetBase::SeekSet).or_else(|errno| {
+                    err_exit!(errno, "lseek on input file")
+                })
+            );
+
+            if data_start > self.offset {
+                let hole = data_start - self.offset;
+                self.offset = data_start;
+                return Ok(Some(Hole(hole as usize)));
+            }
+        }
+
+        let wanted = cmp::min(BUF_SIZE as u64, self.data_end - self.offset);
+        let result = self.fd.read(&mut self.buffer[..wanted as usize]);
+        let bytes_read = match result {
+            // The file was truncated while being copied
+            Ok(0) => return Ok(None),
+            Ok(bytes) => bytes,
+            Err(errno) => return err_exit!(errno, "reading input file"),
+        };
+        self.offset += bytes_read as u64;
+
+        Ok(Some(Data(&self.buffer[..bytes_read])))
+    }
+
+    /// Helper method; sets the file offset, as `lseek()` with an
+    /// absolute `offset`.
+    fn seek(&self, offset: u64, whence: OffsetBase) -> SysResult<u64> {
+        self.fd.lseek(offset as i64, whence)
+    }
+
+    /// Helper method; `read()` by scanning the file for zero bytes.
+    fn scan_zeros(&mut self) -> TlpiResult<Option<Region>> {
         // Have we reached the end of the buffer?
         if self.next_index == self.bytes_read {
             // Try to get more data from the file
@@ -173,20 +334,41 @@ struct BulkWriter<'a> {
 
     /// The total number of bytes written to `fd`.
     bytes_added: u64,
+
+    /// Whether holes are made by deallocating the file's blocks with
+    /// `fallocate()`, rather than by leaving gaps in a truncated file.
+    punch_holes: bool,
 }
 
 impl<'a> BulkWriter<'a> {
 
-    /// Create an empty writer from an existing file descriptor.
+    /// Create an empty writer from an existing file descriptor, which
+    /// must be at the start of the file.
     ///
-    /// The file offset of the descriptor is not changed.
-    fn attach(fd: &FileDescriptor) -> BulkWriter {
-        BulkWriter {
+    /// If the file system can punch holes, any existing contents of
+    /// the file are overwritten in place, with holes punched in them;
+    /// otherwise, the file is truncated first.
+    fn attach(fd: &FileDescriptor) -> TlpiResult<BulkWriter> {
+        // Probe just past the end of the file, where there is no data
+        // to lose
+        let size = try!(
+            statx(Some(fd), "", AT_EMPTY_PATH, STATX_SIZE)
+                .or_else(|errno| err_exit!(errno, "statx on output file"))
+        ).size;
+        let mode = FALLOC_FL_PUNCH_HOLE | FALLOC_FL_KEEP_SIZE;
+        let punch_holes = fd.fallocate(mode, size as i64, 1).is_ok();
+        if !punch_holes {
+            let result = fd.ftruncate(0);
+            try!(result.or_else(|errno| err_exit!(errno, "ftruncate")));
+        }
+
+        Ok(BulkWriter {
             fd: fd,
             buffer: Vec::with_capacity(BUF_SIZE),
             pending_extend: 0,
             bytes_added: 0,
-        }
+            punch_holes: punch_holes,
+        })
     }
 
     /// Writes the given data to file.
@@ -240,15 +422,19 @@ impl<'a> BulkWriter<'a> {
             try!(self.flush_writes());
         }
 
-        if self.pending_extend > 0 {
-            // We can't just advance the file offset here, because
-            // without data to write after it, the file hole will not
-            // be created.
-            let file_length = self.bytes_added + self.pending_extend;
+        // We can't just advance the file offset here, because without
+        // data to write after it, the file hole will not be created.
+        // Existing contents past the end are cut off, too.
+        let file_length = self.bytes_added + self.pending_extend;
+        if self.pending_extend > 0 || self.punch_holes {
             let result = self.fd.ftruncate(file_length as i64);
             try!(result.or_else(|errno| err_exit!(errno, "ftruncate")));
         }
 
+        if self.pending_extend > 0 && self.punch_holes {
+            try!(self.punch(self.bytes_added, self.pending_extend));
+        }
+
         Ok(())
     }
 
@@ -274,6 +460,16 @@ impl<'a> BulkWriter<'a> {
         Ok(())
     }
 
+    /// Helper method; deallocates the `len` bytes of the file at
+    /// `offset`, leaving a hole.
+    fn punch(&self, offset: u64, len: u64) -> TlpiResult<()> {
+        let mode = FALLOC_FL_PUNCH_HOLE | FALLOC_FL_KEEP_SIZE;
+        let result = self.fd.fallocate(mode, offset as i64, len as i64);
+        result.or_else(|errno| {
+            err_exit!(errno, "fallocate at offset {} in output file", offset)
+        })
+    }
+
     /// Helper method; writes all buffered length extensions to file.
     ///
     /// Assumes that data will follow the length extensions!
@@ -285,15 +481,41 @@ impl<'a> BulkWriter<'a> {
                 "lseek by amount {} in output file",
                 self.pending_extend,
             ),
-            _ => self.bytes_added += self.pending_extend,
+            _ => {},
         };
 
+        if self.punch_holes {
+            try!(self.punch(self.bytes_added, self.pending_extend));
+        }
+
+        self.bytes_added += self.pending_extend;
         self.pending_extend = 0;
         Ok(())
     }
 
 }
 
+/// Prints the number of blocks allocated to each file, which shows
+/// whether the holes of the input were preserved.
+fn report_blocks(
+    input_fd: &FileDescriptor, output_fd: &FileDescriptor
+) -> TlpiResult<()> {
+    // Write back the output first, since some file systems only report
+    // the blocks allocated so far
+    try!(output_fd.fdatasync().or_else(|errno| err_exit!(errno, "fdatasync")));
+
+    let input = try!(blocks_of(input_fd, "input"));
+    let output = try!(blocks_of(output_fd, "output"));
+    println!("512-byte blocks allocated: input {}, output {}", input, output);
+    Ok(())
+}
+
+fn blocks_of(fd: &FileDescriptor, desc: &str) -> TlpiResult<u64> {
+    statx(Some(fd), "", AT_EMPTY_PATH, STATX_BLOCKS)
+        .map(|attrs| attrs.blocks)
+        .or_else(|errno| err_exit!(errno, "statx on {} file", desc))
+}
+
 fn clean_up(fd: FileDescriptor, desc: &str) -> TlpiResult<()> {
     fd.close().or_else(|errno| err_exit!(errno, "close {}", desc))
 }
@@ -0,0 +1,73 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::inotify::*;
+
+/// The names of the bits that can be set in an event's mask.
+const MASK_NAMES: [(EventMask, &'static str); 19] = [
+    (IN_ACCESS, "IN_ACCESS"), (IN_ATTRIB, "IN_ATTRIB"),
+    (IN_CLOSE_NOWRITE, "IN_CLOSE_NOWRITE"),
+    (IN_CLOSE_WRITE, "IN_CLOSE_WRITE"), (IN_CREATE, "IN_CREATE"),
+    (IN_DELETE, "IN_DELETE"), (IN_DELETE_SELF, "IN_DELETE_SELF"),
+    (IN_IGNORED, "IN_IGNORED"), (IN_ISDIR, "IN_ISDIR"),
+    (IN_MODIFY, "IN_MODIFY"), (IN_MOVE_SELF, "IN_MOVE_SELF"),
+    (IN_MOVED_FROM, "IN_MOVED_FROM"), (IN_MOVED_TO, "IN_MOVED_TO"),
+    (IN_OPEN, "IN_OPEN"), (IN_Q_OVERFLOW, "IN_Q_OVERFLOW"),
+    (IN_UNMOUNT, "IN_UNMOUNT"), (IN_ONESHOT, "IN_ONESHOT"),
+    (IN_ONLYDIR, "IN_ONLYDIR"), (IN_DONT_FOLLOW, "IN_DONT_FOLLOW"),
+];
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv[1] == "--help" {
+        return usage_err!("{} pathname...", argv[0]);
+    }
+
+    let inotify = try!(
+        Inotify::init().or_else(|errno| err_exit!(errno, "inotify_init"))
+    );
+    for path in argv.iter().skip(1) {
+        let wd = try!(inotify.add_watch(path, IN_ALL_EVENTS).or_else(|errno| {
+            err_exit!(errno, "inotify_add_watch {}", path)
+        }));
+        println!("Watching {} using wd {}", path, wd.0);
+    }
+
+    // Runs until interrupted
+    loop {
+        let events = try!(inotify.read_events().or_else(|errno| {
+            err_exit!(errno, "read")
+        }));
+        println!("Read {} events from inotify fd", events.len());
+        for event in &events {
+            display_event(event);
+        }
+    }
+}
+
+fn display_event(event: &Event) {
+    print!("    wd ={:2}; ", event.wd.0);
+    if event.cookie > 0 {
+        print!("cookie ={:4}; ", event.cookie);
+    }
+
+    let names: Vec<_> = MASK_NAMES.iter()
+        .filter(|&&(bit, _)| event.mask.contains(bit))
+        .map(|&(_, name)| name)
+        .collect();
+    println!("mask = {}", names.join(" "));
+
+    if let Some(ref name) = event.name {
+        println!("        name = {}", name);
+    }
+}
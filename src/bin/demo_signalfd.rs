@@ -0,0 +1,53 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+use tlpi_rust::signal::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv[1] == "--help" {
+        return usage_err!("{} sig-num...", argv[0]);
+    }
+
+    println!("{}: PID = {}", argv[0], getpid());
+
+    let mut mask = SigSet::empty();
+    for arg in &argv[1..] {
+        let sig = match i32::from_str(arg) {
+            Ok(value) => Signal::new(value),
+            Err(_) => return cmd_line_err!("Invalid sig-num: {}", arg),
+        };
+        try!(mask.add(sig).or_else(|errno| err_exit!(errno, "sigaddset")));
+    }
+
+    // Block the signals, so that they wait to be read instead of
+    // being delivered
+    try!(pthread_sigmask(SigmaskHow::Block, &mask)
+         .or_else(|errno| err_exit!(errno, "pthread_sigmask")));
+
+    let sfd = try!(SignalFd::new(&mask, SfdFlags::empty())
+                   .or_else(|errno| err_exit!(errno, "signalfd")));
+
+    loop {
+        let info = try!(sfd.read_signal()
+                        .or_else(|errno| err_exit!(errno, "read")));
+
+        print!("{}: got signal {}", argv[0], info.signo.value());
+        if info.code == SI_QUEUE {
+            print!("; ssi_pid = {}; ssi_int = {}", info.pid, info.int);
+        }
+        println!("");
+    }
+}
@@ -0,0 +1,95 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use std::time::Instant;
+use tlpi_rust::dir::*;
+use tlpi_rust::err::*;
+
+/// Number of times each method reads the directory, if not given.
+const DEFAULT_PASSES: usize = 100;
+
+/// Size of the buffer given to `getdents64()`.
+const BUF_SIZE: usize = 32768;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv.len() > 3 || argv[1] == "--help" {
+        return usage_err!("{} dir [num-passes]", argv[0]);
+    }
+    let path = &argv[1];
+    let passes = match argv.get(2) {
+        Some(arg) => match usize::from_str(arg) {
+            Ok(passes) if passes > 0 => passes,
+            _ => return cmd_line_err!("Invalid num-passes: {}", arg),
+        },
+        None => DEFAULT_PASSES,
+    };
+
+    let start = Instant::now();
+    let mut count = 0;
+    for _ in 0..passes {
+        count = try!(count_with_readdir(path));
+    }
+    report("readdir()", count, passes, start);
+
+    let start = Instant::now();
+    for _ in 0..passes {
+        count = try!(count_with_getdents(path));
+    }
+    report("getdents64()", count, passes, start);
+
+    Ok(())
+}
+
+/// Counts the entries of `path` with the `readdir()`-based `Dir`.
+fn count_with_readdir(path: &str) -> TlpiResult<usize> {
+    let dir = try!(
+        Dir::open(path).or_else(|errno| err_exit!(errno, "opendir {}", path))
+    );
+    let mut count = 0;
+    for entry in dir {
+        try!(entry.or_else(|errno| err_exit!(errno, "readdir {}", path)));
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Counts the entries of `path` with the `getdents64()`-based
+/// `DirReader`.
+fn count_with_getdents(path: &str) -> TlpiResult<usize> {
+    let mut reader = try!(
+        DirReader::open(path, BUF_SIZE)
+            .or_else(|errno| err_exit!(errno, "open {}", path))
+    );
+    let mut count = 0;
+    loop {
+        match reader.next_entry() {
+            Ok(Some(_)) => count += 1,
+            Ok(None) => break,
+            Err(errno) => return err_exit!(errno, "getdents64 {}", path),
+        };
+    }
+    try!(reader.close().or_else(|errno| err_exit!(errno, "close")));
+    Ok(count)
+}
+
+/// Prints the time taken by `passes` passes of `method` since `start`.
+fn report(method: &str, count: usize, passes: usize, start: Instant) {
+    let elapsed = start.elapsed();
+    let micros = elapsed.as_secs() * 1000000 +
+        elapsed.subsec_nanos() as u64 / 1000;
+    println!(
+        "{:13} {} entries, {} passes: {} us total, {} us per pass",
+        method, count, passes, micros, micros / passes as u64
+    );
+}
@@ -0,0 +1,61 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::memory::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 3 || argv.len() > 5 || argv[1] == "--help" {
+        return usage_err!("{} file length [offset [alignment]]", argv[0]);
+    }
+
+    let length = try!(parse_number(&argv[2], "length"));
+    let offset = match argv.get(3) {
+        Some(arg) => try!(parse_number(arg, "offset")),
+        None => 0,
+    };
+    let alignment = match argv.get(4) {
+        Some(arg) => try!(parse_number(arg, "alignment")),
+        None => 4096,
+    };
+
+    let path = argv[1].clone();
+    let flags = O_RDONLY | O_DIRECT;
+    let fd = try!(FileDescriptor::open(path, flags, FilePerms::empty())
+        .or_else(|errno| err_exit!(errno, "open")));
+
+    // Allocate a block aligned on a multiple of twice `alignment`, then
+    // start the read buffer `alignment` bytes in, so that it is aligned
+    // on `alignment` but not on any larger power of two
+    let mut block = try!(alloc_aligned(alignment * 2, length + alignment)
+        .or_else(|errno| err_exit!(errno, "alloc_aligned")));
+    let buf = &mut block.as_mut_slice()[alignment..];
+
+    try!(fd.lseek(offset as i64, OffsetBase::SeekSet)
+        .or_else(|errno| err_exit!(errno, "lseek")));
+
+    let num_read = try!(fd.read(buf).or_else(|errno| {
+        err_exit!(errno, "read")
+    }));
+    println!("Read {} bytes", num_read);
+    Ok(())
+}
+
+fn parse_number(arg: &str, name: &str) -> TlpiResult<usize> {
+    match usize::from_str(arg) {
+        Ok(number) => Ok(number),
+        _ => cmd_line_err!("Invalid {}: {}", name, arg),
+    }
+}
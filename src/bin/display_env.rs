@@ -0,0 +1,19 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use tlpi_rust::env::*;
+use tlpi_rust::err::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    for entry in environ() {
+        println!("{}", entry);
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env::args;
+use tlpi_rust::env::*;
+use tlpi_rust::err::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    for (index, arg) in args().enumerate() {
+        println!("argv[{}] = {}", index, arg);
+    }
+
+    for entry in environ() {
+        println!("environ: {}", entry);
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::epoll::*;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+
+/// Maximum number of events to be returned from a single wait.
+const MAX_EVENTS: usize = 5;
+
+/// Maximum number of bytes fetched by a single read.
+const MAX_BUF: usize = 1000;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv[1] == "--help" {
+        return usage_err!("{} file...", argv[0]);
+    }
+
+    let epoll = try!(
+        Epoll::create().or_else(|errno| err_exit!(errno, "epoll_create"))
+    );
+
+    // Open each file on command line, and add it to the interest list;
+    // its index in `fds` identifies it in the events
+    let mut fds = Vec::new();
+    for (j, path) in argv.iter().skip(1).enumerate() {
+        let fd = try!(FileDescriptor::open(
+            path.clone(), O_RDONLY, FilePerms::empty()
+        ).or_else(|errno| err_exit!(errno, "open {}", path)));
+        println!("Opened \"{}\" on fd {}", path, fd.as_raw());
+
+        try!(epoll.add(&fd, EPOLLIN, j as u64)
+             .or_else(|errno| err_exit!(errno, "epoll_ctl")));
+        fds.push(Some(fd));
+    }
+
+    let mut num_open_fds = fds.len();
+    while num_open_fds > 0 {
+        // Fetch up to MAX_EVENTS items from the ready list
+        println!("About to epoll_wait()");
+        let events = match epoll.wait(MAX_EVENTS, None) {
+            Ok(events) => events,
+            // Restart if interrupted by signal
+            Err(errno) if errno == EINTR => continue,
+            Err(errno) => return err_exit!(errno, "epoll_wait"),
+        };
+        println!("Ready: {}", events.len());
+
+        for event in &events {
+            let index = event.data as usize;
+            let closing = match fds[index] {
+                Some(ref fd) => try!(handle_event(fd, event.events)),
+                None => false,
+            };
+            if closing {
+                // Closing also removes the file from the interest list
+                let fd = fds[index].take().unwrap();
+                println!("    closing fd {}", fd.as_raw());
+                try!(fd.close().or_else(|errno| err_exit!(errno, "close")));
+                num_open_fds -= 1;
+            }
+        }
+    }
+
+    println!("All file descriptors closed; bye");
+    Ok(())
+}
+
+/// Displays the events on `fd`, reading from it if it is ready.
+/// Returns whether it should be closed.
+fn handle_event(fd: &FileDescriptor, events: EpollFlags) -> TlpiResult<bool> {
+    println!("  fd={}; events: {}{}{}", fd.as_raw(),
+             if events.contains(EPOLLIN) { "EPOLLIN " } else { "" },
+             if events.contains(EPOLLHUP) { "EPOLLHUP " } else { "" },
+             if events.contains(EPOLLERR) { "EPOLLERR " } else { "" });
+
+    if events.contains(EPOLLIN) {
+        let mut buf = [0u8; MAX_BUF];
+        let num_read = try!(fd.read(&mut buf).or_else(|errno| {
+            err_exit!(errno, "read")
+        }));
+        println!("    read {} bytes: {}",
+                 num_read, String::from_utf8_lossy(&buf[..num_read]));
+        Ok(false)
+    } else {
+        // After the writers are gone, EPOLLHUP is reported without
+        // EPOLLIN once all of the data has been read
+        Ok(events.intersects(EPOLLHUP | EPOLLERR))
+    }
+}
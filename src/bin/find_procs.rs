@@ -0,0 +1,41 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+use tlpi_rust::procfs::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} pattern", argv[0]);
+    }
+    let pattern = &argv[1];
+
+    let mut pids = try!(pids().or_else(|errno| err_exit!(errno, "/proc")));
+    pids.sort();
+
+    // Leave out this process, whose own command line always matches
+    let self_pid = getpid();
+    for pid in pids.into_iter().filter(|&pid| pid != self_pid) {
+        // The process may have terminated since the directory was read
+        let args = match cmdline(pid) {
+            Ok(args) => args,
+            Err(_) => continue,
+        };
+        let command = args.join(" ");
+        if command.contains(&pattern[..]) {
+            println!("{:>6} {}", pid, command);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::mount::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 2 || (argv.len() == 2 && argv[1] == "--help") {
+        return usage_err!("{} [path]", argv[0]);
+    }
+
+    let entries = if argv.len() == 2 {
+        vec![try!(find_mount(&argv[1]).or_else(|errno| {
+            err_exit!(errno, "finding the mount of {}", argv[1])
+        }))]
+    } else {
+        try!(mount_table().or_else(|errno| {
+            err_exit!(errno, "reading the mount table")
+        }))
+    };
+
+    println!("{:>4} {:>4} {:<24} {:<16} {:<8} {}",
+             "ID", "PAR", "TARGET", "SOURCE", "FSTYPE", "OPTIONS");
+    for entry in entries {
+        println!("{:>4} {:>4} {:<24} {:<16} {:<8} {}",
+                 entry.mount_id, entry.parent_id, entry.target, entry.source,
+                 entry.fstype, entry.options.join(","));
+    }
+
+    Ok(())
+}
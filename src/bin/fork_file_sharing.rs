@@ -0,0 +1,67 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::process::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let (fd, path) = try!(mkstemp("/tmp/testXXXXXX").or_else(|errno| {
+        err_exit!(errno, "mkstemp")
+    }));
+    // The file is only needed while it's open
+    try!(unlink(&path).or_else(|errno| err_exit!(errno, "unlink")));
+
+    try!(display_shared_state(&fd, "before fork()"));
+
+    match fork() {
+        Ok(ForkResult::Child) => {
+            // Both changes are to the open file description, which the
+            // parent shares
+            if let Err(errno) = change_shared_state(&fd) {
+                let _: TlpiResult<()> = err_exit!(errno, "child");
+                _exit(EXIT_FAILURE);
+            }
+            _exit(EXIT_SUCCESS);
+        },
+        Ok(ForkResult::Parent(child)) => {
+            try!(waitpid(child, WaitFlags::empty()).or_else(|errno| {
+                err_exit!(errno, "waitpid")
+            }));
+            println!("Child has exited");
+        },
+        Err(errno) => return err_exit!(errno, "fork"),
+    }
+
+    display_shared_state(&fd, "in parent after child exits")
+}
+
+/// Moves the file offset and turns on `O_APPEND`.
+fn change_shared_state(fd: &FileDescriptor) -> SysResult<()> {
+    try!(fd.lseek(1000, OffsetBase::SeekSet));
+    let flags = try!(fd.status_flags());
+    fd.set_status_flags(flags | O_APPEND)
+}
+
+/// Prints the file offset and the `O_APPEND` flag of `fd`.
+fn display_shared_state(fd: &FileDescriptor, when: &str) -> TlpiResult<()> {
+    let offset = try!(fd.lseek(0, OffsetBase::SeekCur).or_else(|errno| {
+        err_exit!(errno, "lseek")
+    }));
+    println!("File offset {}: {}", when, offset);
+
+    let flags = try!(fd.status_flags().or_else(|errno| {
+        err_exit!(errno, "fcntl(F_GETFL)")
+    }));
+    println!("O_APPEND flag {} is: {}",
+             when, if flags.contains(O_APPEND) { "on" } else { "off" });
+    Ok(())
+}
@@ -0,0 +1,130 @@
+
+#![feature(libc, slice_splits)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+extern crate getopts;
+use getopts::Options;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::process::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let (num_children, stats) = try!(parse_args());
+
+    if stats {
+        return display_stats(num_children);
+    }
+
+    // Whichever process is scheduled first after each fork() prints
+    // first
+    for iteration in 0..num_children {
+        match fork() {
+            Ok(ForkResult::Child) => {
+                println!("{} child", iteration);
+                _exit(EXIT_SUCCESS);
+            },
+            Ok(ForkResult::Parent(child)) => {
+                println!("{} parent", iteration);
+                try!(waitpid(child, WaitFlags::empty()).or_else(|errno| {
+                    err_exit!(errno, "waitpid")
+                }));
+            },
+            Err(errno) => return err_exit!(errno, "fork"),
+        }
+    }
+    Ok(())
+}
+
+/// Counts how often each process is first after `fork()`, and prints
+/// the totals rather than a line per iteration.
+fn display_stats(num_children: u64) -> TlpiResult<()> {
+    let mut parent_first = 0;
+    for _ in 0..num_children {
+        // Each process writes a byte to the pipe as soon as it runs, so
+        // the first byte read shows which one got there first
+        let (read_end, write_end) = try!(pipe().or_else(|errno| {
+            err_exit!(errno, "pipe")
+        }));
+        match fork() {
+            Ok(ForkResult::Child) => {
+                let _ = write_end.write(b"c");
+                _exit(EXIT_SUCCESS);
+            },
+            Ok(ForkResult::Parent(child)) => {
+                try!(write_end.write(b"p").or_else(|errno| {
+                    err_exit!(errno, "write")
+                }));
+                try!(waitpid(child, WaitFlags::empty()).or_else(|errno| {
+                    err_exit!(errno, "waitpid")
+                }));
+            },
+            Err(errno) => return err_exit!(errno, "fork"),
+        }
+
+        let mut first = [0u8; 1];
+        try!(read_end.read(&mut first).or_else(|errno| {
+            err_exit!(errno, "read")
+        }));
+        if first[0] == b'p' {
+            parent_first += 1;
+        }
+        let _ = read_end.close();
+        let _ = write_end.close();
+    }
+
+    let child_first = num_children - parent_first;
+    let percent = |count: u64| count as f64 * 100.0 / num_children as f64;
+    println!("Parent first: {:>8} ({:5.1}%)",
+             parent_first, percent(parent_first));
+    println!("Child first:  {:>8} ({:5.1}%)",
+             child_first, percent(child_first));
+    Ok(())
+}
+
+fn parse_args() -> TlpiResult<(u64, bool)> {
+    let argv: Vec<_> = env::args().collect();
+    let opts = build_options();
+
+    let argv_tail = match argv.split_first() {
+        Some((_, tail)) => tail,
+        _ => return cmd_line_err!("No program name provided!?"),
+    };
+
+    let matches = match opts.parse(argv_tail) {
+        Ok(m) => m,
+        Err(f) => {
+            let usage = opts.usage(&f.to_string());
+            return cmd_line_err!("{}", usage)
+        },
+    };
+
+    if matches.opt_present("help") || matches.free.len() != 1 {
+        let usage = format!("{} [options] num-children", argv[0]);
+        return usage_err!("{}", opts.usage(&usage));
+    }
+
+    let num_children = match u64::from_str(&matches.free[0]) {
+        Ok(count) if count > 0 => count,
+        _ => {
+            let arg = &matches.free[0];
+            return cmd_line_err!("Invalid num-children: {}", arg)
+        },
+    };
+    Ok((num_children, matches.opt_present("stats")))
+}
+
+fn build_options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "display this usage message");
+    opts.optflag("s", "stats", "only print how often each was first");
+    opts
+}
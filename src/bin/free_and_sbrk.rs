@@ -0,0 +1,86 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+extern crate libc;
+
+use std::env;
+use std::ptr;
+use std::str::FromStr;
+use libc::{c_void, size_t};
+use tlpi_rust::err::*;
+use tlpi_rust::memory::*;
+
+/// The most blocks that can be allocated.
+const MAX_ALLOCS: usize = 1_000_000;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 3 || argv.len() > 6 || argv[1] == "--help" {
+        return usage_err!(
+            "{} num-allocs block-size [step [min [max]]]", argv[0]
+        );
+    }
+
+    let num_allocs = try!(parse_count(&argv[1], "num-allocs"));
+    if num_allocs > MAX_ALLOCS {
+        return cmd_line_err!("num-allocs > {}", MAX_ALLOCS);
+    }
+    let block_size = try!(parse_count(&argv[2], "block-size"));
+
+    // Free every `step`'th block numbered from `min` to `max`, counting
+    // from 1 as the book does
+    let step = match argv.get(3) {
+        Some(arg) => try!(parse_count(arg, "step")),
+        None => 1,
+    };
+    let min = match argv.get(4) {
+        Some(arg) => try!(parse_count(arg, "min")),
+        None => 1,
+    };
+    let max = match argv.get(5) {
+        Some(arg) => try!(parse_count(arg, "max")),
+        None => num_allocs,
+    };
+    if max > num_allocs {
+        return cmd_line_err!("max > num-allocs");
+    }
+
+    println!("Initial program break:          {:>10?}", program_break());
+
+    // Call `malloc()` directly, so that it's the C library's heap that
+    // is shown growing and shrinking
+    println!("Allocating {}*{} bytes", num_allocs, block_size);
+    let mut blocks = vec![ptr::null_mut::<c_void>(); num_allocs];
+    for block in &mut blocks {
+        *block = unsafe { libc::malloc(block_size as size_t) };
+        if block.is_null() {
+            return fatal!("malloc");
+        }
+    }
+
+    println!("Program break is now:           {:>10?}", program_break());
+
+    println!("Freeing blocks from {} to {} in steps of {}", min, max, step);
+    let mut index = min - 1;
+    while index < max {
+        unsafe { libc::free(blocks[index]) };
+        index += step;
+    }
+
+    println!("After free(), program break is: {:>10?}", program_break());
+    Ok(())
+}
+
+fn parse_count(arg: &str, name: &str) -> TlpiResult<usize> {
+    match usize::from_str(arg) {
+        Ok(count) if count > 0 => Ok(count),
+        _ => cmd_line_err!("Invalid {}: {}", name, arg),
+    }
+}
@@ -0,0 +1,32 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::sysinfo::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 2 || (argv.len() == 2 && argv[1] == "--help") {
+        return usage_err!("{} [new-hostname]", argv[0]);
+    }
+
+    if argv.len() == 2 {
+        sethostname(&argv[1]).or_else(|errno| {
+            err_exit!(errno, "sethostname {}", argv[1])
+        })
+    } else {
+        match gethostname() {
+            Ok(name) => { println!("{}", name); Ok(()) },
+            Err(errno) => err_exit!(errno, "gethostname"),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use tlpi_rust::creds::*;
+use tlpi_rust::err::*;
+use tlpi_rust::users::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let uids = try!(getresuid().or_else(|errno| {
+        err_exit!(errno, "getresuid")
+    }));
+    let gids = try!(getresgid().or_else(|errno| {
+        err_exit!(errno, "getresgid")
+    }));
+
+    // There are no getters for the file-system IDs, but these calls
+    // return them without making any change
+    let fsuid = setfsuid(None);
+    let fsgid = setfsgid(None);
+
+    println!("UID: real={}; eff={}; saved={}; fs={}",
+             try!(user_label(uids.real)), try!(user_label(uids.effective)),
+             try!(user_label(uids.saved)), try!(user_label(fsuid)));
+    println!("GID: real={}; eff={}; saved={}; fs={}",
+             try!(group_label(gids.real)), try!(group_label(gids.effective)),
+             try!(group_label(gids.saved)), try!(group_label(fsgid)));
+
+    let groups = try!(getgroups().or_else(|errno| {
+        err_exit!(errno, "getgroups")
+    }));
+    let mut labels = Vec::with_capacity(groups.len());
+    for &gid in &groups {
+        labels.push(try!(group_label(gid)));
+    }
+    println!("Supplementary groups ({}): {}", groups.len(), labels.join(" "));
+    Ok(())
+}
+
+/// Formats `uid` with the user's name, as in `root (0)`.
+fn user_label(uid: u32) -> TlpiResult<String> {
+    match user_name_from_id(uid) {
+        Ok(name) => {
+            Ok(format!("{} ({})", name.unwrap_or("???".to_string()), uid))
+        },
+        Err(errno) => err_exit!(errno, "getpwuid"),
+    }
+}
+
+/// Formats `gid` with the group's name, as in `root (0)`.
+fn group_label(gid: u32) -> TlpiResult<String> {
+    match group_name_from_id(gid) {
+        Ok(name) => {
+            Ok(format!("{} ({})", name.unwrap_or("???".to_string()), gid))
+        },
+        Err(errno) => err_exit!(errno, "getgrgid"),
+    }
+}
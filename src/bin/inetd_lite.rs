@@ -0,0 +1,167 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::inet::*;
+use tlpi_rust::process::*;
+use tlpi_rust::select::*;
+use tlpi_rust::socket::*;
+
+/// Maximum number of pending connections per service.
+const BACKLOG: i32 = 10;
+
+/// A service read from the configuration file.
+struct Service {
+    /// Name of the service, for diagnostic messages.
+    name: String,
+    /// Port number on which the service is offered.
+    port: String,
+    /// Pathname of the program that handles each connection.
+    program: String,
+    /// Argument list of the program, starting with its name.
+    args: Vec<String>,
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} config-file", argv[0]);
+    }
+
+    let services = try!(read_config(&argv[1]));
+    if services.is_empty() {
+        return fatal!("no services in {}", argv[1]);
+    }
+
+    // Listening sockets must not be inherited by the handlers
+    let mut listeners = Vec::new();
+    for service in &services {
+        let socket = match listen_tcp(&service.port, BACKLOG) {
+            Ok((socket, _)) => socket,
+            Err(InetError::Resolve(error)) => {
+                return fatal!("{}: getaddrinfo: {}", service.name, error)
+            },
+            Err(InetError::System(errno)) => {
+                return err_exit!(errno, "{}: listen on {}", service.name,
+                                 service.port)
+            },
+        };
+        if let Err(errno) = socket.as_fd().set_close_on_exec(true) {
+            return err_exit!(errno, "fcntl");
+        }
+        println!("{}: listening on port {}", service.name, service.port);
+        listeners.push(socket);
+    }
+
+    if let Err(errno) = install_child_reaper() {
+        return err_exit!(errno, "sigaction");
+    }
+
+    loop {
+        let mut read_fds = FdSet::new();
+        for socket in &listeners {
+            try!(read_fds.set(socket.as_fd())
+                 .or_else(|errno| err_exit!(errno, "FD_SET")));
+        }
+
+        // Interrupted whenever a handler terminates
+        match select(Some(&mut read_fds), None, None, None) {
+            Ok(_) => {},
+            Err(errno) if errno == EINTR => continue,
+            Err(errno) => return err_exit!(errno, "select"),
+        };
+
+        for (socket, service) in listeners.iter().zip(services.iter()) {
+            if read_fds.is_set(socket.as_fd()) {
+                start_handler(socket, service);
+            }
+        }
+    }
+}
+
+/// Accepts a connection for `service` and runs its program in a child
+/// process, with the connected socket as its standard input, output
+/// and error.
+///
+/// Failures only affect the connection, so they are reported and the
+/// server carries on.
+fn start_handler(listen_socket: &Socket, service: &Service) {
+    let (client, addr) = match listen_socket.accept4(SOCK_CLOEXEC) {
+        Ok(connection) => connection,
+        Err(errno) => {
+            let _: TlpiResult<()> = err_exit!(errno, "accept");
+            return;
+        },
+    };
+    println!("{}: connection from {}", service.name, addr);
+
+    match fork() {
+        Ok(ForkResult::Child) => {
+            for target in 0..3 {
+                if let Err(errno) = client.as_fd().dup2(target) {
+                    let _: TlpiResult<()> = err_exit!(errno, "dup2");
+                    _exit(EXIT_FAILURE);
+                }
+            }
+
+            // The connected socket itself is closed on exec
+            let args: Vec<_> = service.args.iter().map(|arg| &arg[..])
+                .collect();
+            let errno = execv(&service.program, &args);
+            let _: TlpiResult<()> =
+                err_exit!(errno, "execv {}", service.program);
+            _exit(EXIT_FAILURE);
+        },
+        Ok(ForkResult::Parent(_)) => {},
+        Err(errno) => {
+            let _: TlpiResult<()> = err_exit!(errno, "fork");
+        },
+    };
+
+    let _ = client.close();
+}
+
+/// Reads the configuration file at `path`.
+///
+/// Each line consists of whitespace-separated fields: the service
+/// name, the port number, the pathname of the program, and the
+/// program's arguments, starting with its name. Blank lines and lines
+/// starting with `#` are ignored.
+fn read_config(path: &str) -> TlpiResult<Vec<Service>> {
+    let contents = match read_whole_file(path) {
+        Ok(contents) => contents,
+        Err(errno) => return err_exit!(errno, "open {}", path),
+    };
+
+    let text = String::from_utf8_lossy(&contents);
+    let mut services = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        if fields.is_empty() || fields[0].starts_with("#") {
+            continue;
+        }
+        if fields.len() < 4 {
+            return fatal!("{}:{}: expected service, port, program and \
+                           arguments", path, index + 1);
+        }
+
+        services.push(Service {
+            name: String::from(fields[0]),
+            port: String::from(fields[1]),
+            program: String::from(fields[2]),
+            args: fields[3..].iter().map(|&arg| String::from(arg)).collect(),
+        });
+    }
+
+    Ok(services)
+}
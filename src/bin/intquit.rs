@@ -0,0 +1,67 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+extern crate libc;
+
+use std::env;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+use libc::c_int;
+use tlpi_rust::err::*;
+use tlpi_rust::signal::*;
+
+/// How many times `SIGINT` has been caught.
+static INT_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// How many times `SIGQUIT` has been caught.
+static QUIT_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+extern "C" fn handler(sig: c_int) {
+    // UNSAFE: `println!()` isn't async-signal-safe, and may deadlock if
+    // the signal interrupts another use of standard output. Here the
+    // main thread only ever waits in `pause()`, so it gets away with it.
+    if sig == SIGINT.value() {
+        let count = INT_COUNT.fetch_add(1, Ordering::SeqCst) + 1;
+        println!("Caught SIGINT ({})", count);
+    } else {
+        QUIT_COUNT.fetch_add(1, Ordering::SeqCst);
+        println!("Caught SIGQUIT");
+    }
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 2 || (argv.len() == 2 && argv[1] == "--help") {
+        return usage_err!("{} [num-quits]", argv[0]);
+    }
+
+    let num_quits = match argv.get(1) {
+        Some(arg) => match usize::from_str(arg) {
+            Ok(n) if n > 0 => n,
+            _ => return cmd_line_err!("Invalid num-quits: {}", arg),
+        },
+        None => 1,
+    };
+
+    // The same handler is established for both signals, and tells them
+    // apart by number
+    let action = SigAction::new(SigHandler::Handler(handler));
+    for &sig in &[SIGINT, SIGQUIT] {
+        try!(unsafe { sigaction(sig, &action) }
+             .or_else(|errno| err_exit!(errno, "sigaction")));
+    }
+
+    while QUIT_COUNT.load(Ordering::SeqCst) < num_quits {
+        pause();
+    }
+
+    println!("That's all folks!");
+    Ok(())
+}
@@ -0,0 +1,83 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::inet::*;
+use tlpi_rust::process::*;
+use tlpi_rust::socket::*;
+
+/// Port of the standard echo service.
+const SERVICE: &'static str = "7";
+
+/// Capacity of the buffers for data sent and received.
+const BUF_SIZE: usize = 100;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} host", argv[0]);
+    }
+
+    let socket = match connect_tcp(&argv[1], SERVICE) {
+        Ok(socket) => socket,
+        Err(InetError::Resolve(error)) => {
+            return fatal!("getaddrinfo: {}", error)
+        },
+        Err(InetError::System(errno)) => {
+            return err_exit!(errno, "Could not connect to {}", argv[1])
+        },
+    };
+
+    let mut buf = [0u8; BUF_SIZE];
+    match fork() {
+        Err(errno) => err_exit!(errno, "fork"),
+
+        // Child: read server's response, echo on stdout
+        Ok(ForkResult::Child) => {
+            loop {
+                let bytes_read = match socket.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(count) => count,
+                    Err(errno) => return err_exit!(errno, "read"),
+                };
+                match STDOUT.write(&buf[..bytes_read]) {
+                    Ok(count) if count == bytes_read => {},
+                    Ok(_) => return fatal!("partial/failed write"),
+                    Err(errno) => return err_exit!(errno, "write"),
+                };
+            }
+            Ok(())
+        },
+
+        // Parent: write contents of stdin to socket
+        Ok(ForkResult::Parent(_)) => {
+            loop {
+                let bytes_read = match STDIN.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(count) => count,
+                    Err(errno) => return err_exit!(errno, "read"),
+                };
+                match socket.write(&buf[..bytes_read]) {
+                    Ok(count) if count == bytes_read => {},
+                    Ok(_) => return fatal!("partial/failed write"),
+                    Err(errno) => return err_exit!(errno, "write"),
+                };
+            }
+
+            // Close writing channel, so server sees EOF; the child
+            // keeps reading the rest of the response
+            socket.shutdown(How::Write)
+                .or_else(|errno| err_exit!(errno, "shutdown"))
+        },
+    }
+}
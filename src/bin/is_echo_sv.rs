@@ -0,0 +1,67 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::inet::*;
+use tlpi_rust::server::serve_forking;
+use tlpi_rust::socket::*;
+
+/// Port of the standard echo service; binding to it requires
+/// privilege.
+const SERVICE: &'static str = "7";
+
+/// Capacity of the buffer for data echoed back to clients.
+const BUF_SIZE: usize = 4096;
+
+/// Maximum number of pending connections.
+const BACKLOG: i32 = 10;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 1 {
+        return usage_err!("{}", argv[0]);
+    }
+
+    let listen_socket = match listen_tcp(SERVICE, BACKLOG) {
+        Ok((socket, _)) => socket,
+        Err(InetError::Resolve(error)) => {
+            return fatal!("getaddrinfo: {}", error)
+        },
+        Err(InetError::System(errno)) => {
+            return err_exit!(errno, "Could not create server socket")
+        },
+    };
+
+    let errno = serve_forking(&listen_socket, handle_request);
+    err_exit!(errno, "Failure in server loop")
+}
+
+/// Handles a client request: copies input from the socket back to the
+/// socket until end-of-file.
+fn handle_request(client: Socket, _: SockAddr) -> TlpiResult<()> {
+    let mut buf = [0u8; BUF_SIZE];
+    loop {
+        let bytes_read = match client.read(&mut buf) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(errno) => return err_exit!(errno, "read"),
+        };
+
+        match client.write(&buf[..bytes_read]) {
+            Ok(count) if count == bytes_read => {},
+            Ok(_) => return fatal!("partial/failed write"),
+            Err(errno) => return err_exit!(errno, "write"),
+        };
+    }
+
+    client.close().or_else(|errno| err_exit!(errno, "close"))
+}
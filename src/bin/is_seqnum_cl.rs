@@ -0,0 +1,70 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::inet::*;
+use tlpi_rust::read_line::*;
+
+/// Port number on which the server listens.
+const PORT_NUM: &'static str = "50000";
+
+/// Maximum length of a decimal integer line, including the newline.
+const INT_LEN: usize = 30;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv.len() > 3 || argv[1] == "--help" {
+        return usage_err!("{} server-host [sequence-len]", argv[0]);
+    }
+
+    let seq_len = match argv.get(2) {
+        Some(arg) => match u64::from_str(arg) {
+            Ok(len) if len > 0 => len,
+            _ => return cmd_line_err!("Invalid sequence-len: {}", arg),
+        },
+        None => 1,
+    };
+
+    let socket = match connect_tcp(&argv[1], PORT_NUM) {
+        Ok(socket) => socket,
+        Err(InetError::Resolve(error)) => {
+            return fatal!("getaddrinfo: {}", error)
+        },
+        Err(InetError::System(errno)) => {
+            return err_exit!(errno, "Could not connect socket to any address")
+        },
+    };
+
+    // Send requested sequence length, with terminating newline
+    let request = format!("{}\n", seq_len);
+    match socket.write(request.as_bytes()) {
+        Ok(count) if count == request.len() => {},
+        _ => return fatal!("Partial/failed write (seqLenStr)"),
+    };
+
+    // Read and display sequence number returned by server
+    let response = match LineReader::new(socket.as_fd(), INT_LEN)
+        .read_line()
+    {
+        Ok(Some(line)) => line,
+        Ok(None) => return fatal!("Unexpected EOF from server"),
+        Err(errno) => return err_exit!(errno, "readLine"),
+    };
+
+    print!("Sequence number: {}", String::from_utf8_lossy(&response.bytes));
+    if response.bytes.last() != Some(&b'\n') {
+        println!("");
+    }
+
+    socket.close().or_else(|errno| err_exit!(errno, "close"))
+}
@@ -0,0 +1,95 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::inet::*;
+use tlpi_rust::read_line::*;
+
+/// Port number on which the server listens.
+const PORT_NUM: &'static str = "50000";
+
+/// Maximum length of a decimal integer line, including the newline.
+const INT_LEN: usize = 30;
+
+/// Maximum number of pending connections.
+const BACKLOG: i32 = 50;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 2 || argv.get(1).map_or(false, |arg| arg == "--help") {
+        return usage_err!("{} [init-seq-num]", argv[0]);
+    }
+
+    let mut seq_num = match argv.get(1) {
+        Some(arg) => match u64::from_str(arg) {
+            Ok(num) => num,
+            Err(_) => return cmd_line_err!("Invalid init-seq-num: {}", arg),
+        },
+        None => 0,
+    };
+
+    let listen_socket = match listen_tcp(PORT_NUM, BACKLOG) {
+        Ok((socket, _)) => socket,
+        Err(InetError::Resolve(error)) => {
+            return fatal!("getaddrinfo: {}", error)
+        },
+        Err(InetError::System(errno)) => {
+            return err_exit!(errno, "Could not bind socket to any address")
+        },
+    };
+
+    // Handle clients iteratively
+    loop {
+        let (client, addr) = match listen_socket.accept() {
+            Ok(connection) => connection,
+            Err(errno) => {
+                let _: TlpiResult<()> = err_exit!(errno, "accept");
+                continue;
+            },
+        };
+
+        println!("Connection from {}", addr);
+
+        // Read client request, send sequence number back
+        let request = match LineReader::new(client.as_fd(), INT_LEN)
+            .read_line()
+        {
+            Ok(Some(line)) => line,
+            _ => {
+                let _ = client.close();
+                continue; // Failed read; skip request
+            },
+        };
+
+        let text = String::from_utf8_lossy(&request.bytes);
+        let req_len = match u64::from_str(text.trim_right()) {
+            Ok(len) if len > 0 => len,
+            _ => {
+                let _ = client.close();
+                continue; // Watch for misbehaving clients
+            },
+        };
+
+        let response = format!("{}\n", seq_num);
+        match client.write(response.as_bytes()) {
+            Ok(count) if count == response.len() => {},
+            _ => { let _: TlpiResult<()> = fatal!("Error on write"); },
+        };
+
+        if let Err(errno) = client.close() {
+            let _: TlpiResult<()> = err_exit!(errno, "close");
+        }
+
+        seq_num += req_len;
+    }
+}
@@ -0,0 +1,87 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::time::Instant;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+
+/// Most bytes to ask the kernel to copy in each call.
+const CHUNK_SIZE: usize = 1 << 30;
+
+fn main() {
+    exit_with_status!(main_with_io());
+}
+
+fn main_with_io() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 3 || argv[1] == "--help" {
+        return usage_err!("{} old-file new-file", argv[0]);
+    }
+
+    let empty_perms = FilePerms::empty();
+    let input_fd = try!(
+        FileDescriptor::open(argv[1].clone(), O_RDONLY, empty_perms)
+            .or_else(|errno| err_exit!(errno, "opening file {}", argv[1]))
+    );
+    let open_flags = O_CREAT | O_WRONLY | O_TRUNC;
+    let file_perms = S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH; // rw-r--r--
+    let output_fd = try!(
+        FileDescriptor::open(argv[2].clone(), open_flags, file_perms)
+            .or_else(|errno| err_exit!(errno, "opening file {}", argv[2]))
+    );
+
+    let start = Instant::now();
+    let (method, total) = try!(kernel_copy(&input_fd, &output_fd));
+    let elapsed = start.elapsed();
+
+    try!(input_fd.close().or_else(|errno| err_exit!(errno, "close input")));
+    try!(output_fd.close().or_else(|errno| err_exit!(errno, "close output")));
+
+    let secs = elapsed.as_secs() as f64 +
+        elapsed.subsec_nanos() as f64 / 1e9;
+    let rate = total as f64 / (1 << 20) as f64 / secs;
+    println!("Copied {} bytes with {} in {:.3} s ({:.1} MiB/s)",
+             total, method, secs, rate);
+    Ok(())
+}
+
+/// Copies all of `input_fd` to `output_fd` without passing the data
+/// through user space, returning the system call used and the number
+/// of bytes copied.
+///
+/// `copy_file_range()` is tried first; if the kernel or file systems
+/// don't support it, `sendfile()` is used instead.
+fn kernel_copy(
+    input_fd: &FileDescriptor, output_fd: &FileDescriptor
+) -> TlpiResult<(&'static str, u64)> {
+    let mut total = 0;
+    loop {
+        match copy_file_range(input_fd, None, output_fd, None, CHUNK_SIZE) {
+            Ok(0) => return Ok(("copy_file_range()", total)),
+            Ok(count) => total += count as u64,
+            // Unsupported; nothing has been copied yet, so start over
+            Err(errno) if total == 0 && is_unsupported(errno) => break,
+            Err(errno) => return err_exit!(errno, "copy_file_range"),
+        }
+    }
+
+    loop {
+        match sendfile(output_fd, input_fd, None, CHUNK_SIZE) {
+            Ok(0) => return Ok(("sendfile()", total)),
+            Ok(count) => total += count as u64,
+            Err(errno) => return err_exit!(errno, "sendfile"),
+        }
+    }
+}
+
+/// Whether `errno`, from `copy_file_range()`, means that the call
+/// can't be used for these files.
+fn is_unsupported(errno: Errno) -> bool {
+    errno == ENOSYS || errno == EXDEV || errno == EINVAL ||
+        errno == EOPNOTSUPP
+}
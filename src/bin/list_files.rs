@@ -0,0 +1,149 @@
+
+#![feature(libc, slice_splits)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+extern crate getopts;
+use getopts::Options;
+
+use std::env;
+use tlpi_rust::dir::*;
+use tlpi_rust::err::*;
+use tlpi_rust::stat::*;
+use tlpi_rust::users::*;
+
+/// How each entry is displayed.
+#[derive(Clone, Copy, Debug)]
+struct Config {
+    /// List entries whose names start with `.`, including `.` and `..`.
+    show_all: bool,
+    /// Show the attributes of each entry, as `ls -l` does.
+    long: bool,
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let (dir_paths, config) = try!(parse_args());
+
+    // Keep going after a failure, but still report it in the exit
+    // status
+    let mut result = Ok(());
+    for dir_path in &dir_paths {
+        if let Err(err) = list_files(dir_path, config) {
+            result = Err(err);
+        }
+    }
+    result
+}
+
+/// Lists the entries of the directory `dir_path`.
+fn list_files(dir_path: &str, config: Config) -> TlpiResult<()> {
+    let dir = try!(Dir::open(dir_path).or_else(|errno| {
+        err_exit!(errno, "opendir failed on '{}'", dir_path)
+    }));
+
+    // The current directory is implied; others prefix each entry
+    let is_current = dir_path == ".";
+    for entry in dir {
+        let entry = try!(entry.or_else(|errno| {
+            err_exit!(errno, "readdir failed on '{}'", dir_path)
+        }));
+        if !config.show_all && entry.name.starts_with('.') {
+            continue;
+        }
+
+        let path = if is_current {
+            entry.name.clone()
+        } else {
+            format!("{}/{}", dir_path, entry.name)
+        };
+        if config.long {
+            try!(display_long(&path));
+        } else {
+            println!("{}", path);
+        }
+    }
+    Ok(())
+}
+
+/// Displays the attributes of the file `path`, without following it
+/// if it is a symbolic link, followed by its pathname.
+fn display_long(path: &str) -> TlpiResult<()> {
+    let sb = try!(lstat(path).or_else(|errno| {
+        err_exit!(errno, "lstat failed on '{}'", path)
+    }));
+    let owner = try!(user_name_from_id(sb.uid).or_else(|errno| {
+        err_exit!(errno, "getpwuid")
+    }));
+    let group = try!(group_name_from_id(sb.gid).or_else(|errno| {
+        err_exit!(errno, "getgrgid")
+    }));
+
+    println!("{}{} {:>3} {:<8} {:<8} {:>8} {} {}",
+             type_char(sb.file_type), sb.perms.to_symbolic_string(true),
+             sb.nlink, owner.unwrap_or(sb.uid.to_string()),
+             group.unwrap_or(sb.gid.to_string()), sb.size,
+             sb.mtime.format_local("%b %e %H:%M"), path);
+    Ok(())
+}
+
+/// The letter that `ls -l` uses for `file_type`.
+fn type_char(file_type: FileType) -> char {
+    match file_type {
+        FileType::Regular => '-',
+        FileType::Directory => 'd',
+        FileType::Symlink => 'l',
+        FileType::Fifo => 'p',
+        FileType::Socket => 's',
+        FileType::CharDevice => 'c',
+        FileType::BlockDevice => 'b',
+        FileType::Unknown => '?',
+    }
+}
+
+fn parse_args() -> TlpiResult<(Vec<String>, Config)> {
+    let argv: Vec<_> = env::args().collect();
+    let opts = build_options();
+
+    let argv_tail = match argv.split_first() {
+        Some((_, tail)) => tail,
+        _ => return cmd_line_err!("No program name provided!?"),
+    };
+
+    let matches = match opts.parse(argv_tail) {
+        Ok(m) => m,
+        Err(f) => {
+            let usage = opts.usage(&f.to_string());
+            return cmd_line_err!("{}", usage)
+        },
+    };
+
+    if matches.opt_present("help") {
+        let usage = format!("{} [options] [dir...]", argv[0]);
+        return usage_err!("{}", opts.usage(&usage));
+    }
+
+    let config = Config {
+        show_all: matches.opt_present("all"),
+        long: matches.opt_present("long"),
+    };
+    // With no arguments, list the current directory
+    let dir_paths = if matches.free.is_empty() {
+        vec![".".to_string()]
+    } else {
+        matches.free
+    };
+    Ok((dir_paths, config))
+}
+
+fn build_options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "display this usage message");
+    opts.optflag("a", "all", "include entries whose names start with .");
+    opts.optflag("l", "long", "show the attributes of each entry");
+    opts
+}
@@ -0,0 +1,44 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::setjmp::*;
+
+/// Where `f1()` and `f2()` jump back to.
+static mut ENV: JmpBuf = JMP_BUF_INIT;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let num_args = env::args().count();
+
+    match unsafe { setjmp(&mut ENV) } {
+        0 => {
+            // The first return, after saving the environment
+            println!("Calling f1() after initial setjmp()");
+            f1(num_args);
+        },
+        1 => println!("We jumped back from f1()"),
+        2 => println!("We jumped back from f2()"),
+        _ => {},
+    }
+    Ok(())
+}
+
+/// Jumps straight back if the program was given no arguments.
+fn f1(num_args: usize) {
+    if num_args == 1 {
+        unsafe { longjmp(&mut ENV, 1) };
+    }
+    f2();
+}
+
+fn f2() {
+    unsafe { longjmp(&mut ENV, 2) };
+}
@@ -0,0 +1,57 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::thread;
+use std::time::Duration;
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+use tlpi_rust::procfs::*;
+use tlpi_rust::signal::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    println!("Parent PID={}", getpid());
+
+    let child = match fork() {
+        Ok(ForkResult::Child) => {
+            println!("Child (PID={}) exiting", getpid());
+            _exit(EXIT_SUCCESS);
+        },
+        Ok(ForkResult::Parent(child)) => child,
+        Err(errno) => return err_exit!(errno, "fork"),
+    };
+
+    // Give the child a chance to terminate; until it is waited for, it
+    // remains in the process table as a zombie
+    thread::sleep(Duration::from_secs(1));
+    display_state(child);
+
+    // Even SIGKILL can't remove a zombie
+    try!(kill(child, SIGKILL).or_else(|errno| err_exit!(errno, "kill")));
+    thread::sleep(Duration::from_secs(1));
+    println!("After sending SIGKILL to zombie:");
+    display_state(child);
+
+    try!(waitpid(child, WaitFlags::empty()).or_else(|errno| {
+        err_exit!(errno, "waitpid")
+    }));
+    println!("After waiting for the zombie:");
+    display_state(child);
+    Ok(())
+}
+
+/// Shows the state of `pid` as listed in `/proc/PID/status`.
+fn display_state(pid: i32) {
+    match status(pid) {
+        Ok(status) => {
+            println!("    {} ({}): {}", pid, status.name, status.state)
+        },
+        Err(_) => println!("    {}: no longer in /proc", pid),
+    }
+}
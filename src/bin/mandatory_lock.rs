@@ -0,0 +1,127 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::thread;
+use std::time::{Duration, Instant};
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::mount::*;
+use tlpi_rust::process::*;
+
+/// How long the child holds its lock, in seconds.
+const HOLD_SECS: u64 = 3;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} file", argv[0]);
+    }
+    let path = &argv[1];
+
+    let fd = try!(FileDescriptor::open(
+        path.clone(), O_RDWR | O_CREAT | O_TRUNC, S_IRUSR | S_IWUSR | S_IRGRP
+    ).or_else(|errno| err_exit!(errno, "open {}", path)));
+    try!(fd.write(b"Mandatory locking test data\n")
+         .or_else(|errno| err_exit!(errno, "write")));
+    try!(fd.close().or_else(|errno| err_exit!(errno, "close")));
+
+    // Set-group-ID without group-execute marks the file for mandatory
+    // locking, which makes sense for no other purpose
+    try!(chmod(path, S_IRUSR | S_IWUSR | S_IRGRP | S_ISGID)
+         .or_else(|errno| err_exit!(errno, "chmod")));
+
+    // The file system must also be mounted with `-o mand`. Linux 5.15
+    // dropped mandatory locking altogether, and ignores the option.
+    let mount = try!(find_mount(path).or_else(|errno| {
+        err_exit!(errno, "find_mount")
+    }));
+    let mut options = mount.options.iter().chain(&mount.super_options);
+    if !options.any(|option| option == "mand") {
+        println!("{} is not mounted with -o mand; expect the locks to be \
+                  advisory only", mount.target);
+    }
+
+    let (read_end, write_end) = try!(pipe().or_else(|errno| {
+        err_exit!(errno, "pipe")
+    }));
+
+    let child = match fork() {
+        Ok(ForkResult::Child) => {
+            let _ = read_end.close();
+            if let Err(errno) = hold_lock(path, write_end) {
+                let _: TlpiResult<()> = err_exit!(errno, "child");
+                _exit(EXIT_FAILURE);
+            }
+            // Terminating releases the lock
+            _exit(EXIT_SUCCESS);
+        },
+        Ok(ForkResult::Parent(child)) => child,
+        Err(errno) => return err_exit!(errno, "fork"),
+    };
+
+    // Wait until the child has placed its lock
+    try!(write_end.close().or_else(|errno| err_exit!(errno, "close")));
+    let mut buf = [0u8; 1];
+    try!(read_end.read(&mut buf).or_else(|errno| err_exit!(errno, "read")));
+    println!("Child {} holds a write lock on {}", child, path);
+
+    // With O_NONBLOCK, I/O that conflicts with a mandatory lock fails
+    // instead of blocking
+    let fd = try!(FileDescriptor::open(
+        path.clone(), O_RDWR | O_NONBLOCK, FilePerms::empty()
+    ).or_else(|errno| err_exit!(errno, "open {}", path)));
+    let mut buf = [0u8; 100];
+    display_result("Nonblocking read", fd.read(&mut buf));
+    display_result("Nonblocking write", fd.write(b"X"));
+    try!(fd.close().or_else(|errno| err_exit!(errno, "close")));
+
+    // Without it, the I/O waits for the lock to be released
+    let fd = try!(FileDescriptor::open(
+        path.clone(), O_RDWR, FilePerms::empty()
+    ).or_else(|errno| err_exit!(errno, "open {}", path)));
+    let start = Instant::now();
+    display_result("Blocking read", fd.read(&mut buf));
+    let elapsed = start.elapsed();
+    println!("    after {}.{:03} seconds",
+             elapsed.as_secs(), elapsed.subsec_nanos() / 1_000_000);
+    try!(fd.close().or_else(|errno| err_exit!(errno, "close")));
+
+    try!(waitpid(child, WaitFlags::empty()).or_else(|errno| {
+        err_exit!(errno, "waitpid")
+    }));
+    Ok(())
+}
+
+/// Locks the whole of `path` for writing, tells the parent through
+/// `sync_fd`, and keeps the lock for a while.
+fn hold_lock(path: &str, sync_fd: FileDescriptor) -> SysResult<()> {
+    let fd = try!(FileDescriptor::open(
+        path.to_string(), O_RDWR, FilePerms::empty()
+    ));
+    try!(fd.set_lock(&FileLock::whole_file(LockType::Write)));
+    try!(sync_fd.write(b"x"));
+    try!(sync_fd.close());
+    thread::sleep(Duration::from_secs(HOLD_SECS));
+    Ok(())
+}
+
+fn display_result(what: &str, result: SysResult<usize>) {
+    match result {
+        Ok(num_bytes) => println!("{}: transferred {} bytes",
+                                  what, num_bytes),
+        Err(errno) if errno == EAGAIN => {
+            println!("{}: failed with EAGAIN; the lock is mandatory", what)
+        },
+        Err(errno) => println!("{}: failed with {:?}", what, errno),
+    }
+}
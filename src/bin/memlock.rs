@@ -0,0 +1,96 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::memory::*;
+use tlpi_rust::sysconf::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 4 || argv[1] == "--help" {
+        return usage_err!(
+            "{} num-pages lock-page-step lock-page-len", argv[0]
+        );
+    }
+
+    let page_size = match sysconf(SysconfName::Pagesize) {
+        Ok(Some(size)) => size as usize,
+        Ok(None) => return fatal!("page size is indeterminate"),
+        Err(errno) => return err_exit!(errno, "sysconf(_SC_PAGESIZE)"),
+    };
+
+    let num_pages = try!(parse_count(&argv[1], "num-pages"));
+    let step_pages = try!(parse_count(&argv[2], "lock-page-step"));
+    let lock_pages = try!(parse_count(&argv[3], "lock-page-len"));
+
+    let len = num_pages * page_size;
+    let step_size = step_pages * page_size;
+    let lock_len = lock_pages * page_size;
+
+    let flags = MAP_SHARED | MAP_ANONYMOUS;
+    let region = match mmap(len, PROT_READ, flags, None, 0) {
+        Ok(region) => region,
+        Err(errno) => return err_exit!(errno, "mmap"),
+    };
+
+    println!("Allocated {} (0x{:x}) bytes starting at {:?}",
+             len, len, region.addr());
+
+    println!("Before mlock:");
+    try!(display_mincore(&region, page_size));
+
+    // Lock pages specified by command-line arguments into memory
+    let base = region.addr() as *const u8;
+    let mut offset = 0;
+    while offset + lock_len <= len {
+        let addr = unsafe { base.offset(offset as isize) };
+        match mlock(addr as *const _, lock_len) {
+            Err(errno) => return err_exit!(errno, "mlock"),
+            _ => {},
+        };
+        offset += step_size;
+    }
+
+    println!("After mlock:");
+    try!(display_mincore(&region, page_size));
+
+    region.munmap().or_else(|errno| err_exit!(errno, "munmap"))
+}
+
+/// Displays the residency of each page in `region`, 64 pages per
+/// line: `*` for resident pages and `.` for others.
+fn display_mincore(region: &MappedRegion, page_size: usize) -> TlpiResult<()> {
+    let residency = match mincore(region.addr(), region.len()) {
+        Ok(vec) => vec,
+        Err(errno) => return err_exit!(errno, "mincore"),
+    };
+
+    let base = region.addr() as usize;
+    for (page, &resident) in residency.iter().enumerate() {
+        if page % 64 == 0 {
+            if page > 0 { println!(""); }
+            print!("{:#x}: ", base + page * page_size);
+        }
+        print!("{}", if resident { '*' } else { '.' });
+    }
+    println!("");
+
+    Ok(())
+}
+
+fn parse_count(arg: &str, name: &str) -> TlpiResult<usize> {
+    match usize::from_str(arg) {
+        Ok(count) if count > 0 => Ok(count),
+        _ => cmd_line_err!("Invalid {}: {}", name, arg),
+    }
+}
@@ -0,0 +1,54 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::memory::*;
+use tlpi_rust::stat::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} file", argv[0]);
+    }
+
+    let fd = try!(
+        FileDescriptor::open(argv[1].clone(), O_RDONLY, FilePerms::empty())
+            .or_else(|errno| err_exit!(errno, "open {}", argv[1]))
+    );
+    let size = try!(
+        statx(Some(&fd), "", AT_EMPTY_PATH, STATX_SIZE)
+            .or_else(|errno| err_exit!(errno, "statx"))
+    ).size as usize;
+
+    // Mappings can't be empty, so an empty file prints nothing
+    if size == 0 {
+        return fd.close().or_else(|errno| err_exit!(errno, "close"));
+    }
+
+    // The mapping is rounded up to a whole number of pages, with the
+    // bytes past the end of the file reading as zero, so only `size`
+    // bytes are written out
+    let region = try!(
+        mmap(size, PROT_READ, MAP_PRIVATE, Some(&fd), 0)
+            .or_else(|errno| err_exit!(errno, "mmap"))
+    );
+    // The mapping remains valid after the file is closed
+    try!(fd.close().or_else(|errno| err_exit!(errno, "close")));
+
+    let contents = &region.as_slice().unwrap()[..size];
+    try!(STDOUT.write_all(contents)
+         .or_else(|errno| err_exit!(errno, "write")));
+
+    region.munmap().or_else(|errno| err_exit!(errno, "munmap"))
+}
@@ -0,0 +1,77 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::memory::*;
+use tlpi_rust::stat::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 3 || argv[1] == "--help" {
+        return usage_err!("{} source-file dest-file", argv[0]);
+    }
+
+    let empty_perms = FilePerms::empty();
+    let src_fd = try!(
+        FileDescriptor::open(argv[1].clone(), O_RDONLY, empty_perms)
+            .or_else(|errno| err_exit!(errno, "open {}", argv[1]))
+    );
+    let size = try!(
+        statx(Some(&src_fd), "", AT_EMPTY_PATH, STATX_SIZE)
+            .or_else(|errno| err_exit!(errno, "statx"))
+    ).size as usize;
+
+    // A shared mapping can only be written if the file is open for
+    // reading and writing
+    let flags = O_RDWR | O_CREAT | O_TRUNC;
+    let perms = S_IRUSR | S_IWUSR;
+    let dst_fd = try!(
+        FileDescriptor::open(argv[2].clone(), flags, perms)
+            .or_else(|errno| err_exit!(errno, "open {}", argv[2]))
+    );
+
+    // Mappings can't be empty, and there's nothing to copy anyway
+    if size > 0 {
+        try!(copy_mapped(&src_fd, &dst_fd, size));
+    }
+
+    try!(src_fd.close().or_else(|errno| err_exit!(errno, "close input")));
+    dst_fd.close().or_else(|errno| err_exit!(errno, "close output"))
+}
+
+/// Copies the first `size` bytes of `src_fd` to `dst_fd` by mapping
+/// both files into memory.
+fn copy_mapped(
+    src_fd: &FileDescriptor, dst_fd: &FileDescriptor, size: usize
+) -> TlpiResult<()> {
+    let src = try!(
+        mmap(size, PROT_READ, MAP_PRIVATE, Some(src_fd), 0)
+            .or_else(|errno| err_exit!(errno, "mmap source"))
+    );
+
+    // The destination must be as large as the mapping, or writing to
+    // it raises SIGBUS
+    try!(dst_fd.ftruncate(size as i64).or_else(|errno| {
+        err_exit!(errno, "ftruncate")
+    }));
+    let mut dst = try!(
+        mmap(size, PROT_READ | PROT_WRITE, MAP_SHARED, Some(dst_fd), 0)
+            .or_else(|errno| err_exit!(errno, "mmap destination"))
+    );
+
+    dst.as_mut_slice().unwrap().clone_from_slice(src.as_slice().unwrap());
+
+    try!(src.munmap().or_else(|errno| err_exit!(errno, "munmap source")));
+    dst.munmap().or_else(|errno| err_exit!(errno, "munmap destination"))
+}
@@ -0,0 +1,45 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env::args;
+use tlpi_rust::env::*;
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = args().collect();
+
+    if argv.len() > 1 && argv[1] == "--help" {
+        return usage_err!("{} [-c] [name=value...]", argv[0]);
+    }
+
+    // Start from an empty environment if asked to
+    let mut entries = &argv[1..];
+    if entries.first().map_or(false, |arg| arg == "-c") {
+        try!(clearenv().or_else(|errno| err_exit!(errno, "clearenv")));
+        entries = &entries[1..];
+    }
+
+    for entry in entries {
+        try!(putenv(entry).or_else(|errno| {
+            err_exit!(errno, "putenv: {}", entry)
+        }));
+    }
+
+    // Doesn't replace an existing definition of GREET
+    try!(setenv("GREET", "Hello world", false).or_else(|errno| {
+        err_exit!(errno, "setenv")
+    }));
+    try!(unsetenv("BYE").or_else(|errno| err_exit!(errno, "unsetenv")));
+
+    // Show the result using the environment as the new program sees it
+    let errno = execvp("printenv", &["printenv"]);
+    err_exit!(errno, "execvp")
+}
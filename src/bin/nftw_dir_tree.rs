@@ -0,0 +1,150 @@
+
+#![feature(libc, slice_splits)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+extern crate getopts;
+use getopts::Options;
+
+use std::env;
+use tlpi_rust::dir::*;
+use tlpi_rust::err::*;
+use tlpi_rust::walk::*;
+
+/// The file types counted for the summary, in the order reported.
+const TYPES: [(char, &'static str); 8] = [
+    ('-', "regular files"), ('d', "directories"),
+    ('l', "symbolic links"), ('p', "FIFOs"), ('s', "sockets"),
+    ('c', "character devices"), ('b', "block devices"),
+    ('?', "unknown or unreadable"),
+];
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let (root, flags) = try!(parse_args());
+
+    let mut counts = [0u64; 8];
+    try!(walk_tree(&root, flags, |entry| {
+        let type_char = type_char(entry);
+        display_entry(entry, type_char);
+
+        // Each file is visited once, even with `WALK_DEPTH`
+        let index = TYPES.iter().position(|&(c, _)| c == type_char);
+        counts[index.unwrap_or(TYPES.len() - 1)] += 1;
+        WalkControl::Continue
+    }).or_else(|errno| err_exit!(errno, "walk_tree")));
+
+    display_summary(&counts);
+    Ok(())
+}
+
+/// The letter that `ls -l` would show for the type of `entry`.
+fn type_char(entry: &WalkEntry) -> char {
+    let file_type = match entry.stat {
+        Some(sb) => sb.file_type,
+        None => return '?',
+    };
+    match file_type {
+        FileType::Regular => '-',
+        FileType::Directory => 'd',
+        FileType::Symlink => 'l',
+        FileType::Fifo => 'p',
+        FileType::Socket => 's',
+        FileType::CharDevice => 'c',
+        FileType::BlockDevice => 'b',
+        FileType::Unknown => '?',
+    }
+}
+
+/// Prints one line of the tree: the file type, how it was visited,
+/// its i-node number and its name, indented by its depth.
+fn display_entry(entry: &WalkEntry, type_char: char) {
+    let kind = match entry.kind {
+        WalkKind::File => "F  ",
+        WalkKind::Directory => "D  ",
+        WalkKind::DirectoryPost => "DP ",
+        WalkKind::Unreadable => "DNR",
+        WalkKind::NoStat => "NS ",
+        WalkKind::Symlink => "SL ",
+        WalkKind::DanglingSymlink => "SLN",
+    };
+    let ino = match entry.stat {
+        Some(sb) => format!("{:>7}", sb.ino),
+        None => format!("{:>7}", ""),
+    };
+    let indent = entry.level * 4;
+    println!("{} {}  {}  {:indent$}{}",
+             type_char, kind, ino, "", entry.name(), indent = indent);
+}
+
+/// Prints the number and percentage of each type of file seen.
+fn display_summary(counts: &[u64]) {
+    let total: u64 = counts.iter().fold(0, |sum, count| sum + count);
+    if total == 0 {
+        return;
+    }
+
+    println!("");
+    println!("Total files: {}", total);
+    for (&(_, name), &count) in TYPES.iter().zip(counts) {
+        let percent = count as f64 * 100.0 / total as f64;
+        println!("    {:<22} {:>7} ({:5.1}%)", name, count, percent);
+    }
+}
+
+fn parse_args() -> TlpiResult<(String, WalkFlags)> {
+    let argv: Vec<_> = env::args().collect();
+    let opts = build_options();
+
+    let argv_tail = match argv.split_first() {
+        Some((_, tail)) => tail,
+        _ => return cmd_line_err!("No program name provided!?"),
+    };
+
+    // Mutable so we can move out the path
+    let mut matches = match opts.parse(argv_tail) {
+        Ok(m) => m,
+        Err(f) => {
+            let usage = opts.usage(&f.to_string());
+            return cmd_line_err!("{}", usage)
+        },
+    };
+
+    if matches.opt_present("help") {
+        let usage = format!("{} [options] [dir]", argv[0]);
+        return usage_err!("{}", opts.usage(&usage));
+    }
+
+    let mut flags = WalkFlags::empty();
+    if matches.opt_present("depth") {
+        flags.insert(WALK_DEPTH);
+    }
+    if matches.opt_present("mount") {
+        flags.insert(WALK_MOUNT);
+    }
+    if matches.opt_present("physical") {
+        flags.insert(WALK_PHYSICAL);
+    }
+
+    match matches.free.len() {
+        0 => Ok((".".to_string(), flags)),
+        1 => Ok((matches.free.swap_remove(0), flags)),
+        _ => {
+            let usage = opts.usage("At most one directory may be given");
+            cmd_line_err!("{}", usage)
+        },
+    }
+}
+
+fn build_options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "display this usage message");
+    opts.optflag("d", "depth", "visit directories after their contents");
+    opts.optflag("m", "mount", "don't cross into other file systems");
+    opts.optflag("p", "physical", "don't follow symbolic links");
+    opts
+}
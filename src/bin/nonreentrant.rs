@@ -0,0 +1,84 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+extern crate libc;
+
+use std::env;
+use std::ffi::{CStr, CString};
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+use libc::{c_char, c_int};
+use tlpi_rust::err::*;
+use tlpi_rust::signal::*;
+
+/// The string that the handler encrypts.
+static mut HANDLER_STR: *const c_char = 0 as *const c_char;
+
+/// How many times the handler has been called.
+static HANDLED: AtomicUsize = ATOMIC_USIZE_INIT;
+
+extern "C" fn handler(_sig: c_int) {
+    // UNSAFE: `crypt()` returns its result in a static buffer, which
+    // this call may overwrite while `main_with_result()` is reading it
+    unsafe { crypt(HANDLER_STR, SALT.as_ptr() as *const c_char) };
+    HANDLED.fetch_add(1, Ordering::SeqCst);
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 3 || argv[1] == "--help" {
+        return usage_err!("{} str1 str2", argv[0]);
+    }
+
+    // Panic if the arguments contain nul chars; crude but good enough
+    let str1 = CString::new(argv[1].clone()).unwrap();
+    let str2 = CString::new(argv[2].clone()).unwrap();
+    unsafe { HANDLER_STR = str2.as_ptr() };
+
+    // Copy the encrypted form of `str1`, before the handler can
+    // overwrite it
+    let cr1 = try!(encrypt(&str1));
+
+    let action = SigAction::new(SigHandler::Handler(handler));
+    try!(unsafe { sigaction(SIGINT, &action) }
+         .or_else(|errno| err_exit!(errno, "sigaction")));
+
+    // Repeatedly encrypt `str1`; whenever the handler runs in between
+    // the call and the comparison, the result will be that of `str2`
+    let mut mismatch = 0;
+    for call_num in 1.. {
+        let result = unsafe {
+            crypt(str1.as_ptr(), SALT.as_ptr() as *const c_char)
+        };
+        if result.is_null() || unsafe { CStr::from_ptr(result) } != &*cr1 {
+            mismatch += 1;
+            println!("Mismatch on call {} (mismatch={} handled={})",
+                     call_num, mismatch, HANDLED.load(Ordering::SeqCst));
+        }
+    }
+    Ok(())
+}
+
+fn encrypt(key: &CStr) -> TlpiResult<CString> {
+    let result = unsafe {
+        crypt(key.as_ptr(), SALT.as_ptr() as *const c_char)
+    };
+    if result.is_null() {
+        return err_exit!(Errno::last(), "crypt");
+    }
+    Ok(unsafe { CStr::from_ptr(result) }.to_owned())
+}
+
+/// The salt for the traditional DES-based encryption.
+const SALT: &'static [u8] = b"xx\0";
+
+#[link(name = "crypt")]
+extern {
+    fn crypt(key: *const c_char, salt: *const c_char) -> *mut c_char;
+}
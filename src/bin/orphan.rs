@@ -0,0 +1,41 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::thread;
+use std::time::Duration;
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let parent = getpid();
+
+    match fork() {
+        Ok(ForkResult::Child) => {
+            println!("Child (PID={}): my parent is PID {}", getpid(), parent);
+
+            // Once the parent terminates, the child is adopted by init,
+            // or by the nearest subreaper ancestor
+            while getppid() == parent {
+                thread::sleep(Duration::from_millis(100));
+            }
+            println!("Child (PID={}): orphaned; my parent is now PID {}",
+                     getpid(), getppid());
+            _exit(EXIT_SUCCESS);
+        },
+        Ok(ForkResult::Parent(child)) => {
+            // Give the child time to report before leaving it behind
+            thread::sleep(Duration::from_secs(1));
+            println!("Parent (PID={}): exiting without waiting for child \
+                      {}", parent, child);
+        },
+        Err(errno) => return err_exit!(errno, "fork"),
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+extern crate libc;
+
+use std::env;
+use std::thread;
+use std::time::Duration;
+use libc::c_int;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::signal::*;
+
+extern "C" fn handler(_sig: c_int) {
+    // `println!()` isn't async-signal-safe: it locks standard output,
+    // which the main loop may already hold. A bare `write()` is safe.
+    let _ = STDOUT.write(b"Ouch!\n");
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 1 {
+        return usage_err!("{}", argv[0]);
+    }
+
+    let action = SigAction::new(SigHandler::Handler(handler));
+    try!(unsafe { sigaction(SIGINT, &action) }
+         .or_else(|errno| err_exit!(errno, "sigaction")));
+
+    // Loop slowly, so that the handler's output stands out
+    for j in 0.. {
+        println!("{}", j);
+        thread::sleep(Duration::from_secs(3));
+    }
+    Ok(())
+}
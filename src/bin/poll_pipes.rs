@@ -0,0 +1,106 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::poll::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv.len() > 3 || argv[1] == "--help" {
+        return usage_err!("{} num-pipes [num-writes]", argv[0]);
+    }
+
+    let num_pipes = try!(parse_count(&argv[1], "num-pipes"));
+    let num_writes = match argv.get(2) {
+        Some(arg) => try!(parse_count(arg, "num-writes")),
+        None => 1,
+    };
+
+    // Create the number of pipes specified on command line
+    let mut pipes = Vec::new();
+    for j in 0..num_pipes {
+        match pipe() {
+            Ok(ends) => pipes.push(ends),
+            Err(errno) => return err_exit!(errno, "pipe {}", j),
+        };
+    }
+
+    // Perform specified number of writes to random pipes
+    let mut random = Random::seeded();
+    for _ in 0..num_writes {
+        let (ref read_end, ref write_end) = pipes[random.below(num_pipes)];
+        println!(
+            "Writing to fd: {:3} (read fd: {:3})",
+            write_end.as_raw(), read_end.as_raw()
+        );
+        match write_end.write(b"a") {
+            Ok(1) => {},
+            Ok(_) => return fatal!("partial/failed write"),
+            Err(errno) => return err_exit!(errno, "write {}",
+                                           write_end.as_raw()),
+        };
+    }
+
+    // Build the file descriptor list to be supplied to poll(). This
+    // list is set to contain the file descriptors for the read ends of
+    // all of the pipes.
+    let mut poll_fds: Vec<_> = pipes.iter().map(|&(ref read_end, _)| {
+        PollFd::new(read_end, POLLIN)
+    }).collect();
+
+    let ready = match poll(&mut poll_fds, Some(Duration::from_secs(0))) {
+        Ok(ready) => ready,
+        Err(errno) => return err_exit!(errno, "poll"),
+    };
+    println!("poll() returned: {}", ready);
+
+    // Check which pipes have data available for reading
+    for (j, poll_fd) in poll_fds.iter().enumerate() {
+        if poll_fd.revents().contains(POLLIN) {
+            println!("Readable: {} {:3}", j, poll_fd.fd());
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_count(arg: &str, name: &str) -> TlpiResult<usize> {
+    match usize::from_str(arg) {
+        Ok(count) if count > 0 => Ok(count),
+        _ => cmd_line_err!("Invalid {}: {}", name, arg),
+    }
+}
+
+/// A simple pseudorandom number generator (xorshift), to choose the
+/// pipes to write to.
+struct Random(u64);
+
+impl Random {
+
+    /// Creates a generator seeded from the current time.
+    fn seeded() -> Random {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        Random(now.as_secs() ^ now.subsec_nanos() as u64 | 1)
+    }
+
+    /// Returns a number in the range `0..limit`.
+    fn below(&mut self, limit: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % limit as u64) as usize
+    }
+
+}
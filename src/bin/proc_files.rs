@@ -0,0 +1,66 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+extern crate libc;
+
+use std::env;
+use std::str::FromStr;
+use libc::pid_t;
+use tlpi_rust::err::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::procfs::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} {{pid | path}}", argv[0]);
+    }
+
+    match pid_t::from_str(&argv[1]) {
+        Ok(pid) => list_open_files(pid),
+        Err(_) => find_openers(&argv[1]),
+    }
+}
+
+/// Lists the open file descriptors of the process `pid`.
+fn list_open_files(pid: pid_t) -> TlpiResult<()> {
+    let files = try!(open_files(pid).or_else(|errno| {
+        err_exit!(errno, "/proc/{}/fd", pid)
+    }));
+    for file in files {
+        println!("{:>4} -> {}", file.fd, file.target);
+    }
+    Ok(())
+}
+
+/// Lists every process, and each of its file descriptors, that has the
+/// file `path` open.
+fn find_openers(path: &str) -> TlpiResult<()> {
+    // The links in /proc/PID/fd hold absolute, symlink-free pathnames
+    let path = try!(realpath(path).or_else(|errno| {
+        err_exit!(errno, "realpath: {}", path)
+    }));
+
+    let mut pids = try!(pids().or_else(|errno| err_exit!(errno, "/proc")));
+    pids.sort();
+
+    for pid in pids {
+        // Skip processes that have terminated, or whose descriptors
+        // can't be read without privilege
+        let files = match open_files(pid) {
+            Ok(files) => files,
+            Err(_) => continue,
+        };
+        for file in files.iter().filter(|file| file.target == path) {
+            println!("{:>6} {:>4}", pid, file.fd);
+        }
+    }
+    Ok(())
+}
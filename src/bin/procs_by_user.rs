@@ -0,0 +1,43 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::procfs::*;
+use tlpi_rust::users::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} user", argv[0]);
+    }
+
+    let uid = match user_id_from_name(&argv[1]) {
+        Ok(Some(uid)) => uid,
+        Ok(None) => return cmd_line_err!("No such user: {}", argv[1]),
+        Err(errno) => return err_exit!(errno, "getpwnam"),
+    };
+
+    let mut pids = try!(pids().or_else(|errno| err_exit!(errno, "/proc")));
+    pids.sort();
+
+    for pid in pids {
+        // The process may have terminated since the directory was read
+        let status = match status(pid) {
+            Ok(status) => status,
+            Err(_) => continue,
+        };
+        if status.uids.real == uid {
+            println!("{:>6} {}", status.pid, status.name);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::sched::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 3 || argv[1] == "--help" {
+        return usage_err!("{} policy priority [pid...]\n    \
+                           policy is 'r' (RR), 'f' (FIFO), 'o' (OTHER), \
+                           'b' (BATCH) or 'i' (IDLE)", argv[0]);
+    }
+
+    let policy = match &argv[1][..] {
+        "r" => SchedPolicy::RoundRobin,
+        "f" => SchedPolicy::Fifo,
+        "o" => SchedPolicy::Other,
+        "b" => SchedPolicy::Batch,
+        "i" => SchedPolicy::Idle,
+        _ => return cmd_line_err!("Invalid policy: {}", argv[1]),
+    };
+    let priority = match i32::from_str(&argv[2]) {
+        Ok(priority) => priority,
+        Err(_) => return cmd_line_err!("Invalid priority: {}", argv[2]),
+    };
+
+    // With no process IDs, change the calling process
+    let pids = if argv.len() == 3 { vec!["0".to_string()] } else {
+        argv[3..].to_vec()
+    };
+    for arg in &pids {
+        let pid = match i32::from_str(arg) {
+            Ok(pid) => pid,
+            Err(_) => return cmd_line_err!("Invalid pid: {}", arg),
+        };
+        try!(sched_setscheduler(pid, policy, priority).or_else(|errno| {
+            err_exit!(errno, "sched_setscheduler: {}", pid)
+        }));
+    }
+    Ok(())
+}
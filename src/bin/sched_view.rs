@@ -0,0 +1,37 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::sched::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv[1] == "--help" {
+        return usage_err!("{} pid...", argv[0]);
+    }
+
+    for arg in &argv[1..] {
+        let pid = match i32::from_str(arg) {
+            Ok(pid) => pid,
+            Err(_) => return cmd_line_err!("Invalid pid: {}", arg),
+        };
+        let policy = try!(sched_getscheduler(pid).or_else(|errno| {
+            err_exit!(errno, "sched_getscheduler: {}", pid)
+        }));
+        let priority = try!(sched_getparam(pid).or_else(|errno| {
+            err_exit!(errno, "sched_getparam: {}", pid)
+        }));
+        println!("{}: {:<5} {:>2}", pid, policy.name(), priority);
+    }
+    Ok(())
+}
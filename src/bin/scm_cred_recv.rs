@@ -0,0 +1,100 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fs::unlink;
+use tlpi_rust::socket::*;
+
+/// Pathname of the receiver's listening socket.
+const SOCK_PATH: &'static str = "/tmp/scm_cred";
+
+/// Capacity of the buffer for received data.
+const BUF_SIZE: usize = 100;
+
+/// Maximum number of pending connections.
+const BACKLOG: i32 = 5;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 1 {
+        return usage_err!("{}", argv[0]);
+    }
+
+    // Remove any socket file left behind by a previous run
+    match unlink(SOCK_PATH) {
+        Err(errno) if errno != ENOENT => {
+            return err_exit!(errno, "remove-{}", SOCK_PATH)
+        },
+        _ => {},
+    };
+
+    let listen_socket = match unix_stream_listen(SOCK_PATH, BACKLOG) {
+        Ok(socket) => socket,
+        Err(errno) => return err_exit!(errno, "listening on {}", SOCK_PATH),
+    };
+
+    // Enabled on the listening socket so that accepted sockets inherit
+    // it before the client can send anything
+    if let Err(errno) = listen_socket.set_pass_credentials(true) {
+        return err_exit!(errno, "setsockopt");
+    }
+
+    let (socket, _) = match listen_socket.accept() {
+        Ok(connection) => connection,
+        Err(errno) => return err_exit!(errno, "accept"),
+    };
+
+    // Receive the data and the accompanying credentials
+    let mut data = [0u8; BUF_SIZE];
+    let message = {
+        let mut bufs = [&mut data[..]];
+        let space = credentials_space();
+        match socket.recvmsg(&mut bufs, space, MsgFlags::empty()) {
+            Ok(message) => message,
+            Err(errno) => return err_exit!(errno, "recvmsg"),
+        }
+    };
+    println!("recvmsg() returned {}", message.bytes);
+    println!(
+        "Received data = {}",
+        String::from_utf8_lossy(&data[..message.bytes])
+    );
+
+    let mut received = None;
+    for control in message.control {
+        match control {
+            ReceivedControl::Credentials(credentials) => {
+                received = Some(credentials);
+            },
+            _ => return fatal!("unexpected control message"),
+        }
+    }
+    match received {
+        Some(credentials) => println!(
+            "Received credentials pid={}, uid={}, gid={}",
+            credentials.pid, credentials.uid, credentials.gid
+        ),
+        None => return fatal!("no credentials received"),
+    };
+
+    // The peer's credentials are also available without any
+    // cooperation on its part
+    match socket.peer_credentials() {
+        Ok(credentials) => println!(
+            "Credentials from SO_PEERCRED: pid={}, euid={}, egid={}",
+            credentials.pid, credentials.uid, credentials.gid
+        ),
+        Err(errno) => return err_exit!(errno, "getsockopt"),
+    };
+
+    socket.close().or_else(|errno| err_exit!(errno, "close"))
+}
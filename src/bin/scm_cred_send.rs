@@ -0,0 +1,67 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::socket::*;
+
+/// Pathname of the receiver's listening socket.
+const SOCK_PATH: &'static str = "/tmp/scm_cred";
+
+/// The data sent along with the credentials.
+const DATA: &'static str = "12345";
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 4 || argv.get(1).map_or(false, |arg| arg == "--help") {
+        return usage_err!("{} [pid [uid [gid]]]", argv[0]);
+    }
+
+    // Send our own credentials, unless others were requested; only a
+    // privileged process can send someone else's
+    let mut credentials = Credentials::current();
+    if let Some(arg) = argv.get(1) {
+        credentials.pid = try!(parse_id(arg, "pid"));
+    }
+    if let Some(arg) = argv.get(2) {
+        credentials.uid = try!(parse_id(arg, "uid"));
+    }
+    if let Some(arg) = argv.get(3) {
+        credentials.gid = try!(parse_id(arg, "gid"));
+    }
+
+    let socket = match unix_stream_connect(SOCK_PATH) {
+        Ok(socket) => socket,
+        Err(errno) => return err_exit!(errno, "connect to {}", SOCK_PATH),
+    };
+
+    println!("Sending data = {}", DATA);
+    println!(
+        "Send credentials pid={}, uid={}, gid={}",
+        credentials.pid, credentials.uid, credentials.gid
+    );
+
+    let control = [ControlMessage::Credentials(credentials)];
+    let bytes = [DATA.as_bytes()];
+    let count = match socket.sendmsg(&bytes, &control, None, MsgFlags::empty())
+    {
+        Ok(count) => count,
+        Err(errno) => return err_exit!(errno, "sendmsg"),
+    };
+    println!("sendmsg() returned {}", count);
+
+    socket.close().or_else(|errno| err_exit!(errno, "close"))
+}
+
+fn parse_id<T: FromStr>(arg: &str, name: &str) -> TlpiResult<T> {
+    T::from_str(arg).or_else(|_| cmd_line_err!("Invalid {}: {}", name, arg))
+}
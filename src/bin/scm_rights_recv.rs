@@ -0,0 +1,112 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::fs::unlink;
+use tlpi_rust::socket::*;
+
+/// Pathname of the receiver's listening socket.
+const SOCK_PATH: &'static str = "/tmp/scm_rights";
+
+/// Capacity of the buffers for received data and file contents.
+const BUF_SIZE: usize = 100;
+
+/// Maximum number of pending connections.
+const BACKLOG: i32 = 5;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 1 {
+        return usage_err!("{}", argv[0]);
+    }
+
+    // Remove any socket file left behind by a previous run
+    match unlink(SOCK_PATH) {
+        Err(errno) if errno != ENOENT => {
+            return err_exit!(errno, "remove-{}", SOCK_PATH)
+        },
+        _ => {},
+    };
+
+    let listen_socket = match unix_stream_listen(SOCK_PATH, BACKLOG) {
+        Ok(socket) => socket,
+        Err(errno) => return err_exit!(errno, "listening on {}", SOCK_PATH),
+    };
+
+    let (socket, _) = match listen_socket.accept() {
+        Ok(connection) => connection,
+        Err(errno) => return err_exit!(errno, "accept"),
+    };
+
+    // Receive the data and the accompanying descriptor
+    let mut data = [0u8; BUF_SIZE];
+    let message = {
+        let mut bufs = [&mut data[..]];
+        match socket.recvmsg(&mut bufs, rights_space(1), MsgFlags::empty()) {
+            Ok(message) => message,
+            Err(errno) => return err_exit!(errno, "recvmsg"),
+        }
+    };
+    println!("recvmsg() returned {}", message.bytes);
+    println!(
+        "Received data = {}",
+        String::from_utf8_lossy(&data[..message.bytes])
+    );
+
+    if message.flags.contains(MSG_CTRUNC) {
+        return fatal!("control data was truncated");
+    }
+
+    let mut received = None;
+    for control in message.control {
+        match control {
+            ReceivedControl::Rights(mut fds) => {
+                if fds.len() != 1 || received.is_some() {
+                    return fatal!("expected exactly one descriptor");
+                }
+                received = fds.pop();
+            },
+            ReceivedControl::Credentials(_) => {
+                return fatal!("unexpected SCM_CREDENTIALS message");
+            },
+            ReceivedControl::Other(level, kind, _) => {
+                return fatal!("unexpected control message {}/{}", level, kind);
+            },
+        }
+    }
+    let fd = match received {
+        Some(fd) => fd,
+        None => return fatal!("no descriptor received"),
+    };
+    println!("Received FD {}", fd.as_raw());
+
+    // Having obtained the file descriptor, read the file's contents
+    // and print them on standard output
+    let mut buf = [0u8; BUF_SIZE];
+    loop {
+        let bytes_read = match fd.read(&mut buf) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(errno) => return err_exit!(errno, "read"),
+        };
+
+        match STDOUT.write(&buf[..bytes_read]) {
+            Ok(count) if count == bytes_read => {},
+            Ok(_) => return fatal!("partial/failed write"),
+            Err(errno) => return err_exit!(errno, "write"),
+        };
+    }
+
+    try!(fd.close().or_else(|errno| err_exit!(errno, "close")));
+    socket.close().or_else(|errno| err_exit!(errno, "close"))
+}
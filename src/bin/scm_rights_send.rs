@@ -0,0 +1,58 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::socket::*;
+
+/// Pathname of the receiver's listening socket.
+const SOCK_PATH: &'static str = "/tmp/scm_rights";
+
+/// The data sent along with the file descriptor.
+const DATA: &'static str = "12345";
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} file", argv[0]);
+    }
+
+    // Open the file named on the command line
+    let fd = match FileDescriptor::open(
+        argv[1].clone(), O_RDONLY, FilePerms::empty()
+    ) {
+        Ok(fd) => fd,
+        Err(errno) => return err_exit!(errno, "open {}", argv[1]),
+    };
+
+    let socket = match unix_stream_connect(SOCK_PATH) {
+        Ok(socket) => socket,
+        Err(errno) => return err_exit!(errno, "connect to {}", SOCK_PATH),
+    };
+
+    // At least one byte of real data must accompany the descriptor
+    println!("Sending data = {}", DATA);
+    println!("Sending FD {}", fd.as_raw());
+
+    let rights = [&fd];
+    let control = [ControlMessage::Rights(&rights)];
+    let bytes = [DATA.as_bytes()];
+    let count = match socket.sendmsg(&bytes, &control, None, MsgFlags::empty())
+    {
+        Ok(count) => count,
+        Err(errno) => return err_exit!(errno, "sendmsg"),
+    };
+    println!("sendmsg() returned {}", count);
+
+    try!(fd.close().or_else(|errno| err_exit!(errno, "close")));
+    socket.close().or_else(|errno| err_exit!(errno, "close"))
+}
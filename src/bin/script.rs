@@ -0,0 +1,115 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::process::*;
+use tlpi_rust::pty::*;
+use tlpi_rust::select::*;
+use tlpi_rust::tty::*;
+
+/// Capacity of the buffer for data relayed in each direction.
+const BUF_SIZE: usize = 256;
+
+/// File that the session is recorded in, if none is given.
+const DEFAULT_SCRIPT_FILE: &'static str = "typescript";
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 2 || (argv.len() > 1 && argv[1] == "--help") {
+        return usage_err!("{} [file]", argv[0]);
+    }
+    let script_path = match argv.get(1) {
+        Some(path) => path.clone(),
+        None => DEFAULT_SCRIPT_FILE.to_string(),
+    };
+
+    // The shell gets a terminal like the one we were started from
+    let orig_attrs = try!(
+        tcgetattr(&STDIN).or_else(|errno| err_exit!(errno, "tcgetattr"))
+    );
+    let window_size = try!(
+        get_window_size(&STDIN)
+            .or_else(|errno| err_exit!(errno, "ioctl-TIOCGWINSZ"))
+    );
+
+    let master = match pty_fork(Some(&orig_attrs), Some(&window_size)) {
+        Err(errno) => return err_exit!(errno, "pty_fork"),
+
+        // Child: execute a shell on the pty slave
+        Ok(PtyForkResult::Child) => {
+            let shell = env::var("SHELL").unwrap_or("/bin/sh".to_string());
+            let errno = execv(&shell, &[&shell[..]]);
+            return err_exit!(errno, "execv");
+        },
+
+        Ok(PtyForkResult::Parent { master, .. }) => master,
+    };
+
+    let flags = O_WRONLY | O_CREAT | O_TRUNC;
+    let perms = S_IRUSR | S_IWUSR | S_IRGRP | S_IWGRP | S_IROTH | S_IWOTH;
+    let script_fd = try!(
+        FileDescriptor::open(script_path.clone(), flags, perms)
+            .or_else(|errno| err_exit!(errno, "open {}", script_path))
+    );
+
+    // Keystrokes are passed to the shell unprocessed; the pty slave
+    // does the terminal processing instead
+    try!(set_raw(&STDIN).or_else(|errno| err_exit!(errno, "set_raw")));
+    let result = relay(&master, &script_fd);
+    try!(
+        tcsetattr(&STDIN, When::Now, &orig_attrs)
+            .or_else(|errno| err_exit!(errno, "tcsetattr"))
+    );
+    result
+}
+
+/// Copies the terminal input to `master`, and the output of `master`
+/// to both the terminal and `script_fd`, until the shell exits.
+fn relay(
+    master: &FileDescriptor, script_fd: &FileDescriptor
+) -> TlpiResult<()> {
+    let mut buf = [0u8; BUF_SIZE];
+    loop {
+        let mut in_fds = FdSet::new();
+        try!(in_fds.set(&STDIN).or_else(|errno| err_exit!(errno, "FD_SET")));
+        try!(in_fds.set(master).or_else(|errno| err_exit!(errno, "FD_SET")));
+
+        try!(
+            select(Some(&mut in_fds), None, None, None)
+                .or_else(|errno| err_exit!(errno, "select"))
+        );
+
+        // stdin --> pty
+        if in_fds.is_set(&STDIN) {
+            let bytes_read = match STDIN.read(&mut buf) {
+                Ok(0) | Err(_) => return Ok(()),
+                Ok(count) => count,
+            };
+            try!(master.write_all(&buf[..bytes_read])
+                .or_else(|errno| err_exit!(errno, "write (master)")));
+        }
+
+        // pty --> stdout + file; reading fails with EIO once the shell
+        // has exited and the slave is closed
+        if in_fds.is_set(master) {
+            let bytes_read = match master.read(&mut buf) {
+                Ok(0) | Err(_) => return Ok(()),
+                Ok(count) => count,
+            };
+            try!(STDOUT.write_all(&buf[..bytes_read])
+                .or_else(|errno| err_exit!(errno, "write (stdout)")));
+            try!(script_fd.write_all(&buf[..bytes_read])
+                .or_else(|errno| err_exit!(errno, "write (script file)")));
+        }
+    }
+}
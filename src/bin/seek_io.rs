@@ -21,7 +21,10 @@ fn main_with_result() -> TlpiResult<()> {
 
     if argv.len() < 3 || argv[1] == "--help" {
         return usage_err!(
-            "{} file {{r<length>|R<length>|w<string>|s<offset>}}...", argv[0]
+            "{} file {{r<length>|R<length>|w<string>|s<offset>[,<whence>]|\
+             o<offset>,<length>|O<offset>,<string>}}...\n\
+             where <whence> is set (the default), cur, end, data or hole",
+            argv[0]
         );
     }
 
@@ -50,7 +53,11 @@ fn main_with_result() -> TlpiResult<()> {
 enum Command<'a> {
     Read { byte_count: usize, format: ReadFormat },
     Write { text: &'a str },
-    Seek { offset: i64 },
+    Seek { offset: i64, whence: OffsetBase },
+    /// Reads with `pread()`, leaving the file offset unchanged.
+    PRead { offset: i64, byte_count: usize },
+    /// Writes with `pwrite()`, leaving the file offset unchanged.
+    PWrite { offset: i64, text: &'a str },
 }
 
 #[derive(Clone, Copy)]
@@ -71,30 +78,65 @@ impl<'a> Command<'a> {
             },
             Some(('w', arg)) => Ok(Write { text: arg }),
             Some(('s', arg)) => {
-                match i64::from_str_radix(arg, 10).ok() {
-                    Some(offset) => Ok(Seek { offset: offset }),
-                    _ => cmd_line_err!("Invalid offset: {}", s),
+                let (offset, whence) = split_at_comma(arg);
+                let offset = match i64::from_str_radix(offset, 10).ok() {
+                    Some(offset) => offset,
+                    _ => return cmd_line_err!("Invalid offset: {}", s),
+                };
+                match whence.map_or(Some(OffsetBase::SeekSet), parse_whence) {
+                    Some(whence) => {
+                        Ok(Seek { offset: offset, whence: whence })
+                    },
+                    _ => cmd_line_err!("Invalid whence: {}", s),
+                }
+            },
+            Some(('o', arg)) => {
+                let (offset, count) = split_at_comma(arg);
+                let offset = i64::from_str_radix(offset, 10).ok();
+                let count =
+                    count.and_then(|c| usize::from_str_radix(c, 10).ok());
+                match (offset, count) {
+                    (Some(offset), Some(count)) => {
+                        Ok(PRead { offset: offset, byte_count: count })
+                    },
+                    _ => cmd_line_err!("Invalid offset or length: {}", s),
                 }
             },
-            _ => cmd_line_err!("Argument must start with [rRws]: {:?}", s),
+            Some(('O', arg)) => {
+                let (offset, text) = split_at_comma(arg);
+                match (i64::from_str_radix(offset, 10).ok(), text) {
+                    (Some(offset), Some(text)) => {
+                        Ok(PWrite { offset: offset, text: text })
+                    },
+                    _ => cmd_line_err!("Invalid offset or string: {}", s),
+                }
+            },
+            _ => cmd_line_err!("Argument must start with [rRwsoO]: {:?}", s),
         }
     }
 
     fn execute(self, fd: &FileDescriptor) -> TlpiResult<()> {
         match self {
             Read { byte_count, format } => {
+                // Find out where the read starts, for labeling the dump
+                let offset = match fd.lseek(0, OffsetBase::SeekCur) {
+                    Ok(offset) => offset,
+                    Err(errno) => return err_exit!(errno, "lseek"),
+                };
                 let mut buf = vec![0u8; byte_count];
                 let num_read = match fd.read(&mut buf[..]) {
                     Ok(count) => count,
                     Err(errno) => return err_exit!(errno, "read"),
                 };
-
-                print!("{}: ", self);
-                if num_read == 0 {
-                    println!("end-of-file");
-                } else {
-                    display_bytes(&buf[..num_read], format);
-                }
+                self.display_read(&buf[..num_read], format, offset);
+            },
+            PRead { offset, byte_count } => {
+                let mut buf = vec![0u8; byte_count];
+                let num_read = match fd.pread(&mut buf[..], offset) {
+                    Ok(count) => count,
+                    Err(errno) => return err_exit!(errno, "pread"),
+                };
+                self.display_read(&buf[..num_read], Text, offset as u64);
             },
             Write { text } => {
                 let num_written = match fd.write(text.as_bytes()) {
@@ -103,18 +145,37 @@ impl<'a> Command<'a> {
                 };
                 println!("{}: wrote {} bytes", self, num_written);
             },
-            Seek { offset } => {
-                match fd.lseek(offset, OffsetBase::SeekSet) {
+            PWrite { offset, text } => {
+                let num_written = match fd.pwrite(text.as_bytes(), offset) {
+                    Ok(bytes) => bytes,
+                    Err(errno) => return err_exit!(errno, "pwrite")
+                };
+                println!("{}: wrote {} bytes", self, num_written);
+            },
+            Seek { offset, whence } => {
+                let new_offset = match fd.lseek(offset, whence) {
+                    Ok(new_offset) => new_offset,
                     Err(errno) => return err_exit!(errno, "lseek"),
-                    _ => {}
                 };
 
-                println!("{}: seek succeeded", self);
+                println!("{}: seek succeeded; offset is now {}",
+                         self, new_offset);
             },
         };
         Ok(())
     }
 
+    /// Prints the bytes read by this command, which start at `offset`
+    /// in the file, or end-of-file if there are none.
+    fn display_read(&self, bytes: &[u8], format: ReadFormat, offset: u64) {
+        print!("{}: ", self);
+        if bytes.len() == 0 {
+            println!("end-of-file");
+        } else {
+            display_bytes(bytes, format, offset);
+        }
+    }
+
 }
 
 impl<'a> core::fmt::Display for Command<'a> {
@@ -129,13 +190,54 @@ impl<'a> core::fmt::Display for Command<'a> {
                 write!(f, "{}{}", command_char, byte_count)
             },
             &Write { text } => write!(f, "w{}", text),
-            &Seek { offset } => write!(f, "s{}", offset),
+            &Seek { offset, whence: OffsetBase::SeekSet } => {
+                write!(f, "s{}", offset)
+            },
+            &Seek { offset, whence } => {
+                write!(f, "s{},{}", offset, whence_name(whence))
+            },
+            &PRead { offset, byte_count } => {
+                write!(f, "o{},{}", offset, byte_count)
+            },
+            &PWrite { offset, text } => write!(f, "O{},{}", offset, text),
         }
     }
 
 }
 
-fn display_bytes(bytes: &[u8], format: ReadFormat) {
+/// Splits a command argument at its first comma, if any.
+fn split_at_comma(arg: &str) -> (&str, Option<&str>) {
+    match arg.find(',') {
+        Some(index) => (&arg[..index], Some(&arg[index + 1..])),
+        None => (arg, None),
+    }
+}
+
+fn parse_whence(name: &str) -> Option<OffsetBase> {
+    match name {
+        "set" => Some(OffsetBase::SeekSet),
+        "cur" => Some(OffsetBase::SeekCur),
+        "end" => Some(OffsetBase::SeekEnd),
+        "data" => Some(OffsetBase::SeekData),
+        "hole" => Some(OffsetBase::SeekHole),
+        _ => None,
+    }
+}
+
+fn whence_name(whence: OffsetBase) -> &'static str {
+    match whence {
+        OffsetBase::SeekSet => "set",
+        OffsetBase::SeekCur => "cur",
+        OffsetBase::SeekEnd => "end",
+        OffsetBase::SeekData => "data",
+        OffsetBase::SeekHole => "hole",
+    }
+}
+
+/// How many bytes `display_bytes()` shows on each line of a hex dump.
+const DUMP_WIDTH: usize = 16;
+
+fn display_bytes(bytes: &[u8], format: ReadFormat, offset: u64) {
     match format {
         Text => {
             let buf_str = std::string::String::from_utf8_lossy(bytes);
@@ -143,12 +245,37 @@ fn display_bytes(bytes: &[u8], format: ReadFormat) {
                 let out_char = if c.is_control() { '\u{FFFD}' } else { c };
                 print!("{}", out_char);
             }
+            println!("");
         },
         Hex => {
-            for byte in bytes {
-                print!("{:0>2x} ", byte);
+            // Start the dump on its own line, so the columns line up
+            println!("");
+            for (index, line) in bytes.chunks(DUMP_WIDTH).enumerate() {
+                let line_offset = offset + (index * DUMP_WIDTH) as u64;
+                display_dump_line(line, line_offset);
             }
         }
     };
-    println!("");
+}
+
+/// Helper function; prints one line of a hex dump, in the style of
+/// `od -A d -t x1z`: the file offset of the first byte, up to
+/// `DUMP_WIDTH` bytes in hex, and a gutter showing the printable
+/// ones as ASCII.
+fn display_dump_line(bytes: &[u8], offset: u64) {
+    print!("{:>10} ", offset);
+    for byte in bytes {
+        print!(" {:0>2x}", byte);
+    }
+    // Pad a short final line, so its gutter lines up with the others
+    for _ in bytes.len()..DUMP_WIDTH {
+        print!("   ");
+    }
+
+    print!("  >");
+    for &byte in bytes {
+        let is_printable = byte >= 0x20 && byte < 0x7f;
+        print!("{}", if is_printable { byte as char } else { '.' });
+    }
+    println!("<");
 }
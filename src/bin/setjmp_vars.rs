@@ -0,0 +1,40 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use tlpi_rust::err::*;
+use tlpi_rust::setjmp::*;
+
+/// Where `do_jump()` jumps back to.
+static mut ENV: JmpBuf = JMP_BUF_INIT;
+
+/// A variable that lives in memory rather than on the stack.
+static mut SVAR: i32 = 0;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+// Build with and without `--release` to compare: with optimization,
+// the compiler may keep `nvar` in a register, whose value `longjmp()`
+// restores to the one saved by `setjmp()`
+fn main_with_result() -> TlpiResult<()> {
+    let mut nvar = 111;
+    unsafe { SVAR = 222 };
+
+    if unsafe { setjmp(&mut ENV) } == 0 {
+        nvar = 777;
+        unsafe { SVAR = 888 };
+        do_jump(nvar, unsafe { SVAR });
+    } else {
+        println!("After longjmp(): nvar={} svar={}", nvar, unsafe { SVAR });
+    }
+    Ok(())
+}
+
+fn do_jump(nvar: i32, svar: i32) {
+    println!("Inside do_jump(): nvar={} svar={}", nvar, svar);
+    unsafe { longjmp(&mut ENV, 1) };
+}
@@ -0,0 +1,94 @@
+
+#![feature(libc, slice_splits)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+extern crate getopts;
+use getopts::Options;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::creds::*;
+use tlpi_rust::err::*;
+use tlpi_rust::quota::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let (device, quota_type, id) = try!(parse_args());
+
+    let quota = try!(get_quota(&device, quota_type, id).or_else(|errno| {
+        err_exit!(errno, "quotactl(Q_GETQUOTA) on {}", device)
+    }));
+
+    let kind = if quota_type == QuotaType::User { "User" } else { "Group" };
+    println!("{} {} on {}:", kind, id, device);
+    println!("{:>8} {:>12} {:>12} {:>12} {:>12}",
+             "", "used", "soft limit", "hard limit", "grace ends");
+    if quota.valid.contains(QIF_SPACE | QIF_BLIMITS) {
+        println!("{:>8} {:>12} {:>12} {:>12} {:>12}",
+                 "KiB", (quota.cur_space + 1023) / 1024,
+                 quota.block_soft_limit, quota.block_hard_limit,
+                 quota.block_time);
+    }
+    if quota.valid.contains(QIF_INODES | QIF_ILIMITS) {
+        println!("{:>8} {:>12} {:>12} {:>12} {:>12}",
+                 "i-nodes", quota.cur_inodes, quota.inode_soft_limit,
+                 quota.inode_hard_limit, quota.inode_time);
+    }
+    Ok(())
+}
+
+fn parse_args() -> TlpiResult<(String, QuotaType, u32)> {
+    let argv: Vec<_> = env::args().collect();
+    let opts = build_options();
+
+    let argv_tail = match argv.split_first() {
+        Some((_, tail)) => tail,
+        _ => return cmd_line_err!("No program name provided!?"),
+    };
+
+    let matches = match opts.parse(argv_tail) {
+        Ok(m) => m,
+        Err(f) => {
+            let usage = opts.usage(&f.to_string());
+            return cmd_line_err!("{}", usage)
+        },
+    };
+
+    if matches.opt_present("help") {
+        let usage = format!("{} [options] <device> [id]", argv[0]);
+        return usage_err!("{}", opts.usage(&usage));
+    }
+
+    let group = matches.opt_present("group");
+    let quota_type = if group { QuotaType::Group } else { QuotaType::User };
+
+    // Default to the caller's own quota, which needs no privilege
+    let id = match matches.free.get(1) {
+        Some(id) => match u32::from_str(id) {
+            Ok(id) => id,
+            Err(_) => return cmd_line_err!("Invalid ID: {}", id),
+        },
+        None if group => getgid(),
+        None => getuid(),
+    };
+
+    match matches.free.len() {
+        1 | 2 => Ok((matches.free[0].clone(), quota_type, id)),
+        _ => {
+            let usage = opts.usage("A device argument is required");
+            cmd_line_err!("{}", usage)
+        },
+    }
+}
+
+fn build_options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "display this usage message");
+    opts.optflag("g", "group", "show a group quota instead of a user quota");
+    opts
+}
@@ -0,0 +1,87 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+extern crate libc;
+
+use std::env;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, ATOMIC_BOOL_INIT, Ordering};
+use std::thread;
+use std::time::Duration;
+use libc::c_int;
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+use tlpi_rust::signal::*;
+
+/// How many times each signal has been delivered.
+static mut SIG_COUNTS: [u64; NSIG as usize] = [0; NSIG as usize];
+
+/// Set when `SIGINT` is delivered, to end the program.
+static GOT_SIGINT: AtomicBool = ATOMIC_BOOL_INIT;
+
+extern "C" fn handler(sig: c_int) {
+    if sig == SIGINT.value() {
+        GOT_SIGINT.store(true, Ordering::SeqCst);
+    } else {
+        unsafe { SIG_COUNTS[sig as usize] += 1 };
+    }
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 2 || (argv.len() == 2 && argv[1] == "--help") {
+        return usage_err!("{} [block-time]", argv[0]);
+    }
+
+    println!("{}: PID is {}", argv[0], getpid());
+
+    // SIGKILL and SIGSTOP can't be caught, so ignore any failures
+    let action = SigAction::new(SigHandler::Handler(handler));
+    for value in 1..NSIG {
+        let _ = unsafe { sigaction(Signal::new(value), &action) };
+    }
+
+    // Signals raised while blocked are pending, but each standard
+    // signal is only queued once
+    if let Some(arg) = argv.get(1) {
+        let block_time = match u64::from_str(arg) {
+            Ok(secs) => secs,
+            Err(_) => return cmd_line_err!("Invalid block-time: {}", arg),
+        };
+
+        let prev_mask = try!(
+            pthread_sigmask(SigmaskHow::SetMask, &SigSet::full())
+                .or_else(|errno| err_exit!(errno, "pthread_sigmask"))
+        );
+        println!("{}: sleeping for {} seconds", argv[0], block_time);
+        thread::sleep(Duration::from_secs(block_time));
+
+        let pending = try!(sigpending().or_else(|errno| {
+            err_exit!(errno, "sigpending")
+        }));
+        println!("{}: pending signals are: {}", argv[0], pending);
+
+        try!(pthread_sigmask(SigmaskHow::SetMask, &prev_mask)
+             .or_else(|errno| err_exit!(errno, "pthread_sigmask")));
+    }
+
+    while !GOT_SIGINT.load(Ordering::SeqCst) {
+        // Wait for signals
+    }
+
+    for value in 1..NSIG {
+        let count = unsafe { SIG_COUNTS[value as usize] };
+        if count != 0 {
+            println!("{}: signal {} caught {} time{}", argv[0], value,
+                     count, if count == 1 { "" } else { "s" });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+use tlpi_rust::signal::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 4 || argv.len() > 5 || argv[1] == "--help" {
+        return usage_err!("{} pid num-sigs sig-num [sig-num-2]", argv[0]);
+    }
+
+    let pid = try!(parse_number::<i32>(&argv[1], "pid"));
+    let num_sigs = try!(parse_number::<u64>(&argv[2], "num-sigs"));
+    let sig = Signal::new(try!(parse_number(&argv[3], "sig-num")));
+
+    println!("{}: sending signal {} to process {} {} times",
+             argv[0], sig.value(), pid, num_sigs);
+    for _ in 0..num_sigs {
+        try!(kill(pid, sig).or_else(|errno| err_exit!(errno, "kill")));
+    }
+
+    // A different signal lets the receiver know that's all
+    if let Some(arg) = argv.get(4) {
+        let sig = Signal::new(try!(parse_number(arg, "sig-num-2")));
+        try!(kill(pid, sig).or_else(|errno| err_exit!(errno, "kill")));
+    }
+
+    println!("{}: exiting (my PID {})", argv[0], getpid());
+    Ok(())
+}
+
+fn parse_number<T: FromStr>(arg: &str, name: &str) -> TlpiResult<T> {
+    match T::from_str(arg) {
+        Ok(number) => Ok(number),
+        Err(_) => cmd_line_err!("Invalid {}: {}", name, arg),
+    }
+}
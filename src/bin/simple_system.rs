@@ -0,0 +1,75 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::process::*;
+use tlpi_rust::read_line::*;
+
+/// The longest command accepted, including the newline.
+const MAX_CMD_LEN: usize = 200;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let mut reader = LineReader::new(&STDIN, MAX_CMD_LEN);
+
+    loop {
+        // Write the prompt directly, since it has no newline to flush
+        // standard output's buffer
+        try!(STDOUT.write(b"Command: ").or_else(|errno| {
+            err_exit!(errno, "write")
+        }));
+
+        let line = match try!(reader.read_line().or_else(|errno| {
+            err_exit!(errno, "read")
+        })) {
+            Some(line) => line,
+            None => break,
+        };
+        if line.truncated {
+            println!("Command too long; at most {} bytes", MAX_CMD_LEN - 1);
+            continue;
+        }
+
+        let text = String::from_utf8_lossy(&line.bytes);
+        let command = text.trim_right_matches('\n');
+        if command.is_empty() {
+            continue;
+        }
+
+        match system(command) {
+            Ok(status) => display_status(status),
+            Err(errno) => return err_exit!(errno, "system"),
+        }
+    }
+
+    println!("");
+    Ok(())
+}
+
+/// Prints how the command terminated, as the values `system()`
+/// decodes.
+fn display_status(status: WaitStatus) {
+    match status {
+        WaitStatus::Exited(127) => {
+            println!("(Probably) could not invoke shell")
+        },
+        WaitStatus::Exited(code) => {
+            println!("child exited, status={}", code)
+        },
+        WaitStatus::Signaled(sig, core_dumped) => {
+            println!("child killed by signal {}{}", sig.value(),
+                     if core_dumped { " (core dumped)" } else { "" })
+        },
+        WaitStatus::Stopped(sig) => {
+            println!("child stopped by signal {}", sig.value())
+        },
+        WaitStatus::Continued => println!("child continued"),
+    }
+}
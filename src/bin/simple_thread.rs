@@ -0,0 +1,30 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use tlpi_rust::err::*;
+use tlpi_rust::thread::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let thread = try!(
+        pthread_create(|| {
+            let message = "Hello world\n";
+            print!("{}", message);
+            message.len()
+        }).or_else(|errno| err_exit!(errno, "pthread_create"))
+    );
+
+    println!("Message from main()");
+    let result = try!(
+        pthread_join(thread).or_else(|errno| err_exit!(errno, "pthread_join"))
+    );
+
+    println!("Thread returned {}", result);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+extern crate libc;
+
+use std::ffi::CStr;
+use libc::{c_char, c_void};
+use tlpi_rust::err::*;
+use tlpi_rust::fd::SysResult;
+use tlpi_rust::thread::*;
+
+/// Capacity of each thread's buffer for the error description.
+const MAX_ERROR_LEN: usize = 256;
+
+/// Ensures that `STRERROR_KEY` is created only once.
+static ONCE: Once = PTHREAD_ONCE_INIT;
+
+/// The key for each thread's buffer, once `create_key()` has been
+/// called; holds the error if the key couldn't be created.
+static mut STRERROR_KEY: Option<SysResult<Key>> = None;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let string = try!(strerror(EINVAL.value()));
+    println!("Main thread has called strerror()");
+
+    let thread = try!(
+        pthread_create(|| {
+            match strerror(EPERM.value()) {
+                Ok(string) => {
+                    println!(
+                        "Other thread: str ({:p}) = {}",
+                        string.as_ptr(), string
+                    );
+                    0
+                },
+                Err(()) => 1,
+            }
+        }).or_else(|errno| err_exit!(errno, "pthread_create"))
+    );
+    try!(
+        pthread_join(thread).or_else(|errno| err_exit!(errno, "pthread_join"))
+    );
+
+    // If strerror() weren't thread-safe, this would be EPERM's string
+    println!("Main thread:  str ({:p}) = {}", string.as_ptr(), string);
+    Ok(())
+}
+
+/// A thread-safe `strerror()`: returns the description of the error
+/// number `err`, in a buffer belonging to the calling thread.
+///
+/// As in C, the result is only valid until the next call in the same
+/// thread.
+fn strerror(err: i32) -> TlpiResult<&'static str> {
+    // The first caller creates the key; later callers wait for it
+    if let Err(errno) = ONCE.call(create_key) {
+        return err_exit!(errno, "pthread_once");
+    }
+    let key = match unsafe { STRERROR_KEY } {
+        Some(Ok(key)) => key,
+        Some(Err(errno)) => return err_exit!(errno, "pthread_key_create"),
+        None => return fatal!("STRERROR_KEY not initialized"),
+    };
+
+    // On the first call in each thread, allocate the thread's buffer
+    let mut buf = key.get_specific() as *mut [u8; MAX_ERROR_LEN];
+    if buf.is_null() {
+        buf = Box::into_raw(Box::new([0; MAX_ERROR_LEN]));
+        if let Err(errno) = key.set_specific(buf as *mut c_void) {
+            let _ = unsafe { Box::from_raw(buf) };
+            return err_exit!(errno, "pthread_setspecific");
+        }
+    }
+
+    let c_buf = buf as *mut c_char;
+    // For an unknown error number this fails, but still writes a
+    // description such as "Unknown error 999"
+    unsafe { libc::strerror_r(err, c_buf, MAX_ERROR_LEN) };
+
+    let c_str = unsafe { CStr::from_ptr(c_buf) };
+    Ok(c_str.to_str().unwrap_or(""))
+}
+
+/// Creates the key for the buffers, recording the result in
+/// `STRERROR_KEY`; called through `ONCE`.
+extern "C" fn create_key() {
+    unsafe { STRERROR_KEY = Some(Key::new(Some(destructor))) };
+}
+
+/// Frees the buffer of a terminating thread.
+unsafe extern "C" fn destructor(buf: *mut c_void) {
+    let _ = Box::from_raw(buf as *mut [u8; MAX_ERROR_LEN]);
+}
@@ -0,0 +1,61 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::ipc::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 1 {
+        return usage_err!("{}", argv[0]);
+    }
+
+    let queues = try!(list_msg_queues().or_else(|errno| {
+        err_exit!(errno, "listing message queues")
+    }));
+    println!("------ Message Queues --------");
+    println!("{:>10} {:>10} {:>6} {:>6} {:>10} {:>8}",
+             "key", "msqid", "owner", "perms", "used-bytes", "messages");
+    for queue in queues {
+        println!("{:>#10x} {:>10} {:>6} {:>6o} {:>10} {:>8}",
+                 queue.perms.key.value(), queue.id, queue.perms.uid,
+                 queue.perms.mode.bits(), queue.cbytes, queue.qnum);
+    }
+
+    let segments = try!(list_shm_segments().or_else(|errno| {
+        err_exit!(errno, "listing shared memory segments")
+    }));
+    println!("");
+    println!("------ Shared Memory Segments --------");
+    println!("{:>10} {:>10} {:>6} {:>6} {:>10} {:>8}",
+             "key", "shmid", "owner", "perms", "bytes", "nattch");
+    for segment in segments {
+        println!("{:>#10x} {:>10} {:>6} {:>6o} {:>10} {:>8}",
+                 segment.perms.key.value(), segment.id, segment.perms.uid,
+                 segment.perms.mode.bits(), segment.size, segment.nattch);
+    }
+
+    let sem_sets = try!(list_sem_sets().or_else(|errno| {
+        err_exit!(errno, "listing semaphore sets")
+    }));
+    println!("");
+    println!("------ Semaphore Arrays --------");
+    println!("{:>10} {:>10} {:>6} {:>6} {:>8}",
+             "key", "semid", "owner", "perms", "nsems");
+    for sem_set in sem_sets {
+        println!("{:>#10x} {:>10} {:>6} {:>6o} {:>8}",
+                 sem_set.perms.key.value(), sem_set.id, sem_set.perms.uid,
+                 sem_set.perms.mode.bits(), sem_set.nsems);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fs::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv[1] == "--help" {
+        return usage_err!("{} path...", argv[0]);
+    }
+
+    for path in argv.iter().skip(1) {
+        println!("{} ==> {} + {}", path, dirname(path), basename(path));
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::process::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} pathname", argv[0]);
+    }
+
+    // The new program is named after the last component of its path
+    let program_name = basename(&argv[1]);
+    let args = [&program_name[..], "hello world", "goodbye"];
+    let env = ["GREET=salut", "BYE=adieu"];
+
+    let errno = execve(&argv[1], &args, &env);
+    err_exit!(errno, "execve")
+}
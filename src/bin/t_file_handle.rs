@@ -0,0 +1,55 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::stat::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 3 || argv[1] == "--help" {
+        return usage_err!("{} path mount-path", argv[0]);
+    }
+
+    let (handle, mount_id) = try!(
+        name_to_handle_at(None, &argv[1], AtFlags::empty())
+            .or_else(|errno| err_exit!(errno, "name_to_handle_at"))
+    );
+    let hex: Vec<_> = handle.bytes.iter().map(|b| format!("{:02x}", b))
+        .collect();
+    println!("mount ID:    {}", mount_id);
+    println!("handle type: {}", handle.handle_type);
+    println!("handle:      {}", hex.concat());
+
+    // Any file opened on the same file system will do
+    let mount_fd = try!(
+        FileDescriptor::open(argv[2].clone(), O_RDONLY, FilePerms::empty())
+            .or_else(|errno| err_exit!(errno, "open {}", argv[2]))
+    );
+    // O_PATH allows the handle to refer to any type of file
+    let result = open_by_handle_at(&mount_fd, &handle, O_RDONLY | O_PATH);
+    let _ = mount_fd.close();
+    let fd = try!(
+        result.or_else(|errno| err_exit!(errno, "open_by_handle_at"))
+    );
+
+    // Show that the handle refers to the same i-node as the pathname
+    let by_path = statx(None, &argv[1], AtFlags::empty(), STATX_INO);
+    let by_handle = statx(Some(&fd), "", AT_EMPTY_PATH, STATX_INO);
+    let _ = fd.close();
+    let by_path = try!(by_path.or_else(|errno| err_exit!(errno, "statx")));
+    let by_handle = try!(by_handle.or_else(|errno| err_exit!(errno, "statx")));
+    println!("i-node by pathname: {}", by_path.ino);
+    println!("i-node by handle:   {}", by_handle.ino);
+    Ok(())
+}
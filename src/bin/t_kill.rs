@@ -0,0 +1,54 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::signal::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 3 || argv[1] == "--help" {
+        return usage_err!("{} pid sig-num", argv[0]);
+    }
+
+    let pid = match i32::from_str(&argv[1]) {
+        Ok(pid) => pid,
+        Err(_) => return cmd_line_err!("Invalid pid: {}", argv[1]),
+    };
+    let sig = match i32::from_str(&argv[2]) {
+        Ok(value) => Signal::new(value),
+        Err(_) => return cmd_line_err!("Invalid sig-num: {}", argv[2]),
+    };
+
+    let result = kill(pid, sig);
+
+    // Signal 0 is never delivered; only the error checking is done, so
+    // the result says whether the process exists
+    if sig.value() != 0 {
+        try!(result.or_else(|errno| err_exit!(errno, "kill")));
+    } else {
+        match result {
+            Ok(()) => {
+                println!("Process exists and we can send it a signal");
+            },
+            Err(errno) if errno == EPERM => {
+                println!("Process exists, but we don't have permission to \
+                          send it a signal");
+            },
+            Err(errno) if errno == ESRCH => {
+                println!("Process does not exist");
+            },
+            Err(errno) => return err_exit!(errno, "kill"),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::mem;
+use std::slice;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+
+const STR_SIZE: usize = 100;
+
+/// Arbitrary fixed-layout structure to read into.
+#[repr(C)]
+#[derive(Debug, Default)]
+struct Record {
+    id: u32,
+    flags: u16,
+    kind: u16,
+    length: u64,
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} file", argv[0]);
+    }
+
+    let fd = try!(
+        FileDescriptor::open(argv[1].clone(), O_RDONLY, FilePerms::empty())
+            .or_else(|errno| err_exit!(errno, "open {}", argv[1]))
+    );
+
+    let mut record = Record::default();
+    let mut x: i32 = 0;
+    let mut text = [0u8; STR_SIZE];
+    let total_required =
+        mem::size_of::<Record>() + mem::size_of::<i32>() + STR_SIZE;
+
+    let num_read = {
+        // Safe, since both types are plain data that any bit pattern
+        // is valid for, and the views don't outlive them
+        let record_bytes = unsafe { as_bytes_mut(&mut record) };
+        let x_bytes = unsafe { as_bytes_mut(&mut x) };
+        let mut bufs = [record_bytes, x_bytes, &mut text[..]];
+        try!(fd.readv(&mut bufs).or_else(|errno| err_exit!(errno, "readv")))
+    };
+
+    if num_read < total_required {
+        println!("Read fewer bytes than requested");
+    }
+    println!("total bytes requested: {}; bytes read: {}",
+             total_required, num_read);
+    println!("record: {:?}", record);
+    println!("x: {}", x);
+    let text_len = text.iter().position(|&b| b == 0).unwrap_or(STR_SIZE);
+    println!("string: {:?}", String::from_utf8_lossy(&text[..text_len]));
+
+    fd.close().or_else(|errno| err_exit!(errno, "close"))
+}
+
+/// Helper function; views the memory of `value` as a byte slice, so
+/// that it can be read into directly.
+///
+/// Unsafe because the caller must ensure that any bytes written there
+/// form a valid `T`.
+unsafe fn as_bytes_mut<T>(value: &mut T) -> &mut [u8] {
+    slice::from_raw_parts_mut(value as *mut T as *mut u8, mem::size_of::<T>())
+}
@@ -0,0 +1,86 @@
+
+#![feature(libc, slice_splits)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+extern crate getopts;
+use getopts::Options;
+
+use tlpi_rust::err::*;
+use tlpi_rust::fs::*;
+use std::env;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let (old_path, new_path, flags) = try!(parse_args());
+
+    // Without flags, this is the same as a plain rename()
+    try!(
+        renameat2(None, &old_path, None, &new_path, flags).or_else(|errno| {
+            err_exit!(errno, "renameat2 {} {}", old_path, new_path)
+        })
+    );
+
+    if flags.contains(RENAME_EXCHANGE) {
+        println!("Exchanged {} and {}", old_path, new_path);
+    } else {
+        println!("Renamed {} to {}", old_path, new_path);
+    }
+    Ok(())
+}
+
+fn parse_args() -> TlpiResult<(String, String, RenameFlags)> {
+    let argv: Vec<_> = env::args().collect();
+    let opts = build_options();
+
+    let argv_tail = match argv.split_first() {
+        Some((_, tail)) => tail,
+        _ => return cmd_line_err!("No program name provided!?"),
+    };
+
+    // Mutable so we can move out the paths
+    let mut matches = match opts.parse(argv_tail) {
+        Ok(m) => m,
+        Err(f) => {
+            let usage = opts.usage(&f.to_string());
+            return cmd_line_err!("{}", usage)
+        },
+    };
+
+    if matches.opt_present("help") {
+        let usage = format!("{} [options] <old_path> <new_path>", argv[0]);
+        return usage_err!("{}", opts.usage(&usage));
+    }
+
+    let mut flags = RenameFlags::empty();
+    if matches.opt_present("noreplace") {
+        flags.insert(RENAME_NOREPLACE);
+    }
+    if matches.opt_present("exchange") {
+        flags.insert(RENAME_EXCHANGE);
+    }
+    if matches.opt_present("whiteout") {
+        flags.insert(RENAME_WHITEOUT);
+    }
+
+    if matches.free.len() == 2 {
+        let new_path = matches.free.swap_remove(1);
+        Ok((matches.free.swap_remove(0), new_path, flags))
+    } else {
+        let usage = opts.usage("Exactly two path arguments are required");
+        return cmd_line_err!("{}", usage)
+    }
+}
+
+fn build_options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "display this usage message");
+    opts.optflag("n", "noreplace", "fail if the new path already exists");
+    opts.optflag("x", "exchange", "atomically swap the two paths");
+    opts.optflag("w", "whiteout", "leave a whiteout at the old path");
+    opts
+}
@@ -0,0 +1,91 @@
+
+#![feature(libc, slice_splits)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+extern crate getopts;
+use getopts::Options;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::sched::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let (pid, mask, burn) = try!(parse_args());
+
+    let set = CpuSet::from_mask(mask);
+    try!(sched_setaffinity(pid, &set).or_else(|errno| {
+        err_exit!(errno, "sched_setaffinity")
+    }));
+
+    // Read the mask back, to show what the kernel accepted
+    let set = try!(sched_getaffinity(pid).or_else(|errno| {
+        err_exit!(errno, "sched_getaffinity")
+    }));
+    let cpus: Vec<_> = set.cpus().iter().map(|cpu| cpu.to_string())
+        .collect();
+    println!("PID {} may run on {} CPU(s): {}",
+             pid, set.count(), cpus.join(" "));
+
+    if burn {
+        // Spin forever, so the pinning shows in `top` or `ps -o psr`
+        println!("Burning CPU; interrupt to stop");
+        let mut counter: u64 = 0;
+        loop {
+            counter = counter.wrapping_add(1);
+        }
+    }
+    Ok(())
+}
+
+fn parse_args() -> TlpiResult<(i32, u64, bool)> {
+    let argv: Vec<_> = env::args().collect();
+    let opts = build_options();
+
+    let argv_tail = match argv.split_first() {
+        Some((_, tail)) => tail,
+        _ => return cmd_line_err!("No program name provided!?"),
+    };
+
+    let matches = match opts.parse(argv_tail) {
+        Ok(m) => m,
+        Err(f) => {
+            let usage = opts.usage(&f.to_string());
+            return cmd_line_err!("{}", usage)
+        },
+    };
+
+    if matches.opt_present("help") || matches.free.len() != 2 {
+        let usage = format!("{} [options] pid mask", argv[0]);
+        return usage_err!("{}", opts.usage(&usage));
+    }
+
+    let pid = match i32::from_str(&matches.free[0]) {
+        Ok(pid) => pid,
+        Err(_) => return cmd_line_err!("Invalid pid: {}", matches.free[0]),
+    };
+
+    // The mask is in hexadecimal, as `taskset` takes it
+    let mask_arg = &matches.free[1];
+    let digits = mask_arg.trim_left_matches("0x");
+    let mask = match u64::from_str_radix(digits, 16) {
+        Ok(mask) if mask != 0 => mask,
+        _ => return cmd_line_err!("Invalid mask: {}", mask_arg),
+    };
+
+    Ok((pid, mask, matches.opt_present("burn")))
+}
+
+fn build_options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "display this usage message");
+    opts.optflag("b", "burn",
+                 "consume CPU afterwards (use pid 0 to pin this process)");
+    opts
+}
@@ -0,0 +1,100 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::select::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv[1] == "--help" {
+        return usage_err!(
+            "{} {{timeout|-}} fd-num[rw]...\n    \
+             - means infinite timeout;\n    \
+             r = monitor for read\n    \
+             w = monitor for write\n\n    \
+             e.g.: {} - 0rw 1w",
+            argv[0], argv[0]
+        );
+    }
+
+    // Timeout for select() is specified in argv[1]
+    let mut timeout = if argv[1] == "-" {
+        None
+    } else {
+        match u64::from_str(&argv[1]) {
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(_) => return cmd_line_err!("Invalid timeout: {}", argv[1]),
+        }
+    };
+
+    // Process remaining arguments to build file descriptor sets
+    let mut read_fds = FdSet::new();
+    let mut write_fds = FdSet::new();
+    for arg in &argv[2..] {
+        let digits_len = arg.find(|c: char| !c.is_digit(10))
+            .unwrap_or(arg.len());
+        let (num, modes) = arg.split_at(digits_len);
+        let fd = match i32::from_str(num) {
+            Ok(raw) => FileDescriptor::from_raw(raw),
+            Err(_) => return cmd_line_err!("Bad argument: {}", arg),
+        };
+        if modes.is_empty() || modes.contains(|c| c != 'r' && c != 'w') {
+            return cmd_line_err!("Bad argument: {}", arg);
+        }
+
+        if modes.contains('r') {
+            try!(read_fds.set(&fd)
+                 .or_else(|_| cmd_line_err!("file descriptor exceeds limit")));
+        }
+        if modes.contains('w') {
+            try!(write_fds.set(&fd)
+                 .or_else(|_| cmd_line_err!("file descriptor exceeds limit")));
+        }
+    }
+
+    // We've built all of the arguments; now call select()
+    let ready = match select(
+        Some(&mut read_fds), Some(&mut write_fds), None, timeout.as_mut()
+    ) {
+        Ok(ready) => ready,
+        Err(errno) => return err_exit!(errno, "select"),
+    };
+
+    // Display results of select()
+    println!("ready = {}", ready);
+    let mut ready_fds = read_fds.fds();
+    ready_fds.extend(write_fds.fds());
+    ready_fds.sort();
+    ready_fds.dedup();
+    for raw in ready_fds {
+        let fd = FileDescriptor::from_raw(raw);
+        let is_read = read_fds.is_set(&fd);
+        let is_write = write_fds.is_set(&fd);
+        println!(
+            "{}: {}{}", raw,
+            if is_read { "r" } else { "" },
+            if is_write { "w" } else { "" }
+        );
+    }
+
+    if let Some(remaining) = timeout {
+        println!(
+            "timeout after select(): {}.{:03}",
+            remaining.as_secs(), remaining.subsec_nanos() / 1000000
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::sched::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 4 || argv[1] == "--help" {
+        return usage_err!("{} {{p|g|u}} who priority\n    \
+                           set priority of: p=process; g=process group; \
+                           u=processes for user", argv[0]);
+    }
+
+    let which = match &argv[1][..] {
+        "p" => PriorityTarget::Process,
+        "g" => PriorityTarget::ProcessGroup,
+        "u" => PriorityTarget::User,
+        _ => return cmd_line_err!("Invalid target: {}", argv[1]),
+    };
+    let who = match u32::from_str(&argv[2]) {
+        Ok(who) => who,
+        Err(_) => return cmd_line_err!("Invalid who: {}", argv[2]),
+    };
+    let prio = match i32::from_str(&argv[3]) {
+        Ok(prio) => prio,
+        Err(_) => return cmd_line_err!("Invalid priority: {}", argv[3]),
+    };
+
+    try!(setpriority(which, who, prio).or_else(|errno| {
+        err_exit!(errno, "setpriority")
+    }));
+
+    // Read the value back, since the kernel clamps it to its range
+    let prio = try!(getpriority(which, who).or_else(|errno| {
+        err_exit!(errno, "getpriority")
+    }));
+    println!("Nice value = {}", prio);
+    Ok(())
+}
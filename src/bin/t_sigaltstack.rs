@@ -0,0 +1,79 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+extern crate libc;
+
+use std::env;
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+use libc::{c_int, c_void};
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+use tlpi_rust::signal::*;
+
+/// The lowest address of the alternate stack, for the handler.
+static ALT_STACK_ADDR: AtomicUsize = ATOMIC_USIZE_INIT;
+
+extern "C" fn sigsegv_handler(
+    sig: c_int, info: &SigInfo, _ucontext: *mut c_void
+) {
+    // UNSAFE: `println!()` isn't async-signal-safe, but the program
+    // is about to end anyway
+    let x = 0u8;
+    let top = &x as *const u8 as usize;
+    println!("Caught signal {} ({})", sig, Signal::new(sig).description());
+    println!("Faulting address is {:#x}", info.addr());
+    println!("Top of handler stack near     {:#14x}", top);
+    println!("{} bytes of the alternate stack remain",
+             top - ALT_STACK_ADDR.load(Ordering::SeqCst));
+
+    // Returning would just fault again
+    _exit(EXIT_FAILURE);
+}
+
+/// Grows the stack until it overflows.
+#[allow(unconditional_recursion)]
+fn overflow_stack(call_num: usize) {
+    // A large frame makes each call count
+    let a = [0u8; 100000];
+    println!("Call {:4} - top of stack near {:#14x}",
+             call_num, &a[0] as *const u8 as usize);
+    overflow_stack(call_num + 1);
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 1 {
+        return usage_err!("{}", argv[0]);
+    }
+
+    let x = 0u8;
+    println!("Top of standard stack is near {:#14x}",
+             &x as *const u8 as usize);
+
+    // Allocate the alternate stack on the heap, and inform the kernel
+    // of it
+    try!(sigaltstack(vec![0; SIGSTKSZ])
+         .or_else(|errno| err_exit!(errno, "sigaltstack")));
+    let stack = try!(current_altstack()
+                     .or_else(|errno| err_exit!(errno, "sigaltstack")));
+    ALT_STACK_ADDR.store(stack.addr, Ordering::SeqCst);
+    println!("Alternate stack is at         {:#14x}-{:#x}",
+             stack.addr, stack.addr + stack.size - 1);
+
+    // Without SA_ONSTACK, the handler couldn't run on the exhausted
+    // standard stack, and the process would be killed
+    let mut action = SigAction::new(SigHandler::InfoHandler(sigsegv_handler));
+    action.flags = SA_ONSTACK;
+    try!(unsafe { sigaction(SIGSEGV, &action) }
+         .or_else(|errno| err_exit!(errno, "sigaction")));
+
+    overflow_stack(1);
+    Ok(())
+}
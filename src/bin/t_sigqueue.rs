@@ -0,0 +1,50 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::creds::*;
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+use tlpi_rust::signal::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 4 || argv.len() > 5 || argv[1] == "--help" {
+        return usage_err!("{} pid sig-num data [num-sigs]", argv[0]);
+    }
+
+    // Display our PID and UID, so that they can be compared with the
+    // corresponding fields of the siginfo argument supplied to the
+    // handler in the receiving process
+    println!("{}: PID is {}, UID is {}", argv[0], getpid(), getuid());
+
+    let pid = try!(parse_number::<i32>(&argv[1], "pid"));
+    let sig = Signal::new(try!(parse_number(&argv[2], "sig-num")));
+    let data = try!(parse_number::<isize>(&argv[3], "data"));
+    let num_sigs = match argv.get(4) {
+        Some(arg) => try!(parse_number::<isize>(arg, "num-sigs")),
+        None => 1,
+    };
+
+    for j in 0..num_sigs {
+        try!(sigqueue(pid, sig, data + j)
+             .or_else(|errno| err_exit!(errno, "sigqueue {}", j)));
+    }
+    Ok(())
+}
+
+fn parse_number<T: FromStr>(arg: &str, name: &str) -> TlpiResult<T> {
+    match T::from_str(arg) {
+        Ok(number) => Ok(number),
+        Err(_) => cmd_line_err!("Invalid {}: {}", name, arg),
+    }
+}
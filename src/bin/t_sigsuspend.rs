@@ -0,0 +1,98 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+extern crate libc;
+
+use std::env;
+use std::sync::atomic::{AtomicBool, ATOMIC_BOOL_INIT, Ordering};
+use std::time::{Duration, Instant};
+use libc::c_int;
+use tlpi_rust::err::*;
+use tlpi_rust::process::*;
+use tlpi_rust::signal::*;
+
+/// Set when `SIGQUIT` is caught, to end the program.
+static GOT_SIGQUIT: AtomicBool = ATOMIC_BOOL_INIT;
+
+extern "C" fn handler(sig: c_int) {
+    // UNSAFE: `println!()` isn't async-signal-safe; the main thread
+    // only waits in `sigsuspend()` while handlers may run, though
+    println!("Caught signal {} ({})", sig, Signal::new(sig).description());
+    if sig == SIGQUIT.value() {
+        GOT_SIGQUIT.store(true, Ordering::SeqCst);
+    }
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 1 {
+        return usage_err!("{}", argv[0]);
+    }
+
+    println!("{}: PID is {}", argv[0], getpid());
+    println!("Initial signal mask is: {}", try!(current_mask()));
+
+    // Block the signals before establishing their handlers, so that
+    // they can only be delivered inside `sigsuspend()`
+    let mut block_mask = SigSet::empty();
+    for &sig in &[SIGINT, SIGQUIT] {
+        try!(block_mask.add(sig)
+             .or_else(|errno| err_exit!(errno, "sigaddset")));
+    }
+    let orig_mask = try!(
+        pthread_sigmask(SigmaskHow::Block, &block_mask)
+            .or_else(|errno| err_exit!(errno, "pthread_sigmask - SIG_BLOCK"))
+    );
+
+    let action = SigAction::new(SigHandler::Handler(handler));
+    for &sig in &[SIGINT, SIGQUIT] {
+        try!(unsafe { sigaction(sig, &action) }
+             .or_else(|errno| err_exit!(errno, "sigaction")));
+    }
+
+    let mut loop_num = 1;
+    while !GOT_SIGQUIT.load(Ordering::SeqCst) {
+        println!("=== LOOP {}", loop_num);
+
+        // Simulate a critical section by burning CPU for a few
+        // seconds, during which the signals stay pending
+        println!("Starting critical section, signal mask is: {}",
+                 try!(current_mask()));
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(4) {
+            continue;
+        }
+
+        let pending = try!(sigpending().or_else(|errno| {
+            err_exit!(errno, "sigpending")
+        }));
+        println!("Before sigsuspend() - pending signals: {}", pending);
+
+        // Unblock the signals and wait for one, in a single step
+        let errno = sigsuspend(&orig_mask);
+        if errno != EINTR {
+            return err_exit!(errno, "sigsuspend");
+        }
+        loop_num += 1;
+    }
+
+    try!(pthread_sigmask(SigmaskHow::SetMask, &orig_mask)
+         .or_else(|errno| err_exit!(errno, "pthread_sigmask - SIG_SETMASK")));
+    println!("=== Exited loop");
+    println!("Restored signal mask to: {}", try!(current_mask()));
+    Ok(())
+}
+
+fn current_mask() -> TlpiResult<SigSet> {
+    // Blocking no signals leaves the mask unchanged, but still returns
+    // it
+    pthread_sigmask(SigmaskHow::Block, &SigSet::empty())
+        .or_else(|errno| err_exit!(errno, "pthread_sigmask"))
+}
@@ -0,0 +1,129 @@
+
+#![feature(libc, slice_splits)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+extern crate getopts;
+use getopts::Options;
+
+use std::env;
+use tlpi_rust::dir::*;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::stat::*;
+use tlpi_rust::users::*;
+
+/// The timestamp format used by `ctime()`.
+const TIME_FORMAT: &'static str = "%a %b %e %H:%M:%S %Y";
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let (path, use_lstat) = try!(parse_args());
+
+    let result = if use_lstat { lstat(&path) } else { stat(&path) };
+    let sb = try!(result.or_else(|errno| err_exit!(errno, "stat {}", path)));
+    display_stat_info(&sb)
+}
+
+fn display_stat_info(sb: &FileStat) -> TlpiResult<()> {
+    println!("File type:                {}", type_name(sb.file_type));
+    println!("Device containing i-node: major={}   minor={}",
+             sb.dev.0, sb.dev.1);
+    println!("I-node number:            {}", sb.ino);
+    println!("Mode:                     {:o} ({})",
+             sb.perms.bits(), sb.perms.to_symbolic_string(true));
+
+    let special = [(S_ISUID, "set-UID"), (S_ISGID, "set-GID"),
+                   (S_ISVTX, "sticky")];
+    let special_set: Vec<_> = special.iter()
+        .filter(|&&(bit, _)| sb.perms.contains(bit))
+        .map(|&(_, name)| name)
+        .collect();
+    if !special_set.is_empty() {
+        println!("    special bits set:     {}", special_set.join(" "));
+    }
+
+    println!("Number of (hard) links:   {}", sb.nlink);
+    let owner = try!(user_name_from_id(sb.uid).or_else(|errno| {
+        err_exit!(errno, "getpwuid")
+    }));
+    let group = try!(group_name_from_id(sb.gid).or_else(|errno| {
+        err_exit!(errno, "getgrgid")
+    }));
+    println!("Ownership:                UID={} ({})   GID={} ({})",
+             sb.uid, owner.unwrap_or("?".to_string()),
+             sb.gid, group.unwrap_or("?".to_string()));
+
+    if sb.file_type == FileType::CharDevice ||
+        sb.file_type == FileType::BlockDevice
+    {
+        println!("Device number (st_rdev):  major={}; minor={}",
+                 sb.rdev.0, sb.rdev.1);
+    }
+
+    println!("File size:                {} bytes", sb.size);
+    println!("Optimal I/O block size:   {} bytes", sb.blksize);
+    println!("512B blocks allocated:    {}", sb.blocks);
+    println!("Last file access:         {}",
+             sb.atime.format_local(TIME_FORMAT));
+    println!("Last file modification:   {}",
+             sb.mtime.format_local(TIME_FORMAT));
+    println!("Last status change:       {}",
+             sb.ctime.format_local(TIME_FORMAT));
+    Ok(())
+}
+
+fn type_name(file_type: FileType) -> &'static str {
+    match file_type {
+        FileType::Regular => "regular file",
+        FileType::Directory => "directory",
+        FileType::CharDevice => "character device",
+        FileType::BlockDevice => "block device",
+        FileType::Symlink => "symbolic (soft) link",
+        FileType::Fifo => "FIFO or pipe",
+        FileType::Socket => "socket",
+        FileType::Unknown => "unknown file type?",
+    }
+}
+
+fn parse_args() -> TlpiResult<(String, bool)> {
+    let argv: Vec<_> = env::args().collect();
+    let opts = build_options();
+
+    let argv_tail = match argv.split_first() {
+        Some((_, tail)) => tail,
+        _ => return cmd_line_err!("No program name provided!?"),
+    };
+
+    // Mutable so we can move out the path
+    let mut matches = match opts.parse(argv_tail) {
+        Ok(m) => m,
+        Err(f) => {
+            let usage = opts.usage(&f.to_string());
+            return cmd_line_err!("{}", usage)
+        },
+    };
+
+    if matches.opt_present("help") {
+        let usage = format!("{} [options] <file>", argv[0]);
+        return usage_err!("{}", opts.usage(&usage));
+    }
+
+    if matches.free.len() == 1 {
+        Ok((matches.free.swap_remove(0), matches.opt_present("lstat")))
+    } else {
+        let usage = opts.usage("Exactly one path argument is required");
+        return cmd_line_err!("{}", usage)
+    }
+}
+
+fn build_options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "display this usage message");
+    opts.optflag("l", "lstat", "don't follow a symbolic link");
+    opts
+}
@@ -0,0 +1,67 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::SysResult;
+use tlpi_rust::sysconf::*;
+
+/// The system limits to display.
+const SYSCONF_NAMES: [(&'static str, SysconfName); 8] = [
+    ("_SC_ARG_MAX", SysconfName::ArgMax),
+    ("_SC_LOGIN_NAME_MAX", SysconfName::LoginNameMax),
+    ("_SC_OPEN_MAX", SysconfName::OpenMax),
+    ("_SC_NGROUPS_MAX", SysconfName::NgroupsMax),
+    ("_SC_PAGESIZE", SysconfName::Pagesize),
+    ("_SC_RTSIG_MAX", SysconfName::RtsigMax),
+    ("_SC_CLK_TCK", SysconfName::ClkTck),
+    ("_SC_NPROCESSORS_ONLN", SysconfName::NprocessorsOnln),
+];
+
+/// The file limits to display for the given path.
+const PATHCONF_NAMES: [(&'static str, PathconfName); 4] = [
+    ("_PC_NAME_MAX", PathconfName::NameMax),
+    ("_PC_PATH_MAX", PathconfName::PathMax),
+    ("_PC_PIPE_BUF", PathconfName::PipeBuf),
+    ("_PC_LINK_MAX", PathconfName::LinkMax),
+];
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 2 || (argv.len() == 2 && argv[1] == "--help") {
+        return usage_err!("{} [path]", argv[0]);
+    }
+    let path = argv.get(1).map_or(".", |path| &path[..]);
+
+    for &(label, name) in SYSCONF_NAMES.iter() {
+        try!(display_limit(label, sysconf(name)));
+    }
+
+    println!("");
+    println!("Limits for {}:", path);
+    for &(label, name) in PATHCONF_NAMES.iter() {
+        try!(display_limit(label, pathconf(path, name)));
+    }
+    Ok(())
+}
+
+/// Displays one limit, which may be indeterminate, or exits if it
+/// couldn't be queried.
+fn display_limit(
+    label: &str, limit: SysResult<Option<i64>>
+) -> TlpiResult<()> {
+    match limit {
+        Ok(Some(value)) => println!("{:<22} {}", label, value),
+        Ok(None) => println!("{:<22} (indeterminate)", label),
+        Err(errno) => return err_exit!(errno, "{}", label),
+    }
+    Ok(())
+}
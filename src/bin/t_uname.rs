@@ -0,0 +1,24 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use tlpi_rust::err::*;
+use tlpi_rust::sysinfo::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let uts = try!(uname().or_else(|errno| err_exit!(errno, "uname")));
+
+    println!("Node name:   {}", uts.nodename);
+    println!("System name: {}", uts.sysname);
+    println!("Release:     {}", uts.release);
+    println!("Version:     {}", uts.version);
+    println!("Machine:     {}", uts.machine);
+    println!("Domain name: {}", uts.domainname);
+    Ok(())
+}
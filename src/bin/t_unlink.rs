@@ -0,0 +1,96 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::stat::*;
+
+const BLOCK_SIZE: usize = 1024;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 3 || argv[1] == "--help" {
+        return usage_err!("{} temp-file num-1kB-blocks", argv[0]);
+    }
+    let path = &argv[1];
+    let num_blocks = match usize::from_str(&argv[2]) {
+        Ok(count) => count,
+        Err(_) => return cmd_line_err!("Invalid num-1kB-blocks: {}", argv[2]),
+    };
+
+    let flags = O_RDWR | O_CREAT | O_EXCL;
+    let perms = S_IRUSR | S_IWUSR; // rw-------
+    let fd = try!(
+        FileDescriptor::open(path.clone(), flags, perms)
+            .or_else(|errno| err_exit!(errno, "open {}", path))
+    );
+
+    // Remove the filename; the i-node lives on while `fd` is open
+    try!(unlink(path).or_else(|errno| err_exit!(errno, "unlink {}", path)));
+    println!("Unlinked {}", path);
+
+    let mut buf = [0u8; BLOCK_SIZE];
+    for block in 0..num_blocks {
+        for byte in buf.iter_mut() {
+            *byte = block as u8;
+        }
+        match fd.write(&buf) {
+            Ok(count) if count == BLOCK_SIZE => {},
+            Ok(_) => return fatal!("partial write"),
+            Err(errno) => return err_exit!(errno, "write"),
+        }
+    }
+    try!(report_usage(&fd));
+
+    // Read everything back, to show the data is still reachable
+    try!(fd.lseek(0, OffsetBase::SeekSet).or_else(|errno| {
+        err_exit!(errno, "lseek")
+    }));
+    for block in 0..num_blocks {
+        match fd.read(&mut buf) {
+            Ok(count) if count == BLOCK_SIZE => {},
+            Ok(_) => return fatal!("short read"),
+            Err(errno) => return err_exit!(errno, "read"),
+        }
+        if buf.iter().any(|&byte| byte != block as u8) {
+            return fatal!("block {} was not read back intact", block);
+        }
+    }
+    println!("Read back {} blocks intact", num_blocks);
+
+    try!(fd.close().or_else(|errno| err_exit!(errno, "close")));
+    println!("********** Closed file descriptor");
+
+    // With the last descriptor closed, the i-node and its data are gone
+    match FileDescriptor::open(path.clone(), O_RDONLY, FilePerms::empty()) {
+        Err(errno) if errno == ENOENT => {
+            println!("{} no longer exists", path);
+            Ok(())
+        },
+        Err(errno) => err_exit!(errno, "open {}", path),
+        Ok(_) => fatal!("{} still exists!?", path),
+    }
+}
+
+/// Prints the link count and disk usage of the file open on `fd`.
+fn report_usage(fd: &FileDescriptor) -> TlpiResult<()> {
+    let mask = STATX_NLINK | STATX_SIZE | STATX_BLOCKS;
+    let stx = try!(
+        statx(Some(fd), "", AT_EMPTY_PATH, mask)
+            .or_else(|errno| err_exit!(errno, "statx"))
+    );
+    println!("Links: {}; size: {}; 512-byte blocks allocated: {}",
+             stx.nlink, stx.size, stx.blocks);
+    Ok(())
+}
@@ -0,0 +1,68 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::stat::*;
+
+/// The format for times, both on the command line and displayed;
+/// the latter also show the nanoseconds.
+const TIME_FORMAT: &'static str = "%Y-%m-%d %H:%M:%S";
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if (argv.len() != 2 && argv.len() != 4) || argv[1] == "--help" {
+        return usage_err!(
+            "{} file [atime mtime]\n\
+             where each time is \"now\", \"omit\" or \"YYYY-MM-DD HH:MM:SS\"",
+            argv[0]
+        );
+    }
+    let path = &argv[1];
+
+    if argv.len() == 2 {
+        return display_times(path);
+    }
+
+    let atime = try!(parse_update(&argv[2]));
+    let mtime = try!(parse_update(&argv[3]));
+    try!(display_times(path));
+    let result = utimensat(None, path, atime, mtime, AtFlags::empty());
+    try!(result.or_else(|errno| err_exit!(errno, "utimensat")));
+
+    println!("Changed to:");
+    display_times(path)
+}
+
+fn parse_update(arg: &str) -> TlpiResult<TimeUpdate> {
+    match arg {
+        "now" => Ok(TimeUpdate::Now),
+        "omit" => Ok(TimeUpdate::Omit),
+        _ => match Timestamp::parse_local(arg, TIME_FORMAT) {
+            Some(time) => Ok(TimeUpdate::At(time)),
+            None => cmd_line_err!("Invalid time: {}", arg),
+        },
+    }
+}
+
+fn display_times(path: &str) -> TlpiResult<()> {
+    let sb = try!(
+        stat(path).or_else(|errno| err_exit!(errno, "stat {}", path))
+    );
+    println!("Last file access:       {}.{:09}",
+             sb.atime.format_local(TIME_FORMAT), sb.atime.nanos);
+    println!("Last file modification: {}.{:09}",
+             sb.mtime.format_local(TIME_FORMAT), sb.mtime.nanos);
+    println!("Last status change:     {}.{:09}",
+             sb.ctime.format_local(TIME_FORMAT), sb.ctime.nanos);
+    Ok(())
+}
@@ -9,6 +9,7 @@ use getopts::Options;
 
 use tlpi_rust::err::*;
 use tlpi_rust::fd::*;
+use tlpi_rust::signal::*;
 use std::env;
 
 fn main() {
@@ -16,15 +17,28 @@ fn main() {
 }
 
 fn main_with_result() -> TlpiResult<()> {
-    let (output_path, write_mode) = try!(parse_args());
+    let (output_paths, write_mode, ignore_interrupts) = try!(parse_args());
+
+    if ignore_interrupts {
+        let action = SigAction::new(SigHandler::Ignore);
+        // Safe, since no handler is installed
+        try!(unsafe { sigaction(SIGINT, &action) }.or_else(|errno| {
+            err_exit!(errno, "sigaction")
+        }));
+    }
 
-    let path = output_path.clone();
     let flags = O_WRONLY | O_CREAT | write_mode;
     let perms = S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH; // rw-r--r--
-    let dest_fd = match FileDescriptor::open(path, flags, perms) {
-        Ok(fd) => fd,
-        Err(errno) => return err_exit!(errno, "open() on file {}", output_path),
-    };
+    let mut dest_fds = Vec::with_capacity(output_paths.len());
+    for output_path in &output_paths {
+        let path = output_path.clone();
+        match FileDescriptor::open(path, flags, perms) {
+            Ok(fd) => dest_fds.push(fd),
+            Err(errno) => {
+                return err_exit!(errno, "open() on file {}", output_path)
+            },
+        };
+    }
 
     let mut buf = [0u8; 1 << 16]; // 64k buffer
     loop {
@@ -34,13 +48,18 @@ fn main_with_result() -> TlpiResult<()> {
             Err(errno) => return err_exit!(errno, "read() on stdin"),
         };
 
-        try!(write_buf(&dest_fd, &buf[..bytes_read], &output_path[..]));
+        for (dest_fd, output_path) in dest_fds.iter().zip(&output_paths) {
+            try!(write_buf(dest_fd, &buf[..bytes_read], &output_path[..]));
+        }
         try!(write_buf(&STDOUT, &buf[..bytes_read], "stdout"));
     }
 
-    dest_fd.close().or_else(|errno| {
-        err_exit!(errno, "close() on file {}", output_path)
-    })
+    for (dest_fd, output_path) in dest_fds.into_iter().zip(&output_paths) {
+        try!(dest_fd.close().or_else(|errno| {
+            err_exit!(errno, "close() on file {}", output_path)
+        }));
+    }
+    Ok(())
 }
 
 fn write_buf(
@@ -53,7 +72,7 @@ fn write_buf(
     }
 }
 
-fn parse_args() -> TlpiResult<(String, OpenFlags)> {
+fn parse_args() -> TlpiResult<(Vec<String>, OpenFlags, bool)> {
     let argv: Vec<_> = env::args().collect();
     let opts = build_options();
 
@@ -62,8 +81,7 @@ fn parse_args() -> TlpiResult<(String, OpenFlags)> {
         _ => return cmd_line_err!("No program name provided!?"),
     };
 
-    // Mutable so we can move out the output path
-    let mut matches = match opts.parse(argv_tail) {
+    let matches = match opts.parse(argv_tail) {
         Ok(m) => m,
         Err(f) => {
             let usage = opts.usage(&f.to_string());
@@ -72,23 +90,21 @@ fn parse_args() -> TlpiResult<(String, OpenFlags)> {
     };
 
     if matches.opt_present("help") {
-        let usage = format!("{} [options] <output_file>", argv[0]);
+        let usage = format!("{} [options] [output_file...]", argv[0]);
         return usage_err!("{}", opts.usage(&usage));
     }
 
-    if matches.free.len() == 1 {
-        let write_mode =
-            if matches.opt_present("append") { O_APPEND } else { O_TRUNC };
-        Ok((matches.free.swap_remove(0), write_mode))
-    } else {
-        let usage = opts.usage("Exactly one file argument is required");
-        return cmd_line_err!("{}", usage)
-    }
+    // With no files, standard input is just copied to standard output
+    let write_mode =
+        if matches.opt_present("append") { O_APPEND } else { O_TRUNC };
+    let ignore_interrupts = matches.opt_present("ignore-interrupts");
+    Ok((matches.free, write_mode, ignore_interrupts))
 }
 
 fn build_options() -> Options {
     let mut opts = Options::new();
     opts.optflag("h", "help", "display this usage message");
     opts.optflag("a", "append", "append output instead of truncating");
+    opts.optflag("i", "ignore-interrupts", "ignore the SIGINT signal");
     opts
 }
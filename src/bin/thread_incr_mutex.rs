@@ -0,0 +1,80 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use tlpi_rust::err::*;
+use tlpi_rust::thread::*;
+
+/// Number of times each thread increments `GLOB`, if not given.
+const DEFAULT_LOOPS: usize = 10000000;
+
+/// The counter incremented by both threads.
+static mut GLOB: usize = 0;
+
+/// Protects `GLOB`.
+static MTX: Mutex = PTHREAD_MUTEX_INITIALIZER;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() > 2 || (argv.len() > 1 && argv[1] == "--help") {
+        return usage_err!("{} [num-loops]", argv[0]);
+    }
+    let loops = match argv.get(1) {
+        Some(arg) => match usize::from_str(arg) {
+            Ok(loops) if loops > 0 => loops,
+            _ => return cmd_line_err!("Invalid num-loops: {}", arg),
+        },
+        None => DEFAULT_LOOPS,
+    };
+
+    let t1 = try!(
+        pthread_create(move || increment(loops))
+            .or_else(|errno| err_exit!(errno, "pthread_create"))
+    );
+    let t2 = try!(
+        pthread_create(move || increment(loops))
+            .or_else(|errno| err_exit!(errno, "pthread_create"))
+    );
+
+    for thread in [t1, t2].iter() {
+        match pthread_join(*thread) {
+            Ok(0) => {},
+            Ok(_) => return fatal!("thread failed"),
+            Err(errno) => return err_exit!(errno, "pthread_join"),
+        };
+    }
+
+    println!("glob = {}", unsafe { GLOB });
+    Ok(())
+}
+
+/// Increments `GLOB` `loops` times, holding `MTX` for each increment.
+/// Returns nonzero if the mutex couldn't be used.
+fn increment(loops: usize) -> usize {
+    for _ in 0..loops {
+        if let Err(errno) = MTX.lock() {
+            let _: TlpiResult<()> = err_exit!(errno, "pthread_mutex_lock");
+            return 1;
+        }
+
+        // Deliberately not `GLOB += 1`, to show a read-modify-write
+        let mut loc = unsafe { GLOB };
+        loc += 1;
+        unsafe { GLOB = loc };
+
+        if let Err(errno) = MTX.unlock() {
+            let _: TlpiResult<()> = err_exit!(errno, "pthread_mutex_unlock");
+            return 1;
+        }
+    }
+    0
+}
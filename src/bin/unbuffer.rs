@@ -0,0 +1,117 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::poll::*;
+use tlpi_rust::process::*;
+use tlpi_rust::pty::*;
+use tlpi_rust::tty::*;
+
+/// Capacity of the buffer for data relayed in each direction.
+const BUF_SIZE: usize = 4096;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 2 || argv[1] == "--help" {
+        return usage_err!("{} command [arg...]", argv[0]);
+    }
+
+    let (child, master) = match pty_fork(None, None) {
+        Err(errno) => return err_exit!(errno, "pty_fork"),
+
+        // Child: run the command with the pty slave as its stdio, so
+        // that stdio in the command buffers output by line
+        Ok(PtyForkResult::Child) => {
+            try!(plain_slave().or_else(|errno| err_exit!(errno, "termios")));
+            let args: Vec<_> = argv[1..].iter().map(|arg| &arg[..]).collect();
+            let errno = execvp(args[0], &args);
+            return err_exit!(errno, "execvp {}", args[0]);
+        },
+
+        Ok(PtyForkResult::Parent { child, master, .. }) => (child, master),
+    };
+
+    let result = relay(&master);
+    let _ = master.close();
+    try!(result);
+
+    match waitpid(child, WaitFlags::empty()) {
+        Ok(Some((_, WaitStatus::Exited(0)))) => Ok(()),
+        // The command reports its own errors
+        Ok(_) => Err(()),
+        Err(errno) => err_exit!(errno, "waitpid"),
+    }
+}
+
+/// Configures the pty slave, which is the standard input of the
+/// child, so that the output of the command passes through unchanged:
+/// input is not echoed, and newlines are not mapped to CR-NL.
+fn plain_slave() -> SysResult<()> {
+    let mut attrs = try!(tcgetattr(&STDIN));
+    attrs.local_flags.remove(ECHO);
+    attrs.output_flags.remove(OPOST);
+    tcsetattr(&STDIN, When::Now, &attrs)
+}
+
+/// Copies standard input to `master`, and the output of `master` to
+/// standard output, until the command closes the pty slave.
+///
+/// Once standard input reaches end-of-file, the end-of-file character
+/// is sent to the command instead.
+fn relay(master: &FileDescriptor) -> TlpiResult<()> {
+    let eof_char = try!(
+        tcgetattr(master).or_else(|errno| err_exit!(errno, "tcgetattr"))
+    ).control_chars[VEOF];
+
+    let mut buf = [0u8; BUF_SIZE];
+    let mut stdin_open = true;
+    loop {
+        let mut poll_fds = vec![PollFd::new(master, POLLIN)];
+        if stdin_open {
+            poll_fds.push(PollFd::new(&STDIN, POLLIN));
+        }
+
+        try!(
+            poll(&mut poll_fds, None).or_else(|errno| {
+                if errno == EINTR { Ok(0) } else { err_exit!(errno, "poll") }
+            })
+        );
+
+        // pty --> stdout; reading fails with EIO once the command has
+        // exited and the slave is closed
+        if !poll_fds[0].revents().is_empty() {
+            let bytes_read = match master.read(&mut buf) {
+                Ok(0) | Err(_) => return Ok(()),
+                Ok(count) => count,
+            };
+            try!(STDOUT.write_all(&buf[..bytes_read])
+                .or_else(|errno| err_exit!(errno, "write (stdout)")));
+        }
+
+        // stdin --> pty
+        if stdin_open && !poll_fds[1].revents().is_empty() {
+            match STDIN.read(&mut buf) {
+                Ok(0) => {
+                    stdin_open = false;
+                    try!(master.write_all(&[eof_char])
+                        .or_else(|errno| err_exit!(errno, "write (master)")));
+                },
+                Ok(count) => {
+                    try!(master.write_all(&buf[..count])
+                        .or_else(|errno| err_exit!(errno, "write (master)")));
+                },
+                Err(errno) => return err_exit!(errno, "read"),
+            };
+        }
+    }
+}
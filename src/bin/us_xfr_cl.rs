@@ -0,0 +1,52 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::socket::*;
+
+/// Pathname of the server's listening socket.
+const SV_SOCK_PATH: &'static str = "/tmp/us_xfr";
+
+/// Capacity of the buffer for data sent to the server.
+const BUF_SIZE: usize = 100;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 1 {
+        return usage_err!("{} < input-file", argv[0]);
+    }
+
+    let socket = match unix_stream_connect(SV_SOCK_PATH) {
+        Ok(socket) => socket,
+        Err(errno) => return err_exit!(errno, "connect to {}", SV_SOCK_PATH),
+    };
+
+    // Copy stdin to the socket
+    let mut buf = [0u8; BUF_SIZE];
+    loop {
+        let bytes_read = match STDIN.read(&mut buf) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(errno) => return err_exit!(errno, "read"),
+        };
+
+        match socket.write(&buf[..bytes_read]) {
+            Ok(count) if count == bytes_read => {},
+            Ok(_) => return fatal!("partial/failed write"),
+            Err(errno) => return err_exit!(errno, "write"),
+        };
+    }
+
+    // Closing the socket causes the server to see end-of-file
+    socket.close().or_else(|errno| err_exit!(errno, "close"))
+}
@@ -0,0 +1,71 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+use tlpi_rust::fs::unlink;
+use tlpi_rust::socket::*;
+
+/// Pathname of the server's listening socket.
+const SV_SOCK_PATH: &'static str = "/tmp/us_xfr";
+
+/// Capacity of the buffer for data received from clients.
+const BUF_SIZE: usize = 100;
+
+/// Maximum number of pending connections.
+const BACKLOG: i32 = 5;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 1 {
+        return usage_err!("{}", argv[0]);
+    }
+
+    // Remove any socket file left behind by a previous run
+    match unlink(SV_SOCK_PATH) {
+        Err(errno) if errno != ENOENT => {
+            return err_exit!(errno, "remove-{}", SV_SOCK_PATH)
+        },
+        _ => {},
+    };
+
+    let listen_socket = match unix_stream_listen(SV_SOCK_PATH, BACKLOG) {
+        Ok(socket) => socket,
+        Err(errno) => return err_exit!(errno, "listening on {}", SV_SOCK_PATH),
+    };
+
+    // Handle client connections iteratively, copying each one's data
+    // to stdout
+    loop {
+        let (client, _) = match listen_socket.accept() {
+            Ok(connection) => connection,
+            Err(errno) => return err_exit!(errno, "accept"),
+        };
+
+        let mut buf = [0u8; BUF_SIZE];
+        loop {
+            let bytes_read = match client.read(&mut buf) {
+                Ok(0) => break,
+                Ok(count) => count,
+                Err(errno) => return err_exit!(errno, "read"),
+            };
+
+            match STDOUT.write(&buf[..bytes_read]) {
+                Ok(count) if count == bytes_read => {},
+                Ok(_) => return fatal!("partial/failed write"),
+                Err(errno) => return err_exit!(errno, "write"),
+            };
+        }
+
+        try!(client.close().or_else(|errno| err_exit!(errno, "close")));
+    }
+}
@@ -0,0 +1,40 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use tlpi_rust::dir::*;
+use tlpi_rust::err::*;
+use tlpi_rust::fs::*;
+use tlpi_rust::stat::*;
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() != 2 || argv[1] == "--help" {
+        return usage_err!("{} pathname", argv[0]);
+    }
+    let path = &argv[1];
+
+    let sb = try!(lstat(path).or_else(|errno| err_exit!(errno, "lstat")));
+    if sb.file_type != FileType::Symlink {
+        return fatal!("{} is not a symbolic link", path);
+    }
+
+    let target = try!(
+        readlink(path).or_else(|errno| err_exit!(errno, "readlink"))
+    );
+    println!("readlink: {} --> {}", path, target);
+
+    let resolved = try!(
+        realpath(path).or_else(|errno| err_exit!(errno, "realpath"))
+    );
+    println!("realpath: {} --> {}", path, resolved);
+    Ok(())
+}
@@ -0,0 +1,117 @@
+
+#![feature(libc)]
+
+#[macro_use]
+extern crate tlpi_rust;
+
+use std::env;
+use std::str::FromStr;
+use std::time::Instant;
+use tlpi_rust::err::*;
+use tlpi_rust::fd::*;
+
+/// How the written data is pushed out of the page cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Leave write-back to the kernel.
+    None,
+    /// `fsync()` after each block.
+    Fsync,
+    /// `fdatasync()` after each block.
+    Fdatasync,
+    /// `sync_file_range()` starting write-back of each block, without
+    /// waiting for it.
+    SyncRange,
+    /// `sync_file_range()` writing back each block and waiting for it.
+    SyncRangeWait,
+    /// `posix_fadvise(POSIX_FADV_DONTNEED)` after each block, which
+    /// starts write-back and drops the pages once written.
+    DontNeed,
+}
+
+fn main() {
+    exit_with_status!(main_with_result());
+}
+
+fn main_with_result() -> TlpiResult<()> {
+    let argv: Vec<_> = env::args().collect();
+
+    if argv.len() < 4 || argv.len() > 5 || argv[1] == "--help" {
+        return usage_err!(
+            "{} file num-blocks block-size [none|fsync|fdatasync|\
+             sync-range|sync-range-wait|dontneed]", argv[0]
+        );
+    }
+    let path = &argv[1];
+    let num_blocks = try!(parse_count(&argv[2], "num-blocks"));
+    let block_size = try!(parse_count(&argv[3], "block-size"));
+    let mode = match argv.get(4).map(|arg| &arg[..]) {
+        None | Some("none") => Mode::None,
+        Some("fsync") => Mode::Fsync,
+        Some("fdatasync") => Mode::Fdatasync,
+        Some("sync-range") => Mode::SyncRange,
+        Some("sync-range-wait") => Mode::SyncRangeWait,
+        Some("dontneed") => Mode::DontNeed,
+        Some(arg) => return cmd_line_err!("Invalid mode: {}", arg),
+    };
+
+    let flags = O_WRONLY | O_CREAT | O_TRUNC;
+    let perms = S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH; // rw-r--r--
+    let fd = try!(
+        FileDescriptor::open(path.clone(), flags, perms)
+            .or_else(|errno| err_exit!(errno, "open {}", path))
+    );
+
+    let buf = vec![b'x'; block_size];
+    let start = Instant::now();
+    let result = write_blocks(&fd, &buf, num_blocks, mode);
+    let elapsed = start.elapsed();
+    let _ = fd.close();
+    try!(result);
+
+    let secs = elapsed.as_secs() as f64 +
+        elapsed.subsec_nanos() as f64 / 1e9;
+    println!("{:?}: wrote {} bytes in {:.3} s",
+             mode, num_blocks * block_size, secs);
+    Ok(())
+}
+
+/// Writes `buf` to `fd` `num_blocks` times, pushing each block out of
+/// the page cache as chosen by `mode`.
+fn write_blocks(
+    fd: &FileDescriptor, buf: &[u8], num_blocks: usize, mode: Mode
+) -> TlpiResult<()> {
+    let len = buf.len() as i64;
+    for block in 0..num_blocks {
+        match fd.write(buf) {
+            Ok(count) if count == buf.len() => {},
+            Ok(_) => return fatal!("partial write"),
+            Err(errno) => return err_exit!(errno, "write"),
+        }
+
+        let offset = block as i64 * len;
+        let result = match mode {
+            Mode::None => Ok(()),
+            Mode::Fsync => fd.fsync(),
+            Mode::Fdatasync => fd.fdatasync(),
+            Mode::SyncRange => {
+                fd.sync_file_range(offset, len, SYNC_FILE_RANGE_WRITE)
+            },
+            Mode::SyncRangeWait => {
+                let flags = SYNC_FILE_RANGE_WAIT_BEFORE |
+                    SYNC_FILE_RANGE_WRITE | SYNC_FILE_RANGE_WAIT_AFTER;
+                fd.sync_file_range(offset, len, flags)
+            },
+            Mode::DontNeed => fd.fadvise(offset, len, FileAdvice::DontNeed),
+        };
+        try!(result.or_else(|errno| err_exit!(errno, "{:?}", mode)));
+    }
+    Ok(())
+}
+
+fn parse_count(arg: &str, name: &str) -> TlpiResult<usize> {
+    match usize::from_str(arg) {
+        Ok(count) if count > 0 => Ok(count),
+        _ => cmd_line_err!("Invalid {}: {}", name, arg),
+    }
+}
@@ -0,0 +1,262 @@
+
+//! Provides process credentials: user and group IDs.
+//!
+//! Besides the system calls, this module provides helpers for
+//! set-user-ID and set-group-ID programs, which should hold their
+//! privileges only while they need them, following the guidance of
+//! chapter 38 of _The Linux Programming Interface_.
+
+use std::ptr;
+use libc::{gid_t, uid_t};
+use err::EPERM;
+use fd::SysResult;
+
+/// A set of real, effective and saved IDs, as returned by
+/// `getresuid()` and `getresgid()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResIds<T> {
+    /// The real ID: who owns the process.
+    pub real: T,
+    /// The effective ID, used for permission checks.
+    pub effective: T,
+    /// The saved set ID, which the effective ID can be switched back
+    /// to.
+    pub saved: T,
+}
+
+/// The effective IDs of a process whose privileges are temporarily
+/// dropped, as returned by `temporarily_drop()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Privileges {
+    /// The privileged effective user ID.
+    pub euid: uid_t,
+    /// The privileged effective group ID.
+    pub egid: gid_t,
+}
+
+/// The `getuid()` system call.
+///
+/// Returns the real user ID of the calling process. This function
+/// cannot fail.
+///
+/// Consult the man page (command `man 2 getuid`) for further details.
+pub fn getuid() -> uid_t {
+    unsafe { ::libc::getuid() }
+}
+
+/// The `geteuid()` system call.
+///
+/// Returns the effective user ID of the calling process. This
+/// function cannot fail.
+///
+/// Consult the man page (command `man 2 geteuid`) for further details.
+pub fn geteuid() -> uid_t {
+    unsafe { ::libc::geteuid() }
+}
+
+/// The `getgid()` system call.
+///
+/// Returns the real group ID of the calling process. This function
+/// cannot fail.
+///
+/// Consult the man page (command `man 2 getgid`) for further details.
+pub fn getgid() -> gid_t {
+    unsafe { ::libc::getgid() }
+}
+
+/// The `getegid()` system call.
+///
+/// Returns the effective group ID of the calling process. This
+/// function cannot fail.
+///
+/// Consult the man page (command `man 2 getegid`) for further details.
+pub fn getegid() -> gid_t {
+    unsafe { ::libc::getegid() }
+}
+
+/// The `getresuid()` system call.
+///
+/// Returns the real, effective and saved set-user-IDs of the calling
+/// process.
+///
+/// Consult the man page (command `man 2 getresuid`) for further
+/// details.
+pub fn getresuid() -> SysResult<ResIds<uid_t>> {
+    let mut ids = ResIds { real: 0, effective: 0, saved: 0 };
+    let status = unsafe {
+        ::libc::getresuid(&mut ids.real, &mut ids.effective, &mut ids.saved)
+    };
+    errno_check!(status, ids)
+}
+
+/// The `getresgid()` system call.
+///
+/// Returns the real, effective and saved set-group-IDs of the calling
+/// process.
+///
+/// Consult the man page (command `man 2 getresgid`) for further
+/// details.
+pub fn getresgid() -> SysResult<ResIds<gid_t>> {
+    let mut ids = ResIds { real: 0, effective: 0, saved: 0 };
+    let status = unsafe {
+        ::libc::getresgid(&mut ids.real, &mut ids.effective, &mut ids.saved)
+    };
+    errno_check!(status, ids)
+}
+
+/// The `setresuid()` system call.
+///
+/// Sets the real, effective and saved set-user-IDs of the calling
+/// process; `None` leaves an ID unchanged. An unprivileged process
+/// can only set each ID to the value of one of the three current IDs.
+///
+/// Consult the man page (command `man 2 setresuid`) for further
+/// details.
+pub fn setresuid(
+    real: Option<uid_t>, effective: Option<uid_t>, saved: Option<uid_t>
+) -> SysResult<()> {
+    let status = unsafe {
+        ::libc::setresuid(
+            real.unwrap_or(!0), effective.unwrap_or(!0), saved.unwrap_or(!0)
+        )
+    };
+    errno_check!(status, ())
+}
+
+/// The `setresgid()` system call.
+///
+/// Sets the real, effective and saved set-group-IDs of the calling
+/// process; `None` leaves an ID unchanged. An unprivileged process
+/// can only set each ID to the value of one of the three current IDs.
+///
+/// Consult the man page (command `man 2 setresgid`) for further
+/// details.
+pub fn setresgid(
+    real: Option<gid_t>, effective: Option<gid_t>, saved: Option<gid_t>
+) -> SysResult<()> {
+    let status = unsafe {
+        ::libc::setresgid(
+            real.unwrap_or(!0), effective.unwrap_or(!0), saved.unwrap_or(!0)
+        )
+    };
+    errno_check!(status, ())
+}
+
+/// The `setfsuid()` system call.
+///
+/// Sets the file-system user ID of the calling process, which is used
+/// for permission checks when accessing files, and returns its
+/// previous value; `None` leaves it unchanged, so that it can be
+/// retrieved. The ID normally follows the effective user ID, and so
+/// rarely needs to be set.
+///
+/// There is no indication of failure, other than the ID being left
+/// unchanged.
+///
+/// Consult the man page (command `man 2 setfsuid`) for further
+/// details.
+pub fn setfsuid(fsuid: Option<uid_t>) -> uid_t {
+    // An invalid ID is rejected, but still returns the current one
+    (unsafe { ::libc::setfsuid(fsuid.unwrap_or(!0)) }) as uid_t
+}
+
+/// The `setfsgid()` system call.
+///
+/// Like `setfsuid()`, but for the file-system group ID.
+///
+/// Consult the man page (command `man 2 setfsgid`) for further
+/// details.
+pub fn setfsgid(fsgid: Option<gid_t>) -> gid_t {
+    (unsafe { ::libc::setfsgid(fsgid.unwrap_or(!0)) }) as gid_t
+}
+
+/// The `getgroups()` system call.
+///
+/// Returns the supplementary group IDs of the calling process, which
+/// may or may not include its effective group ID.
+///
+/// Consult the man page (command `man 2 getgroups`) for further
+/// details.
+pub fn getgroups() -> SysResult<Vec<gid_t>> {
+    // A size of zero just returns the number of groups
+    let count = unsafe { ::libc::getgroups(0, ptr::null_mut()) };
+    try!(errno_check!(count, ()));
+
+    let mut groups = vec![0; count as usize];
+    let count = unsafe { ::libc::getgroups(count, groups.as_mut_ptr()) };
+    try!(errno_check!(count, ()));
+    // Groups may have been dropped in between the calls
+    groups.truncate(count as usize);
+    Ok(groups)
+}
+
+/// Permanently drops the privileges of a set-user-ID or set-group-ID
+/// program, by setting all of its user and group IDs to the real IDs.
+///
+/// Group IDs are changed first, since changing them may need the
+/// privileges given by the user IDs. If the process is privileged
+/// (effective user ID 0), its supplementary groups are also reduced
+/// to just the real group ID. Each change is verified afterwards,
+/// failing with `EPERM` if any ID was not changed as expected.
+pub fn drop_privileges_permanently() -> SysResult<()> {
+    let uids = try!(getresuid());
+    let gids = try!(getresgid());
+
+    if uids.effective == 0 {
+        let groups = [gids.real];
+        let status = unsafe { ::libc::setgroups(1, groups.as_ptr()) };
+        try!(errno_check!(status, ()));
+    }
+    try!(setresgid(Some(gids.real), Some(gids.real), Some(gids.real)));
+    try!(setresuid(Some(uids.real), Some(uids.real), Some(uids.real)));
+
+    if all_equal(&try!(getresgid()), gids.real) &&
+        all_equal(&try!(getresuid()), uids.real)
+    {
+        Ok(())
+    } else {
+        Err(EPERM)
+    }
+}
+
+/// Temporarily drops the privileges of a set-user-ID or set-group-ID
+/// program, by setting its effective user and group IDs to the real
+/// IDs; the saved IDs still hold the privileged IDs, which are
+/// returned so that they can be restored by `regain()`.
+///
+/// The group ID is changed first, and each change is verified, failing
+/// with `EPERM` if an ID was not changed as expected. Supplementary
+/// groups are not changed.
+pub fn temporarily_drop() -> SysResult<Privileges> {
+    let privileges = Privileges { euid: geteuid(), egid: getegid() };
+    let (ruid, rgid) = (getuid(), getgid());
+
+    try!(setresgid(None, Some(rgid), None));
+    try!(setresuid(None, Some(ruid), None));
+    if getegid() == rgid && geteuid() == ruid {
+        Ok(privileges)
+    } else {
+        Err(EPERM)
+    }
+}
+
+/// Restores the effective user and group IDs saved by
+/// `temporarily_drop()`.
+///
+/// The user ID is changed first, since it may give the privileges
+/// needed to change the group ID. Each change is verified, failing
+/// with `EPERM` if an ID was not changed as expected.
+pub fn regain(privileges: &Privileges) -> SysResult<()> {
+    try!(setresuid(None, Some(privileges.euid), None));
+    try!(setresgid(None, Some(privileges.egid), None));
+    if geteuid() == privileges.euid && getegid() == privileges.egid {
+        Ok(())
+    } else {
+        Err(EPERM)
+    }
+}
+
+/// Helper function; whether all three of `ids` are `value`.
+fn all_equal<T: PartialEq>(ids: &ResIds<T>, value: T) -> bool {
+    ids.real == value && ids.effective == value && ids.saved == value
+}
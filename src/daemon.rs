@@ -0,0 +1,68 @@
+
+//! Provides utilities for writing daemon processes.
+
+use libc::{c_int, EIO};
+use err::Errno;
+use fd::*;
+use process::getpid;
+
+bitflags! {
+    #[doc = "Options for `create_pid_file()`."]
+    flags PidFileFlags: c_int {
+        #[doc = "set the close-on-exec flag on the returned descriptor"]
+        const CPF_CLOEXEC = 0b1,
+    }
+}
+
+/// Creates a PID file at `path`, ensuring that only one instance of
+/// a program (e.g. a daemon) runs at a time.
+///
+/// The file is opened (and created if necessary), and an exclusive
+/// record lock is placed on the whole of it. The file is then
+/// truncated and the PID of the calling process written to it, as a
+/// decimal number followed by a newline.
+///
+/// Returns the descriptor of the PID file; it must be kept open, as
+/// closing it releases the lock.
+///
+/// Fails with `EAGAIN` or `EACCES` if the file is locked by another
+/// process, which probably means the program is already running, and
+/// with `EIO` if the PID couldn't be written in full.
+///
+/// This is a port of `createPidFile()` from _The Linux Programming
+/// Interface_.
+pub fn create_pid_file(
+    path: &str, flags: PidFileFlags
+) -> SysResult<FileDescriptor> {
+    let mut open_flags = O_RDWR | O_CREAT;
+    if flags.contains(CPF_CLOEXEC) {
+        open_flags = open_flags | O_CLOEXEC;
+    }
+
+    let fd = try!(FileDescriptor::open(
+        String::from(path), open_flags, S_IRUSR | S_IWUSR
+    ));
+
+    match write_pid(&fd) {
+        Ok(()) => Ok(fd),
+        Err(errno) => {
+            // The original error is more informative than any close
+            // error
+            let _ = fd.close();
+            Err(errno)
+        },
+    }
+}
+
+/// Helper function; locks the already-open PID file and replaces its
+/// contents with the PID of the calling process.
+fn write_pid(fd: &FileDescriptor) -> SysResult<()> {
+    try!(fd.set_lock(&FileLock::whole_file(LockType::Write)));
+    try!(fd.ftruncate(0));
+
+    let pid_line = format!("{}\n", getpid());
+    match try!(fd.write(pid_line.as_bytes())) {
+        count if count == pid_line.len() => Ok(()),
+        _ => Err(Errno::new(EIO)),
+    }
+}
@@ -0,0 +1,300 @@
+
+//! Provides reading of directories.
+//!
+//! `Dir` reads entries with the `readdir()` library function, and is
+//! an iterator over them. `DirReader` instead calls the underlying
+//! `getdents64()` system call directly, with a buffer that is reused
+//! for every batch of entries, so that large directories can be
+//! scanned without allocating for each entry.
+
+use std::ffi::{CStr, CString};
+use std::mem;
+use std::ptr;
+use libc::{c_long, dirent64, DIR};
+use err::Errno;
+use fd::{FileDescriptor, SysResult, FilePerms, O_DIRECTORY, O_RDONLY};
+use fd::O_CLOEXEC;
+
+/// The type of a file, as given by a directory entry (`d_type`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileType {
+    /// `DT_REG`: a regular file.
+    Regular,
+    /// `DT_DIR`: a directory.
+    Directory,
+    /// `DT_LNK`: a symbolic link.
+    Symlink,
+    /// `DT_FIFO`: a FIFO or pipe.
+    Fifo,
+    /// `DT_SOCK`: a socket.
+    Socket,
+    /// `DT_CHR`: a character device.
+    CharDevice,
+    /// `DT_BLK`: a block device.
+    BlockDevice,
+    /// `DT_UNKNOWN`: the file system doesn't supply the type; use
+    /// `stat()` to find it.
+    Unknown,
+}
+
+/// An entry read by `Dir`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirEntry {
+    /// The i-node number of the file.
+    pub ino: u64,
+    /// The type of the file.
+    pub file_type: FileType,
+    /// The name of the entry, with any bytes that are not valid UTF-8
+    /// replaced by U+FFFD.
+    pub name: String,
+}
+
+/// A directory stream (`DIR *`), opened with `opendir()`.
+///
+/// Iterating over the stream yields its entries, including `.` and
+/// `..`, in no particular order. The stream is closed when dropped,
+/// ignoring any error; call `close()` explicitly to check for
+/// failure.
+pub struct Dir(*mut DIR);
+
+/// An entry read by `DirReader`, borrowing its name from the reader's
+/// buffer (`struct linux_dirent64`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawDirEntry<'a> {
+    /// The i-node number of the file.
+    pub ino: u64,
+    /// The position of the next entry, as understood by `lseek()`.
+    pub offset: i64,
+    /// The type of the file.
+    pub file_type: FileType,
+    /// The name of the entry.
+    pub name: &'a [u8],
+}
+
+/// Reads the entries of a directory with the `getdents64()` system
+/// call.
+///
+/// Each call fills the buffer with as many entries as fit, which are
+/// then returned one by one by `next_entry()`.
+pub struct DirReader {
+    /// The open directory.
+    fd: FileDescriptor,
+
+    /// The buffer filled by `getdents64()`; made of `u64`s so that
+    /// the entries, whose lengths are multiples of 8, are aligned.
+    buffer: Vec<u64>,
+
+    /// The offset in bytes of the next entry in the buffer.
+    next_index: usize,
+
+    /// The number of bytes in the buffer returned by the last call.
+    bytes_read: usize,
+}
+
+impl FileType {
+
+    /// Helper function; converts a `d_type` value.
+    fn from_raw(d_type: u8) -> FileType {
+        match d_type {
+            DT_REG => FileType::Regular,
+            DT_DIR => FileType::Directory,
+            DT_LNK => FileType::Symlink,
+            DT_FIFO => FileType::Fifo,
+            DT_SOCK => FileType::Socket,
+            DT_CHR => FileType::CharDevice,
+            DT_BLK => FileType::BlockDevice,
+            _ => FileType::Unknown,
+        }
+    }
+
+    /// Converts the file type bits (`S_IFMT`) of a file mode, as
+    /// returned by the stat family of system calls.
+    ///
+    /// Intended for the other modules of this crate.
+    pub fn from_mode(mode: u32) -> FileType {
+        // The file type bits are the `d_type` values shifted left
+        FileType::from_raw(((mode & S_IFMT) >> 12) as u8)
+    }
+
+}
+
+impl Dir {
+
+    /// The `opendir()` library function.
+    ///
+    /// Opens the directory `path` for reading.
+    ///
+    /// Consult the man page (command `man 3 opendir`) for further
+    /// details.
+    pub fn open(path: &str) -> SysResult<Dir> {
+        // Panic if `path` contains nul chars; crude but good enough
+        let c_path = CString::new(path).unwrap();
+        let raw = unsafe { ::libc::opendir(c_path.as_ptr()) };
+        let status = if raw.is_null() { -1 } else { 0 };
+        errno_check!(status, Dir(raw))
+    }
+
+    /// The `readdir()` library function.
+    ///
+    /// Returns the next entry of the directory, or `None` once all of
+    /// them have been read.
+    ///
+    /// Consult the man page (command `man 3 readdir`) for further
+    /// details.
+    pub fn read(&mut self) -> SysResult<Option<DirEntry>> {
+        // The end of the directory and an error both return null, so
+        // clear errno to tell them apart
+        Errno::set_last(Errno::new(0));
+        let raw = unsafe { ::libc::readdir64(self.0) };
+        if raw.is_null() {
+            let errno = Errno::last();
+            return if errno.value() == 0 { Ok(None) } else { Err(errno) };
+        }
+
+        let raw: &dirent64 = unsafe { &*raw };
+        let name = unsafe { CStr::from_ptr(raw.d_name.as_ptr()) };
+        Ok(Some(DirEntry {
+            ino: raw.d_ino,
+            file_type: FileType::from_raw(raw.d_type),
+            name: name.to_string_lossy().into_owned(),
+        }))
+    }
+
+    /// The `rewinddir()` library function.
+    ///
+    /// Moves back to the start of the directory, so that the entries
+    /// are read again, including any changes made since.
+    ///
+    /// Consult the man page (command `man 3 rewinddir`) for further
+    /// details.
+    pub fn rewind(&mut self) {
+        unsafe { ::libc::rewinddir(self.0) };
+    }
+
+    /// The `closedir()` library function.
+    ///
+    /// Consult the man page (command `man 3 closedir`) for further
+    /// details.
+    pub fn close(self) -> SysResult<()> {
+        let status = unsafe { ::libc::closedir(self.0) };
+        // Already closed (or failed to close); don't try again
+        mem::forget(self);
+        errno_check!(status, ())
+    }
+
+}
+
+impl Iterator for Dir {
+
+    type Item = SysResult<DirEntry>;
+
+    fn next(&mut self) -> Option<SysResult<DirEntry>> {
+        match self.read() {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => None,
+            Err(errno) => Some(Err(errno)),
+        }
+    }
+
+}
+
+impl Drop for Dir {
+
+    fn drop(&mut self) {
+        unsafe { ::libc::closedir(self.0) };
+    }
+
+}
+
+impl DirReader {
+
+    /// Opens the directory `path`, to be read with a buffer of
+    /// `buffer_size` bytes (rounded up to a multiple of 8).
+    pub fn open(path: &str, buffer_size: usize) -> SysResult<DirReader> {
+        let flags = O_RDONLY | O_DIRECTORY | O_CLOEXEC;
+        let fd = try!(
+            FileDescriptor::open(path.to_string(), flags, FilePerms::empty())
+        );
+        Ok(DirReader::new(fd, buffer_size))
+    }
+
+    /// Reads the already open directory `fd`, with a buffer of
+    /// `buffer_size` bytes (rounded up to a multiple of 8).
+    pub fn new(fd: FileDescriptor, buffer_size: usize) -> DirReader {
+        let words = (buffer_size + 7) / 8;
+        DirReader {
+            fd: fd,
+            buffer: vec![0; words],
+            next_index: 0,
+            bytes_read: 0,
+        }
+    }
+
+    /// Returns the next entry of the directory, or `None` once all of
+    /// them have been read. When the buffer is used up, the
+    /// `getdents64()` system call refills it.
+    ///
+    /// Fails with `EINVAL` if the buffer is too small for the next
+    /// entry.
+    ///
+    /// Consult the man page (command `man 2 getdents64`) for further
+    /// details.
+    pub fn next_entry(&mut self) -> SysResult<Option<RawDirEntry>> {
+        if self.next_index >= self.bytes_read {
+            let count = unsafe {
+                ::libc::syscall(
+                    SYS_GETDENTS64, self.fd.as_raw(),
+                    self.buffer.as_mut_ptr(), self.buffer.len() * 8
+                )
+            };
+            try!(errno_check!(count, ()));
+            if count == 0 {
+                return Ok(None);
+            }
+            self.next_index = 0;
+            self.bytes_read = count as usize;
+        }
+
+        let entry = unsafe {
+            (self.buffer.as_ptr() as *const u8)
+                .offset(self.next_index as isize)
+        };
+        let (ino, offset, reclen, d_type) = unsafe {
+            (
+                ptr::read(entry as *const u64),
+                ptr::read(entry.offset(8) as *const i64),
+                ptr::read(entry.offset(16) as *const u16),
+                *entry.offset(18),
+            )
+        };
+        let name = unsafe {
+            CStr::from_ptr(entry.offset(19) as *const _).to_bytes()
+        };
+        self.next_index += reclen as usize;
+
+        Ok(Some(RawDirEntry {
+            ino: ino,
+            offset: offset,
+            file_type: FileType::from_raw(d_type),
+            name: name,
+        }))
+    }
+
+    /// Closes the directory.
+    pub fn close(self) -> SysResult<()> {
+        self.fd.close()
+    }
+
+}
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const SYS_GETDENTS64: c_long = 217;
+const DT_FIFO: u8 = 1;
+const DT_CHR: u8 = 2;
+const DT_DIR: u8 = 4;
+const DT_BLK: u8 = 6;
+const DT_REG: u8 = 8;
+const DT_LNK: u8 = 10;
+const DT_SOCK: u8 = 12;
+const S_IFMT: u32 = 0o170000;
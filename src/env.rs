@@ -0,0 +1,124 @@
+
+//! Provides access to the environment list of the process.
+//!
+//! Unlike `std::env`, these functions work on the C library's
+//! environment directly, as the programs of chapter 6 of _The Linux
+//! Programming Interface_ do. None of them are thread-safe: the
+//! environment must not be changed while another thread may be
+//! reading it.
+
+use std::ffi::{CStr, CString};
+use std::mem;
+use libc::{c_char, c_int};
+use err::Errno;
+use fd::SysResult;
+
+/// Returns the entries of the environment list, each of the form
+/// `name=value`, by walking the `environ` pointer.
+///
+/// Any bytes that are not valid UTF-8 are replaced by U+FFFD.
+pub fn environ() -> Vec<String> {
+    let mut entries = Vec::new();
+    unsafe {
+        // The list is terminated by a null pointer, and may itself be
+        // null after `clearenv()`
+        let mut entry = environ_c;
+        while !entry.is_null() && !(*entry).is_null() {
+            let entry_str = CStr::from_ptr(*entry).to_string_lossy();
+            entries.push(entry_str.into_owned());
+            entry = entry.offset(1);
+        }
+    }
+    entries
+}
+
+/// The `getenv()` library function.
+///
+/// Returns the value of the environment variable `name`, or `None` if
+/// it isn't set. Any bytes that are not valid UTF-8 are replaced by
+/// U+FFFD.
+///
+/// Consult the man page (command `man 3 getenv`) for further details.
+pub fn getenv(name: &str) -> Option<String> {
+    // Panic if `name` contains nul chars; crude but good enough
+    let c_name = CString::new(name).unwrap();
+    let value = unsafe { ::libc::getenv(c_name.as_ptr()) };
+    if value.is_null() {
+        None
+    } else {
+        let value = unsafe { CStr::from_ptr(value) };
+        Some(value.to_string_lossy().into_owned())
+    }
+}
+
+/// The `putenv()` library function.
+///
+/// Adds `entry`, of the form `name=value`, to the environment,
+/// replacing any existing variable `name`.
+///
+/// The environment refers to the string itself rather than a copy,
+/// so it is leaked, to stay valid for the rest of the life of the
+/// process.
+///
+/// Consult the man page (command `man 3 putenv`) for further details.
+pub fn putenv(entry: &str) -> SysResult<()> {
+    // Panic if `entry` contains nul chars; crude but good enough
+    let c_entry = CString::new(entry).unwrap();
+    let ptr = c_entry.as_ptr() as *mut c_char;
+    mem::forget(c_entry);
+    let status = unsafe { ::libc::putenv(ptr) };
+    // Unlike most functions, `putenv()` returns a nonzero value on
+    // error, rather than -1
+    errno_check!(if status == 0 { 0 } else { -1 }, ())
+}
+
+/// The `setenv()` library function.
+///
+/// Sets the environment variable `name` to a copy of `value`. If
+/// `name` is already set, it is only changed if `overwrite` is true.
+///
+/// Consult the man page (command `man 3 setenv`) for further details.
+pub fn setenv(name: &str, value: &str, overwrite: bool) -> SysResult<()> {
+    // Panic if the strings contain nul chars; crude but good enough
+    let c_name = CString::new(name).unwrap();
+    let c_value = CString::new(value).unwrap();
+    let status = unsafe {
+        ::libc::setenv(c_name.as_ptr(), c_value.as_ptr(), overwrite as c_int)
+    };
+    errno_check!(status, ())
+}
+
+/// The `unsetenv()` library function.
+///
+/// Removes the environment variable `name`, if it is set.
+///
+/// Consult the man page (command `man 3 unsetenv`) for further
+/// details.
+pub fn unsetenv(name: &str) -> SysResult<()> {
+    // Panic if `name` contains nul chars; crude but good enough
+    let c_name = CString::new(name).unwrap();
+    let status = unsafe { ::libc::unsetenv(c_name.as_ptr()) };
+    errno_check!(status, ())
+}
+
+/// The `clearenv()` library function.
+///
+/// Removes every variable from the environment.
+///
+/// Consult the man page (command `man 3 clearenv`) for further
+/// details.
+pub fn clearenv() -> SysResult<()> {
+    // It isn't specified to set errno on failure, so don't let a stale
+    // value be reported
+    Errno::set_last(Errno::new(0));
+    let status = unsafe { clearenv_c() };
+    if status == 0 { Ok(()) } else { Err(Errno::last()) }
+}
+
+extern {
+    #[link_name = "environ"]
+    static mut environ_c: *const *const c_char;
+
+    #[link_name = "clearenv"]
+    fn clearenv_c() -> c_int;
+}
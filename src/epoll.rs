@@ -0,0 +1,209 @@
+
+//! Provides I/O multiplexing with epoll.
+//!
+//! An `Epoll` instance holds an interest list of file descriptors,
+//! each with the events to monitor it for and a value identifying it.
+//! Unlike `poll()`, the list is kept by the kernel between calls, so
+//! waiting costs time in proportion to the number of ready file
+//! descriptors rather than the number monitored.
+
+use std::time::Duration;
+use libc::{c_int, uint32_t, uint64_t};
+use fd::{FileDescriptor, SysResult};
+use poll::timeout_millis;
+
+/// An epoll instance, created with `epoll_create1()`.
+///
+/// As with `FileDescriptor`, does not implement `Copy` so that
+/// `Epoll::close()` can take ownership.
+#[derive(Debug)]
+pub struct Epoll(FileDescriptor);
+
+/// A ready file descriptor, as returned by `Epoll::wait()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpollEvent {
+    /// The events that occurred.
+    pub events: EpollFlags,
+    /// The value given when the file descriptor was added.
+    pub data: u64,
+}
+
+impl Epoll {
+
+    /// The `epoll_create1()` system call.
+    ///
+    /// Creates a new epoll instance, with an empty interest list.
+    ///
+    /// Consult the man page (command `man 2 epoll_create1`) for
+    /// further details.
+    pub fn create() -> SysResult<Epoll> {
+        let fd = unsafe { epoll_create1_c(0) };
+        errno_check!(fd, Epoll(FileDescriptor::from_raw(fd)))
+    }
+
+    /// Returns the file descriptor of the instance, which can itself
+    /// be monitored by `poll()`, `select()` or another epoll instance.
+    pub fn as_fd(&self) -> &FileDescriptor { &self.0 }
+
+    /// The `epoll_ctl()` system call, with `EPOLL_CTL_ADD`.
+    ///
+    /// Adds `fd` to the interest list, to be monitored for `events`;
+    /// `data` is returned with each of its events, to identify it.
+    /// Fails with `EEXIST` if `fd` is already in the list, or with
+    /// `EPERM` if it refers to a regular file or directory.
+    ///
+    /// Consult the man page (command `man 2 epoll_ctl`) for further
+    /// details.
+    pub fn add(
+        &self, fd: &FileDescriptor, events: EpollFlags, data: u64
+    ) -> SysResult<()> {
+        self.ctl(EPOLL_CTL_ADD, fd, events, data)
+    }
+
+    /// The `epoll_ctl()` system call, with `EPOLL_CTL_MOD`.
+    ///
+    /// Replaces the events and data of `fd`, which must be in the
+    /// interest list; this also rearms a file descriptor disabled by
+    /// `EPOLLONESHOT`.
+    ///
+    /// Consult the man page (command `man 2 epoll_ctl`) for further
+    /// details.
+    pub fn modify(
+        &self, fd: &FileDescriptor, events: EpollFlags, data: u64
+    ) -> SysResult<()> {
+        self.ctl(EPOLL_CTL_MOD, fd, events, data)
+    }
+
+    /// The `epoll_ctl()` system call, with `EPOLL_CTL_DEL`.
+    ///
+    /// Removes `fd` from the interest list. A file descriptor is also
+    /// removed once every descriptor referring to its open file
+    /// description has been closed.
+    ///
+    /// Consult the man page (command `man 2 epoll_ctl`) for further
+    /// details.
+    pub fn delete(&self, fd: &FileDescriptor) -> SysResult<()> {
+        self.ctl(EPOLL_CTL_DEL, fd, EpollFlags::empty(), 0)
+    }
+
+    /// The `epoll_wait()` system call.
+    ///
+    /// Blocks until at least one file descriptor in the interest list
+    /// is ready, or until `timeout` expires; `None` means wait
+    /// indefinitely, and zero means don't block. The timeout is
+    /// rounded up to whole milliseconds. Returns up to `max_events`
+    /// ready file descriptors (none on timeout); any others are
+    /// returned by later calls, in turn.
+    ///
+    /// Consult the man page (command `man 2 epoll_wait`) for further
+    /// details.
+    pub fn wait(
+        &self, max_events: usize, timeout: Option<Duration>
+    ) -> SysResult<Vec<EpollEvent>> {
+        let empty = RawEpollEvent { events: 0, data: 0 };
+        let mut raw = vec![empty; max_events];
+        let ready = unsafe {
+            epoll_wait_c(
+                self.0.as_raw(), raw.as_mut_ptr(), max_events as c_int,
+                timeout_millis(timeout)
+            )
+        };
+        try!(errno_check!(ready, ()));
+
+        let events = raw[..ready as usize].iter()
+            .map(|event| EpollEvent {
+                events: EpollFlags::from_bits_truncate(event.events),
+                data: event.data,
+            })
+            .collect();
+        Ok(events)
+    }
+
+    /// The `close()` system call; empties the interest list.
+    pub fn close(self) -> SysResult<()> {
+        self.0.close()
+    }
+
+    /// Helper method; performs one of the `epoll_ctl()` operations.
+    fn ctl(
+        &self, op: c_int, fd: &FileDescriptor, events: EpollFlags,
+        data: u64
+    ) -> SysResult<()> {
+        let mut raw = RawEpollEvent { events: events.bits(), data: data };
+        let status = unsafe {
+            epoll_ctl_c(self.0.as_raw(), op, fd.as_raw(), &mut raw)
+        };
+        errno_check!(status, ())
+    }
+
+}
+
+/// The C `struct epoll_event`, which is packed on x86-64.
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct RawEpollEvent {
+    events: uint32_t,
+    data: uint64_t,
+}
+
+bitflags! {
+    #[doc = "Events monitored and reported by epoll, and options for"]
+    #[doc = "monitoring them."]
+    #[doc = ""]
+    #[doc = "Flags marked (reported) are only meaningful in the"]
+    #[doc = "returned events; they are always monitored. Flags marked"]
+    #[doc = "(option) are never returned."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 epoll_ctl` for details on each flag."]
+    #[doc = ""]
+    #[doc = "Taken from C header files on an x86-64 Linux system; not"]
+    #[doc = "intended to be portable!"]
+    flags EpollFlags: u32 {
+        #[doc = "data other than high-priority data can be read"]
+        const EPOLLIN        = 0x0000_0001,
+        #[doc = "high-priority data can be read"]
+        const EPOLLPRI       = 0x0000_0002,
+        #[doc = "normal data can be written"]
+        const EPOLLOUT       = 0x0000_0004,
+        #[doc = "(reported) an error has occurred"]
+        const EPOLLERR       = 0x0000_0008,
+        #[doc = "(reported) a hangup has occurred"]
+        const EPOLLHUP       = 0x0000_0010,
+        #[doc = "the peer of a stream socket has shut down writing"]
+        const EPOLLRDHUP     = 0x0000_2000,
+        #[doc = "(option) wake only one of the epoll instances waiting"]
+        #[doc = "for the same file descriptor"]
+        const EPOLLEXCLUSIVE = 0x1000_0000,
+        #[doc = "(option) prevent system suspension while events are"]
+        #[doc = "pending"]
+        const EPOLLWAKEUP    = 0x2000_0000,
+        #[doc = "(option) disable monitoring after the first event,"]
+        #[doc = "until rearmed by `Epoll::modify()`"]
+        const EPOLLONESHOT   = 0x4000_0000,
+        #[doc = "(option) report only changes in readiness"]
+        #[doc = "(edge-triggered), rather than readiness itself"]
+        const EPOLLET        = 0x8000_0000,
+    }
+}
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const EPOLL_CTL_ADD: c_int = 1;
+const EPOLL_CTL_DEL: c_int = 2;
+const EPOLL_CTL_MOD: c_int = 3;
+
+extern {
+    #[link_name = "epoll_create1"]
+    fn epoll_create1_c(flags: c_int) -> c_int;
+
+    #[link_name = "epoll_ctl"]
+    fn epoll_ctl_c(
+        epfd: c_int, op: c_int, fd: c_int, event: *mut RawEpollEvent
+    ) -> c_int;
+
+    #[link_name = "epoll_wait"]
+    fn epoll_wait_c(
+        epfd: c_int, events: *mut RawEpollEvent, maxevents: c_int,
+        timeout: c_int
+    ) -> c_int;
+}
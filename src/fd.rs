@@ -2,11 +2,15 @@
 //! Provides operations on file descriptors.
 
 use std::ffi;
-use std::io;
-use libc::{open, read, write, close, lseek, ftruncate};
-use libc::{c_int, size_t, mode_t, c_void, off_t};
+use std::mem;
+use std::ptr;
+use libc::{open, read, write, close, lseek, ftruncate, fcntl};
+use libc::{c_int, c_short, size_t, mode_t, c_void, off_t, pid_t, flock};
+use libc::{c_long, iovec};
+use libc::{F_ADD_SEALS, F_GET_SEALS, F_GETLK, F_SETLK, F_SETLKW};
+use libc::{FD_CLOEXEC, F_GETFD, F_SETFD, F_GETFL, F_SETFL};
 use libc::{STDIN_FILENO, STDOUT_FILENO, STDERR_FILENO};
-use err::Errno;
+use err::{Errno, EIO};
 
 /// The result of a system call.
 pub type SysResult<T> = Result<T, Errno>;
@@ -16,6 +20,7 @@ pub type SysResult<T> = Result<T, Errno>;
 /// Does not implement `Copy` so that `FileDescriptor::close()` can
 /// take ownership, preventing file descriptors from being used
 /// afterwards.
+#[derive(Debug)]
 pub struct FileDescriptor(c_int);
 
 /// File descritor for standard input
@@ -27,17 +32,6 @@ pub const STDOUT: FileDescriptor = FileDescriptor(STDOUT_FILENO);
 /// File descriptor for standard error
 pub const STDERR: FileDescriptor = FileDescriptor(STDERR_FILENO);
 
-/// Factors out the common operation of creating a `SysResult` based
-/// on a syscall return value and `errno`.
-macro_rules! errno_check {
-    ($status:expr, $success:expr) => (
-        {
-            let errno = io::Error::last_os_error().raw_os_error().unwrap();
-            if $status == -1 { Err(Errno::new(errno)) } else { Ok($success) }
-        }
-    )
-}
-
 impl FileDescriptor {
 
     /// The `open()` system call.
@@ -62,11 +56,26 @@ impl FileDescriptor {
         path: String, flags: OpenFlags, mode: FilePerms
     ) -> SysResult<FileDescriptor> {
         // Panic if `path` contains nul chars; crude but good enough
-        let cstring_path = ffi::CString::new(path).unwrap().as_ptr();
-        let fd = unsafe { open(cstring_path, flags.bits(), mode.bits()) };
+        let cstring_path = ffi::CString::new(path).unwrap();
+        let fd = unsafe {
+            open(cstring_path.as_ptr(), flags.bits(), mode.bits())
+        };
         errno_check!(fd, FileDescriptor(fd))
     }
 
+    /// Returns the underlying integer file descriptor.
+    ///
+    /// Intended for the other modules of this crate, whose system
+    /// calls need to be given the raw value.
+    pub fn as_raw(&self) -> c_int { self.0 }
+
+    /// Wraps a raw integer file descriptor, taking responsibility for
+    /// closing it.
+    ///
+    /// Intended for the other modules of this crate, whose system
+    /// calls create new file descriptors.
+    pub fn from_raw(fd: c_int) -> FileDescriptor { FileDescriptor(fd) }
+
     /// The `read()` system call.
     ///
     /// Copies up to `buf.len()` bytes from the file into `buf`,
@@ -96,6 +105,91 @@ impl FileDescriptor {
         errno_check!(bytes_written, bytes_written as usize)
     }
 
+    /// Copies all of `buf` to the file, calling `write()` again after
+    /// a partial write.
+    ///
+    /// Fails with `EIO` if a write makes no progress. On failure, an
+    /// unknown amount of `buf` may already have been written.
+    pub fn write_all(&self, buf: &[u8]) -> SysResult<()> {
+        let mut written = 0;
+        while written < buf.len() {
+            match try!(self.write(&buf[written..])) {
+                0 => return Err(EIO),
+                count => written += count,
+            }
+        }
+        Ok(())
+    }
+
+    /// The `pread()` system call.
+    ///
+    /// Like `read()`, but reads from the given `offset` in the file,
+    /// without using or changing the file offset.
+    ///
+    /// Consult the man page (command `man 2 pread`) for further
+    /// details.
+    pub fn pread(&self, buf: &mut [u8], offset: i64) -> SysResult<usize> {
+        let buf_ptr = buf.as_mut_ptr() as *mut c_void;
+        let buf_len = buf.len() as size_t;
+        let bytes_read = unsafe {
+            ::libc::pread(self.0, buf_ptr, buf_len, offset as off_t)
+        };
+        errno_check!(bytes_read, bytes_read as usize)
+    }
+
+    /// The `pwrite()` system call.
+    ///
+    /// Like `write()`, but writes at the given `offset` in the file,
+    /// without using or changing the file offset.
+    ///
+    /// Consult the man page (command `man 2 pwrite`) for further
+    /// details.
+    pub fn pwrite(&self, buf: &[u8], offset: i64) -> SysResult<usize> {
+        let buf_ptr = buf.as_ptr() as *const c_void;
+        let buf_len = buf.len() as size_t;
+        let bytes_written = unsafe {
+            ::libc::pwrite(self.0, buf_ptr, buf_len, offset as off_t)
+        };
+        errno_check!(bytes_written, bytes_written as usize)
+    }
+
+    /// The `readv()` system call.
+    ///
+    /// Like `read()`, but scatters the data read across `bufs`,
+    /// filling each buffer completely before moving on to the next.
+    /// The read is atomic with respect to the file offset.
+    ///
+    /// Consult the man page (command `man 2 readv`) for further
+    /// details.
+    pub fn readv(&self, bufs: &mut [&mut [u8]]) -> SysResult<usize> {
+        let iov: Vec<iovec> = bufs.iter_mut().map(|buf| iovec {
+            iov_base: buf.as_mut_ptr() as *mut c_void,
+            iov_len: buf.len() as size_t,
+        }).collect();
+        let bytes_read = unsafe {
+            ::libc::readv(self.0, iov.as_ptr(), iov.len() as c_int)
+        };
+        errno_check!(bytes_read, bytes_read as usize)
+    }
+
+    /// The `writev()` system call.
+    ///
+    /// Like `write()`, but gathers the data to write from `bufs`, in
+    /// order. The write is atomic with respect to the file offset.
+    ///
+    /// Consult the man page (command `man 2 writev`) for further
+    /// details.
+    pub fn writev(&self, bufs: &[&[u8]]) -> SysResult<usize> {
+        let iov: Vec<iovec> = bufs.iter().map(|buf| iovec {
+            iov_base: buf.as_ptr() as *mut c_void,
+            iov_len: buf.len() as size_t,
+        }).collect();
+        let bytes_written = unsafe {
+            ::libc::writev(self.0, iov.as_ptr(), iov.len() as c_int)
+        };
+        errno_check!(bytes_written, bytes_written as usize)
+    }
+
     /// The `close()` system call.
     ///
     /// Cleans up kernel resources for the file descriptor; it can no
@@ -140,6 +234,362 @@ impl FileDescriptor {
         errno_check!(status, ())
     }
 
+    /// The `fallocate()` system call.
+    ///
+    /// With empty `mode`, allocates disk space for the `len` bytes of
+    /// the file at `offset`, growing the file if needed, so that later
+    /// writes there cannot fail for lack of space. Other modes change
+    /// the allocation in other ways; for example,
+    /// `FALLOC_FL_PUNCH_HOLE | FALLOC_FL_KEEP_SIZE` deallocates the
+    /// range, leaving a hole. Not every file system supports every
+    /// mode; others fail with `EOPNOTSUPP`.
+    ///
+    /// Linux-specific.
+    ///
+    /// Consult the man page (command `man 2 fallocate`) for further
+    /// details.
+    pub fn fallocate(
+        &self, mode: FallocateFlags, offset: i64, len: i64
+    ) -> SysResult<()> {
+        let status = unsafe {
+            ::libc::fallocate(
+                self.0, mode.bits(), offset as off_t, len as off_t
+            )
+        };
+        errno_check!(status, ())
+    }
+
+    /// The `fsync()` system call.
+    ///
+    /// Flushes the data and metadata of the file to the storage
+    /// device, returning once the transfer is complete.
+    ///
+    /// Consult the man page (command `man 2 fsync`) for further
+    /// details.
+    pub fn fsync(&self) -> SysResult<()> {
+        let status = unsafe { ::libc::fsync(self.0) };
+        errno_check!(status, ())
+    }
+
+    /// The `fdatasync()` system call.
+    ///
+    /// Like `fsync()`, but only flushes the metadata needed to read
+    /// the data back, such as the file size, and not timestamps.
+    ///
+    /// Consult the man page (command `man 2 fdatasync`) for further
+    /// details.
+    pub fn fdatasync(&self) -> SysResult<()> {
+        let status = unsafe { ::libc::fdatasync(self.0) };
+        errno_check!(status, ())
+    }
+
+    /// The `sync_file_range()` system call.
+    ///
+    /// Starts and/or waits for write-back of the `nbytes` bytes of the
+    /// file at `offset`, as chosen by `flags`; `nbytes` of zero extends
+    /// the range to the end of the file. Unlike `fdatasync()`, no
+    /// metadata is flushed, so the data may not survive a crash.
+    ///
+    /// Linux-specific.
+    ///
+    /// Consult the man page (command `man 2 sync_file_range`) for
+    /// further details.
+    pub fn sync_file_range(
+        &self, offset: i64, nbytes: i64, flags: SyncFileRangeFlags
+    ) -> SysResult<()> {
+        let status = unsafe {
+            ::libc::sync_file_range(
+                self.0, offset as off_t, nbytes as off_t, flags.bits()
+            )
+        };
+        errno_check!(status, ())
+    }
+
+    /// The `readahead()` system call.
+    ///
+    /// Reads the `count` bytes of the file at `offset` into the page
+    /// cache, blocking until they have been read, so that later reads
+    /// need not wait for the disk.
+    ///
+    /// Linux-specific.
+    ///
+    /// Consult the man page (command `man 2 readahead`) for further
+    /// details.
+    pub fn readahead(&self, offset: i64, count: usize) -> SysResult<()> {
+        let status = unsafe {
+            ::libc::readahead(self.0, offset as off_t, count as size_t)
+        };
+        errno_check!(status, ())
+    }
+
+    /// The `posix_fadvise()` library function.
+    ///
+    /// Tells the kernel how the `len` bytes of the file at `offset`
+    /// are likely to be accessed, so that it can adjust caching; `len`
+    /// of zero extends the range to the end of the file.
+    ///
+    /// Consult the man page (command `man 2 posix_fadvise`) for
+    /// further details.
+    pub fn fadvise(
+        &self, offset: i64, len: i64, advice: FileAdvice
+    ) -> SysResult<()> {
+        let status = unsafe {
+            ::libc::posix_fadvise(
+                self.0, offset as off_t, len as off_t, advice as c_int
+            )
+        };
+        errnum_check!(status, ())
+    }
+
+    /// The `fcntl(F_SETLK)` operation.
+    ///
+    /// Acquires (or, with `LockType::Unlock`, releases) the record
+    /// lock described by `lock`. Fails with `EAGAIN` or `EACCES` if a
+    /// conflicting lock is held by another process.
+    ///
+    /// Consult the man page (command `man 2 fcntl`) for further
+    /// details.
+    pub fn set_lock(&self, lock: &FileLock) -> SysResult<()> {
+        let raw = lock.to_raw();
+        let status = unsafe { fcntl(self.0, F_SETLK, &raw) };
+        errno_check!(status, ())
+    }
+
+    /// The `fcntl(F_SETLKW)` operation.
+    ///
+    /// Like `set_lock()`, but blocks until any conflicting locks are
+    /// released. Fails with `EDEADLK` if waiting would deadlock.
+    ///
+    /// Consult the man page (command `man 2 fcntl`) for further
+    /// details.
+    pub fn set_lock_wait(&self, lock: &FileLock) -> SysResult<()> {
+        let raw = lock.to_raw();
+        let status = unsafe { fcntl(self.0, F_SETLKW, &raw) };
+        errno_check!(status, ())
+    }
+
+    /// The `fcntl(F_GETLK)` operation.
+    ///
+    /// Checks whether the record lock described by `lock` could be
+    /// acquired. Returns `None` if so, or one of the conflicting locks
+    /// (including the PID of its holder) if not.
+    ///
+    /// Consult the man page (command `man 2 fcntl`) for further
+    /// details.
+    pub fn get_lock(&self, lock: &FileLock) -> SysResult<Option<FileLock>> {
+        let mut raw = lock.to_raw();
+        let status = unsafe { fcntl(self.0, F_GETLK, &mut raw) };
+        try!(errno_check!(status, ()));
+
+        let conflict = FileLock::from_raw(&raw);
+        Ok(if conflict.lock_type == LockType::Unlock {
+            None
+        } else {
+            Some(conflict)
+        })
+    }
+
+    /// The `dup()` system call.
+    ///
+    /// Returns a new file descriptor, the lowest one unused, that
+    /// refers to the same open file description as this one.
+    ///
+    /// Consult the man page (command `man 2 dup`) for further
+    /// details.
+    pub fn dup(&self) -> SysResult<FileDescriptor> {
+        let fd = unsafe { ::libc::dup(self.0) };
+        errno_check!(fd, FileDescriptor(fd))
+    }
+
+    /// The `dup2()` system call.
+    ///
+    /// Makes `new_fd` a duplicate of this file descriptor, silently
+    /// closing any file it previously referred to, and returns it. The
+    /// close-on-exec flag is clear on the new descriptor.
+    ///
+    /// Consult the man page (command `man 2 dup2`) for further
+    /// details.
+    pub fn dup2(&self, new_fd: c_int) -> SysResult<FileDescriptor> {
+        let fd = unsafe { ::libc::dup2(self.0, new_fd) };
+        errno_check!(fd, FileDescriptor(fd))
+    }
+
+    /// The `fcntl(F_GETFD)` operation, for the close-on-exec flag.
+    ///
+    /// Returns whether this descriptor will be closed by a successful
+    /// `exec()`.
+    ///
+    /// Consult the man page (command `man 2 fcntl`) for further
+    /// details.
+    pub fn close_on_exec(&self) -> SysResult<bool> {
+        let flags = unsafe { fcntl(self.0, F_GETFD) };
+        errno_check!(flags, flags & FD_CLOEXEC != 0)
+    }
+
+    /// The `fcntl(F_SETFD)` operation, for the close-on-exec flag.
+    ///
+    /// Sets or clears the close-on-exec flag, preserving any other
+    /// file descriptor flags.
+    ///
+    /// Consult the man page (command `man 2 fcntl`) for further
+    /// details.
+    pub fn set_close_on_exec(&self, enable: bool) -> SysResult<()> {
+        let flags = unsafe { fcntl(self.0, F_GETFD) };
+        try!(errno_check!(flags, ()));
+
+        let new_flags =
+            if enable { flags | FD_CLOEXEC } else { flags & !FD_CLOEXEC };
+        let status = unsafe { fcntl(self.0, F_SETFD, new_flags) };
+        errno_check!(status, ())
+    }
+
+    /// The `fcntl(F_GETFL)` operation.
+    ///
+    /// Returns the access mode and file status flags of the open file
+    /// description, which is shared with any duplicates of this
+    /// descriptor, including those inherited across `fork()`.
+    ///
+    /// Consult the man page (command `man 2 fcntl`) for further
+    /// details.
+    pub fn status_flags(&self) -> SysResult<OpenFlags> {
+        let flags = unsafe { fcntl(self.0, F_GETFL) };
+        errno_check!(flags, OpenFlags::from_bits_truncate(flags))
+    }
+
+    /// The `fcntl(F_SETFL)` operation.
+    ///
+    /// Sets the file status flags of the open file description to
+    /// `flags`. Of the flags defined here, only `O_APPEND`, `O_DIRECT`,
+    /// `O_NOATIME` and `O_NONBLOCK` can be changed; the access mode and
+    /// any other flags are ignored.
+    ///
+    /// Consult the man page (command `man 2 fcntl`) for further
+    /// details.
+    pub fn set_status_flags(&self, flags: OpenFlags) -> SysResult<()> {
+        let status = unsafe { fcntl(self.0, F_SETFL, flags.bits()) };
+        errno_check!(status, ())
+    }
+
+    /// The `fcntl(F_ADD_SEALS)` operation.
+    ///
+    /// Adds `seals` to the set of seals on the file, which restrict
+    /// the ways it can be modified. Only files created by
+    /// `memfd_create()` with `MFD_ALLOW_SEALING` support sealing.
+    ///
+    /// Available since Linux version 3.17.
+    ///
+    /// Consult the man page (command `man 2 fcntl`) for further
+    /// details.
+    pub fn add_seals(&self, seals: SealFlags) -> SysResult<()> {
+        let status = unsafe { fcntl(self.0, F_ADD_SEALS, seals.bits()) };
+        errno_check!(status, ())
+    }
+
+    /// The `fcntl(F_GET_SEALS)` operation.
+    ///
+    /// Returns the set of seals currently on the file.
+    ///
+    /// Available since Linux version 3.17.
+    ///
+    /// Consult the man page (command `man 2 fcntl`) for further
+    /// details.
+    pub fn get_seals(&self) -> SysResult<SealFlags> {
+        let seals = unsafe { fcntl(self.0, F_GET_SEALS) };
+        errno_check!(seals, SealFlags::from_bits_truncate(seals))
+    }
+
+}
+
+/// The `pipe()` system call.
+///
+/// Creates a pipe, returning its read end and write end, in that
+/// order.
+///
+/// Consult the man page (command `man 2 pipe`) for further details.
+pub fn pipe() -> SysResult<(FileDescriptor, FileDescriptor)> {
+    let mut fds: [c_int; 2] = [-1, -1];
+    let status = unsafe { ::libc::pipe(fds.as_mut_ptr()) };
+    errno_check!(status, (FileDescriptor(fds[0]), FileDescriptor(fds[1])))
+}
+
+/// The `sendfile()` system call.
+///
+/// Copies up to `count` bytes from `in_fd` to `out_fd` within the
+/// kernel, returning the number of bytes copied. With `offset`,
+/// reading starts there, the offset is advanced past the bytes read,
+/// and the file offset of `in_fd` is unchanged; otherwise, reading
+/// starts at the file offset of `in_fd`. `in_fd` must support
+/// `mmap()`-like operations, so it can't be a socket.
+///
+/// Consult the man page (command `man 2 sendfile`) for further
+/// details.
+pub fn sendfile(
+    out_fd: &FileDescriptor, in_fd: &FileDescriptor,
+    offset: Option<&mut i64>, count: usize
+) -> SysResult<usize> {
+    let offset_ptr = offset.map_or(ptr::null_mut(), |off| off as *mut i64);
+    let bytes = unsafe {
+        ::libc::sendfile(
+            out_fd.0, in_fd.0, offset_ptr as *mut off_t, count as size_t
+        )
+    };
+    errno_check!(bytes, bytes as usize)
+}
+
+/// The `copy_file_range()` system call.
+///
+/// Copies up to `len` bytes from the regular file `fd_in` to the
+/// regular file `fd_out` within the kernel, returning the number of
+/// bytes copied, which is zero at the end of `fd_in`. Each offset is
+/// used and advanced like the `offset` of `sendfile()`. Some file
+/// systems share the data blocks instead of copying them.
+///
+/// Available since Linux version 4.5; before 5.3, both files must be
+/// on the same file system, or the call fails with `EXDEV`.
+///
+/// Consult the man page (command `man 2 copy_file_range`) for further
+/// details.
+pub fn copy_file_range(
+    fd_in: &FileDescriptor, off_in: Option<&mut i64>,
+    fd_out: &FileDescriptor, off_out: Option<&mut i64>, len: usize
+) -> SysResult<usize> {
+    let off_in_ptr = off_in.map_or(ptr::null_mut(), |off| off as *mut i64);
+    let off_out_ptr = off_out.map_or(ptr::null_mut(), |off| off as *mut i64);
+    let bytes = unsafe {
+        ::libc::syscall(
+            SYS_COPY_FILE_RANGE, fd_in.0, off_in_ptr, fd_out.0, off_out_ptr,
+            len as size_t, 0
+        )
+    };
+    errno_check!(bytes, bytes as usize)
+}
+
+/// Reads the entire contents of the file at `path`.
+///
+/// Mainly useful for the small text files under `/proc`, which must
+/// be read in full to get a consistent snapshot.
+pub fn read_whole_file(path: &str) -> SysResult<Vec<u8>> {
+    let fd = try!(FileDescriptor::open(
+        String::from(path), O_RDONLY, FilePerms::empty()
+    ));
+
+    let mut contents = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let count = match fd.read(&mut buf) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(errno) => {
+                // The read error is more informative than any close error
+                let _ = fd.close();
+                return Err(errno);
+            },
+        };
+        contents.extend(&buf[..count]);
+    }
+
+    try!(fd.close());
+    Ok(contents)
 }
 
 bitflags! {
@@ -214,7 +664,185 @@ bitflags! {
     }
 }
 
+impl FilePerms {
+
+    /// Formats the permissions as `ls -l` does, such as `rwxr-x---`.
+    ///
+    /// If `include_special` is true, the set-user-ID, set-group-ID and
+    /// sticky bits are shown in place of the corresponding execute
+    /// permission: as `s` or `t` if the execute permission is also
+    /// set, or `S` or `T` if it isn't, as in `rwsr-x--T`. Otherwise,
+    /// those bits are ignored.
+    pub fn to_symbolic_string(&self, include_special: bool) -> String {
+        let triples = [
+            (S_IRUSR, S_IWUSR, S_IXUSR, S_ISUID, 's', 'S'),
+            (S_IRGRP, S_IWGRP, S_IXGRP, S_ISGID, 's', 'S'),
+            (S_IROTH, S_IWOTH, S_IXOTH, S_ISVTX, 't', 'T'),
+        ];
+
+        let mut text = String::with_capacity(9);
+        for &(read, write, exec, special, lower, upper) in &triples {
+            text.push(if self.contains(read) { 'r' } else { '-' });
+            text.push(if self.contains(write) { 'w' } else { '-' });
+            let special = include_special && self.contains(special);
+            text.push(match (self.contains(exec), special) {
+                (true, true) => lower,
+                (false, true) => upper,
+                (true, false) => 'x',
+                (false, false) => '-',
+            });
+        }
+        text
+    }
+
+}
+
+/// A record lock on a region of a file, for use with
+/// `FileDescriptor::set_lock()` and related methods.
+#[derive(Clone, Copy, Debug)]
+pub struct FileLock {
+    /// The kind of lock.
+    pub lock_type: LockType,
+    /// How `start` is interpreted.
+    pub whence: OffsetBase,
+    /// The offset where the locked region starts.
+    pub start: i64,
+    /// The length of the locked region; zero extends the region to
+    /// the end of the file, however large it grows.
+    pub len: i64,
+    /// The process holding the lock; only filled in by `get_lock()`.
+    pub pid: pid_t,
+}
+
+impl FileLock {
+
+    /// Describes a lock of the given type on the entire file.
+    pub fn whole_file(lock_type: LockType) -> FileLock {
+        FileLock {
+            lock_type: lock_type,
+            whence: OffsetBase::SeekSet,
+            start: 0,
+            len: 0,
+            pid: 0,
+        }
+    }
+
+    /// Helper method; converts to the C representation of a lock.
+    fn to_raw(&self) -> flock {
+        let mut raw: flock = unsafe { mem::zeroed() };
+        raw.l_type = self.lock_type as c_short;
+        raw.l_whence = self.whence as c_short;
+        raw.l_start = self.start as off_t;
+        raw.l_len = self.len as off_t;
+        raw.l_pid = self.pid;
+        raw
+    }
+
+    /// Helper method; converts from the C representation of a lock,
+    /// as returned by `F_GETLK`.
+    fn from_raw(raw: &flock) -> FileLock {
+        let lock_type = match raw.l_type as c_int {
+            ::libc::F_RDLCK => LockType::Read,
+            ::libc::F_WRLCK => LockType::Write,
+            _ => LockType::Unlock,
+        };
+
+        // The kernel always reports the start as an absolute offset
+        FileLock {
+            lock_type: lock_type,
+            whence: OffsetBase::SeekSet,
+            start: raw.l_start as i64,
+            len: raw.l_len as i64,
+            pid: raw.l_pid,
+        }
+    }
+
+}
+
+/// Kinds of record lock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockType {
+    /// `F_RDLCK`: a shared lock; requires the file to be open for
+    /// reading.
+    Read   = 0,
+    /// `F_WRLCK`: an exclusive lock; requires the file to be open for
+    /// writing.
+    Write  = 1,
+    /// `F_UNLCK`: release any locks held on the region.
+    Unlock = 2,
+}
+
+bitflags! {
+    #[doc = "File seals for `FileDescriptor::add_seals()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 fcntl` for details on each flag."]
+    flags SealFlags: c_int {
+        #[doc = "prevent further seals from being added"]
+        const F_SEAL_SEAL   = 0b0001,
+        #[doc = "prevent the file from shrinking"]
+        const F_SEAL_SHRINK = 0b0010,
+        #[doc = "prevent the file from growing"]
+        const F_SEAL_GROW   = 0b0100,
+        #[doc = "prevent any modification of the file contents"]
+        const F_SEAL_WRITE  = 0b1000,
+    }
+}
+
+bitflags! {
+    #[doc = "Modes for `FileDescriptor::fallocate()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 fallocate` for details on each flag."]
+    flags FallocateFlags: c_int {
+        #[doc = "don't change the file size"]
+        const FALLOC_FL_KEEP_SIZE      = 0b00_0001,
+        #[doc = "deallocate the range; needs `FALLOC_FL_KEEP_SIZE`"]
+        const FALLOC_FL_PUNCH_HOLE     = 0b00_0010,
+        #[doc = "remove the range, moving the rest of the file down"]
+        const FALLOC_FL_COLLAPSE_RANGE = 0b00_1000,
+        #[doc = "zero the range, preferably by converting it"]
+        const FALLOC_FL_ZERO_RANGE     = 0b01_0000,
+        #[doc = "insert a hole, moving the rest of the file up"]
+        const FALLOC_FL_INSERT_RANGE   = 0b10_0000,
+    }
+}
+
+bitflags! {
+    #[doc = "Flags for `FileDescriptor::sync_file_range()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 sync_file_range` for details on each flag."]
+    flags SyncFileRangeFlags: ::libc::c_uint {
+        #[doc = "wait for write-back of pages already being written"]
+        const SYNC_FILE_RANGE_WAIT_BEFORE = 0b001,
+        #[doc = "start write-back of dirty pages not yet being written"]
+        const SYNC_FILE_RANGE_WRITE       = 0b010,
+        #[doc = "wait for write-back to finish"]
+        const SYNC_FILE_RANGE_WAIT_AFTER  = 0b100,
+    }
+}
+
+/// Expected access patterns for `FileDescriptor::fadvise()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileAdvice {
+    /// `POSIX_FADV_NORMAL`: no special treatment.
+    Normal     = 0,
+    /// `POSIX_FADV_RANDOM`: access in random order; disables
+    /// read-ahead.
+    Random     = 1,
+    /// `POSIX_FADV_SEQUENTIAL`: access in increasing order of offset;
+    /// increases read-ahead.
+    Sequential = 2,
+    /// `POSIX_FADV_WILLNEED`: the data will be accessed soon, so
+    /// start reading it into the page cache.
+    WillNeed   = 3,
+    /// `POSIX_FADV_DONTNEED`: the data will not be accessed soon, so
+    /// remove it from the page cache (once written back, if dirty).
+    DontNeed   = 4,
+    /// `POSIX_FADV_NOREUSE`: the data will be accessed only once.
+    NoReuse    = 5,
+}
+
 /// Interpretations for the `offset` argument of `lseek()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum OffsetBase {
     /// The offset is set to `offset` bytes.
     SeekSet  = 0,
@@ -243,3 +871,7 @@ pub enum OffsetBase {
     /// Available since Linux version 3.1.
     SeekHole = 4,
 }
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const SYS_COPY_FILE_RANGE: c_long = 326;
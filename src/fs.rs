@@ -0,0 +1,531 @@
+
+//! Provides operations on files and directories by pathname.
+//!
+//! The `*at()` variants of these operations take an optional
+//! directory, relative to which the pathname is interpreted; with
+//! `None`, relative pathnames are interpreted relative to the current
+//! working directory, as with the plain operations.
+
+use std::ffi;
+use std::ptr;
+use libc::{c_char, c_int, c_long, c_void, gid_t, time_t, timespec, uid_t};
+use fd::{FileDescriptor, FilePerms, OpenFlags, SysResult};
+use stat::Timestamp;
+
+/// A handle identifying a file independently of its pathname, as
+/// returned by `name_to_handle_at()` (`struct file_handle`).
+///
+/// A handle stays valid for as long as the file exists, even across
+/// reboots on most file systems, so it can be saved and later given
+/// to `open_by_handle_at()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileHandle {
+    /// The type of the handle, which depends on the file system.
+    pub handle_type: c_int,
+    /// The opaque contents of the handle.
+    pub bytes: Vec<u8>,
+}
+
+/// A new value for a file timestamp, as given to `utimensat()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeUpdate {
+    /// `UTIME_NOW`: set the timestamp to the current time.
+    Now,
+    /// `UTIME_OMIT`: leave the timestamp unchanged.
+    Omit,
+    /// Set the timestamp to the given time; the caller must own the
+    /// file.
+    At(Timestamp),
+}
+
+/// The `unlink()` system call.
+///
+/// Removes the link `path` from its directory; the file itself is
+/// removed once no links or open file descriptors refer to it.
+///
+/// Consult the man page (command `man 2 unlink`) for further details.
+pub fn unlink(path: &str) -> SysResult<()> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    let status = unsafe { ::libc::unlink(c_path.as_ptr()) };
+    errno_check!(status, ())
+}
+
+/// The `mkstemp()` library function.
+///
+/// Creates and opens a new file with a unique name, formed by
+/// replacing the final `XXXXXX` of `template`, which must end with
+/// those six characters; fails with `EINVAL` if it doesn't. The file
+/// is opened for reading and writing, with permissions that only allow
+/// access by its owner. Returns the file descriptor and the pathname.
+///
+/// Consult the man page (command `man 3 mkstemp`) for further details.
+pub fn mkstemp(template: &str) -> SysResult<(FileDescriptor, String)> {
+    // Panic if `template` contains nul chars; crude but good enough
+    let c_template = ffi::CString::new(template).unwrap();
+    let mut buf = c_template.into_bytes_with_nul();
+    let fd = unsafe { ::libc::mkstemp(buf.as_mut_ptr() as *mut c_char) };
+    try!(errno_check!(fd, ()));
+
+    // Only the placeholder characters were changed, to ASCII ones
+    buf.pop();
+    let path = String::from_utf8(buf).unwrap();
+    Ok((FileDescriptor::from_raw(fd), path))
+}
+
+/// The `chmod()` system call.
+///
+/// Sets the permissions of the file `path`, following symbolic links,
+/// to `perms`. Unless the caller is privileged, it must own the file.
+///
+/// Consult the man page (command `man 2 chmod`) for further details.
+pub fn chmod(path: &str, perms: FilePerms) -> SysResult<()> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    let status = unsafe { ::libc::chmod(c_path.as_ptr(), perms.bits()) };
+    errno_check!(status, ())
+}
+
+/// The `mkdir()` system call.
+///
+/// Creates the directory `path`, with the permissions `perms` as
+/// modified by the umask. Fails with `EEXIST` if `path` exists.
+///
+/// Consult the man page (command `man 2 mkdir`) for further details.
+pub fn mkdir(path: &str, perms: FilePerms) -> SysResult<()> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    let status = unsafe { ::libc::mkdir(c_path.as_ptr(), perms.bits()) };
+    errno_check!(status, ())
+}
+
+/// The `rmdir()` system call.
+///
+/// Removes the directory `path`, which must be empty.
+///
+/// Consult the man page (command `man 2 rmdir`) for further details.
+pub fn rmdir(path: &str) -> SysResult<()> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    let status = unsafe { ::libc::rmdir(c_path.as_ptr()) };
+    errno_check!(status, ())
+}
+
+/// The `mkfifo()` library function.
+///
+/// Creates the FIFO (named pipe) `path`, with the permissions `perms`
+/// as modified by the umask.
+///
+/// Consult the man page (command `man 3 mkfifo`) for further details.
+pub fn mkfifo(path: &str, perms: FilePerms) -> SysResult<()> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    let status = unsafe { ::libc::mkfifo(c_path.as_ptr(), perms.bits()) };
+    errno_check!(status, ())
+}
+
+/// The `mkdtemp()` library function.
+///
+/// As `mkstemp()`, but creates a directory, which only its owner may
+/// access, and returns its pathname.
+///
+/// Consult the man page (command `man 3 mkdtemp`) for further details.
+pub fn mkdtemp(template: &str) -> SysResult<String> {
+    // Panic if `template` contains nul chars; crude but good enough
+    let c_template = ffi::CString::new(template).unwrap();
+    let mut buf = c_template.into_bytes_with_nul();
+    let result = unsafe { ::libc::mkdtemp(buf.as_mut_ptr() as *mut c_char) };
+    try!(errno_check!(if result.is_null() { -1 } else { 0 }, ()));
+
+    buf.pop();
+    Ok(String::from_utf8(buf).unwrap())
+}
+
+/// The `readlink()` system call.
+///
+/// Returns the contents of the symbolic link `path`: the pathname it
+/// refers to, which need not exist. Any bytes that are not valid
+/// UTF-8 are replaced by U+FFFD.
+///
+/// Consult the man page (command `man 2 readlink`) for further
+/// details.
+pub fn readlink(path: &str) -> SysResult<String> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    let mut size = PATH_MAX;
+    loop {
+        let mut buf = vec![0u8; size];
+        let len = unsafe {
+            ::libc::readlink(
+                c_path.as_ptr(), buf.as_mut_ptr() as *mut c_char, size
+            )
+        };
+        try!(errno_check!(len, ()));
+
+        // The contents are silently truncated to fit, so a full
+        // buffer may have been too small
+        if (len as usize) < size {
+            buf.truncate(len as usize);
+            return Ok(String::from_utf8_lossy(&buf).into_owned());
+        }
+        size *= 2;
+    }
+}
+
+/// The `realpath()` library function.
+///
+/// Returns the absolute pathname of `path`, with every symbolic link
+/// resolved and every `.` and `..` component removed. The file must
+/// exist. Any bytes that are not valid UTF-8 are replaced by U+FFFD.
+///
+/// Consult the man page (command `man 3 realpath`) for further
+/// details.
+pub fn realpath(path: &str) -> SysResult<String> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    // With a null buffer, the result is allocated with `malloc()`
+    let resolved = unsafe {
+        ::libc::realpath(c_path.as_ptr(), ptr::null_mut())
+    };
+    let status = if resolved.is_null() { -1 } else { 0 };
+    try!(errno_check!(status, ()));
+
+    let result = unsafe { ffi::CStr::from_ptr(resolved) }
+        .to_string_lossy()
+        .into_owned();
+    unsafe { ::libc::free(resolved as *mut c_void) };
+    Ok(result)
+}
+
+/// Returns the directory part of `path`: everything before its last
+/// component, without trailing slashes.
+///
+/// Follows the rules of the POSIX `dirname()` library function, so
+/// that `dirname("/usr/lib/")` is `/usr`, `dirname("lib")` is `.`
+/// and `dirname("/")` is `/`. Together with `basename()`, the result
+/// can be joined with a slash to form a pathname of the same file.
+pub fn dirname(path: &str) -> String {
+    if path.is_empty() {
+        return ".".to_string();
+    }
+    let trimmed = path.trim_right_matches('/');
+    if trimmed.is_empty() {
+        return "/".to_string();
+    }
+
+    match trimmed.rfind('/') {
+        None => ".".to_string(),
+        Some(index) => {
+            let parent = trimmed[..index].trim_right_matches('/');
+            if parent.is_empty() { "/" } else { parent }.to_string()
+        },
+    }
+}
+
+/// Returns the last component of `path`, without trailing slashes.
+///
+/// Follows the rules of the POSIX `basename()` library function, so
+/// that `basename("/usr/lib/")` is `lib`, `basename("/")` is `/` and
+/// `basename("")` is `.`.
+pub fn basename(path: &str) -> String {
+    if path.is_empty() {
+        return ".".to_string();
+    }
+    let trimmed = path.trim_right_matches('/');
+    if trimmed.is_empty() {
+        return "/".to_string();
+    }
+
+    match trimmed.rfind('/') {
+        Some(index) => trimmed[index + 1..].to_string(),
+        None => trimmed.to_string(),
+    }
+}
+
+/// The `renameat2()` system call.
+///
+/// Renames `old_path` to `new_path`, like the `rename()` system call,
+/// with each pathname interpreted relative to its directory (`None`
+/// for the current working directory). `flags` changes how an
+/// existing file `new_path` is treated: with `RENAME_NOREPLACE`, the
+/// call fails with `EEXIST` instead of replacing it; with
+/// `RENAME_EXCHANGE`, the two files are swapped atomically.
+///
+/// Consult the man page (command `man 2 renameat2`) for further
+/// details.
+pub fn renameat2(
+    old_dirfd: Option<&FileDescriptor>, old_path: &str,
+    new_dirfd: Option<&FileDescriptor>, new_path: &str, flags: RenameFlags
+) -> SysResult<()> {
+    // Panic if the paths contain nul chars; crude but good enough
+    let c_old_path = ffi::CString::new(old_path).unwrap();
+    let c_new_path = ffi::CString::new(new_path).unwrap();
+    let status = unsafe {
+        ::libc::syscall(
+            SYS_RENAMEAT2, raw_dirfd(old_dirfd), c_old_path.as_ptr(),
+            raw_dirfd(new_dirfd), c_new_path.as_ptr(), flags.bits()
+        )
+    };
+    errno_check!(status, ())
+}
+
+/// The `utimensat()` system call.
+///
+/// Changes the last access and modification times of the file
+/// `path`, interpreted relative to the directory `dirfd` (`None` for
+/// the current working directory). With `AT_SYMLINK_NOFOLLOW`, a
+/// symbolic link's own timestamps are changed rather than its
+/// target's. The last status change time is always set to the
+/// current time, unless both updates are `TimeUpdate::Omit`.
+///
+/// Consult the man page (command `man 2 utimensat`) for further
+/// details.
+pub fn utimensat(
+    dirfd: Option<&FileDescriptor>, path: &str, atime: TimeUpdate,
+    mtime: TimeUpdate, flags: AtFlags
+) -> SysResult<()> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    let times = [to_timespec(atime), to_timespec(mtime)];
+    let status = unsafe {
+        ::libc::utimensat(
+            raw_dirfd(dirfd), c_path.as_ptr(), times.as_ptr(), flags.bits()
+        )
+    };
+    errno_check!(status, ())
+}
+
+/// The `fchownat()` system call.
+///
+/// Changes the owner and group of the file `path`, interpreted
+/// relative to the directory `dirfd` (`None` for the current working
+/// directory), leaving either unchanged if it is `None`. With
+/// `AT_SYMLINK_NOFOLLOW`, a symbolic link itself is changed rather
+/// than its target; with `AT_EMPTY_PATH` and an empty `path`, `dirfd`
+/// itself, which may be any open file. Changing the owner requires
+/// privilege; the owner of the file may only change its group to one
+/// of its own groups.
+///
+/// Consult the man page (command `man 2 fchownat`) for further
+/// details.
+pub fn fchownat(
+    dirfd: Option<&FileDescriptor>, path: &str, owner: Option<uid_t>,
+    group: Option<gid_t>, flags: AtFlags
+) -> SysResult<()> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+
+    // An ID of -1 leaves it unchanged
+    let status = unsafe {
+        ::libc::fchownat(
+            raw_dirfd(dirfd), c_path.as_ptr(), owner.unwrap_or(!0),
+            group.unwrap_or(!0), flags.bits()
+        )
+    };
+    errno_check!(status, ())
+}
+
+/// Changes the owner and group of the file `path`, without following
+/// a symbolic link at the end of it, like the `lchown()` system call.
+pub fn lchown(
+    path: &str, owner: Option<uid_t>, group: Option<gid_t>
+) -> SysResult<()> {
+    fchownat(None, path, owner, group, AT_SYMLINK_NOFOLLOW)
+}
+
+/// The `fchmodat()` system call.
+///
+/// Sets the permissions of the file `path`, interpreted relative to
+/// the directory `dirfd` (`None` for the current working directory),
+/// to `perms`. Linux doesn't support permissions on a symbolic link,
+/// so with `AT_SYMLINK_NOFOLLOW` the call fails with `EOPNOTSUPP` if
+/// `path` is one, rather than following it. Before version 2.32,
+/// glibc doesn't support `AT_SYMLINK_NOFOLLOW` here at all, and fails
+/// with `ENOTSUP` (the same value on Linux) whatever `path` is.
+///
+/// Consult the man page (command `man 2 fchmodat`) for further
+/// details.
+pub fn fchmodat(
+    dirfd: Option<&FileDescriptor>, path: &str, perms: FilePerms,
+    flags: AtFlags
+) -> SysResult<()> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    let status = unsafe {
+        ::libc::fchmodat(
+            raw_dirfd(dirfd), c_path.as_ptr(), perms.bits(), flags.bits()
+        )
+    };
+    errno_check!(status, ())
+}
+
+/// Sets the permissions of the file `path` to `perms`, refusing to
+/// follow a symbolic link at the end of it, like the BSD `lchmod()`
+/// library function. Fails with `EOPNOTSUPP` for a symbolic link, and
+/// for every file with glibc versions before 2.32; see `fchmodat()`.
+pub fn lchmod(path: &str, perms: FilePerms) -> SysResult<()> {
+    fchmodat(None, path, perms, AT_SYMLINK_NOFOLLOW)
+}
+
+/// The `unlinkat()` system call.
+///
+/// Removes the link `path`, interpreted relative to the directory
+/// `dirfd` (`None` for the current working directory), as with
+/// `unlink()`; with `AT_REMOVEDIR`, removes the empty directory
+/// `path` instead, as with `rmdir()`.
+///
+/// Consult the man page (command `man 2 unlinkat`) for further
+/// details.
+pub fn unlinkat(
+    dirfd: Option<&FileDescriptor>, path: &str, flags: AtFlags
+) -> SysResult<()> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    let status = unsafe {
+        ::libc::unlinkat(raw_dirfd(dirfd), c_path.as_ptr(), flags.bits())
+    };
+    errno_check!(status, ())
+}
+
+/// The `name_to_handle_at()` system call.
+///
+/// Returns a handle for the file `path`, interpreted relative to the
+/// directory `dirfd` (`None` for the current working directory),
+/// together with the ID of the mount holding the file. `flags` may
+/// include `AT_SYMLINK_FOLLOW` and `AT_EMPTY_PATH`. Not every file
+/// system supports handles; others fail with `EOPNOTSUPP`.
+///
+/// Consult the man page (command `man 2 name_to_handle_at`) for
+/// further details.
+pub fn name_to_handle_at(
+    dirfd: Option<&FileDescriptor>, path: &str, flags: AtFlags
+) -> SysResult<(FileHandle, c_int)> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+
+    // The `handle_bytes` and `handle_type` fields, followed by room for
+    // the largest handle; made of `u32`s so that the fields are aligned
+    let mut raw = vec![0u32; 2 + MAX_HANDLE_SZ / 4];
+    raw[0] = MAX_HANDLE_SZ as u32;
+    let mut mount_id: c_int = 0;
+    let status = unsafe {
+        ::libc::syscall(
+            SYS_NAME_TO_HANDLE_AT, raw_dirfd(dirfd), c_path.as_ptr(),
+            raw.as_mut_ptr(), &mut mount_id, flags.bits()
+        )
+    };
+    try!(errno_check!(status, ()));
+
+    let len = raw[0] as usize;
+    let mut bytes = vec![0u8; len];
+    unsafe {
+        let src = raw[2..].as_ptr() as *const u8;
+        ptr::copy_nonoverlapping(src, bytes.as_mut_ptr(), len);
+    }
+    let handle = FileHandle { handle_type: raw[1] as c_int, bytes: bytes };
+    Ok((handle, mount_id))
+}
+
+/// The `open_by_handle_at()` system call.
+///
+/// Opens the file identified by `handle`, which must be on the same
+/// file system as the open file `mount_fd`. `flags` are as for
+/// `open()`, except that `O_CREAT` is not allowed. The caller needs
+/// the `CAP_DAC_READ_SEARCH` capability.
+///
+/// Consult the man page (command `man 2 open_by_handle_at`) for further
+/// details.
+pub fn open_by_handle_at(
+    mount_fd: &FileDescriptor, handle: &FileHandle, flags: OpenFlags
+) -> SysResult<FileDescriptor> {
+    let len = handle.bytes.len();
+    let mut raw = vec![0u32; 2 + (len + 3) / 4];
+    raw[0] = len as u32;
+    raw[1] = handle.handle_type as u32;
+    unsafe {
+        let dest = raw[2..].as_mut_ptr() as *mut u8;
+        ptr::copy_nonoverlapping(handle.bytes.as_ptr(), dest, len);
+    }
+
+    let fd = unsafe {
+        ::libc::syscall(
+            SYS_OPEN_BY_HANDLE_AT, mount_fd.as_raw(), raw.as_ptr(),
+            flags.bits()
+        )
+    };
+    errno_check!(fd, FileDescriptor::from_raw(fd as c_int))
+}
+
+/// Returns the raw file descriptor to give an `*at()` system call for
+/// the directory `dirfd`, which is `AT_FDCWD` for `None`.
+///
+/// Intended for the other modules of this crate.
+pub fn raw_dirfd(dirfd: Option<&FileDescriptor>) -> c_int {
+    dirfd.map_or(AT_FDCWD, |fd| fd.as_raw())
+}
+
+bitflags! {
+    #[doc = "Flags for the `*at()` system calls."]
+    #[doc = ""]
+    #[doc = "Each system call accepts only some of these; consult its man"]
+    #[doc = "page for details."]
+    #[doc = ""]
+    #[doc = "Taken from C header files on an x86-64 Linux system; not"]
+    #[doc = "intended to be portable!"]
+    flags AtFlags: c_int {
+        #[doc = "don't follow a symbolic link at the end of the pathname"]
+        const AT_SYMLINK_NOFOLLOW = 0x0100,
+        #[doc = "`unlinkat()`: remove a directory rather than a file"]
+        const AT_REMOVEDIR        = 0x0200,
+        #[doc = "follow a symbolic link at the end of the pathname"]
+        const AT_SYMLINK_FOLLOW   = 0x0400,
+        #[doc = "don't automount the last component of the pathname"]
+        const AT_NO_AUTOMOUNT     = 0x0800,
+        #[doc = "if the pathname is empty, operate on the directory itself"]
+        const AT_EMPTY_PATH       = 0x1000,
+        #[doc = "`statx()`: synchronize the attributes with the server"]
+        const AT_STATX_FORCE_SYNC = 0x2000,
+        #[doc = "`statx()`: use whatever attributes are cached"]
+        const AT_STATX_DONT_SYNC  = 0x4000,
+    }
+}
+
+bitflags! {
+    #[doc = "Flags for `renameat2()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 renameat2` for details on each flag."]
+    #[doc = ""]
+    #[doc = "Taken from C header files on an x86-64 Linux system; not"]
+    #[doc = "intended to be portable!"]
+    flags RenameFlags: u32 {
+        #[doc = "fail with `EEXIST` rather than replace the new path"]
+        const RENAME_NOREPLACE = 0b001,
+        #[doc = "atomically exchange the old and new paths"]
+        const RENAME_EXCHANGE  = 0b010,
+        #[doc = "leave a whiteout object at the old path (overlay and"]
+        #[doc = "union file systems only)"]
+        const RENAME_WHITEOUT  = 0b100,
+    }
+}
+
+/// Helper function; converts a timestamp update to the C
+/// representation.
+fn to_timespec(update: TimeUpdate) -> timespec {
+    let (secs, nanos) = match update {
+        TimeUpdate::Now => (0, UTIME_NOW),
+        TimeUpdate::Omit => (0, UTIME_OMIT),
+        TimeUpdate::At(time) => (time.secs, time.nanos as c_long),
+    };
+    timespec { tv_sec: secs as time_t, tv_nsec: nanos }
+}
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const AT_FDCWD: c_int = -100;
+const SYS_NAME_TO_HANDLE_AT: c_long = 303;
+const SYS_OPEN_BY_HANDLE_AT: c_long = 304;
+const SYS_RENAMEAT2: c_long = 316;
+const MAX_HANDLE_SZ: usize = 128;
+const PATH_MAX: usize = 4096;
+const UTIME_NOW: c_long = (1 << 30) - 1;
+const UTIME_OMIT: c_long = (1 << 30) - 2;
@@ -0,0 +1,465 @@
+
+//! Provides name resolution and connection helpers for Internet
+//! domain sockets.
+
+use std::error;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ptr;
+use libc::{addrinfo, c_char, c_int, c_void, sockaddr, sockaddr_storage};
+use libc::socklen_t;
+use libc::{AF_INET, AF_INET6, AF_UNSPEC, EAI_SYSTEM};
+use libc::{SOCK_DGRAM, SOCK_SEQPACKET, SOCK_STREAM};
+use err::{Errno, EADDRNOTAVAIL, EAFNOSUPPORT, EINVAL};
+use fd::SysResult;
+use socket::*;
+
+/// An error reported by `getaddrinfo()`, which uses its own error
+/// codes rather than `errno`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GaiError(c_int);
+
+impl GaiError {
+
+    /// The raw `EAI_*` value of this error.
+    pub fn value(&self) -> i32 { self.0 }
+
+}
+
+impl fmt::Display for GaiError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = unsafe {
+            CStr::from_ptr(::libc::gai_strerror(self.0))
+        };
+        write!(f, "{}", message.to_string_lossy())
+    }
+
+}
+
+impl error::Error for GaiError {
+
+    fn description(&self) -> &str { "name resolution failure" }
+
+}
+
+/// Failures of the helper functions in this module, which can come
+/// from either name resolution or a system call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InetError {
+    /// The host or service couldn't be resolved.
+    Resolve(GaiError),
+    /// A system call failed; if several addresses were tried, this is
+    /// the error for the last of them.
+    System(Errno),
+}
+
+bitflags! {
+    #[doc = "Options for `getaddrinfo()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 3 getaddrinfo` for details on each flag."]
+    flags AiFlags: c_int {
+        #[doc = "return wildcard addresses suitable for `bind()`"]
+        const AI_PASSIVE     = 0x0001,
+        #[doc = "return the canonical name of the host"]
+        const AI_CANONNAME   = 0x0002,
+        #[doc = "the host must be a numeric address string"]
+        const AI_NUMERICHOST = 0x0004,
+        #[doc = "return IPv4-mapped IPv6 addresses if no IPv6 ones exist"]
+        const AI_V4MAPPED    = 0x0008,
+        #[doc = "with `AI_V4MAPPED`, return both kinds of address"]
+        const AI_ALL         = 0x0010,
+        #[doc = "only return address kinds configured on this host"]
+        const AI_ADDRCONFIG  = 0x0020,
+        #[doc = "the service must be a numeric port string"]
+        const AI_NUMERICSERV = 0x0400,
+    }
+}
+
+/// Criteria for selecting the addresses returned by `getaddrinfo()`.
+#[derive(Clone, Copy, Debug)]
+pub struct AddrInfoHints {
+    /// Only return addresses in this domain; `None` allows both IPv4
+    /// and IPv6.
+    pub domain: Option<Domain>,
+    /// Only return addresses for this socket type; `None` allows any.
+    pub sock_type: Option<SockType>,
+    /// Options for the lookup.
+    pub flags: AiFlags,
+}
+
+/// One of the results of `getaddrinfo()`.
+#[derive(Clone, Debug)]
+pub struct AddrInfo {
+    /// The socket type the address is intended for.
+    pub sock_type: SockType,
+    /// The protocol the address is intended for.
+    pub protocol: i32,
+    /// The address itself.
+    pub addr: SockAddr,
+    /// The canonical name of the host; only present in the first
+    /// result, and only if `AI_CANONNAME` was given.
+    pub canon_name: Option<String>,
+}
+
+/// The `getaddrinfo()` library function.
+///
+/// Resolves `host` and `service` into the socket addresses that
+/// match `hints`. At least one of `host` and `service` must be given;
+/// each may be a name or a numeric string.
+///
+/// Returns an iterator over the results. Results in domains or of
+/// socket types not supported by this crate are skipped.
+///
+/// Consult the man page (command `man 3 getaddrinfo`) for further
+/// details.
+pub fn getaddrinfo(
+    host: Option<&str>, service: Option<&str>, hints: &AddrInfoHints
+) -> Result<AddrInfoIter, InetError> {
+    // Panic if the strings contain nul chars; crude but good enough
+    let c_host = host.map(|h| CString::new(h).unwrap());
+    let c_service = service.map(|s| CString::new(s).unwrap());
+    let host_ptr = c_host.as_ref().map_or(ptr::null(), |h| h.as_ptr());
+    let service_ptr =
+        c_service.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
+    let mut raw_hints: addrinfo = unsafe { mem::zeroed() };
+    raw_hints.ai_family = hints.domain.map_or(AF_UNSPEC, |d| d as c_int);
+    raw_hints.ai_socktype = hints.sock_type.map_or(0, |t| t as c_int);
+    raw_hints.ai_flags = hints.flags.bits();
+
+    let mut result: *mut addrinfo = ptr::null_mut();
+    let status = unsafe {
+        ::libc::getaddrinfo(host_ptr, service_ptr, &raw_hints, &mut result)
+    };
+
+    match status {
+        0 => Ok(AddrInfoIter { head: result, next: result }),
+        EAI_SYSTEM => Err(InetError::System(Errno::last())),
+        _ => Err(InetError::Resolve(GaiError(status))),
+    }
+}
+
+/// Iterator over the results of `getaddrinfo()`.
+///
+/// Frees the underlying list when dropped.
+pub struct AddrInfoIter {
+    /// Start of the list, for freeing.
+    head: *mut addrinfo,
+
+    /// The next element of the list to examine.
+    next: *mut addrinfo,
+}
+
+impl Iterator for AddrInfoIter {
+    type Item = AddrInfo;
+
+    fn next(&mut self) -> Option<AddrInfo> {
+        while !self.next.is_null() {
+            let raw = unsafe { &*self.next };
+            self.next = raw.ai_next;
+
+            if let Some(info) = convert_addrinfo(raw) {
+                return Some(info);
+            }
+        }
+        None
+    }
+
+}
+
+impl Drop for AddrInfoIter {
+
+    fn drop(&mut self) {
+        if !self.head.is_null() {
+            unsafe { ::libc::freeaddrinfo(self.head) };
+        }
+    }
+
+}
+
+/// Creates a TCP socket connected to `service` on `host`.
+///
+/// Each address that `host` resolves to is tried in turn until a
+/// connection succeeds.
+///
+/// This is a port of `inetConnect()` from _The Linux Programming
+/// Interface_, restricted to stream sockets.
+pub fn connect_tcp(host: &str, service: &str) -> Result<Socket, InetError> {
+    let hints = AddrInfoHints {
+        domain: None,
+        sock_type: Some(SockType::Stream),
+        flags: AI_NUMERICSERV,
+    };
+
+    let mut last_errno = None;
+    for info in try!(getaddrinfo(Some(host), Some(service), &hints)) {
+        let domain = info.addr.domain();
+        let flags = SockFlags::empty();
+        let socket = match Socket::new(domain, info.sock_type, flags) {
+            Ok(socket) => socket,
+            Err(errno) => { last_errno = Some(errno); continue },
+        };
+
+        match socket.connect(&info.addr) {
+            Ok(()) => return Ok(socket),
+            Err(errno) => {
+                last_errno = Some(errno);
+                let _ = socket.close();
+            },
+        };
+    }
+
+    Err(InetError::System(last_errno.unwrap_or(EADDRNOTAVAIL)))
+}
+
+/// Creates a TCP socket bound to the wildcard address on `service`,
+/// and listening for connections with the given `backlog`.
+///
+/// Returns the socket together with the address it was bound to. The
+/// `SO_REUSEADDR` option is set, so that the server can be restarted
+/// while old connections are in the `TIME_WAIT` state.
+///
+/// This is a port of `inetListen()` from _The Linux Programming
+/// Interface_.
+pub fn listen_tcp(
+    service: &str, backlog: i32
+) -> Result<(Socket, SockAddr), InetError> {
+    let hints = AddrInfoHints {
+        domain: None,
+        sock_type: Some(SockType::Stream),
+        flags: AI_PASSIVE | AI_NUMERICSERV,
+    };
+
+    let mut last_errno = None;
+    for info in try!(getaddrinfo(None, Some(service), &hints)) {
+        let domain = info.addr.domain();
+        let flags = SockFlags::empty();
+        let socket = match Socket::new(domain, info.sock_type, flags) {
+            Ok(socket) => socket,
+            Err(errno) => { last_errno = Some(errno); continue },
+        };
+
+        let result = socket.set_reuse_addr(true)
+            .and_then(|_| socket.bind(&info.addr))
+            .and_then(|_| socket.listen(backlog));
+
+        match result {
+            Ok(()) => return Ok((socket, info.addr)),
+            Err(errno) => {
+                last_errno = Some(errno);
+                let _ = socket.close();
+            },
+        };
+    }
+
+    Err(InetError::System(last_errno.unwrap_or(EADDRNOTAVAIL)))
+}
+
+bitflags! {
+    #[doc = "Options for `getnameinfo()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 3 getnameinfo` for details on each flag."]
+    flags NiFlags: c_int {
+        #[doc = "return the host as a numeric address string"]
+        const NI_NUMERICHOST = 0x01,
+        #[doc = "return the service as a numeric port string"]
+        const NI_NUMERICSERV = 0x02,
+        #[doc = "return only the hostname part of local hosts' names"]
+        const NI_NOFQDN      = 0x04,
+        #[doc = "fail if the host has no name"]
+        const NI_NAMEREQD    = 0x08,
+        #[doc = "look up the service as a UDP rather than TCP one"]
+        const NI_DGRAM       = 0x10,
+    }
+}
+
+/// The `getnameinfo()` library function.
+///
+/// Converts the Internet domain socket address `addr` into a host
+/// name and service name, returned in that order. Numeric strings are
+/// returned for any part that can't be resolved, unless
+/// `NI_NAMEREQD` is given; fails with `EAFNOSUPPORT` for UNIX domain
+/// addresses.
+///
+/// Consult the man page (command `man 3 getnameinfo`) for further
+/// details.
+pub fn getnameinfo(
+    addr: &SockAddr, flags: NiFlags
+) -> Result<(String, String), InetError> {
+    if addr.domain() == Domain::Unix {
+        return Err(InetError::System(EAFNOSUPPORT));
+    }
+
+    let (storage, len) = addr.to_raw();
+    let mut host = [0 as c_char; NI_MAXHOST];
+    let mut service = [0 as c_char; NI_MAXSERV];
+    let status = unsafe {
+        ::libc::getnameinfo(
+            &storage as *const _ as *const sockaddr, len,
+            host.as_mut_ptr(), host.len() as socklen_t,
+            service.as_mut_ptr(), service.len() as socklen_t,
+            flags.bits()
+        )
+    };
+
+    match status {
+        0 => Ok((c_buf_to_string(&host), c_buf_to_string(&service))),
+        EAI_SYSTEM => Err(InetError::System(Errno::last())),
+        _ => Err(InetError::Resolve(GaiError(status))),
+    }
+}
+
+/// The `inet_pton()` library function.
+///
+/// Converts the presentation (textual) form of an IPv4 or IPv6
+/// address in `domain` into binary form. Fails with `EINVAL` if `src`
+/// isn't a valid address, and with `EAFNOSUPPORT` for the UNIX
+/// domain.
+///
+/// Consult the man page (command `man 3 inet_pton`) for further
+/// details.
+pub fn inet_pton(domain: Domain, src: &str) -> SysResult<IpAddr> {
+    // Panic if `src` contains nul chars; crude but good enough
+    let c_src = CString::new(src).unwrap();
+    let mut buf = [0u8; 16];
+    let status = unsafe {
+        inet_pton_c(
+            domain as c_int, c_src.as_ptr(), buf.as_mut_ptr() as *mut c_void
+        )
+    };
+
+    match status {
+        1 => Ok(match domain {
+            Domain::Inet => {
+                IpAddr::V4(Ipv4Addr::new(buf[0], buf[1], buf[2], buf[3]))
+            },
+            _ => {
+                let mut segments = [0u16; 8];
+                for (i, segment) in segments.iter_mut().enumerate() {
+                    let (high, low) = (buf[2 * i], buf[2 * i + 1]);
+                    *segment = (high as u16) << 8 | low as u16;
+                }
+                IpAddr::V6(Ipv6Addr::new(
+                    segments[0], segments[1], segments[2], segments[3],
+                    segments[4], segments[5], segments[6], segments[7]
+                ))
+            },
+        }),
+        0 => Err(EINVAL),
+        _ => Err(Errno::last()),
+    }
+}
+
+/// The `inet_ntop()` library function.
+///
+/// Converts the binary form of an IPv4 or IPv6 address into its
+/// presentation (textual) form.
+///
+/// Consult the man page (command `man 3 inet_ntop`) for further
+/// details.
+pub fn inet_ntop(addr: &IpAddr) -> SysResult<String> {
+    let mut bytes = [0u8; 16];
+    let domain = match *addr {
+        IpAddr::V4(ref ip) => {
+            for (dest, src) in bytes.iter_mut().zip(ip.octets().iter()) {
+                *dest = *src;
+            }
+            Domain::Inet
+        },
+        IpAddr::V6(ref ip) => {
+            for (i, segment) in ip.segments().iter().enumerate() {
+                bytes[2 * i] = (segment >> 8) as u8;
+                bytes[2 * i + 1] = *segment as u8;
+            }
+            Domain::Inet6
+        },
+    };
+
+    // Large enough for the longest IPv6 address (INET6_ADDRSTRLEN)
+    let mut dest = [0 as c_char; 46];
+    let result = unsafe {
+        inet_ntop_c(
+            domain as c_int, bytes.as_ptr() as *const c_void,
+            dest.as_mut_ptr(), dest.len() as socklen_t
+        )
+    };
+
+    if result.is_null() {
+        Err(Errno::last())
+    } else {
+        Ok(c_buf_to_string(&dest))
+    }
+}
+
+/// Size of a buffer large enough for any host name returned by
+/// `getnameinfo()`.
+const NI_MAXHOST: usize = 1025;
+
+/// Size of a buffer large enough for any service name returned by
+/// `getnameinfo()`.
+const NI_MAXSERV: usize = 32;
+
+extern {
+    #[link_name = "inet_pton"]
+    fn inet_pton_c(af: c_int, src: *const c_char, dst: *mut c_void) -> c_int;
+
+    #[link_name = "inet_ntop"]
+    fn inet_ntop_c(
+        af: c_int, src: *const c_void, dst: *mut c_char, size: socklen_t
+    ) -> *const c_char;
+}
+
+/// Helper function; converts a nul-terminated buffer filled in by a
+/// library function into an owned string.
+fn c_buf_to_string(buf: &[c_char]) -> String {
+    let c_str = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    c_str.to_string_lossy().into_owned()
+}
+
+/// Helper function; converts one element of a `getaddrinfo()` result
+/// list, if its domain and socket type are supported.
+fn convert_addrinfo(raw: &addrinfo) -> Option<AddrInfo> {
+    let sock_type = match raw.ai_socktype {
+        SOCK_STREAM => SockType::Stream,
+        SOCK_DGRAM => SockType::Datagram,
+        SOCK_SEQPACKET => SockType::SeqPacket,
+        _ => return None,
+    };
+
+    if raw.ai_family != AF_INET && raw.ai_family != AF_INET6 {
+        return None;
+    }
+
+    let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+    let len = raw.ai_addrlen as usize;
+    if len > mem::size_of::<sockaddr_storage>() {
+        return None;
+    }
+    unsafe {
+        ptr::copy_nonoverlapping(
+            raw.ai_addr as *const u8, &mut storage as *mut _ as *mut u8, len
+        );
+    }
+
+    let addr = match SockAddr::from_raw(&storage, len as socklen_t) {
+        Ok(addr) => addr,
+        Err(_) => return None,
+    };
+
+    let canon_name = if raw.ai_canonname.is_null() {
+        None
+    } else {
+        let name = unsafe { CStr::from_ptr(raw.ai_canonname) };
+        Some(name.to_string_lossy().into_owned())
+    };
+
+    Some(AddrInfo {
+        sock_type: sock_type,
+        protocol: raw.ai_protocol,
+        addr: addr,
+        canon_name: canon_name,
+    })
+}
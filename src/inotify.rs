@@ -0,0 +1,278 @@
+
+//! Provides monitoring of file system events with inotify.
+//!
+//! An `Inotify` instance holds a list of watches, each naming a file
+//! or directory and the events of interest for it. Events on the
+//! watched files are queued, and are retrieved by reading from the
+//! instance's file descriptor.
+
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::mem;
+use std::ptr;
+use libc::{c_int, inotify_event};
+use err::EAGAIN;
+use fd::{FileDescriptor, SysResult};
+
+/// An inotify instance, created with `inotify_init()` or
+/// `inotify_init1()`.
+///
+/// As with `FileDescriptor`, does not implement `Copy` so that
+/// `Inotify::close()` can take ownership.
+#[derive(Debug)]
+pub struct Inotify(FileDescriptor);
+
+/// Identifies a watch within an inotify instance, as returned by
+/// `Inotify::add_watch()` and reported with each event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WatchDescriptor(pub c_int);
+
+/// An event read from an inotify instance (`struct inotify_event`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The watch that the event occurred for.
+    pub wd: WatchDescriptor,
+    /// What happened; a single event, possibly with `IN_ISDIR`, or one
+    /// of `IN_IGNORED`, `IN_Q_OVERFLOW` and `IN_UNMOUNT`.
+    pub mask: EventMask,
+    /// Links the `IN_MOVED_FROM` and `IN_MOVED_TO` events of a single
+    /// rename; zero for other events.
+    pub cookie: u32,
+    /// For a watched directory, the name of the file within it that
+    /// the event occurred for, with any bytes that are not valid UTF-8
+    /// replaced by U+FFFD; `None` for the directory itself.
+    pub name: Option<String>,
+}
+
+impl Inotify {
+
+    /// The `inotify_init()` system call.
+    ///
+    /// Creates a new inotify instance, with no watches.
+    ///
+    /// Consult the man page (command `man 2 inotify_init`) for
+    /// further details.
+    pub fn init() -> SysResult<Inotify> {
+        let fd = unsafe { ::libc::inotify_init() };
+        errno_check!(fd, Inotify(FileDescriptor::from_raw(fd)))
+    }
+
+    /// The `inotify_init1()` system call.
+    ///
+    /// As `Inotify::init()`, but with options for the new file
+    /// descriptor.
+    ///
+    /// Consult the man page (command `man 2 inotify_init1`) for
+    /// further details.
+    pub fn init1(flags: InitFlags) -> SysResult<Inotify> {
+        let fd = unsafe { ::libc::inotify_init1(flags.bits()) };
+        errno_check!(fd, Inotify(FileDescriptor::from_raw(fd)))
+    }
+
+    /// Returns the file descriptor of the instance, for use with
+    /// `select()`, `poll()` and similar.
+    pub fn as_fd(&self) -> &FileDescriptor { &self.0 }
+
+    /// The `inotify_add_watch()` system call.
+    ///
+    /// Watches `path` for the events in `mask`, which may also include
+    /// flags such as `IN_ONLYDIR` and `IN_ONESHOT`. If `path` is
+    /// already watched, its existing watch is modified, and returned.
+    ///
+    /// Consult the man page (command `man 2 inotify_add_watch`) for
+    /// further details.
+    pub fn add_watch(
+        &self, path: &str, mask: EventMask
+    ) -> SysResult<WatchDescriptor> {
+        // Panic if `path` contains nul chars; crude but good enough
+        let c_path = CString::new(path).unwrap();
+        let wd = unsafe {
+            ::libc::inotify_add_watch(
+                self.0.as_raw(), c_path.as_ptr(), mask.bits()
+            )
+        };
+        errno_check!(wd, WatchDescriptor(wd))
+    }
+
+    /// The `inotify_rm_watch()` system call.
+    ///
+    /// Removes the watch `wd`, which generates an `IN_IGNORED` event
+    /// for it. Fails with `EINVAL` if `wd` isn't a watch of this
+    /// instance.
+    ///
+    /// Consult the man page (command `man 2 inotify_rm_watch`) for
+    /// further details.
+    pub fn rm_watch(&self, wd: WatchDescriptor) -> SysResult<()> {
+        let status = unsafe {
+            ::libc::inotify_rm_watch(self.0.as_raw(), wd.0 as _)
+        };
+        errno_check!(status, ())
+    }
+
+    /// Returns an iterator over the events, which reads more of them
+    /// as needed.
+    ///
+    /// The iterator blocks until an event is queued; for an instance
+    /// created with `IN_NONBLOCK`, it ends instead once the queue is
+    /// empty.
+    pub fn events(&self) -> Events {
+        Events { inotify: self, pending: VecDeque::new() }
+    }
+
+    /// Reads the events that are queued, blocking until there is at
+    /// least one.
+    pub fn read_events(&self) -> SysResult<Vec<Event>> {
+        // Room for at least one event with the longest possible name
+        let mut buf = vec![0u8; BUF_LEN];
+        let num_read = try!(self.0.read(&mut buf));
+
+        let header_len = mem::size_of::<inotify_event>();
+        let mut events = Vec::new();
+        let mut offset = 0;
+        while offset + header_len <= num_read {
+            // The buffer has no particular alignment, so copy the
+            // header out rather than referring to it in place
+            let mut raw: inotify_event = unsafe { mem::zeroed() };
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    buf[offset..].as_ptr(), &mut raw as *mut _ as *mut u8,
+                    header_len
+                )
+            };
+            let name_start = offset + header_len;
+            let name_bytes = &buf[name_start..name_start + raw.len as usize];
+            events.push(Event {
+                wd: WatchDescriptor(raw.wd),
+                mask: EventMask::from_bits_truncate(raw.mask),
+                cookie: raw.cookie,
+                name: parse_name(name_bytes),
+            });
+            offset = name_start + raw.len as usize;
+        }
+        Ok(events)
+    }
+
+    /// The `close()` system call; removes all of the watches.
+    pub fn close(self) -> SysResult<()> {
+        self.0.close()
+    }
+
+}
+
+/// An iterator over the events of an inotify instance, as returned by
+/// `Inotify::events()`.
+pub struct Events<'a> {
+    inotify: &'a Inotify,
+    pending: VecDeque<Event>,
+}
+
+impl<'a> Iterator for Events<'a> {
+
+    type Item = SysResult<Event>;
+
+    fn next(&mut self) -> Option<SysResult<Event>> {
+        if self.pending.is_empty() {
+            match self.inotify.read_events() {
+                Ok(events) => self.pending.extend(events),
+                Err(errno) if errno == EAGAIN => return None,
+                Err(errno) => return Some(Err(errno)),
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+
+}
+
+/// Helper function; converts the name following an event header,
+/// which is padded with null bytes, and absent for a watched file
+/// itself.
+fn parse_name(bytes: &[u8]) -> Option<String> {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    if len == 0 {
+        None
+    } else {
+        Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+}
+
+bitflags! {
+    #[doc = "Events and flags for inotify watches."]
+    #[doc = ""]
+    #[doc = "Consult `man 7 inotify` for details on each flag."]
+    #[doc = ""]
+    #[doc = "Taken from C header files on an x86-64 Linux system; not"]
+    #[doc = "intended to be portable!"]
+    flags EventMask: u32 {
+        #[doc = "file was read"]
+        const IN_ACCESS        = 0x0000_0001,
+        #[doc = "file was written"]
+        const IN_MODIFY        = 0x0000_0002,
+        #[doc = "file metadata, such as permissions, changed"]
+        const IN_ATTRIB        = 0x0000_0004,
+        #[doc = "file opened for writing was closed"]
+        const IN_CLOSE_WRITE   = 0x0000_0008,
+        #[doc = "file not opened for writing was closed"]
+        const IN_CLOSE_NOWRITE = 0x0000_0010,
+        #[doc = "file was opened"]
+        const IN_OPEN          = 0x0000_0020,
+        #[doc = "file was moved out of the watched directory"]
+        const IN_MOVED_FROM    = 0x0000_0040,
+        #[doc = "file was moved into the watched directory"]
+        const IN_MOVED_TO      = 0x0000_0080,
+        #[doc = "file was created in the watched directory"]
+        const IN_CREATE        = 0x0000_0100,
+        #[doc = "file was deleted from the watched directory"]
+        const IN_DELETE        = 0x0000_0200,
+        #[doc = "watched file or directory was itself deleted"]
+        const IN_DELETE_SELF   = 0x0000_0400,
+        #[doc = "watched file or directory was itself moved"]
+        const IN_MOVE_SELF     = 0x0000_0800,
+        #[doc = "file system holding the watched file was unmounted"]
+        const IN_UNMOUNT       = 0x0000_2000,
+        #[doc = "event queue overflowed, so events were lost"]
+        const IN_Q_OVERFLOW    = 0x0000_4000,
+        #[doc = "watch was removed, explicitly or by the kernel"]
+        const IN_IGNORED       = 0x0000_8000,
+        #[doc = "either close event"]
+        const IN_CLOSE         = IN_CLOSE_WRITE.bits | IN_CLOSE_NOWRITE.bits,
+        #[doc = "either move event"]
+        const IN_MOVE          = IN_MOVED_FROM.bits | IN_MOVED_TO.bits,
+        #[doc = "all of the events above that can be watched for"]
+        const IN_ALL_EVENTS    = 0x0000_0fff,
+        #[doc = "only watch the path if it is a directory"]
+        const IN_ONLYDIR       = 0x0100_0000,
+        #[doc = "don't follow a symbolic link at the end of the path"]
+        const IN_DONT_FOLLOW   = 0x0200_0000,
+        #[doc = "don't report events for files unlinked from a watched"]
+        #[doc = "directory"]
+        const IN_EXCL_UNLINK   = 0x0400_0000,
+        #[doc = "add to the events of an existing watch, rather than"]
+        #[doc = "replacing them"]
+        const IN_MASK_ADD      = 0x2000_0000,
+        #[doc = "the event occurred for a directory"]
+        const IN_ISDIR         = 0x4000_0000,
+        #[doc = "remove the watch after its first event"]
+        const IN_ONESHOT       = 0x8000_0000,
+    }
+}
+
+bitflags! {
+    #[doc = "Options for `Inotify::init1()`."]
+    #[doc = ""]
+    #[doc = "Taken from C header files on an x86-64 Linux system; not"]
+    #[doc = "intended to be portable!"]
+    flags InitFlags: c_int {
+        #[doc = "don't block when reading with no event queued"]
+        const IN_NONBLOCK = 0o0004000,
+        #[doc = "close the file descriptor on a successful `execve()`"]
+        const IN_CLOEXEC  = 0o2000000,
+    }
+}
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const NAME_MAX: usize = 255;
+
+/// Size of the buffer for reading events: enough for ten events with
+/// the longest possible names.
+const BUF_LEN: usize = 10 * (16 + NAME_MAX + 1);
@@ -0,0 +1,298 @@
+
+//! Provides the facilities shared by the System V IPC mechanisms:
+//! message queues, semaphores, and shared memory.
+
+use std::ffi;
+use std::str::{FromStr, SplitWhitespace};
+use libc::{c_int, key_t, mode_t, pid_t, uid_t, gid_t, EINVAL};
+use err::Errno;
+use fd::{read_whole_file, FilePerms, SysResult};
+use fd::{S_IRUSR, S_IWUSR, S_IRGRP, S_IWGRP, S_IROTH, S_IWOTH};
+
+/// A key identifying a System V IPC object.
+///
+/// Keys are converted into identifiers by the `*get()` system call of
+/// each IPC mechanism.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpcKey(key_t);
+
+/// Key that always causes a new, unique IPC object to be created.
+pub const IPC_PRIVATE: IpcKey = IpcKey(0);
+
+impl IpcKey {
+
+    /// Create an `IpcKey` from its raw value.
+    pub fn new(value: key_t) -> IpcKey { IpcKey(value) }
+
+    /// The raw value of this key.
+    pub fn value(&self) -> key_t { self.0 }
+
+}
+
+/// The `ftok()` library function.
+///
+/// Generates a key from the i-node number and device of the file at
+/// `path` (which must exist) and the low 8 bits of `proj`.
+///
+/// Beware of these pitfalls:
+///
+/// - Only the low 8 bits of the C function's `proj` argument are
+/// used, so e.g. `1` and `257` produce the same key; this function
+/// takes a `u8` to rule that out, and rejects zero with `EINVAL`.
+/// - Keys are not guaranteed to be unique, since only some bits of
+/// the i-node number and device are used.
+/// - If the file is deleted and recreated, it will probably get a
+/// different i-node number, and so a different key.
+///
+/// Consult the man page (command `man 3 ftok`) for further details.
+pub fn ftok(path: &str, proj: u8) -> SysResult<IpcKey> {
+    // A zero project ID is allowed by glibc, but POSIX leaves it
+    // unspecified
+    if proj == 0 {
+        return Err(Errno::new(EINVAL));
+    }
+
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    let key = unsafe { ::libc::ftok(c_path.as_ptr(), proj as c_int) };
+    errno_check!(key, IpcKey(key))
+}
+
+bitflags! {
+    #[doc = "Flags for the `*get()` and related System V IPC system calls."]
+    #[doc = ""]
+    #[doc = "Consult `man 7 svipc` for details on each flag."]
+    flags IpcFlags: c_int {
+        #[doc = "create the object if it doesn't exist"]
+        const IPC_CREAT  = 0o1000,
+        #[doc = "with `IPC_CREAT`, fail if the object already exists"]
+        const IPC_EXCL   = 0o2000,
+        #[doc = "return an error instead of blocking"]
+        const IPC_NOWAIT = 0o4000,
+    }
+}
+
+/// Combines `flags` with the permissions to give a newly created IPC
+/// object, as expected by the `*get()` system calls.
+///
+/// Only the read and write permissions are meaningful for IPC
+/// objects; any others are discarded.
+pub fn get_flags(flags: IpcFlags, perms: FilePerms) -> c_int {
+    let rw_perms = perms & (S_IRUSR | S_IWUSR | S_IRGRP | S_IWGRP |
+                            S_IROTH | S_IWOTH);
+    flags.bits() | rw_perms.bits() as c_int
+}
+
+/// Ownership and permissions of a System V IPC object; the
+/// equivalent of `struct ipc_perm`.
+#[derive(Clone, Copy, Debug)]
+pub struct IpcPerms {
+    /// The key the object was created with.
+    pub key: IpcKey,
+    /// User ID of the owner.
+    pub uid: uid_t,
+    /// Group ID of the owner.
+    pub gid: gid_t,
+    /// User ID of the creator.
+    pub cuid: uid_t,
+    /// Group ID of the creator.
+    pub cgid: gid_t,
+    /// Permissions of the object.
+    pub mode: FilePerms,
+}
+
+/// Control operations common to all System V IPC mechanisms.
+#[derive(Clone, Copy, Debug)]
+pub enum IpcCommand {
+    /// `IPC_RMID`: immediately remove the object.
+    Remove = 0,
+    /// `IPC_SET`: update the object's ownership and permissions.
+    Set    = 1,
+    /// `IPC_STAT`: retrieve the object's associated data structure.
+    Stat   = 2,
+}
+
+/// Status of a message queue, as listed in `/proc/sysvipc/msg`.
+#[derive(Clone, Copy, Debug)]
+pub struct MsgQueueInfo {
+    /// Identifier of the queue.
+    pub id: c_int,
+    /// Ownership and permissions of the queue.
+    pub perms: IpcPerms,
+    /// Number of bytes currently in the queue.
+    pub cbytes: u64,
+    /// Number of messages currently in the queue.
+    pub qnum: u64,
+    /// PID of the process that last called `msgsnd()`.
+    pub lspid: pid_t,
+    /// PID of the process that last called `msgrcv()`.
+    pub lrpid: pid_t,
+    /// Time of the last `msgsnd()`, in seconds since the Epoch.
+    pub stime: i64,
+    /// Time of the last `msgrcv()`, in seconds since the Epoch.
+    pub rtime: i64,
+    /// Time of the last change, in seconds since the Epoch.
+    pub ctime: i64,
+}
+
+/// Status of a semaphore set, as listed in `/proc/sysvipc/sem`.
+#[derive(Clone, Copy, Debug)]
+pub struct SemSetInfo {
+    /// Identifier of the semaphore set.
+    pub id: c_int,
+    /// Ownership and permissions of the semaphore set.
+    pub perms: IpcPerms,
+    /// Number of semaphores in the set.
+    pub nsems: u64,
+    /// Time of the last `semop()`, in seconds since the Epoch.
+    pub otime: i64,
+    /// Time of the last change, in seconds since the Epoch.
+    pub ctime: i64,
+}
+
+/// Status of a shared memory segment, as listed in
+/// `/proc/sysvipc/shm`.
+#[derive(Clone, Copy, Debug)]
+pub struct ShmSegmentInfo {
+    /// Identifier of the segment.
+    pub id: c_int,
+    /// Ownership and permissions of the segment.
+    pub perms: IpcPerms,
+    /// Size of the segment in bytes.
+    pub size: u64,
+    /// PID of the creator.
+    pub cpid: pid_t,
+    /// PID of the process that last called `shmat()` or `shmdt()`.
+    pub lpid: pid_t,
+    /// Number of current attaches.
+    pub nattch: u64,
+    /// Time of the last `shmat()`, in seconds since the Epoch.
+    pub atime: i64,
+    /// Time of the last `shmdt()`, in seconds since the Epoch.
+    pub dtime: i64,
+    /// Time of the last change, in seconds since the Epoch.
+    pub ctime: i64,
+}
+
+/// Lists all System V message queues on the system.
+///
+/// Fails with `EINVAL` if `/proc/sysvipc/msg` has unexpected
+/// contents.
+pub fn list_msg_queues() -> SysResult<Vec<MsgQueueInfo>> {
+    parse_sysvipc_file("/proc/sysvipc/msg", |fields| {
+        let (key, id, mode) = try!(fields.key_id_mode());
+        let cbytes = try!(fields.next());
+        let qnum = try!(fields.next());
+        let lspid = try!(fields.next());
+        let lrpid = try!(fields.next());
+        let perms = try!(fields.perms(key, mode));
+        Ok(MsgQueueInfo {
+            id: id, perms: perms, cbytes: cbytes, qnum: qnum,
+            lspid: lspid, lrpid: lrpid,
+            stime: try!(fields.next()),
+            rtime: try!(fields.next()),
+            ctime: try!(fields.next()),
+        })
+    })
+}
+
+/// Lists all System V semaphore sets on the system.
+///
+/// Fails with `EINVAL` if `/proc/sysvipc/sem` has unexpected
+/// contents.
+pub fn list_sem_sets() -> SysResult<Vec<SemSetInfo>> {
+    parse_sysvipc_file("/proc/sysvipc/sem", |fields| {
+        let (key, id, mode) = try!(fields.key_id_mode());
+        let nsems = try!(fields.next());
+        let perms = try!(fields.perms(key, mode));
+        Ok(SemSetInfo {
+            id: id, perms: perms, nsems: nsems,
+            otime: try!(fields.next()),
+            ctime: try!(fields.next()),
+        })
+    })
+}
+
+/// Lists all System V shared memory segments on the system.
+///
+/// Fails with `EINVAL` if `/proc/sysvipc/shm` has unexpected
+/// contents.
+pub fn list_shm_segments() -> SysResult<Vec<ShmSegmentInfo>> {
+    parse_sysvipc_file("/proc/sysvipc/shm", |fields| {
+        let (key, id, mode) = try!(fields.key_id_mode());
+        let size = try!(fields.next());
+        let cpid = try!(fields.next());
+        let lpid = try!(fields.next());
+        let nattch = try!(fields.next());
+        let perms = try!(fields.perms(key, mode));
+        Ok(ShmSegmentInfo {
+            id: id, perms: perms, size: size,
+            cpid: cpid, lpid: lpid, nattch: nattch,
+            atime: try!(fields.next()),
+            dtime: try!(fields.next()),
+            ctime: try!(fields.next()),
+        })
+    })
+}
+
+/// Helper function; reads one of the `/proc/sysvipc` files and
+/// converts each line after the header into a record with
+/// `parse_line`.
+fn parse_sysvipc_file<T, F>(
+    path: &str, mut parse_line: F
+) -> SysResult<Vec<T>>
+    where F: FnMut(&mut Fields) -> SysResult<T>
+{
+    let contents = try!(read_whole_file(path));
+    let text = String::from_utf8_lossy(&contents);
+
+    text.lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_line(&mut Fields(line.split_whitespace())))
+        .collect()
+}
+
+/// Helper type; the whitespace-separated fields of a line from one of
+/// the `/proc/sysvipc` files.
+struct Fields<'a>(SplitWhitespace<'a>);
+
+impl<'a> Fields<'a> {
+
+    /// Parses the next field as a decimal number.
+    fn next<T: FromStr>(&mut self) -> SysResult<T> {
+        match self.0.next().map(|field| field.parse()) {
+            Some(Ok(value)) => Ok(value),
+            _ => Err(Errno::new(EINVAL)),
+        }
+    }
+
+    /// Parses the key, identifier, and (octal) permissions fields
+    /// that begin every line.
+    fn key_id_mode(&mut self) -> SysResult<(IpcKey, c_int, FilePerms)> {
+        let key = IpcKey(try!(self.next()));
+        let id = try!(self.next());
+        let field = self.0.next();
+        let mode = match field.map(|f| mode_t::from_str_radix(f, 8)) {
+            Some(Ok(bits)) => FilePerms::from_bits_truncate(bits),
+            _ => return Err(Errno::new(EINVAL)),
+        };
+        Ok((key, id, mode))
+    }
+
+    /// Parses the four ownership fields that follow the
+    /// mechanism-specific fields, combining them into an `IpcPerms`.
+    fn perms(
+        &mut self, key: IpcKey, mode: FilePerms
+    ) -> SysResult<IpcPerms> {
+        Ok(IpcPerms {
+            key: key,
+            uid: try!(self.next()),
+            gid: try!(self.next()),
+            cuid: try!(self.next()),
+            cgid: try!(self.next()),
+            mode: mode,
+        })
+    }
+
+}
@@ -7,5 +7,50 @@ extern crate libc;
 #[macro_use]
 extern crate bitflags;
 
+#[macro_use]
 pub mod err;
+
+#[cfg(test)]
+#[macro_use]
+mod testing;
+
+pub mod acct;
+pub mod aio;
+pub mod creds;
+pub mod daemon;
+pub mod dir;
+pub mod env;
+pub mod epoll;
 pub mod fd;
+pub mod fs;
+pub mod inet;
+pub mod inotify;
+pub mod ipc;
+pub mod memory;
+pub mod mount;
+pub mod mqueue;
+pub mod poll;
+pub mod process;
+pub mod procfs;
+pub mod pty;
+pub mod quota;
+pub mod read_line;
+pub mod sched;
+pub mod seccomp;
+pub mod select;
+pub mod server;
+pub mod setjmp;
+pub mod sigevent;
+pub mod signal;
+pub mod socket;
+pub mod sockopt;
+pub mod stat;
+pub mod sysconf;
+pub mod sysinfo;
+pub mod thread;
+pub mod tty;
+pub mod users;
+pub mod walk;
+
+#[cfg(feature = "uring")]
+pub mod uring;
@@ -0,0 +1,577 @@
+
+//! Provides operations on the virtual memory of the process.
+
+use std::ffi;
+use std::mem;
+use std::ops::Range;
+use std::ptr;
+use std::slice;
+use libc::{c_int, c_uchar, c_uint, c_void, intptr_t, off_t, size_t};
+use libc::{MAP_FAILED, EINVAL};
+use err::Errno;
+use fd::{FileDescriptor, SysResult};
+use sysconf::{sysconf, SysconfName};
+
+/// The `brk()` system call.
+///
+/// Sets the program break (the end of the process's data segment) to
+/// `addr`.
+///
+/// This is unsafe because it can release memory that is still in use
+/// by the `malloc()` family of functions.
+///
+/// Consult the man page (command `man 2 brk`) for further details.
+pub unsafe fn brk(addr: *mut c_void) -> SysResult<()> {
+    let status = ::libc::brk(addr);
+    errno_check!(status, ())
+}
+
+/// The `sbrk()` library function.
+///
+/// Adjusts the program break by `increment` bytes, returning the
+/// previous program break. An `increment` of zero returns the current
+/// program break without changing it, which is always safe.
+///
+/// This is unsafe for the same reasons as `brk()`.
+///
+/// Consult the man page (command `man 2 sbrk`) for further details.
+pub unsafe fn sbrk(increment: isize) -> SysResult<*mut c_void> {
+    let prev_break = ::libc::sbrk(increment as intptr_t);
+    errno_check!(prev_break as isize, prev_break)
+}
+
+/// Returns the current program break.
+///
+/// Useful for observing how the heap grows and shrinks.
+pub fn program_break() -> *mut c_void {
+    // The break can't fail to be queried, so ignore the error case
+    unsafe { sbrk(0).unwrap() }
+}
+
+/// The `posix_memalign()` library function.
+///
+/// Allocates `size` bytes of memory on the heap, starting at an
+/// address that is a multiple of `alignment`, which must be a power
+/// of two and a multiple of the size of a pointer; fails with `EINVAL`
+/// if it isn't. The memory is zeroed, so that it can be viewed safely
+/// before anything is written to it. Returns an `AlignedBuffer` that
+/// frees itself when dropped.
+///
+/// Suitable for the buffers used with `O_DIRECT`, which must be
+/// aligned to the block size of the file system.
+///
+/// Consult the man page (command `man 3 posix_memalign`) for further
+/// details.
+pub fn alloc_aligned(
+    alignment: usize, size: usize
+) -> SysResult<AlignedBuffer> {
+    let mut addr = ptr::null_mut();
+    let status = unsafe {
+        ::libc::posix_memalign(&mut addr, alignment as size_t, size as size_t)
+    };
+    try!(errnum_check!(status, ()));
+
+    unsafe { ptr::write_bytes(addr as *mut u8, 0, size) };
+    Ok(AlignedBuffer { addr: addr, len: size })
+}
+
+/// A block of memory allocated by `alloc_aligned()`.
+///
+/// The block is released with `free()` when dropped, which is the
+/// only way to release it, since it came from the C library's heap.
+pub struct AlignedBuffer {
+    /// Start address of the block.
+    addr: *mut c_void,
+
+    /// Length of the block in bytes.
+    len: usize,
+}
+
+impl AlignedBuffer {
+
+    /// The start address of the block.
+    pub fn addr(&self) -> *mut c_void { self.addr }
+
+    /// The length of the block in bytes.
+    pub fn len(&self) -> usize { self.len }
+
+    /// Views the contents of the block.
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.addr as *const u8, self.len) }
+    }
+
+    /// Views the contents of the block mutably.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.addr as *mut u8, self.len) }
+    }
+
+}
+
+impl Drop for AlignedBuffer {
+
+    fn drop(&mut self) {
+        unsafe { ::libc::free(self.addr) };
+    }
+
+}
+
+/// The `mmap()` system call.
+///
+/// ## Arguments
+///
+/// - `length`: the size of the mapping in bytes. Must be nonzero.
+/// - `prot`: the memory protection to apply to the mapping.
+/// - `flags`: whether the mapping is shared or private, plus any
+/// other options; include `MAP_ANONYMOUS` for a mapping that is not
+/// backed by a file.
+/// - `fd`: the file to map, or `None` for an anonymous mapping.
+/// - `offset`: the starting point of the mapping in the file; must be
+/// a multiple of the page size. Ignored for anonymous mappings.
+///
+/// The kernel chooses the address of the mapping. Returns a
+/// `MappedRegion` that unmaps itself when dropped.
+///
+/// Consult the man page (command `man 2 mmap`) for further details.
+pub fn mmap(
+    length: usize, prot: ProtFlags, flags: MapFlags,
+    fd: Option<&FileDescriptor>, offset: u64
+) -> SysResult<MappedRegion> {
+    let raw_fd = fd.map_or(-1, |fd| fd.as_raw());
+    let addr = unsafe {
+        ::libc::mmap(
+            ptr::null_mut(), length as size_t, prot.bits(), flags.bits(),
+            raw_fd, offset as off_t
+        )
+    };
+    let status = if addr == MAP_FAILED { -1 } else { 0 };
+    errno_check!(status, MappedRegion { addr: addr, len: length, prot: prot })
+}
+
+/// A region of memory created by `mmap()`.
+///
+/// Knows its own address, length, and protection, so that its
+/// contents can be accessed as a byte slice when the protection
+/// permits.
+///
+/// The region is unmapped when dropped, ignoring any error; call
+/// `munmap()` explicitly to check for failure.
+pub struct MappedRegion {
+    /// Start address of the mapping.
+    addr: *mut c_void,
+
+    /// Length of the mapping in bytes.
+    len: usize,
+
+    /// The protection most recently applied to the whole mapping.
+    prot: ProtFlags,
+}
+
+impl MappedRegion {
+
+    /// The start address of the mapping.
+    pub fn addr(&self) -> *mut c_void { self.addr }
+
+    /// The length of the mapping in bytes.
+    pub fn len(&self) -> usize { self.len }
+
+    /// The memory protection of the mapping.
+    pub fn prot(&self) -> ProtFlags { self.prot }
+
+    /// Views the contents of the mapping, if it is readable.
+    ///
+    /// Accessing a file mapping beyond the end of its file raises
+    /// `SIGBUS`, so the slice should not be read past the file size.
+    pub fn as_slice(&self) -> Option<&[u8]> {
+        if self.prot.contains(PROT_READ) {
+            let bytes = self.addr as *const u8;
+            Some(unsafe { slice::from_raw_parts(bytes, self.len) })
+        } else {
+            None
+        }
+    }
+
+    /// Views the contents of the mapping mutably, if it is writable.
+    ///
+    /// Writes to a `MAP_SHARED` mapping are visible to other
+    /// processes mapping the same object, and are carried through to
+    /// the underlying file.
+    pub fn as_mut_slice(&mut self) -> Option<&mut [u8]> {
+        if self.prot.contains(PROT_WRITE) {
+            let bytes = self.addr as *mut u8;
+            Some(unsafe { slice::from_raw_parts_mut(bytes, self.len) })
+        } else {
+            None
+        }
+    }
+
+    /// Changes the protection of the pages spanned by `range`, given
+    /// as byte offsets within the mapping, using `mprotect()`.
+    ///
+    /// The start of `range` must be a multiple of the page size. If
+    /// `range` covers only part of the mapping, the protection
+    /// reported by `prot()` (and so the availability of the slice
+    /// accessors) becomes the intersection of the old and new
+    /// protections, since only that much is known to apply to every
+    /// page.
+    ///
+    /// Fails with `EINVAL` if `range` extends past the end of the
+    /// mapping.
+    pub fn protect(
+        &mut self, range: Range<usize>, prot: ProtFlags
+    ) -> SysResult<()> {
+        let (addr, len) = try!(self.subrange(range));
+        try!(unsafe { mprotect(addr, len, prot) });
+
+        self.prot = if len == self.len { prot } else { self.prot & prot };
+        Ok(())
+    }
+
+    /// Flushes the pages spanned by `range`, given as byte offsets
+    /// within the mapping, to the underlying file using `msync()`.
+    ///
+    /// The start of `range` must be a multiple of the page size.
+    /// Exactly one of `MS_SYNC` and `MS_ASYNC` should be given in
+    /// `flags`.
+    ///
+    /// Fails with `EINVAL` if `range` extends past the end of the
+    /// mapping.
+    ///
+    /// Consult the man page (command `man 2 msync`) for further
+    /// details.
+    pub fn sync(&self, range: Range<usize>, flags: MsFlags) -> SysResult<()> {
+        let (addr, len) = try!(self.subrange(range));
+        let status = unsafe {
+            ::libc::msync(addr, len as size_t, flags.bits())
+        };
+        errno_check!(status, ())
+    }
+
+    /// Resizes the mapping to `new_len` bytes using `mremap()`; a
+    /// Linux-specific facility.
+    ///
+    /// Unless `MREMAP_MAYMOVE` is given, this fails with `ENOMEM` if
+    /// the mapping can't be resized in place. On success, the recorded
+    /// address and length are updated, so any previously computed
+    /// addresses within the mapping must be considered invalid.
+    ///
+    /// Consult the man page (command `man 2 mremap`) for further
+    /// details.
+    pub fn remap(
+        &mut self, new_len: usize, flags: MremapFlags
+    ) -> SysResult<()> {
+        let new_addr = unsafe {
+            ::libc::mremap(
+                self.addr, self.len as size_t, new_len as size_t,
+                flags.bits()
+            )
+        };
+        let status = if new_addr == MAP_FAILED { -1 } else { 0 };
+        try!(errno_check!(status, ()));
+
+        self.addr = new_addr;
+        self.len = new_len;
+        Ok(())
+    }
+
+    /// Advises the kernel about the expected usage of the pages
+    /// spanned by `range`, given as byte offsets within the mapping,
+    /// using `madvise()`.
+    ///
+    /// The start of `range` must be a multiple of the page size.
+    ///
+    /// Fails with `EINVAL` if `range` extends past the end of the
+    /// mapping.
+    ///
+    /// Consult the man page (command `man 2 madvise`) for further
+    /// details.
+    pub fn advise(
+        &self, range: Range<usize>, advice: Advice
+    ) -> SysResult<()> {
+        let (addr, len) = try!(self.subrange(range));
+        let status = unsafe {
+            ::libc::madvise(addr, len as size_t, advice as c_int)
+        };
+        errno_check!(status, ())
+    }
+
+    /// The `munmap()` system call.
+    ///
+    /// Removes the whole mapping from the address space of the
+    /// process. The region is moved into this method so it can't be
+    /// used afterwards.
+    ///
+    /// Consult the man page (command `man 2 munmap`) for further
+    /// details.
+    pub fn munmap(self) -> SysResult<()> {
+        let status = unsafe { ::libc::munmap(self.addr, self.len as size_t) };
+        // Already unmapped (or failed to unmap); don't try again
+        mem::forget(self);
+        errno_check!(status, ())
+    }
+
+    /// Helper method; converts a range of byte offsets within the
+    /// mapping into an address and length, checking that it's in
+    /// bounds.
+    fn subrange(
+        &self, range: Range<usize>
+    ) -> SysResult<(*mut c_void, usize)> {
+        if range.start > range.end || range.end > self.len {
+            return Err(Errno::new(EINVAL));
+        }
+
+        let base = self.addr as *mut u8;
+        let start = unsafe { base.offset(range.start as isize) };
+        Ok((start as *mut c_void, range.end - range.start))
+    }
+
+}
+
+impl Drop for MappedRegion {
+
+    fn drop(&mut self) {
+        unsafe { ::libc::munmap(self.addr, self.len as size_t) };
+    }
+
+}
+
+/// The `mprotect()` system call.
+///
+/// Changes the protection of the pages spanning `len` bytes starting
+/// at `addr`, which must be page-aligned.
+///
+/// This is unsafe because the memory might be in use by code that
+/// depends on its existing protection; prefer
+/// `MappedRegion::protect()`.
+///
+/// Consult the man page (command `man 2 mprotect`) for further
+/// details.
+pub unsafe fn mprotect(
+    addr: *mut c_void, len: usize, prot: ProtFlags
+) -> SysResult<()> {
+    let status = ::libc::mprotect(addr, len as size_t, prot.bits());
+    errno_check!(status, ())
+}
+
+/// The `mlock()` system call.
+///
+/// Locks the pages spanning `len` bytes starting at `addr` into
+/// physical memory, so that they can't be swapped out.
+///
+/// Consult the man page (command `man 2 mlock`) for further details.
+pub fn mlock(addr: *const c_void, len: usize) -> SysResult<()> {
+    let status = unsafe { ::libc::mlock(addr, len as size_t) };
+    errno_check!(status, ())
+}
+
+/// The `munlock()` system call.
+///
+/// Unlocks the pages spanning `len` bytes starting at `addr`.
+///
+/// Consult the man page (command `man 2 munlock`) for further
+/// details.
+pub fn munlock(addr: *const c_void, len: usize) -> SysResult<()> {
+    let status = unsafe { ::libc::munlock(addr, len as size_t) };
+    errno_check!(status, ())
+}
+
+/// The `mlockall()` system call.
+///
+/// Locks all current and/or future pages of the process into
+/// physical memory, according to `flags`.
+///
+/// Consult the man page (command `man 2 mlockall`) for further
+/// details.
+pub fn mlockall(flags: MclFlags) -> SysResult<()> {
+    let status = unsafe { ::libc::mlockall(flags.bits()) };
+    errno_check!(status, ())
+}
+
+/// The `munlockall()` system call.
+///
+/// Unlocks all pages of the process, and undoes the effect of any
+/// earlier `mlockall(MCL_FUTURE)`.
+///
+/// Consult the man page (command `man 2 munlockall`) for further
+/// details.
+pub fn munlockall() -> SysResult<()> {
+    let status = unsafe { ::libc::munlockall() };
+    errno_check!(status, ())
+}
+
+/// The `mincore()` system call.
+///
+/// Reports which of the pages spanning `len` bytes starting at
+/// `addr` (which must be page-aligned) are resident in physical
+/// memory. The result has one element per page.
+///
+/// Consult the man page (command `man 2 mincore`) for further
+/// details.
+pub fn mincore(addr: *const c_void, len: usize) -> SysResult<Vec<bool>> {
+    let page_size = match sysconf(SysconfName::Pagesize) {
+        Ok(Some(size)) => size as usize,
+        Ok(None) => return Err(Errno::new(EINVAL)),
+        Err(errno) => return Err(errno),
+    };
+
+    let num_pages = (len + page_size - 1) / page_size;
+    let mut vec = vec![0 as c_uchar; num_pages];
+    let status = unsafe {
+        ::libc::mincore(addr as *mut c_void, len as size_t, vec.as_mut_ptr())
+    };
+
+    // Only the least significant bit of each byte is defined
+    errno_check!(status, vec.iter().map(|&byte| byte & 1 != 0).collect())
+}
+
+/// The `memfd_create()` system call.
+///
+/// Creates an anonymous file that lives in memory, returning a file
+/// descriptor that refers to it. The file behaves like a regular file
+/// and can be resized with `ftruncate()`, mapped with `mmap()`, and
+/// (with `MFD_ALLOW_SEALING`) sealed with
+/// `FileDescriptor::add_seals()`. The `name` is only used for display
+/// in `/proc/PID/fd`.
+///
+/// Available since Linux version 3.17.
+///
+/// Consult the man page (command `man 2 memfd_create`) for further
+/// details.
+pub fn memfd_create(
+    name: &str, flags: MemfdFlags
+) -> SysResult<FileDescriptor> {
+    // Panic if `name` contains nul chars; crude but good enough
+    let c_name = ffi::CString::new(name).unwrap();
+    let fd = unsafe { ::libc::memfd_create(c_name.as_ptr(), flags.bits()) };
+    errno_check!(fd, FileDescriptor::from_raw(fd))
+}
+
+bitflags! {
+    #[doc = "Memory protection flags for `mmap()` and `mprotect()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 mmap` for details on each flag."]
+    flags ProtFlags: c_int {
+        #[doc = "pages may not be accessed"]
+        const PROT_NONE  = 0b000,
+        #[doc = "pages may be read"]
+        const PROT_READ  = 0b001,
+        #[doc = "pages may be written"]
+        const PROT_WRITE = 0b010,
+        #[doc = "pages may be executed"]
+        const PROT_EXEC  = 0b100,
+    }
+}
+
+bitflags! {
+    #[doc = "Options for `mmap()`."]
+    #[doc = ""]
+    #[doc = "Exactly one of `MAP_SHARED` and `MAP_PRIVATE` must be given."]
+    #[doc = "Consult `man 2 mmap` for details on each flag."]
+    #[doc = ""]
+    #[doc = "Taken from C header files on an x86-64 Linux system; not"]
+    #[doc = "intended to be portable!"]
+    flags MapFlags: c_int {
+        #[doc = "updates are visible to other processes and the file"]
+        const MAP_SHARED    = 0x0000_0001,
+        #[doc = "updates are private to this process (copy-on-write)"]
+        const MAP_PRIVATE   = 0x0000_0002,
+        #[doc = "the mapping is not backed by a file"]
+        const MAP_ANONYMOUS = 0x0000_0020,
+        #[doc = "the mapping grows downward, like a stack"]
+        const MAP_GROWSDOWN = 0x0000_0100,
+        #[doc = "lock the pages of the mapping into memory"]
+        const MAP_LOCKED    = 0x0000_2000,
+        #[doc = "don't reserve swap space for the mapping"]
+        const MAP_NORESERVE = 0x0000_4000,
+        #[doc = "prefault the page tables of the mapping"]
+        const MAP_POPULATE  = 0x0000_8000,
+        #[doc = "with `MAP_POPULATE`, don't block on prefaulting"]
+        const MAP_NONBLOCK  = 0x0001_0000,
+        #[doc = "the mapping is suitable for a thread stack"]
+        const MAP_STACK     = 0x0002_0000,
+        #[doc = "allocate the mapping using huge pages"]
+        const MAP_HUGETLB   = 0x0004_0000,
+    }
+}
+
+bitflags! {
+    #[doc = "Options for `msync()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 msync` for details on each flag."]
+    flags MsFlags: c_int {
+        #[doc = "schedule the write-back and return immediately"]
+        const MS_ASYNC      = 0b001,
+        #[doc = "invalidate other mappings so they see the new data"]
+        const MS_INVALIDATE = 0b010,
+        #[doc = "block until the write-back is complete"]
+        const MS_SYNC       = 0b100,
+    }
+}
+
+bitflags! {
+    #[doc = "Options for `mremap()`."]
+    #[doc = ""]
+    #[doc = "`MREMAP_FIXED` is not provided, since `MappedRegion::remap()`"]
+    #[doc = "does not accept a target address."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 mremap` for details on each flag."]
+    flags MremapFlags: c_int {
+        #[doc = "allow the kernel to relocate the mapping"]
+        const MREMAP_MAYMOVE = 0b01,
+    }
+}
+
+bitflags! {
+    #[doc = "Options for `mlockall()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 mlockall` for details on each flag."]
+    flags MclFlags: c_int {
+        #[doc = "lock all pages currently mapped"]
+        const MCL_CURRENT = 0b01,
+        #[doc = "lock all pages mapped in the future"]
+        const MCL_FUTURE  = 0b10,
+    }
+}
+
+/// Expected memory usage patterns for `madvise()`.
+///
+/// Taken from C header files on an x86-64 Linux system; not intended
+/// to be portable!
+#[derive(Clone, Copy, Debug)]
+pub enum Advice {
+    /// `MADV_NORMAL`: no special treatment; the default.
+    Normal     = 0,
+    /// `MADV_RANDOM`: pages will be accessed in random order, so
+    /// read-ahead is not useful.
+    Random     = 1,
+    /// `MADV_SEQUENTIAL`: pages will be accessed once, in sequential
+    /// order, so aggressive read-ahead is useful and pages can be
+    /// freed soon after access.
+    Sequential = 2,
+    /// `MADV_WILLNEED`: pages will be accessed soon, so they should
+    /// be read ahead.
+    WillNeed   = 3,
+    /// `MADV_DONTNEED`: pages won't be accessed soon. For private
+    /// mappings, the next access sees fresh zero-filled pages or the
+    /// original file contents.
+    DontNeed   = 4,
+    /// `MADV_FREE`: the contents of the pages are no longer needed,
+    /// and may be freed lazily when memory is scarce.
+    ///
+    /// Available since Linux version 4.5, for private anonymous
+    /// mappings only.
+    Free       = 8,
+}
+
+bitflags! {
+    #[doc = "Options for `memfd_create()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 memfd_create` for details on each flag."]
+    flags MemfdFlags: c_uint {
+        #[doc = "set the close-on-exec flag on the new file descriptor"]
+        const MFD_CLOEXEC       = 0b001,
+        #[doc = "allow seals to be added to the file"]
+        const MFD_ALLOW_SEALING = 0b010,
+        #[doc = "back the file with huge pages"]
+        const MFD_HUGETLB       = 0b100,
+    }
+}
@@ -0,0 +1,158 @@
+
+//! Provides the mount table of the calling process, as listed in
+//! `/proc/self/mountinfo`.
+//!
+//! Each line of that file describes one mount: the IDs linking it into
+//! the mount tree, which part of which file system is mounted where,
+//! and with which options. Consult the man page (command `man 5 proc`)
+//! for the format.
+
+use std::cmp;
+use std::str::{FromStr, SplitWhitespace};
+use err::{ENOENT, EINVAL};
+use fd::{read_whole_file, SysResult};
+use fs::AtFlags;
+use stat::{statx, STATX_MNT_ID};
+
+/// One mount, as listed in `/proc/self/mountinfo`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MountEntry {
+    /// The unique ID of the mount.
+    pub mount_id: u64,
+    /// The ID of the parent mount, or of this mount itself for the root
+    /// of the mount tree.
+    pub parent_id: u64,
+    /// The major and minor numbers of the device holding the file
+    /// system.
+    pub dev: (u32, u32),
+    /// The directory within the file system that is mounted; `/`
+    /// unless this is a bind mount of a subdirectory.
+    pub root: String,
+    /// The mount point, relative to the root directory of the process.
+    pub target: String,
+    /// The options of this mount, such as `rw` and `nosuid`.
+    pub options: Vec<String>,
+    /// The propagation type of the mount, such as `shared:1` or
+    /// `master:2`; empty for a private mount.
+    pub propagation: Vec<String>,
+    /// The type of the file system, such as `ext4`.
+    pub fstype: String,
+    /// The source of the mount, such as a device pathname, or `none`.
+    pub source: String,
+    /// The options of the file system, shared by all of its mounts.
+    pub super_options: Vec<String>,
+}
+
+/// Lists the mounts visible to the calling process, in the order they
+/// appear in `/proc/self/mountinfo`, so that a mount is listed after
+/// any mount it hides.
+///
+/// Fails with `EINVAL` if the file has unexpected contents.
+pub fn mount_table() -> SysResult<Vec<MountEntry>> {
+    let contents = try!(read_whole_file("/proc/self/mountinfo"));
+    let text = String::from_utf8_lossy(&contents);
+
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_line(line))
+        .collect()
+}
+
+/// Returns the mount on which the file `path` lives, following any
+/// symbolic link at the end of the pathname.
+///
+/// The mount ID reported by `statx()` is used if the kernel supplies
+/// it (Linux 5.8 and later); otherwise, the latest mount of the
+/// device holding the file is assumed. Fails with `ENOENT` if no mount
+/// in the table matches.
+pub fn find_mount(path: &str) -> SysResult<MountEntry> {
+    let attrs = try!(statx(None, path, AtFlags::empty(), STATX_MNT_ID));
+    let table = try!(mount_table());
+
+    let found = if attrs.mask.contains(STATX_MNT_ID) {
+        table.into_iter().find(|entry| entry.mount_id == attrs.mnt_id)
+    } else {
+        table.into_iter().filter(|entry| entry.dev == attrs.dev).last()
+    };
+    found.ok_or(ENOENT)
+}
+
+/// Helper function; parses one line of `/proc/self/mountinfo`.
+fn parse_line(line: &str) -> SysResult<MountEntry> {
+    let mut fields = line.split_whitespace();
+    let mount_id = try!(parse_number(&mut fields));
+    let parent_id = try!(parse_number(&mut fields));
+
+    let dev = try!(next_field(&mut fields));
+    let mut numbers = dev.splitn(2, ':').map(|n| u32::from_str(n).ok());
+    let dev = match (numbers.next(), numbers.next()) {
+        (Some(Some(major)), Some(Some(minor))) => (major, minor),
+        _ => return Err(EINVAL),
+    };
+
+    let root = unescape(try!(next_field(&mut fields)));
+    let target = unescape(try!(next_field(&mut fields)));
+    let options = split_options(try!(next_field(&mut fields)));
+
+    // A variable number of optional fields, ended by a lone hyphen
+    let mut propagation = Vec::new();
+    loop {
+        match try!(next_field(&mut fields)) {
+            "-" => break,
+            field => propagation.push(field.to_string()),
+        }
+    }
+
+    Ok(MountEntry {
+        mount_id: mount_id,
+        parent_id: parent_id,
+        dev: dev,
+        root: root,
+        target: target,
+        options: options,
+        propagation: propagation,
+        fstype: unescape(try!(next_field(&mut fields))),
+        source: unescape(try!(next_field(&mut fields))),
+        super_options: split_options(try!(next_field(&mut fields))),
+    })
+}
+
+/// Helper function; the next field of a line, failing with `EINVAL`
+/// if there are no more.
+fn next_field<'a>(fields: &mut SplitWhitespace<'a>) -> SysResult<&'a str> {
+    fields.next().ok_or(EINVAL)
+}
+
+/// Helper function; parses the next field of a line as a decimal
+/// number.
+fn parse_number(fields: &mut SplitWhitespace) -> SysResult<u64> {
+    u64::from_str(try!(next_field(fields))).or(Err(EINVAL))
+}
+
+/// Helper function; splits a comma-separated list of options.
+fn split_options(field: &str) -> Vec<String> {
+    field.split(',').map(unescape).collect()
+}
+
+/// Helper function; replaces the octal escapes (such as `\040` for a
+/// space) that the kernel uses for whitespace and backslashes.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let digits = &bytes[i + 1..cmp::min(i + 4, bytes.len())];
+        let is_escape = bytes[i] == b'\\' && digits.len() == 3 &&
+            digits.iter().all(|&d| d >= b'0' && d <= b'7');
+        if is_escape {
+            let value = digits.iter()
+                .fold(0u32, |acc, &d| acc * 8 + (d - b'0') as u32);
+            result.push(value as u8);
+            i += 4;
+        } else {
+            result.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&result).into_owned()
+}
@@ -0,0 +1,214 @@
+
+//! Provides POSIX message queues.
+
+use std::ffi;
+use std::mem;
+use libc::{c_char, c_int, c_long, c_uint, mqd_t, mq_attr, size_t};
+use fd::{FilePerms, OpenFlags, SysResult, O_NONBLOCK};
+use sigevent::{Notification, RawSigevent};
+
+/// Newtype for message queue descriptors.
+///
+/// As with `FileDescriptor`, does not implement `Copy` so that
+/// `MessageQueue::close()` can take ownership.
+pub struct MessageQueue(mqd_t);
+
+/// Attributes of a message queue.
+#[derive(Clone, Copy, Debug)]
+pub struct MqAttr {
+    /// Flags of the message queue descriptor; only `O_NONBLOCK` is
+    /// meaningful.
+    pub flags: OpenFlags,
+    /// Maximum number of messages on the queue.
+    pub max_msg: i64,
+    /// Maximum size of each message, in bytes.
+    pub msg_size: i64,
+    /// Number of messages currently on the queue; ignored when
+    /// creating a queue.
+    pub cur_msgs: i64,
+}
+
+impl MessageQueue {
+
+    /// The `mq_open()` library function.
+    ///
+    /// ## Arguments
+    ///
+    /// - `name`: the name of the queue, of the form `/somename`.
+    /// - `flags`: the access mode, plus optionally `O_CREAT`,
+    /// `O_EXCL`, and `O_NONBLOCK`.
+    /// - `mode`: the permissions to give the queue if it is being
+    /// created.
+    /// - `attr`: the maximum message count and size to give the queue
+    /// if it is being created, or `None` for the system defaults.
+    ///
+    /// Consult the man page (command `man 3 mq_open`) for further
+    /// details.
+    pub fn open(
+        name: &str, flags: OpenFlags, mode: FilePerms, attr: Option<MqAttr>
+    ) -> SysResult<MessageQueue> {
+        // Panic if `name` contains nul chars; crude but good enough
+        let c_name = ffi::CString::new(name).unwrap();
+        let raw_attr = attr.map(to_raw_attr);
+        let attr_ptr = match raw_attr {
+            Some(ref raw) => raw as *const mq_attr,
+            None => 0 as *const mq_attr,
+        };
+
+        let mqd = unsafe {
+            ::libc::mq_open(
+                c_name.as_ptr(), flags.bits(), mode.bits() as c_uint,
+                attr_ptr
+            )
+        };
+        errno_check!(mqd, MessageQueue(mqd))
+    }
+
+    /// The `mq_send()` library function.
+    ///
+    /// Adds the message `msg` to the queue with the given `priority`;
+    /// higher priorities are received first. Blocks while the queue
+    /// is full, unless `O_NONBLOCK` is in effect.
+    ///
+    /// Consult the man page (command `man 3 mq_send`) for further
+    /// details.
+    pub fn send(&self, msg: &[u8], priority: u32) -> SysResult<()> {
+        let msg_ptr = msg.as_ptr() as *const c_char;
+        let status = unsafe {
+            ::libc::mq_send(
+                self.0, msg_ptr, msg.len() as size_t, priority as c_uint
+            )
+        };
+        errno_check!(status, ())
+    }
+
+    /// The `mq_receive()` library function.
+    ///
+    /// Removes the oldest message of the highest priority from the
+    /// queue and copies it into `buf`, which must be at least as large
+    /// as the queue's `msg_size` attribute. Returns the length and
+    /// priority of the message. Blocks while the queue is empty,
+    /// unless `O_NONBLOCK` is in effect.
+    ///
+    /// Consult the man page (command `man 3 mq_receive`) for further
+    /// details.
+    pub fn receive(&self, buf: &mut [u8]) -> SysResult<(usize, u32)> {
+        let buf_ptr = buf.as_mut_ptr() as *mut c_char;
+        let mut priority: c_uint = 0;
+        let num_read = unsafe {
+            ::libc::mq_receive(
+                self.0, buf_ptr, buf.len() as size_t, &mut priority
+            )
+        };
+        errno_check!(num_read, (num_read as usize, priority as u32))
+    }
+
+    /// The `mq_getattr()` library function.
+    ///
+    /// Consult the man page (command `man 3 mq_getattr`) for further
+    /// details.
+    pub fn getattr(&self) -> SysResult<MqAttr> {
+        let mut raw: mq_attr = unsafe { mem::zeroed() };
+        let status = unsafe { ::libc::mq_getattr(self.0, &mut raw) };
+        errno_check!(status, from_raw_attr(&raw))
+    }
+
+    /// The `mq_setattr()` library function.
+    ///
+    /// Changes the flags of the message queue descriptor; only
+    /// `O_NONBLOCK` can be changed. Returns the previous attributes.
+    ///
+    /// Consult the man page (command `man 3 mq_setattr`) for further
+    /// details.
+    pub fn setattr(&self, flags: OpenFlags) -> SysResult<MqAttr> {
+        let mut new_attr: mq_attr = unsafe { mem::zeroed() };
+        new_attr.mq_flags = (flags & O_NONBLOCK).bits() as c_long;
+        let mut old_attr: mq_attr = unsafe { mem::zeroed() };
+        let status = unsafe {
+            ::libc::mq_setattr(self.0, &new_attr, &mut old_attr)
+        };
+        errno_check!(status, from_raw_attr(&old_attr))
+    }
+
+    /// The `mq_notify()` library function.
+    ///
+    /// Registers the calling process to be notified with
+    /// `notification` when a message arrives on the queue while it is
+    /// empty, or removes the existing registration if `None` is given.
+    ///
+    /// The registration is removed once a notification is delivered,
+    /// so it must be renewed to receive further notifications. Only
+    /// one process can be registered on a queue at a time.
+    ///
+    /// Consult the man page (command `man 3 mq_notify`) for further
+    /// details.
+    pub fn notify(
+        &self, notification: Option<Notification>
+    ) -> SysResult<()> {
+        let raw = notification.map(|n| n.into_raw());
+        let raw_ptr = match raw {
+            Some(ref sigevent) => sigevent as *const RawSigevent,
+            None => 0 as *const RawSigevent,
+        };
+
+        let status = unsafe { mq_notify(self.0, raw_ptr) };
+        let result = errno_check!(status, ());
+        if let (&Err(_), Some(raw)) = (&result, raw) {
+            unsafe { raw.free_closure() };
+        }
+        result
+    }
+
+    /// The `mq_close()` library function.
+    ///
+    /// The descriptor can't be used after this call returns. Any
+    /// notification registration held through it is removed.
+    ///
+    /// Consult the man page (command `man 3 mq_close`) for further
+    /// details.
+    pub fn close(self) -> SysResult<()> {
+        let status = unsafe { ::libc::mq_close(self.0) };
+        errno_check!(status, ())
+    }
+
+}
+
+/// The `mq_unlink()` library function.
+///
+/// Removes the queue named `name`; it is destroyed once all
+/// processes have closed it.
+///
+/// Consult the man page (command `man 3 mq_unlink`) for further
+/// details.
+pub fn mq_unlink(name: &str) -> SysResult<()> {
+    // Panic if `name` contains nul chars; crude but good enough
+    let c_name = ffi::CString::new(name).unwrap();
+    let status = unsafe { ::libc::mq_unlink(c_name.as_ptr()) };
+    errno_check!(status, ())
+}
+
+/// Helper function; converts attributes to their C representation.
+fn to_raw_attr(attr: MqAttr) -> mq_attr {
+    let mut raw: mq_attr = unsafe { mem::zeroed() };
+    raw.mq_flags = attr.flags.bits() as c_long;
+    raw.mq_maxmsg = attr.max_msg as c_long;
+    raw.mq_msgsize = attr.msg_size as c_long;
+    raw.mq_curmsgs = attr.cur_msgs as c_long;
+    raw
+}
+
+/// Helper function; converts attributes from their C representation.
+fn from_raw_attr(raw: &mq_attr) -> MqAttr {
+    MqAttr {
+        flags: OpenFlags::from_bits_truncate(raw.mq_flags as i32),
+        max_msg: raw.mq_maxmsg as i64,
+        msg_size: raw.mq_msgsize as i64,
+        cur_msgs: raw.mq_curmsgs as i64,
+    }
+}
+
+// The libc crate's `struct sigevent` can't express `SIGEV_THREAD`
+#[link(name = "rt")]
+extern {
+    fn mq_notify(mqdes: mqd_t, sevp: *const RawSigevent) -> c_int;
+}
@@ -0,0 +1,107 @@
+
+//! Provides I/O multiplexing with `poll()`.
+
+use std::time::Duration;
+use libc::{c_int, c_short, nfds_t};
+use fd::{FileDescriptor, SysResult};
+
+bitflags! {
+    #[doc = "Events monitored and reported by `poll()`."]
+    #[doc = ""]
+    #[doc = "Flags marked (reported) are only meaningful in the"]
+    #[doc = "returned events; they are always monitored."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 poll` for details on each flag."]
+    flags PollFlags: c_short {
+        #[doc = "data other than high-priority data can be read"]
+        const POLLIN    = 0x0001,
+        #[doc = "high-priority data can be read"]
+        const POLLPRI   = 0x0002,
+        #[doc = "normal data can be written"]
+        const POLLOUT   = 0x0004,
+        #[doc = "(reported) an error has occurred"]
+        const POLLERR   = 0x0008,
+        #[doc = "(reported) a hangup has occurred"]
+        const POLLHUP   = 0x0010,
+        #[doc = "(reported) the file descriptor is not open"]
+        const POLLNVAL  = 0x0020,
+        #[doc = "the peer of a stream socket has shut down writing"]
+        const POLLRDHUP = 0x2000,
+    }
+}
+
+/// A file descriptor to be monitored by `poll()`, with the events of
+/// interest and, after the call, the events that occurred.
+///
+/// Has the same layout as the C `struct pollfd`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PollFd {
+    fd: c_int,
+    events: c_short,
+    revents: c_short,
+}
+
+impl PollFd {
+
+    /// Monitors `fd` for `events`.
+    pub fn new(fd: &FileDescriptor, events: PollFlags) -> PollFd {
+        PollFd { fd: fd.as_raw(), events: events.bits(), revents: 0 }
+    }
+
+    /// The raw file descriptor being monitored.
+    pub fn fd(&self) -> c_int { self.fd }
+
+    /// The events being monitored.
+    pub fn events(&self) -> PollFlags {
+        PollFlags::from_bits_truncate(self.events)
+    }
+
+    /// The events that occurred, as of the last `poll()`.
+    pub fn revents(&self) -> PollFlags {
+        PollFlags::from_bits_truncate(self.revents)
+    }
+
+}
+
+/// The `poll()` system call.
+///
+/// Blocks until one of the events of interest occurs on one of the
+/// file descriptors in `fds`, or until `timeout` expires; `None`
+/// means wait indefinitely, and zero means don't block. The timeout
+/// is rounded up to whole milliseconds. Returns the number of entries
+/// of `fds` with events (zero on timeout); their events can be
+/// obtained with `PollFd::revents()`.
+///
+/// Consult the man page (command `man 2 poll`) for further details.
+pub fn poll(
+    fds: &mut [PollFd], timeout: Option<Duration>
+) -> SysResult<usize> {
+    let timeout_ms = timeout_millis(timeout);
+    let fds_ptr = fds.as_mut_ptr() as *mut ::libc::pollfd;
+    let ready = unsafe {
+        ::libc::poll(fds_ptr, fds.len() as nfds_t, timeout_ms)
+    };
+    errno_check!(ready, ready as usize)
+}
+
+/// Converts a timeout to the milliseconds expected by `poll()` and
+/// similar system calls: rounded up, at most `c_int::max_value()`,
+/// and -1 for `None`.
+///
+/// Intended for the other modules of this crate.
+pub fn timeout_millis(timeout: Option<Duration>) -> c_int {
+    match timeout {
+        Some(duration) => {
+            let nanos = duration.subsec_nanos() as u64;
+            let millis = duration.as_secs().saturating_mul(1000)
+                .saturating_add((nanos + 999999) / 1000000);
+            if millis > c_int::max_value() as u64 {
+                c_int::max_value()
+            } else {
+                millis as c_int
+            }
+        },
+        None => -1,
+    }
+}
@@ -0,0 +1,335 @@
+
+//! Provides operations on processes.
+
+use std::ffi::CString;
+use std::ptr;
+use libc::{c_int, pid_t};
+use err::{Errno, EINTR};
+use fd::SysResult;
+use signal::{preserving_errno, pthread_sigmask, sigaction};
+use signal::{SigAction, SigHandler, SigmaskHow, SigSet, Signal};
+use signal::{SA_RESTART, SIGCHLD, SIGINT, SIGQUIT};
+
+/// The `getpid()` system call.
+///
+/// Returns the process ID of the calling process. This function
+/// cannot fail.
+///
+/// Consult the man page (command `man 2 getpid`) for further details.
+pub fn getpid() -> pid_t {
+    unsafe { ::libc::getpid() }
+}
+
+/// The `getppid()` system call.
+///
+/// Returns the process ID of the parent of the calling process. This
+/// function cannot fail.
+///
+/// Consult the man page (command `man 2 getppid`) for further
+/// details.
+pub fn getppid() -> pid_t {
+    unsafe { ::libc::getppid() }
+}
+
+/// The `setsid()` system call.
+///
+/// Makes the calling process the leader of a new session and of a new
+/// process group, with no controlling terminal, returning the ID of
+/// the new session. Fails with `EPERM` if the caller is already a
+/// process group leader.
+///
+/// Consult the man page (command `man 2 setsid`) for further details.
+pub fn setsid() -> SysResult<pid_t> {
+    let sid = unsafe { ::libc::setsid() };
+    errno_check!(sid, sid)
+}
+
+/// The result of a successful `fork()`, as seen by each process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForkResult {
+    /// Returned in the parent, with the process ID of the new child.
+    Parent(pid_t),
+    /// Returned in the child.
+    Child,
+}
+
+/// The `fork()` system call.
+///
+/// Creates a new process that is a duplicate of the caller. Both
+/// processes continue from the return of this function; the result
+/// tells each one which it is.
+///
+/// Only the calling thread is duplicated in the child, so in a
+/// multithreaded program the child should restrict itself to
+/// async-signal-safe functions until it calls `exec()`.
+///
+/// Consult the man page (command `man 2 fork`) for further details.
+pub fn fork() -> SysResult<ForkResult> {
+    let pid = unsafe { ::libc::fork() };
+    errno_check!(pid, {
+        if pid == 0 { ForkResult::Child } else { ForkResult::Parent(pid) }
+    })
+}
+
+/// The `_exit()` system call.
+///
+/// Terminates the calling process immediately with the given exit
+/// status, without running any exit handlers or flushing any stdio
+/// buffers. This is the usual way for a child created by `fork()` to
+/// terminate.
+///
+/// Consult the man page (command `man 2 _exit`) for further details.
+pub fn _exit(status: i32) -> ! {
+    unsafe { ::libc::_exit(status as c_int) }
+}
+
+/// The `execv()` library function.
+///
+/// Replaces the program of the calling process with the one at
+/// `path`, passing it the argument list `argv` (which conventionally
+/// starts with the program name) and the current environment.
+///
+/// Only returns if the new program couldn't be started, so the result
+/// is always an error.
+///
+/// Consult the man page (command `man 3 execv`) for further details.
+pub fn execv(path: &str, argv: &[&str]) -> Errno {
+    // Panic if any string contains nul chars; crude but good enough
+    let c_path = CString::new(path).unwrap();
+    let c_args: Vec<_> = argv.iter().map(|arg| {
+        CString::new(*arg).unwrap()
+    }).collect();
+
+    let mut arg_ptrs: Vec<_> = c_args.iter().map(|arg| arg.as_ptr()).collect();
+    arg_ptrs.push(ptr::null());
+
+    unsafe { ::libc::execv(c_path.as_ptr(), arg_ptrs.as_ptr()) };
+    Errno::last()
+}
+
+/// The `execve()` system call.
+///
+/// As `execv()`, except that the new program gets the environment
+/// `envp`, a list of `name=value` strings, instead of the current
+/// one.
+///
+/// Consult the man page (command `man 2 execve`) for further details.
+pub fn execve(path: &str, argv: &[&str], envp: &[&str]) -> Errno {
+    // Panic if any string contains nul chars; crude but good enough
+    let c_path = CString::new(path).unwrap();
+    let c_args: Vec<_> = argv.iter().map(|arg| {
+        CString::new(*arg).unwrap()
+    }).collect();
+    let c_env: Vec<_> = envp.iter().map(|entry| {
+        CString::new(*entry).unwrap()
+    }).collect();
+
+    let mut arg_ptrs: Vec<_> = c_args.iter().map(|arg| arg.as_ptr()).collect();
+    arg_ptrs.push(ptr::null());
+    let mut env_ptrs: Vec<_> = c_env.iter().map(|entry| entry.as_ptr())
+        .collect();
+    env_ptrs.push(ptr::null());
+
+    unsafe {
+        ::libc::execve(c_path.as_ptr(), arg_ptrs.as_ptr(), env_ptrs.as_ptr())
+    };
+    Errno::last()
+}
+
+/// The `execvp()` library function.
+///
+/// As `execv()`, except that if `file` contains no slash, the
+/// program is searched for in the directories listed in the `PATH`
+/// environment variable.
+///
+/// Consult the man page (command `man 3 execvp`) for further details.
+pub fn execvp(file: &str, argv: &[&str]) -> Errno {
+    // Panic if any string contains nul chars; crude but good enough
+    let c_file = CString::new(file).unwrap();
+    let c_args: Vec<_> = argv.iter().map(|arg| {
+        CString::new(*arg).unwrap()
+    }).collect();
+
+    let mut arg_ptrs: Vec<_> = c_args.iter().map(|arg| arg.as_ptr()).collect();
+    arg_ptrs.push(ptr::null());
+
+    unsafe { ::libc::execvp(c_file.as_ptr(), arg_ptrs.as_ptr()) };
+    Errno::last()
+}
+
+/// How a child process changed state, as reported by `waitpid()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitStatus {
+    /// The child terminated normally with the given exit status.
+    Exited(i32),
+    /// The child was killed by the given signal; the flag shows
+    /// whether it produced a core dump.
+    Signaled(Signal, bool),
+    /// The child was stopped by the given signal.
+    Stopped(Signal),
+    /// The child was resumed by `SIGCONT`.
+    Continued,
+}
+
+impl WaitStatus {
+
+    /// Decodes the status value filled in by `waitpid()`, as done by
+    /// the `W*()` macros in C.
+    pub fn from_raw(status: c_int) -> WaitStatus {
+        if status & 0x7f == 0 {
+            WaitStatus::Exited((status >> 8) & 0xff)
+        } else if status & 0xff == 0x7f {
+            WaitStatus::Stopped(Signal::new((status >> 8) & 0xff))
+        } else if status == 0xffff {
+            WaitStatus::Continued
+        } else {
+            let core_dumped = status & 0x80 != 0;
+            WaitStatus::Signaled(Signal::new(status & 0x7f), core_dumped)
+        }
+    }
+
+}
+
+bitflags! {
+    #[doc = "Options for `waitpid()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 waitpid` for details on each flag."]
+    flags WaitFlags: c_int {
+        #[doc = "return immediately if no child has changed state"]
+        const WNOHANG    = 0x00000001,
+        #[doc = "also report children that have been stopped"]
+        const WUNTRACED  = 0x00000002,
+        #[doc = "also report stopped children resumed by `SIGCONT`"]
+        const WCONTINUED = 0x00000008,
+    }
+}
+
+/// The `waitpid()` system call.
+///
+/// Waits for a child to change state: the child with process ID `pid`
+/// if it is positive, or any child if it is -1 (see the man page for
+/// other values). Returns the child's process ID and status, or
+/// `None` if `WNOHANG` was given and no child has changed state.
+///
+/// Fails with `ECHILD` if there are no children to wait for.
+///
+/// Consult the man page (command `man 2 waitpid`) for further
+/// details.
+pub fn waitpid(
+    pid: pid_t, flags: WaitFlags
+) -> SysResult<Option<(pid_t, WaitStatus)>> {
+    let mut status: c_int = 0;
+    let child = unsafe { ::libc::waitpid(pid, &mut status, flags.bits()) };
+    errno_check!(child, {
+        if child == 0 {
+            None
+        } else {
+            Some((child, WaitStatus::from_raw(status)))
+        }
+    })
+}
+
+/// The `system()` library function.
+///
+/// Runs `command` with `/bin/sh -c`, waiting for it to finish, and
+/// returns its termination status; if the shell can't be executed,
+/// the status is `Exited(127)`.
+///
+/// Implemented as in _The Linux Programming Interface_: `SIGCHLD` is
+/// blocked while the command runs, so that a handler in the caller
+/// can't reap the child first, and `SIGINT` and `SIGQUIT` are ignored,
+/// so that only the command responds to them from the terminal. The
+/// child gets back the original signal mask and dispositions.
+///
+/// Consult the man page (command `man 3 system`) for further details.
+pub fn system(command: &str) -> SysResult<WaitStatus> {
+    let mut block_mask = SigSet::empty();
+    try!(block_mask.add(SIGCHLD));
+    let orig_mask = try!(pthread_sigmask(SigmaskHow::Block, &block_mask));
+
+    // On failure, undo whatever was already changed
+    let ignore = SigAction::new(SigHandler::Ignore);
+    let orig_int = match unsafe { sigaction(SIGINT, &ignore) } {
+        Ok(action) => action,
+        Err(errno) => {
+            let _ = pthread_sigmask(SigmaskHow::SetMask, &orig_mask);
+            return Err(errno);
+        },
+    };
+    let orig_quit = match unsafe { sigaction(SIGQUIT, &ignore) } {
+        Ok(action) => action,
+        Err(errno) => {
+            let _ = pthread_sigmask(SigmaskHow::SetMask, &orig_mask);
+            let _ = unsafe { sigaction(SIGINT, &orig_int) };
+            return Err(errno);
+        },
+    };
+
+    let result = match fork() {
+        Ok(ForkResult::Child) => {
+            // Dispositions that were already ignored stay that way
+            let default = SigAction::new(SigHandler::Default);
+            if !is_ignored(&orig_int) {
+                let _ = unsafe { sigaction(SIGINT, &default) };
+            }
+            if !is_ignored(&orig_quit) {
+                let _ = unsafe { sigaction(SIGQUIT, &default) };
+            }
+            let _ = pthread_sigmask(SigmaskHow::SetMask, &orig_mask);
+
+            execv("/bin/sh", &["sh", "-c", command]);
+            _exit(127);
+        },
+        Ok(ForkResult::Parent(child)) => wait_restarting(child),
+        Err(errno) => Err(errno),
+    };
+
+    // Undo the changes even if the command couldn't be run
+    let _ = pthread_sigmask(SigmaskHow::SetMask, &orig_mask);
+    let _ = unsafe { sigaction(SIGINT, &orig_int) };
+    let _ = unsafe { sigaction(SIGQUIT, &orig_quit) };
+    result
+}
+
+/// Helper function; waits for the child `pid` to terminate, restarting
+/// the wait if a signal handler interrupts it.
+fn wait_restarting(pid: pid_t) -> SysResult<WaitStatus> {
+    loop {
+        match waitpid(pid, WaitFlags::empty()) {
+            Ok(Some((_, status))) => return Ok(status),
+            // Only returned with `WNOHANG`
+            Ok(None) => continue,
+            Err(errno) if errno == EINTR => continue,
+            Err(errno) => return Err(errno),
+        }
+    }
+}
+
+/// Helper function; whether `action` ignores its signal.
+fn is_ignored(action: &SigAction) -> bool {
+    match action.handler {
+        SigHandler::Ignore => true,
+        _ => false,
+    }
+}
+
+/// Installs a `SIGCHLD` handler that reaps terminated children as
+/// soon as they exit, so that they don't linger as zombies.
+///
+/// Interrupted system calls are restarted. The handler preserves
+/// `errno`; note that it reaps _all_ children, so the program can't
+/// also collect their statuses with `waitpid()`.
+pub fn install_child_reaper() -> SysResult<()> {
+    let mut action = SigAction::new(SigHandler::Handler(reap_children));
+    action.flags = SA_RESTART;
+    unsafe { sigaction(SIGCHLD, &action) }.map(|_| ())
+}
+
+/// Signal handler for `install_child_reaper()`.
+extern "C" fn reap_children(_: c_int) {
+    preserving_errno(|| {
+        // Reap all dead children, since several may have terminated
+        // while the signal was blocked
+        while let Ok(Some(_)) = waitpid(-1, WNOHANG) {}
+    });
+}
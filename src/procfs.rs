@@ -0,0 +1,162 @@
+
+//! Provides information about processes from the `/proc` file system.
+//!
+//! Each process has a directory `/proc/PID`, whose files describe its
+//! state, command line and open files. A process may terminate at any
+//! time, so any of these functions may fail with `ENOENT` (or `ESRCH`)
+//! for a process that was just listed. Consult the man page (command
+//! `man 5 proc`) for the formats of the files.
+
+use std::str::FromStr;
+use libc::{gid_t, pid_t, uid_t};
+use creds::ResIds;
+use dir::Dir;
+use err::EINVAL;
+use fd::{read_whole_file, SysResult};
+use fs::readlink;
+
+/// Selected fields of `/proc/PID/status`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcStatus {
+    /// The command name of the process, truncated by the kernel to 15
+    /// characters.
+    pub name: String,
+    /// The state of the process, such as `R (running)`.
+    pub state: String,
+    /// The process ID.
+    pub pid: pid_t,
+    /// The ID of the parent process.
+    pub ppid: pid_t,
+    /// The real, effective and saved set user IDs.
+    pub uids: ResIds<uid_t>,
+    /// The real, effective and saved set group IDs.
+    pub gids: ResIds<gid_t>,
+}
+
+/// An open file descriptor of a process, as listed in `/proc/PID/fd`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpenFile {
+    /// The file descriptor number.
+    pub fd: i32,
+    /// The target of the descriptor's symbolic link: the pathname of
+    /// the file, or a description such as `pipe:[1234]` or
+    /// `socket:[5678]`.
+    pub target: String,
+}
+
+/// Lists the IDs of all processes, by reading the numbered entries of
+/// `/proc`, in no particular order.
+pub fn pids() -> SysResult<Vec<pid_t>> {
+    let dir = try!(Dir::open("/proc"));
+
+    let mut pids = Vec::new();
+    for entry in dir {
+        let entry = try!(entry);
+        if let Ok(pid) = pid_t::from_str(&entry.name) {
+            pids.push(pid);
+        }
+    }
+    Ok(pids)
+}
+
+/// Reads `/proc/PID/status` for the process `pid`.
+///
+/// Fails with `EINVAL` if the file has unexpected contents.
+pub fn status(pid: pid_t) -> SysResult<ProcStatus> {
+    let contents = try!(read_whole_file(&format!("/proc/{}/status", pid)));
+    let text = String::from_utf8_lossy(&contents);
+
+    let mut name = None;
+    let mut state = None;
+    let mut ppid = None;
+    let mut uids = None;
+    let mut gids = None;
+    for line in text.lines() {
+        let mut parts = line.splitn(2, ':');
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key, value.trim()),
+            _ => continue,
+        };
+        match key {
+            "Name" => name = Some(value.to_string()),
+            "State" => state = Some(value.to_string()),
+            "PPid" => ppid = pid_t::from_str(value).ok(),
+            "Uid" => uids = parse_ids(value),
+            "Gid" => gids = parse_ids(value),
+            _ => {},
+        }
+    }
+
+    match (name, state, ppid, uids, gids) {
+        (Some(name), Some(state), Some(ppid), Some(uids), Some(gids)) => {
+            Ok(ProcStatus {
+                name: name,
+                state: state,
+                pid: pid,
+                ppid: ppid,
+                uids: uids,
+                gids: gids,
+            })
+        },
+        _ => Err(EINVAL),
+    }
+}
+
+/// Reads the command-line arguments of the process `pid` from
+/// `/proc/PID/cmdline`, with any bytes that are not valid UTF-8
+/// replaced by U+FFFD.
+///
+/// The list is empty for a kernel thread or a zombie process.
+pub fn cmdline(pid: pid_t) -> SysResult<Vec<String>> {
+    let contents = try!(read_whole_file(&format!("/proc/{}/cmdline", pid)));
+    if contents.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Each argument is terminated by a null byte
+    let mut args: Vec<_> = contents.split(|&b| b == 0)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    if contents.last() == Some(&0) {
+        args.pop();
+    }
+    Ok(args)
+}
+
+/// Lists the open file descriptors of the process `pid`, in
+/// ascending order, by reading the symbolic links in `/proc/PID/fd`.
+///
+/// Reading the links of another user's process requires privilege.
+/// A descriptor that is closed while the list is being read is left
+/// out.
+pub fn open_files(pid: pid_t) -> SysResult<Vec<OpenFile>> {
+    let fd_dir = format!("/proc/{}/fd", pid);
+    let dir = try!(Dir::open(&fd_dir));
+
+    let mut files = Vec::new();
+    for entry in dir {
+        let entry = try!(entry);
+        let fd = match i32::from_str(&entry.name) {
+            Ok(fd) => fd,
+            Err(_) => continue,
+        };
+        if let Ok(target) = readlink(&format!("{}/{}", fd_dir, fd)) {
+            files.push(OpenFile { fd: fd, target: target });
+        }
+    }
+    files.sort_by(|a, b| a.fd.cmp(&b.fd));
+    Ok(files)
+}
+
+/// Helper function; parses the real, effective and saved IDs from the
+/// `Uid` or `Gid` line of `/proc/PID/status`, ignoring the file system
+/// ID that follows them.
+fn parse_ids(value: &str) -> Option<ResIds<u32>> {
+    let mut ids = value.split_whitespace().map(|id| u32::from_str(id).ok());
+    match (ids.next(), ids.next(), ids.next()) {
+        (Some(Some(real)), Some(Some(effective)), Some(Some(saved))) => {
+            Some(ResIds { real: real, effective: effective, saved: saved })
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,145 @@
+
+//! Provides pseudoterminals.
+//!
+//! A pseudoterminal is a pair of connected devices: the slave behaves
+//! like a terminal for the program using it, while the master lets
+//! another program act as the user at that terminal.
+
+use std::ffi::CStr;
+use libc::{c_char, pid_t};
+use err::{Errno, EXIT_FAILURE};
+use fd::{FileDescriptor, SysResult, O_NOCTTY, O_RDWR, FilePerms};
+use process::{_exit, fork, setsid, ForkResult};
+use tty::{set_window_size, tcsetattr, Termios, When, WindowSize};
+
+/// The result of a successful `pty_fork()`, as seen by each process.
+#[derive(Debug)]
+pub enum PtyForkResult {
+    /// Returned in the parent, with the process ID of the new child,
+    /// the master of the pseudoterminal, and the pathname of its
+    /// slave device.
+    Parent {
+        /// The process ID of the child.
+        child: pid_t,
+        /// The master device, connected to the child's terminal.
+        master: FileDescriptor,
+        /// The pathname of the slave device.
+        slave_name: String,
+    },
+    /// Returned in the child, whose standard input, output and error
+    /// are now the slave device.
+    Child,
+}
+
+/// Opens an unused pseudoterminal master, using the
+/// `posix_openpt()`, `grantpt()`, `unlockpt()` and `ptsname_r()`
+/// library functions.
+///
+/// Returns the master device together with the pathname of the
+/// corresponding slave device, which is ready to be opened.
+///
+/// This is `ptyMasterOpen()` from _The Linux Programming Interface_.
+pub fn pty_master_open() -> SysResult<(FileDescriptor, String)> {
+    let raw_fd = unsafe { ::libc::posix_openpt((O_RDWR | O_NOCTTY).bits()) };
+    try!(errno_check!(raw_fd, ()));
+    let master = FileDescriptor::from_raw(raw_fd);
+
+    match unlock_slave(&master) {
+        Ok(slave_name) => Ok((master, slave_name)),
+        Err(errno) => {
+            let _ = master.close();
+            Err(errno)
+        },
+    }
+}
+
+/// Creates a child process connected to the parent by a
+/// pseudoterminal.
+///
+/// In the child, a new session is created, and the slave device
+/// becomes its controlling terminal as well as its standard input,
+/// output and error. The slave's attributes and window size are set
+/// to `slave_attrs` and `window_size`, if given; typically these are
+/// taken from the terminal of the parent. If any of this fails, the
+/// child exits with `EXIT_FAILURE`.
+///
+/// This is `ptyFork()` from _The Linux Programming Interface_.
+pub fn pty_fork(
+    slave_attrs: Option<&Termios>, window_size: Option<&WindowSize>
+) -> SysResult<PtyForkResult> {
+    let (master, slave_name) = try!(pty_master_open());
+
+    match fork() {
+        Ok(ForkResult::Parent(child)) => Ok(PtyForkResult::Parent {
+            child: child,
+            master: master,
+            slave_name: slave_name,
+        }),
+        Ok(ForkResult::Child) => {
+            // Not needed in the child
+            let _ = master.close();
+
+            match setup_slave(slave_name, slave_attrs, window_size) {
+                Ok(()) => Ok(PtyForkResult::Child),
+                Err(_) => _exit(EXIT_FAILURE),
+            }
+        },
+        Err(errno) => {
+            let _ = master.close();
+            Err(errno)
+        },
+    }
+}
+
+/// Helper function; grants access to the slave device of `master`
+/// and unlocks it, returning its pathname.
+fn unlock_slave(master: &FileDescriptor) -> SysResult<String> {
+    let status = unsafe { ::libc::grantpt(master.as_raw()) };
+    try!(errno_check!(status, ()));
+    let status = unsafe { ::libc::unlockpt(master.as_raw()) };
+    try!(errno_check!(status, ()));
+
+    let mut buf: [c_char; MAX_SLAVE_NAME] = [0; MAX_SLAVE_NAME];
+    let status = unsafe {
+        ::libc::ptsname_r(master.as_raw(), buf.as_mut_ptr(), buf.len())
+    };
+    if status != 0 {
+        return Err(Errno::new(status));
+    }
+    let c_str = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    Ok(c_str.to_string_lossy().into_owned())
+}
+
+/// Helper function; in the child of `pty_fork()`, makes the slave
+/// device the controlling terminal and standard streams.
+fn setup_slave(
+    slave_name: String, slave_attrs: Option<&Termios>,
+    window_size: Option<&WindowSize>
+) -> SysResult<()> {
+    try!(setsid());
+
+    // As the first terminal opened by the new session leader, the
+    // slave becomes its controlling terminal
+    let slave = try!(FileDescriptor::open(
+        slave_name, O_RDWR, FilePerms::empty()
+    ));
+
+    if let Some(attrs) = slave_attrs {
+        try!(tcsetattr(&slave, When::Now, attrs));
+    }
+    if let Some(size) = window_size {
+        try!(set_window_size(&slave, size));
+    }
+
+    for std_fd in 0..3 {
+        try!(slave.dup2(std_fd));
+    }
+    if slave.as_raw() > 2 {
+        try!(slave.close());
+    }
+    Ok(())
+}
+
+/// Size of the buffer for the pathname of a slave device
+/// (`/dev/pts/N`).
+const MAX_SLAVE_NAME: usize = 64;
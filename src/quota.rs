@@ -0,0 +1,218 @@
+
+//! Provides disk quotas, which limit the space and number of i-nodes
+//! that each user or group may use on a file system.
+//!
+//! Quotas must already be enabled on the file system (for example with
+//! the `quotacheck` and `quotaon` commands); the functions here only
+//! query and change the limits of individual users and groups.
+
+use std::ffi::CString;
+use std::ptr;
+use libc::{c_char, c_int};
+use fd::SysResult;
+
+/// The kind of ID that a quota applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuotaType {
+    /// `USRQUOTA`: a user ID.
+    User  = 0,
+    /// `GRPQUOTA`: a group ID.
+    Group = 1,
+}
+
+/// The limits and usage of one user or group (`struct dqblk`).
+///
+/// Block limits are in units of 1024 bytes, while current space is in
+/// bytes. A limit of 0 means no limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dqblk {
+    /// The limit on disk space that cannot be exceeded.
+    pub block_hard_limit: u64,
+    /// The limit on disk space that may be exceeded until the grace
+    /// period ends.
+    pub block_soft_limit: u64,
+    /// The disk space currently used, in bytes.
+    pub cur_space: u64,
+    /// The limit on i-nodes that cannot be exceeded.
+    pub inode_hard_limit: u64,
+    /// The limit on i-nodes that may be exceeded until the grace period
+    /// ends.
+    pub inode_soft_limit: u64,
+    /// The number of i-nodes currently used.
+    pub cur_inodes: u64,
+    /// When the block soft limit is enforced as a hard limit, in
+    /// seconds since the Epoch; 0 if it is not exceeded.
+    pub block_time: u64,
+    /// When the i-node soft limit is enforced as a hard limit, in
+    /// seconds since the Epoch; 0 if it is not exceeded.
+    pub inode_time: u64,
+    /// Which of the fields are valid: filled in by `get_quota()`, or
+    /// to be changed by `set_quota()`.
+    pub valid: QuotaFields,
+}
+
+/// The `Q_GETQUOTA` operation of the `quotactl()` system call.
+///
+/// Returns the limits and usage of the user or group `id` on the file
+/// system mounted from the block device `device`. Unprivileged
+/// processes may only get their own quotas.
+///
+/// Consult the man page (command `man 2 quotactl`) for further
+/// details.
+pub fn get_quota(
+    device: &str, quota_type: QuotaType, id: u32
+) -> SysResult<Dqblk> {
+    let mut raw = RawDqblk::new();
+    try!(quotactl(Q_GETQUOTA, quota_type, device, id, &mut raw));
+    Ok(Dqblk {
+        block_hard_limit: raw.dqb_bhardlimit,
+        block_soft_limit: raw.dqb_bsoftlimit,
+        cur_space: raw.dqb_curspace,
+        inode_hard_limit: raw.dqb_ihardlimit,
+        inode_soft_limit: raw.dqb_isoftlimit,
+        cur_inodes: raw.dqb_curinodes,
+        block_time: raw.dqb_btime,
+        inode_time: raw.dqb_itime,
+        valid: QuotaFields::from_bits_truncate(raw.dqb_valid),
+    })
+}
+
+/// The `Q_SETQUOTA` operation of the `quotactl()` system call.
+///
+/// Changes the fields of `quota` named by `quota.valid` for the user or
+/// group `id` on the file system mounted from the block device
+/// `device`; the other fields are ignored. Only privileged processes
+/// may set quotas.
+///
+/// Consult the man page (command `man 2 quotactl`) for further
+/// details.
+pub fn set_quota(
+    device: &str, quota_type: QuotaType, id: u32, quota: &Dqblk
+) -> SysResult<()> {
+    let mut raw = RawDqblk {
+        dqb_bhardlimit: quota.block_hard_limit,
+        dqb_bsoftlimit: quota.block_soft_limit,
+        dqb_curspace: quota.cur_space,
+        dqb_ihardlimit: quota.inode_hard_limit,
+        dqb_isoftlimit: quota.inode_soft_limit,
+        dqb_curinodes: quota.cur_inodes,
+        dqb_btime: quota.block_time,
+        dqb_itime: quota.inode_time,
+        dqb_valid: quota.valid.bits(),
+    };
+    quotactl(Q_SETQUOTA, quota_type, device, id, &mut raw)
+}
+
+/// The `Q_SYNC` operation of the `quotactl()` system call.
+///
+/// Writes the quota usage of the file system mounted from `device`,
+/// or of every file system for `None`, to the quota files on disk.
+///
+/// Consult the man page (command `man 2 quotactl`) for further
+/// details.
+pub fn sync_quotas(
+    device: Option<&str>, quota_type: QuotaType
+) -> SysResult<()> {
+    // Panic if `device` contains nul chars; crude but good enough
+    let c_device = device.map(|device| CString::new(device).unwrap());
+    let c_device_ptr = c_device.as_ref().map_or(ptr::null(), |d| d.as_ptr());
+    let status = unsafe {
+        quotactl_c(
+            qcmd(Q_SYNC, quota_type), c_device_ptr, 0, ptr::null_mut()
+        )
+    };
+    errno_check!(status, ())
+}
+
+bitflags! {
+    #[doc = "The fields of a `Dqblk` that are valid."]
+    #[doc = ""]
+    #[doc = "Taken from C header files on an x86-64 Linux system; not"]
+    #[doc = "intended to be portable!"]
+    flags QuotaFields: u32 {
+        #[doc = "`block_hard_limit` and `block_soft_limit`"]
+        const QIF_BLIMITS = 0b00_0001,
+        #[doc = "`cur_space`"]
+        const QIF_SPACE   = 0b00_0010,
+        #[doc = "`inode_hard_limit` and `inode_soft_limit`"]
+        const QIF_ILIMITS = 0b00_0100,
+        #[doc = "`cur_inodes`"]
+        const QIF_INODES  = 0b00_1000,
+        #[doc = "`block_time`"]
+        const QIF_BTIME   = 0b01_0000,
+        #[doc = "`inode_time`"]
+        const QIF_ITIME   = 0b10_0000,
+        #[doc = "both kinds of limit"]
+        const QIF_LIMITS  = 0b00_0101,
+        #[doc = "both kinds of usage"]
+        const QIF_USAGE   = 0b00_1010,
+        #[doc = "both grace period times"]
+        const QIF_TIMES   = 0b11_0000,
+        #[doc = "all fields"]
+        const QIF_ALL     = 0b11_1111,
+    }
+}
+
+/// Helper type; the quota structure used by `quotactl()`
+/// (`struct dqblk`).
+#[repr(C)]
+struct RawDqblk {
+    dqb_bhardlimit: u64,
+    dqb_bsoftlimit: u64,
+    dqb_curspace: u64,
+    dqb_ihardlimit: u64,
+    dqb_isoftlimit: u64,
+    dqb_curinodes: u64,
+    dqb_btime: u64,
+    dqb_itime: u64,
+    dqb_valid: u32,
+}
+
+impl RawDqblk {
+
+    /// Helper function; a structure with every field zero.
+    fn new() -> RawDqblk {
+        RawDqblk {
+            dqb_bhardlimit: 0, dqb_bsoftlimit: 0, dqb_curspace: 0,
+            dqb_ihardlimit: 0, dqb_isoftlimit: 0, dqb_curinodes: 0,
+            dqb_btime: 0, dqb_itime: 0, dqb_valid: 0,
+        }
+    }
+
+}
+
+/// Helper function; calls `quotactl()` with an operation on the quota
+/// of `id` on the file system mounted from `device`.
+fn quotactl(
+    cmd: c_int, quota_type: QuotaType, device: &str, id: u32,
+    raw: &mut RawDqblk
+) -> SysResult<()> {
+    // Panic if `device` contains nul chars; crude but good enough
+    let c_device = CString::new(device).unwrap();
+    let status = unsafe {
+        quotactl_c(
+            qcmd(cmd, quota_type), c_device.as_ptr(), id as c_int,
+            raw as *mut RawDqblk as *mut c_char
+        )
+    };
+    errno_check!(status, ())
+}
+
+/// Helper function; combines an operation with the quota type, like
+/// the `QCMD()` macro.
+fn qcmd(cmd: c_int, quota_type: QuotaType) -> c_int {
+    (cmd << 8) | (quota_type as c_int & 0xff)
+}
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const Q_SYNC: c_int = 0x800001;
+const Q_GETQUOTA: c_int = 0x800007;
+const Q_SETQUOTA: c_int = 0x800008;
+
+extern {
+    #[link_name = "quotactl"]
+    fn quotactl_c(
+        cmd: c_int, special: *const c_char, id: c_int, addr: *mut c_char
+    ) -> c_int;
+}
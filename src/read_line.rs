@@ -0,0 +1,115 @@
+
+//! Provides line-at-a-time input from file descriptors and sockets.
+
+use err::EINTR;
+use fd::{FileDescriptor, SysResult};
+
+/// Capacity of the buffer used by `LineReader` for each `read()`.
+const BUF_SIZE: usize = 4096;
+
+/// A line read by `LineReader::read_line()`.
+#[derive(Clone, Debug)]
+pub struct Line {
+    /// The contents of the line, including the terminating newline
+    /// unless the line was truncated or ended at end-of-file.
+    pub bytes: Vec<u8>,
+    /// Whether the line was longer than the reader's maximum; if so,
+    /// the excess bytes (and the newline) were discarded.
+    pub truncated: bool,
+}
+
+/// Reads newline-terminated lines from a file descriptor.
+///
+/// Input is read in blocks, so reading the same descriptor other than
+/// through this reader will see data out of order. Reads interrupted
+/// by signal handlers are restarted.
+///
+/// This is an adaptation of `readLine()` from _The Linux Programming
+/// Interface_, with buffering added.
+pub struct LineReader<'a> {
+    /// The file to read from.
+    fd: &'a FileDescriptor,
+
+    /// Maximum number of bytes to return for a single line.
+    max_len: usize,
+
+    /// Data read from `fd` but not yet returned.
+    buffer: [u8; BUF_SIZE],
+
+    /// Index in `buffer` of the first byte not yet returned.
+    next_index: usize,
+
+    /// The number of bytes in `buffer` from the most recent read.
+    bytes_read: usize,
+}
+
+impl<'a> LineReader<'a> {
+
+    /// Creates a reader for `fd` that returns lines of at most
+    /// `max_len` bytes, including the newline.
+    ///
+    /// Use `Socket::as_fd()` to read from a socket.
+    pub fn new(fd: &'a FileDescriptor, max_len: usize) -> LineReader<'a> {
+        LineReader {
+            fd: fd,
+            max_len: max_len,
+            buffer: [0; BUF_SIZE],
+            next_index: 0,
+            bytes_read: 0,
+        }
+    }
+
+    /// Reads the next line.
+    ///
+    /// Returns `Ok(None)` at end-of-file if no bytes were read. A
+    /// final line without a terminating newline is returned as is.
+    pub fn read_line(&mut self) -> SysResult<Option<Line>> {
+        let mut line = Line { bytes: Vec::new(), truncated: false };
+        let mut any_read = false;
+
+        loop {
+            if self.next_index == self.bytes_read && !try!(self.fill()) {
+                // End-of-file
+                return Ok(if any_read { Some(line) } else { None });
+            }
+            any_read = true;
+
+            let available = &self.buffer[self.next_index..self.bytes_read];
+            let (chunk_len, found_newline) =
+                match available.iter().position(|&byte| byte == b'\n') {
+                    Some(pos) => (pos + 1, true),
+                    None => (available.len(), false),
+                };
+            self.next_index += chunk_len;
+
+            // Keep as much of the chunk as fits; discard the rest
+            let room = self.max_len - line.bytes.len();
+            if chunk_len > room {
+                line.truncated = true;
+            }
+            let keep = if chunk_len < room { chunk_len } else { room };
+            line.bytes.extend(&available[..keep]);
+
+            if found_newline {
+                return Ok(Some(line));
+            }
+        }
+    }
+
+    /// Helper method; refills `buffer` from `fd`, restarting reads
+    /// interrupted by signals. Returns `false` at end-of-file.
+    fn fill(&mut self) -> SysResult<bool> {
+        loop {
+            match self.fd.read(&mut self.buffer) {
+                Ok(count) => {
+                    self.next_index = 0;
+                    self.bytes_read = count;
+                    return Ok(count > 0);
+                },
+                Err(errno) if errno == EINTR => continue,
+                Err(errno) => return Err(errno),
+            }
+        }
+    }
+
+}
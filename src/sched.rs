@@ -0,0 +1,327 @@
+
+//! Provides control over process priorities and scheduling.
+//!
+//! The nice value of a process adjusts its share of the CPU under the
+//! default, time-sharing policy. The realtime policies instead give a
+//! process a fixed priority, above every time-sharing process.
+
+use std::fmt;
+use std::mem;
+use libc::{c_int, id_t, pid_t, sched_param, size_t};
+use err::{Errno, EINVAL};
+use fd::SysResult;
+
+/// The kind of ID given to `getpriority()` and `setpriority()`.
+///
+/// Taken from C header files on an x86-64 Linux system; not intended
+/// to be portable!
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriorityTarget {
+    /// `PRIO_PROCESS`: a process ID.
+    Process = 0,
+    /// `PRIO_PGRP`: a process group ID; every member is affected.
+    ProcessGroup = 1,
+    /// `PRIO_USER`: a real user ID; every process of the user is
+    /// affected.
+    User = 2,
+}
+
+/// Scheduling policies.
+///
+/// Taken from C header files on an x86-64 Linux system; not intended
+/// to be portable!
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchedPolicy {
+    /// `SCHED_OTHER`: the default, time-sharing policy.
+    Other = 0,
+    /// `SCHED_FIFO`: realtime; runs until it blocks or yields, or a
+    /// higher-priority process becomes ready.
+    Fifo = 1,
+    /// `SCHED_RR`: realtime, like `Fifo`, but sharing the CPU with
+    /// processes of the same priority in fixed time slices.
+    RoundRobin = 2,
+    /// `SCHED_BATCH`: like `Other`, but for CPU-bound jobs that
+    /// needn't respond quickly.
+    Batch = 3,
+    /// `SCHED_IDLE`: runs only when nothing else wants the CPU.
+    Idle = 5,
+}
+
+impl SchedPolicy {
+
+    /// Converts a raw policy value, ignoring the
+    /// `SCHED_RESET_ON_FORK` flag, or returns `None` if the policy is
+    /// not one of the above.
+    pub fn from_raw(value: c_int) -> Option<SchedPolicy> {
+        match value & !SCHED_RESET_ON_FORK {
+            0 => Some(SchedPolicy::Other),
+            1 => Some(SchedPolicy::Fifo),
+            2 => Some(SchedPolicy::RoundRobin),
+            3 => Some(SchedPolicy::Batch),
+            5 => Some(SchedPolicy::Idle),
+            _ => None,
+        }
+    }
+
+    /// The name of the policy, without the `SCHED_` prefix.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            SchedPolicy::Other => "OTHER",
+            SchedPolicy::Fifo => "FIFO",
+            SchedPolicy::RoundRobin => "RR",
+            SchedPolicy::Batch => "BATCH",
+            SchedPolicy::Idle => "IDLE",
+        }
+    }
+
+}
+
+/// The `getpriority()` system call.
+///
+/// Returns the nice value, from -20 (highest priority) to 19 (lowest),
+/// of the processes selected by `which` and `who`; if there are
+/// several, the highest priority among them. A `who` of 0 means the
+/// calling process, its process group, or its real user.
+///
+/// Consult the man page (command `man 2 getpriority`) for further
+/// details.
+pub fn getpriority(which: PriorityTarget, who: id_t) -> SysResult<i32> {
+    // -1 is a valid nice value, so `errno` must be checked instead
+    Errno::set_last(Errno::new(0));
+    let prio = unsafe { ::libc::getpriority(which as c_int as _, who) };
+    let errno = Errno::last();
+    if prio == -1 && errno.value() != 0 { Err(errno) } else { Ok(prio) }
+}
+
+/// The `setpriority()` system call.
+///
+/// Sets the nice value of the processes selected by `which` and `who`
+/// to `prio`, which is clamped to the range -20 to 19. Lowering the
+/// nice value below its limit requires privilege, or fails with
+/// `EACCES`; changing another user's processes fails with `EPERM`.
+///
+/// Consult the man page (command `man 2 setpriority`) for further
+/// details.
+pub fn setpriority(
+    which: PriorityTarget, who: id_t, prio: i32
+) -> SysResult<()> {
+    let status = unsafe {
+        ::libc::setpriority(which as c_int as _, who, prio)
+    };
+    errno_check!(status, ())
+}
+
+/// The `sched_get_priority_min()` system call.
+///
+/// Returns the lowest priority allowed for `policy`: 1 for the
+/// realtime policies, and 0 for the others.
+///
+/// Consult the man page (command `man 2 sched_get_priority_min`) for
+/// further details.
+pub fn sched_get_priority_min(policy: SchedPolicy) -> SysResult<i32> {
+    let prio = unsafe { ::libc::sched_get_priority_min(policy as c_int) };
+    errno_check!(prio, prio)
+}
+
+/// The `sched_get_priority_max()` system call.
+///
+/// Returns the highest priority allowed for `policy`: 99 for the
+/// realtime policies, and 0 for the others.
+///
+/// Consult the man page (command `man 2 sched_get_priority_max`) for
+/// further details.
+pub fn sched_get_priority_max(policy: SchedPolicy) -> SysResult<i32> {
+    let prio = unsafe { ::libc::sched_get_priority_max(policy as c_int) };
+    errno_check!(prio, prio)
+}
+
+/// The `sched_setscheduler()` system call.
+///
+/// Sets the policy of the process `pid` (0 for the calling process)
+/// to `policy`, with the realtime priority `priority`, which must be 0
+/// for the non-realtime policies. Changing to a realtime policy
+/// requires privilege, or fails with `EPERM`.
+///
+/// Consult the man page (command `man 2 sched_setscheduler`) for
+/// further details.
+pub fn sched_setscheduler(
+    pid: pid_t, policy: SchedPolicy, priority: i32
+) -> SysResult<()> {
+    let param = to_sched_param(priority);
+    let status = unsafe {
+        ::libc::sched_setscheduler(pid, policy as c_int, &param)
+    };
+    errno_check!(status, ())
+}
+
+/// The `sched_getscheduler()` system call.
+///
+/// Returns the policy of the process `pid` (0 for the calling
+/// process). Fails with `EINVAL` if the kernel reports a policy that
+/// `SchedPolicy` doesn't cover, such as `SCHED_DEADLINE`.
+///
+/// Consult the man page (command `man 2 sched_getscheduler`) for
+/// further details.
+pub fn sched_getscheduler(pid: pid_t) -> SysResult<SchedPolicy> {
+    let policy = unsafe { ::libc::sched_getscheduler(pid) };
+    try!(errno_check!(policy, ()));
+    SchedPolicy::from_raw(policy).ok_or(EINVAL)
+}
+
+/// The `sched_setparam()` system call.
+///
+/// Sets the realtime priority of the process `pid` (0 for the calling
+/// process), without changing its policy.
+///
+/// Consult the man page (command `man 2 sched_setparam`) for further
+/// details.
+pub fn sched_setparam(pid: pid_t, priority: i32) -> SysResult<()> {
+    let param = to_sched_param(priority);
+    let status = unsafe { ::libc::sched_setparam(pid, &param) };
+    errno_check!(status, ())
+}
+
+/// The `sched_getparam()` system call.
+///
+/// Returns the realtime priority of the process `pid` (0 for the
+/// calling process); always 0 for the non-realtime policies.
+///
+/// Consult the man page (command `man 2 sched_getparam`) for further
+/// details.
+pub fn sched_getparam(pid: pid_t) -> SysResult<i32> {
+    let mut param: sched_param = unsafe { mem::zeroed() };
+    let status = unsafe { ::libc::sched_getparam(pid, &mut param) };
+    errno_check!(status, param.sched_priority)
+}
+
+/// The `sched_yield()` system call.
+///
+/// Gives up the CPU to any other ready process of the same priority.
+///
+/// Consult the man page (command `man 2 sched_yield`) for further
+/// details.
+pub fn sched_yield() -> SysResult<()> {
+    let status = unsafe { ::libc::sched_yield() };
+    errno_check!(status, ())
+}
+
+/// A set of CPUs, for `sched_setaffinity()` and `sched_getaffinity()`
+/// (`cpu_set_t`).
+///
+/// Holds CPUs numbered from 0 up to, but not including, `CPU_SETSIZE`.
+///
+/// Taken from C header files on an x86-64 Linux system; not intended
+/// to be portable!
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CpuSet {
+    bits: [u64; CPU_SETSIZE / 64],
+}
+
+impl CpuSet {
+
+    /// Creates an empty set (`CPU_ZERO()`).
+    pub fn new() -> CpuSet {
+        CpuSet { bits: [0; CPU_SETSIZE / 64] }
+    }
+
+    /// Creates a set from a bit mask of the CPUs numbered below 64,
+    /// where the least significant bit is CPU 0.
+    pub fn from_mask(mask: u64) -> CpuSet {
+        let mut set = CpuSet::new();
+        set.bits[0] = mask;
+        set
+    }
+
+    /// Adds `cpu` to the set (`CPU_SET()`); panics if it is not below
+    /// `CPU_SETSIZE`.
+    pub fn set(&mut self, cpu: usize) {
+        self.bits[cpu / 64] |= 1u64 << (cpu % 64);
+    }
+
+    /// Removes `cpu` from the set (`CPU_CLR()`); panics if it is not
+    /// below `CPU_SETSIZE`.
+    pub fn clear(&mut self, cpu: usize) {
+        self.bits[cpu / 64] &= !(1u64 << (cpu % 64));
+    }
+
+    /// Whether `cpu` is in the set (`CPU_ISSET()`); false if it is not
+    /// below `CPU_SETSIZE`.
+    pub fn is_set(&self, cpu: usize) -> bool {
+        cpu < CPU_SETSIZE && self.bits[cpu / 64] & (1u64 << (cpu % 64)) != 0
+    }
+
+    /// The number of CPUs in the set (`CPU_COUNT()`).
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize)
+            .fold(0, |sum, count| sum + count)
+    }
+
+    /// The CPUs in the set, in ascending order.
+    pub fn cpus(&self) -> Vec<usize> {
+        (0..CPU_SETSIZE).filter(|&cpu| self.is_set(cpu)).collect()
+    }
+
+}
+
+impl fmt::Debug for CpuSet {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CpuSet({:?})", self.cpus())
+    }
+
+}
+
+/// The `sched_setaffinity()` system call.
+///
+/// Restricts the process `pid` (0 for the calling process) to run
+/// only on the CPUs in `set`. Fails with `EINVAL` if none of them are
+/// available.
+///
+/// Consult the man page (command `man 2 sched_setaffinity`) for
+/// further details.
+pub fn sched_setaffinity(pid: pid_t, set: &CpuSet) -> SysResult<()> {
+    let size = mem::size_of::<CpuSet>() as size_t;
+    let status = unsafe { sched_setaffinity_c(pid, size, set) };
+    errno_check!(status, ())
+}
+
+/// The `sched_getaffinity()` system call.
+///
+/// Returns the set of CPUs that the process `pid` (0 for the calling
+/// process) may run on.
+///
+/// Consult the man page (command `man 2 sched_getaffinity`) for
+/// further details.
+pub fn sched_getaffinity(pid: pid_t) -> SysResult<CpuSet> {
+    let mut set = CpuSet::new();
+    let size = mem::size_of::<CpuSet>() as size_t;
+    let status = unsafe { sched_getaffinity_c(pid, size, &mut set) };
+    errno_check!(status, set)
+}
+
+/// Helper function; builds the parameters for a realtime priority.
+fn to_sched_param(priority: i32) -> sched_param {
+    let mut param: sched_param = unsafe { mem::zeroed() };
+    param.sched_priority = priority;
+    param
+}
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const SCHED_RESET_ON_FORK: c_int = 0x4000_0000;
+
+/// The number of CPUs that a `CpuSet` can hold.
+pub const CPU_SETSIZE: usize = 1024;
+
+extern {
+    #[link_name = "sched_setaffinity"]
+    fn sched_setaffinity_c(
+        pid: pid_t, cpusetsize: size_t, mask: *const CpuSet
+    ) -> c_int;
+
+    #[link_name = "sched_getaffinity"]
+    fn sched_getaffinity_c(
+        pid: pid_t, cpusetsize: size_t, mask: *mut CpuSet
+    ) -> c_int;
+}
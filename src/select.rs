@@ -0,0 +1,184 @@
+
+//! Provides I/O multiplexing with `select()`.
+
+use std::mem;
+use std::ptr;
+use std::time::Duration;
+use libc::{c_int, fd_set, suseconds_t, time_t, timeval, FD_SETSIZE};
+use err::EINVAL;
+use fd::{FileDescriptor, SysResult};
+
+/// A set of file descriptors (`fd_set`), as monitored by `select()`.
+///
+/// Also keeps track of the highest file descriptor in the set, so
+/// that `select()` can compute its `nfds` argument.
+#[derive(Clone, Copy)]
+pub struct FdSet {
+    /// The raw set.
+    set: fd_set,
+
+    /// The highest file descriptor added to the set, or -1 if none
+    /// have been.
+    highest: c_int,
+}
+
+impl FdSet {
+
+    /// The `FD_ZERO()` macro; creates an empty set.
+    pub fn new() -> FdSet {
+        let mut set: fd_set = unsafe { mem::zeroed() };
+        unsafe { ::libc::FD_ZERO(&mut set) };
+        FdSet { set: set, highest: -1 }
+    }
+
+    /// The `FD_SET()` macro; adds `fd` to the set.
+    ///
+    /// Fails with `EINVAL` if `fd` is not less than `FD_SETSIZE`.
+    pub fn set(&mut self, fd: &FileDescriptor) -> SysResult<()> {
+        let raw = try!(FdSet::check(fd));
+        unsafe { ::libc::FD_SET(raw, &mut self.set) };
+        if raw > self.highest {
+            self.highest = raw;
+        }
+        Ok(())
+    }
+
+    /// The `FD_CLR()` macro; removes `fd` from the set.
+    ///
+    /// Fails with `EINVAL` if `fd` is not less than `FD_SETSIZE`.
+    pub fn clear(&mut self, fd: &FileDescriptor) -> SysResult<()> {
+        let raw = try!(FdSet::check(fd));
+        unsafe { ::libc::FD_CLR(raw, &mut self.set) };
+        if raw == self.highest {
+            self.recompute_highest();
+        }
+        Ok(())
+    }
+
+    /// The `FD_ISSET()` macro; whether `fd` is in the set.
+    pub fn is_set(&self, fd: &FileDescriptor) -> bool {
+        match FdSet::check(fd) {
+            Ok(raw) => unsafe { ::libc::FD_ISSET(raw, &self.set) },
+            Err(_) => false,
+        }
+    }
+
+    /// The highest file descriptor in the set, or `None` if the set
+    /// is empty.
+    pub fn highest(&self) -> Option<c_int> {
+        if self.highest < 0 { None } else { Some(self.highest) }
+    }
+
+    /// The raw values of the file descriptors in the set, in
+    /// ascending order.
+    pub fn fds(&self) -> Vec<c_int> {
+        (0..self.highest + 1).filter(|&raw| {
+            unsafe { ::libc::FD_ISSET(raw, &self.set) }
+        }).collect()
+    }
+
+    /// Helper method; finds the highest file descriptor left in the
+    /// set, after the previous highest one was removed.
+    fn recompute_highest(&mut self) {
+        while self.highest >= 0 &&
+            !unsafe { ::libc::FD_ISSET(self.highest, &self.set) }
+        {
+            self.highest -= 1;
+        }
+    }
+
+    /// Helper function; the raw value of `fd`, if it can be stored in
+    /// an `fd_set`.
+    fn check(fd: &FileDescriptor) -> SysResult<c_int> {
+        let raw = fd.as_raw();
+        if raw < 0 || raw as usize >= FD_SETSIZE as usize {
+            Err(EINVAL)
+        } else {
+            Ok(raw)
+        }
+    }
+
+}
+
+/// The `select()` system call.
+///
+/// Blocks until one or more of the file descriptors in the given sets
+/// is ready for reading, writing or has an exceptional condition,
+/// respectively, or until `timeout` expires; `None` means wait
+/// indefinitely. On return, each set contains only the descriptors
+/// that are ready, and the total number of ready descriptors (zero
+/// on timeout) is returned; a descriptor that is ready in two sets
+/// is counted twice.
+///
+/// As on Linux for C programs, `timeout` is updated to the time that
+/// was left when `select()` returned, with microsecond precision. The
+/// sets are unspecified if an error occurs.
+///
+/// Consult the man page (command `man 2 select`) for further
+/// details.
+pub fn select(
+    read: Option<&mut FdSet>, write: Option<&mut FdSet>,
+    except: Option<&mut FdSet>, timeout: Option<&mut Duration>
+) -> SysResult<usize> {
+    let mut sets = [read, write, except];
+    let mut nfds = 0;
+    let mut raw_sets = [ptr::null_mut(); 3];
+    for (raw, fd_set) in raw_sets.iter_mut().zip(sets.iter_mut()) {
+        if let Some(ref mut fd_set) = *fd_set {
+            if fd_set.highest + 1 > nfds {
+                nfds = fd_set.highest + 1;
+            }
+            *raw = &mut fd_set.set as *mut fd_set;
+        }
+    }
+
+    let mut raw_timeout = timeout.as_ref().map(|duration| {
+        timeout_timeval(**duration)
+    });
+    let timeout_ptr = match raw_timeout {
+        Some(ref mut value) => value as *mut timeval,
+        None => ptr::null_mut(),
+    };
+
+    let ready = unsafe {
+        ::libc::select(
+            nfds, raw_sets[0], raw_sets[1], raw_sets[2], timeout_ptr
+        )
+    };
+    try!(errno_check!(ready, ()));
+
+    if let (Some(duration), Some(remaining)) = (timeout, raw_timeout) {
+        let nanos = remaining.tv_usec as u32 * 1000;
+        *duration = Duration::new(remaining.tv_sec as u64, nanos);
+    }
+
+    // Descriptors that weren't ready have been removed from the sets
+    for fd_set in sets.iter_mut() {
+        if let Some(ref mut fd_set) = *fd_set {
+            fd_set.recompute_highest();
+        }
+    }
+
+    Ok(ready as usize)
+}
+
+/// Converts a timeout to the `timeval` expected by `select()` and
+/// similar system calls: rounded up to a whole number of
+/// microseconds, so that a nonzero timeout never becomes zero, and at
+/// most the largest representable value.
+///
+/// Intended for the other modules of this crate.
+pub fn timeout_timeval(duration: Duration) -> timeval {
+    let max_secs = time_t::max_value() as u64;
+    let mut secs = duration.as_secs();
+    let mut micros = (duration.subsec_nanos() + 999) / 1000;
+    if micros == 1000000 {
+        secs = secs.saturating_add(1);
+        micros = 0;
+    }
+    if secs > max_secs {
+        timeval { tv_sec: max_secs as time_t, tv_usec: 999999 }
+    } else {
+        timeval { tv_sec: secs as time_t, tv_usec: micros as suseconds_t }
+    }
+}
@@ -0,0 +1,66 @@
+
+//! Provides the common structure of concurrent network servers.
+
+use std::io::{self, Write};
+use err::{Errno, TlpiResult, EINTR, EXIT_FAILURE, EXIT_SUCCESS};
+use process::{_exit, fork, install_child_reaper, ForkResult};
+use socket::{SockAddr, Socket};
+
+/// Accepts connections on `listen_socket` indefinitely, handling
+/// each one in a new child process.
+///
+/// In each child, the listening socket is closed, then `handler` is
+/// called with the connected socket and the address of the client.
+/// The child then exits, with a successful status if `handler`
+/// returned `Ok`; the handler should report any errors itself. The
+/// parent closes its copy of the connected socket and goes back to
+/// accepting connections.
+///
+/// A `SIGCHLD` handler is installed with `install_child_reaper()` to
+/// reap the children as they terminate. A failure of `fork()` is
+/// assumed to be temporary, so the client is dropped and the server
+/// continues; any other failure ends the loop, and its error is
+/// returned.
+///
+/// This is the design of `is_echo_sv` from _The Linux Programming
+/// Interface_.
+pub fn serve_forking<F>(listen_socket: &Socket, mut handler: F) -> Errno
+    where F: FnMut(Socket, SockAddr) -> TlpiResult<()>
+{
+    if let Err(errno) = install_child_reaper() {
+        return errno;
+    }
+
+    loop {
+        let (client, addr) = match listen_socket.accept() {
+            Ok(connection) => connection,
+            Err(errno) if errno == EINTR => continue,
+            Err(errno) => return errno,
+        };
+
+        // Otherwise the child would inherit any buffered output
+        let _ = io::stdout().flush();
+
+        match fork() {
+            Ok(ForkResult::Child) => {
+                // The child doesn't accept connections itself
+                unsafe { ::libc::close(listen_socket.as_fd().as_raw()) };
+
+                let status = match handler(client, addr) {
+                    Ok(()) => EXIT_SUCCESS,
+                    Err(()) => EXIT_FAILURE,
+                };
+                let _ = io::stdout().flush();
+                _exit(status);
+            },
+            Ok(ForkResult::Parent(_)) => {
+                // Unneeded copy of the connected socket
+                let _ = client.close();
+            },
+            Err(_) => {
+                // May be temporary; try the next client
+                let _ = client.close();
+            },
+        };
+    }
+}
@@ -0,0 +1,86 @@
+
+//! Provides nonlocal gotos with `setjmp()` and `longjmp()`.
+//!
+//! These are here only to show the programs of chapter 6 of _The
+//! Linux Programming Interface_; they have no place in real Rust
+//! code. A `longjmp()` skips the destructors of every frame that it
+//! unwinds, and the compiler doesn't know that `setjmp()` can return
+//! twice, so local variables changed between the two returns may
+//! have either value, especially with optimization enabled. Without
+//! that knowledge, the optimizer may even break a program whose use
+//! of `longjmp()` is well defined in C, so the examples built on this
+//! module (`longjmp` as well as `setjmp_vars`) may misbehave when
+//! built with `--release`.
+//!
+//! Since `setjmp()` must return into a frame that is still active, it
+//! can't be wrapped in a Rust function; the C functions are declared
+//! directly instead, and so are all unsafe to call. Jumping to a
+//! `JmpBuf` whose `setjmp()` call has since returned from its
+//! function is undefined behavior.
+
+use libc::c_int;
+
+/// The saved state for a nonlocal goto (`jmp_buf`, and also
+/// `sigjmp_buf`, which glibc defines identically).
+///
+/// The C library writes into it, so it must stay at the same address
+/// between `setjmp()` and `longjmp()`; typically it is a `static mut`
+/// initialized with `JMP_BUF_INIT`.
+///
+/// Taken from C header files on an x86-64 Linux system; not intended
+/// to be portable!
+#[repr(C)]
+pub struct JmpBuf {
+    /// The saved registers, the mask-was-saved flag, and the saved
+    /// signal mask: 200 bytes in all.
+    _buf: [u64; 25],
+}
+
+/// An empty `JmpBuf`, for use as the initializer of a `static`.
+pub const JMP_BUF_INIT: JmpBuf = JmpBuf { _buf: [0; 25] };
+
+extern {
+    /// The `setjmp()` library function.
+    ///
+    /// Saves the calling environment in `env` and returns 0. A later
+    /// `longjmp()` on `env` makes this call return again, with the
+    /// value passed to `longjmp()`. Whether the signal mask is saved
+    /// depends on the C library; glibc doesn't save it.
+    ///
+    /// Consult the man page (command `man 3 setjmp`) for further
+    /// details.
+    // In glibc, `setjmp()` is a macro for this function; the exported
+    // `setjmp` symbol is the BSD version, which saves the signal mask
+    #[link_name = "_setjmp"]
+    pub fn setjmp(env: *mut JmpBuf) -> c_int;
+
+    /// The `longjmp()` library function.
+    ///
+    /// Returns once more from the `setjmp()` call that filled in
+    /// `env`, making it return `val`, or 1 if `val` is 0.
+    ///
+    /// Consult the man page (command `man 3 longjmp`) for further
+    /// details.
+    pub fn longjmp(env: *mut JmpBuf, val: c_int) -> !;
+
+    /// The `sigsetjmp()` library function.
+    ///
+    /// Like `setjmp()`, but also saves the signal mask in `env` if
+    /// `savesigs` is nonzero, for `siglongjmp()` to restore. This is
+    /// the right way to jump out of a signal handler.
+    ///
+    /// Consult the man page (command `man 3 sigsetjmp`) for further
+    /// details.
+    // In glibc, `sigsetjmp()` is a macro for this function
+    #[link_name = "__sigsetjmp"]
+    pub fn sigsetjmp(env: *mut JmpBuf, savesigs: c_int) -> c_int;
+
+    /// The `siglongjmp()` library function.
+    ///
+    /// Like `longjmp()`, but restores the signal mask if it was saved
+    /// by `sigsetjmp()`.
+    ///
+    /// Consult the man page (command `man 3 siglongjmp`) for further
+    /// details.
+    pub fn siglongjmp(env: *mut JmpBuf, val: c_int) -> !;
+}
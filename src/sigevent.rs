@@ -0,0 +1,104 @@
+
+//! Provides the notification methods used by asynchronous
+//! facilities such as POSIX message queues and asynchronous I/O.
+
+use std::ptr;
+use libc::{c_int, c_void};
+use signal::Signal;
+
+/// How a process is to be notified that an event has occurred.
+pub enum Notification {
+    /// `SIGEV_NONE`: don't notify; the caller will poll for the
+    /// event instead.
+    Nothing,
+    /// `SIGEV_SIGNAL`: send the process the given signal, with a
+    /// `si_value` equal to the given integer.
+    Signal(Signal, isize),
+    /// `SIGEV_THREAD`: call the given closure in a new thread.
+    ///
+    /// The closure is called at most once, then dropped. If the event
+    /// never occurs, the closure is leaked.
+    Thread(Box<FnMut() + Send>),
+}
+
+/// The C `struct sigevent`, with its union flattened into the
+/// `SIGEV_THREAD` variant.
+///
+/// Intended for the other modules of this crate, which pass it to
+/// system calls.
+#[repr(C)]
+pub struct RawSigevent {
+    value: *mut c_void,
+    signo: c_int,
+    notify: c_int,
+    function: Option<extern "C" fn(*mut c_void)>,
+    attributes: *mut c_void,
+    pad: [c_int; 8],
+}
+
+impl Notification {
+
+    /// Converts this notification into the form expected by the C
+    /// library.
+    ///
+    /// Any closure in a `Thread` notification is moved onto the heap,
+    /// where it remains until the notification is delivered.
+    pub fn into_raw(self) -> RawSigevent {
+        let mut raw = RawSigevent {
+            value: ptr::null_mut(),
+            signo: 0,
+            notify: SIGEV_NONE,
+            function: None,
+            attributes: ptr::null_mut(),
+            pad: [0; 8],
+        };
+
+        match self {
+            Notification::Nothing => {},
+            Notification::Signal(signo, value) => {
+                raw.notify = SIGEV_SIGNAL;
+                raw.signo = signo.value();
+                raw.value = value as *mut c_void;
+            },
+            Notification::Thread(closure) => {
+                // Double box so that a thin pointer fits in `si_value`
+                let boxed: Box<Box<FnMut() + Send>> = Box::new(closure);
+                raw.notify = SIGEV_THREAD;
+                raw.function = Some(thread_trampoline);
+                raw.value = Box::into_raw(boxed) as *mut c_void;
+            },
+        };
+
+        raw
+    }
+
+}
+
+impl RawSigevent {
+
+    /// Frees the closure of a `Thread` notification, if any, for a
+    /// notification that will never be delivered because the call
+    /// given it failed.
+    ///
+    /// Intended for the other modules of this crate. Unsafe because
+    /// the closure must not be freed if it may still be called.
+    pub unsafe fn free_closure(self) {
+        if self.notify == SIGEV_THREAD && !self.value.is_null() {
+            drop(Box::from_raw(self.value as *mut Box<FnMut() + Send>));
+        }
+    }
+
+}
+
+/// Entry point of the thread created by the C library for a
+/// `SIGEV_THREAD` notification.
+extern "C" fn thread_trampoline(value: *mut c_void) {
+    let mut closure = unsafe {
+        Box::from_raw(value as *mut Box<FnMut() + Send>)
+    };
+    (**closure)();
+}
+
+const SIGEV_SIGNAL: c_int = 0;
+const SIGEV_NONE: c_int = 1;
+const SIGEV_THREAD: c_int = 2;
@@ -0,0 +1,723 @@
+
+//! Provides signals, signal sets and signal dispositions.
+//!
+//! A signal handler can interrupt the program between any two
+//! instructions, including in the middle of a function that the
+//! handler itself calls. Such a function must be async-signal-safe:
+//! either reentrant, because it keeps no state outside of its
+//! arguments and locals, or not interruptible by signals. Few are:
+//! anything that allocates, locks (`println!()` among them) or
+//! returns a pointer to a static buffer, such as `crypt()` and
+//! `Signal::description()`, is not. Apart from the async-signal-safe
+//! system calls listed in `man 7 signal-safety`, a handler should
+//! only set a flag of an atomic type, and leave the rest of the work
+//! to the program.
+
+use std::ffi::CStr;
+use std::fmt;
+use std::mem;
+use std::ptr;
+use libc::{c_int, c_void, pid_t, sigset_t, uid_t};
+use err::Errno;
+use fd::{FileDescriptor, SysResult};
+
+/// A signal number.
+///
+/// The standard signals are provided as constants; realtime signals
+/// can be created with `Signal::new()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signal(c_int);
+
+impl Signal {
+
+    /// Creates a signal from its number.
+    pub fn new(value: i32) -> Signal { Signal(value) }
+
+    /// The number of this signal.
+    pub fn value(&self) -> i32 { self.0 }
+
+    /// The `strsignal()` library function; describes the signal, such
+    /// as "Interrupt" for `SIGINT`.
+    ///
+    /// Not thread-safe, since the C library may describe unknown
+    /// signals in a static buffer.
+    pub fn description(&self) -> String {
+        let text = unsafe { CStr::from_ptr(::libc::strsignal(self.0)) };
+        text.to_string_lossy().into_owned()
+    }
+
+}
+
+/// Hangup detected on controlling terminal, or death of the
+/// controlling process.
+pub const SIGHUP: Signal = Signal(1);
+/// Interrupt from keyboard.
+pub const SIGINT: Signal = Signal(2);
+/// Quit from keyboard.
+pub const SIGQUIT: Signal = Signal(3);
+/// Illegal instruction.
+pub const SIGILL: Signal = Signal(4);
+/// Trace/breakpoint trap.
+pub const SIGTRAP: Signal = Signal(5);
+/// Abort signal from `abort()`.
+pub const SIGABRT: Signal = Signal(6);
+/// Bus error (bad memory access).
+pub const SIGBUS: Signal = Signal(7);
+/// Floating-point exception.
+pub const SIGFPE: Signal = Signal(8);
+/// Kill signal; can't be caught, blocked or ignored.
+pub const SIGKILL: Signal = Signal(9);
+/// User-defined signal 1.
+pub const SIGUSR1: Signal = Signal(10);
+/// Invalid memory reference.
+pub const SIGSEGV: Signal = Signal(11);
+/// User-defined signal 2.
+pub const SIGUSR2: Signal = Signal(12);
+/// Broken pipe: write to pipe with no readers.
+pub const SIGPIPE: Signal = Signal(13);
+/// Timer signal from `alarm()`.
+pub const SIGALRM: Signal = Signal(14);
+/// Termination signal.
+pub const SIGTERM: Signal = Signal(15);
+/// Stack fault on coprocessor (unused).
+pub const SIGSTKFLT: Signal = Signal(16);
+/// Child stopped or terminated.
+pub const SIGCHLD: Signal = Signal(17);
+/// Continue if stopped.
+pub const SIGCONT: Signal = Signal(18);
+/// Stop process; can't be caught, blocked or ignored.
+pub const SIGSTOP: Signal = Signal(19);
+/// Stop typed at terminal.
+pub const SIGTSTP: Signal = Signal(20);
+/// Terminal input for background process.
+pub const SIGTTIN: Signal = Signal(21);
+/// Terminal output for background process.
+pub const SIGTTOU: Signal = Signal(22);
+/// Urgent condition on socket.
+pub const SIGURG: Signal = Signal(23);
+/// CPU time limit exceeded.
+pub const SIGXCPU: Signal = Signal(24);
+/// File size limit exceeded.
+pub const SIGXFSZ: Signal = Signal(25);
+/// Virtual alarm clock.
+pub const SIGVTALRM: Signal = Signal(26);
+/// Profiling timer expired.
+pub const SIGPROF: Signal = Signal(27);
+/// Window resize signal.
+pub const SIGWINCH: Signal = Signal(28);
+/// I/O now possible.
+pub const SIGIO: Signal = Signal(29);
+/// Power failure.
+pub const SIGPWR: Signal = Signal(30);
+/// Bad system call.
+pub const SIGSYS: Signal = Signal(31);
+
+/// One more than the highest signal number.
+pub const NSIG: i32 = 65;
+
+/// The lowest realtime signal available to applications.
+///
+/// This is a function rather than a constant because the C library
+/// reserves a few of the kernel's realtime signals for its own use.
+pub fn sigrtmin() -> Signal {
+    Signal(unsafe { __libc_current_sigrtmin() })
+}
+
+/// The highest realtime signal available to applications.
+pub fn sigrtmax() -> Signal {
+    Signal(unsafe { __libc_current_sigrtmax() })
+}
+
+/// A set of signals (`sigset_t`).
+#[derive(Clone, Copy)]
+pub struct SigSet(sigset_t);
+
+impl SigSet {
+
+    /// The `sigemptyset()` library function; creates a set containing
+    /// no signals.
+    pub fn empty() -> SigSet {
+        let mut raw: sigset_t = unsafe { mem::zeroed() };
+        unsafe { ::libc::sigemptyset(&mut raw) };
+        SigSet(raw)
+    }
+
+    /// The `sigfillset()` library function; creates a set containing
+    /// all signals.
+    pub fn full() -> SigSet {
+        let mut raw: sigset_t = unsafe { mem::zeroed() };
+        unsafe { ::libc::sigfillset(&mut raw) };
+        SigSet(raw)
+    }
+
+    /// The `sigaddset()` library function; adds `sig` to the set.
+    ///
+    /// Fails with `EINVAL` if `sig` is not a valid signal.
+    pub fn add(&mut self, sig: Signal) -> SysResult<()> {
+        let status = unsafe { ::libc::sigaddset(&mut self.0, sig.0) };
+        errno_check!(status, ())
+    }
+
+    /// The `sigdelset()` library function; removes `sig` from the set.
+    ///
+    /// Fails with `EINVAL` if `sig` is not a valid signal.
+    pub fn remove(&mut self, sig: Signal) -> SysResult<()> {
+        let status = unsafe { ::libc::sigdelset(&mut self.0, sig.0) };
+        errno_check!(status, ())
+    }
+
+    /// The `sigismember()` library function; whether `sig` is in the
+    /// set. Invalid signals are never members.
+    pub fn contains(&self, sig: Signal) -> bool {
+        unsafe { ::libc::sigismember(&self.0, sig.0) == 1 }
+    }
+
+    /// The signals in the set, in ascending order.
+    pub fn signals(&self) -> Vec<Signal> {
+        (1..NSIG).map(Signal).filter(|&sig| self.contains(sig)).collect()
+    }
+
+    /// The C representation of this set.
+    ///
+    /// Intended for the other modules of this crate.
+    pub fn as_raw(&self) -> &sigset_t { &self.0 }
+
+    /// Wraps the C representation of a set.
+    ///
+    /// Intended for the other modules of this crate.
+    pub fn from_raw(raw: sigset_t) -> SigSet { SigSet(raw) }
+
+}
+
+impl fmt::Display for SigSet {
+
+    /// Lists the signals in the set with their descriptions, such as
+    /// `2 (Interrupt), 3 (Quit)`, or `<empty signal set>`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let signals = self.signals();
+        if signals.is_empty() {
+            return write!(f, "<empty signal set>");
+        }
+        for (i, sig) in signals.iter().enumerate() {
+            let separator = if i == 0 { "" } else { ", " };
+            try!(write!(f, "{}{} ({})", separator, sig.0, sig.description()));
+        }
+        Ok(())
+    }
+
+}
+
+/// What happens when a signal is delivered.
+#[derive(Clone, Copy)]
+pub enum SigHandler {
+    /// `SIG_DFL`: the default action for the signal.
+    Default,
+    /// `SIG_IGN`: the signal is discarded.
+    Ignore,
+    /// The given function is called with the signal number.
+    Handler(extern "C" fn(c_int)),
+    /// The given function is called with the signal number, the
+    /// details of the signal and the interrupted context (a
+    /// `ucontext_t`); `sigaction()` sets `SA_SIGINFO` for it.
+    InfoHandler(extern "C" fn(c_int, &SigInfo, *mut c_void)),
+}
+
+/// Details of a delivered signal (`siginfo_t`), as passed to a
+/// `SigHandler::InfoHandler`.
+///
+/// Only the fields common to the signals sent by processes are
+/// provided. Taken from C header files on an x86-64 Linux system; not
+/// intended to be portable!
+#[repr(C)]
+pub struct SigInfo {
+    signo: c_int,
+    errno: c_int,
+    code: c_int,
+    pad: c_int,
+    pid: pid_t,
+    uid: uid_t,
+    value: isize,
+    rest: [u64; 12],
+}
+
+impl SigInfo {
+
+    /// The signal number (`si_signo`).
+    pub fn signo(&self) -> i32 { self.signo }
+
+    /// Where the signal came from (`si_code`), such as `SI_USER` for
+    /// `kill()` or `SI_QUEUE` for `sigqueue()`.
+    pub fn code(&self) -> i32 { self.code }
+
+    /// The process ID of the sender (`si_pid`); only meaningful for
+    /// signals sent by a process.
+    pub fn pid(&self) -> pid_t { self.pid }
+
+    /// The real user ID of the sender (`si_uid`); only meaningful for
+    /// signals sent by a process.
+    pub fn uid(&self) -> uid_t { self.uid }
+
+    /// The data accompanying the signal (`si_value`); only meaningful
+    /// for `SI_QUEUE` signals and notifications.
+    pub fn value(&self) -> isize { self.value }
+
+    /// The address that caused the fault (`si_addr`); only meaningful
+    /// for `SIGSEGV`, `SIGBUS`, `SIGILL` and `SIGFPE`.
+    pub fn addr(&self) -> usize {
+        // It overlays `si_pid` and `si_uid`
+        unsafe { *(&self.pid as *const pid_t as *const usize) }
+    }
+
+}
+
+bitflags! {
+    #[doc = "Options for `sigaction()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 sigaction` for details on each flag."]
+    flags SaFlags: c_int {
+        #[doc = "don't generate `SIGCHLD` when children stop or resume"]
+        const SA_NOCLDSTOP = 0x00000001,
+        #[doc = "don't turn terminated children into zombies"]
+        const SA_NOCLDWAIT = 0x00000002,
+        #[doc = "call the handler on the alternate signal stack"]
+        const SA_ONSTACK   = 0x08000000,
+        #[doc = "restart system calls interrupted by the handler"]
+        const SA_RESTART   = 0x10000000,
+        #[doc = "don't block the signal while its handler runs"]
+        const SA_NODEFER   = 0x40000000,
+        #[doc = "reset the disposition to the default on delivery"]
+        const SA_RESETHAND = 0x80000000u32 as c_int,
+    }
+}
+
+/// The disposition of a signal, as set by `sigaction()`.
+#[derive(Clone, Copy)]
+pub struct SigAction {
+    /// What happens when the signal is delivered.
+    pub handler: SigHandler,
+    /// Signals blocked while the handler runs, in addition to the
+    /// signal itself (unless `SA_NODEFER` is given).
+    pub mask: SigSet,
+    /// Options modifying the delivery of the signal.
+    pub flags: SaFlags,
+}
+
+impl SigAction {
+
+    /// Creates a disposition with the given handler, an empty mask
+    /// and no flags.
+    pub fn new(handler: SigHandler) -> SigAction {
+        SigAction {
+            handler: handler,
+            mask: SigSet::empty(),
+            flags: SaFlags::empty(),
+        }
+    }
+
+}
+
+/// The `sigaction()` system call.
+///
+/// Changes the disposition of `sig` to `action`, returning the
+/// previous disposition.
+///
+/// This function is unsafe because a handler can interrupt the
+/// program at any point, so it must only call async-signal-safe
+/// functions and take care with any data it shares with the program.
+///
+/// Consult the man page (command `man 2 sigaction`) for further
+/// details.
+pub unsafe fn sigaction(
+    sig: Signal, action: &SigAction
+) -> SysResult<SigAction> {
+    let mut raw: ::libc::sigaction = mem::zeroed();
+    raw.sa_sigaction = match action.handler {
+        SigHandler::Default => ::libc::SIG_DFL,
+        SigHandler::Ignore => ::libc::SIG_IGN,
+        SigHandler::Handler(function) => function as usize,
+        SigHandler::InfoHandler(function) => function as usize,
+    };
+    raw.sa_mask = action.mask.0;
+    raw.sa_flags = action.flags.bits();
+    if let SigHandler::InfoHandler(_) = action.handler {
+        raw.sa_flags |= SA_SIGINFO;
+    }
+
+    let mut old: ::libc::sigaction = mem::zeroed();
+    let status = ::libc::sigaction(sig.0, &raw, &mut old);
+    try!(errno_check!(status, ()));
+
+    let handler = match old.sa_sigaction {
+        ::libc::SIG_DFL => SigHandler::Default,
+        ::libc::SIG_IGN => SigHandler::Ignore,
+        address if old.sa_flags & SA_SIGINFO != 0 => {
+            SigHandler::InfoHandler(mem::transmute(address))
+        },
+        address => SigHandler::Handler(mem::transmute(address)),
+    };
+    Ok(SigAction {
+        handler: handler,
+        mask: SigSet(old.sa_mask),
+        flags: SaFlags::from_bits_truncate(old.sa_flags),
+    })
+}
+
+/// The `kill()` system call.
+///
+/// Sends `sig` to the process `pid` if it is positive, to every
+/// process in the caller's process group if it is 0, to every process
+/// that the caller may signal if it is -1, or to every process in
+/// the process group `-pid` otherwise. With the null signal
+/// (`Signal::new(0)`), nothing is sent, but the existence of the
+/// target and the caller's permission to signal it are still checked.
+///
+/// Consult the man page (command `man 2 kill`) for further details.
+pub fn kill(pid: pid_t, sig: Signal) -> SysResult<()> {
+    let status = unsafe { ::libc::kill(pid, sig.0) };
+    errno_check!(status, ())
+}
+
+/// The `sigqueue()` library function.
+///
+/// Sends `sig` to the process `pid`, along with `value`, which the
+/// receiver's `SigHandler::InfoHandler` can retrieve. Unlike standard
+/// signals, multiple instances of a realtime signal are queued.
+/// Fails with `EAGAIN` if the receiver's queue is full.
+///
+/// Consult the man page (command `man 3 sigqueue`) for further
+/// details.
+pub fn sigqueue(pid: pid_t, sig: Signal, value: isize) -> SysResult<()> {
+    let status = unsafe { sigqueue_c(pid, sig.0, value as *mut c_void) };
+    errno_check!(status, ())
+}
+
+/// How `pthread_sigmask()` changes the signal mask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigmaskHow {
+    /// `SIG_BLOCK`: add the given signals to the mask.
+    Block = 0,
+    /// `SIG_UNBLOCK`: remove the given signals from the mask.
+    Unblock = 1,
+    /// `SIG_SETMASK`: replace the mask with the given signals.
+    SetMask = 2,
+}
+
+/// The `pthread_sigmask()` library function.
+///
+/// Changes the signal mask of the calling thread, as specified by
+/// `how` and `set`, returning the previous mask. New threads inherit
+/// the mask of the thread that creates them.
+///
+/// Consult the man page (command `man 3 pthread_sigmask`) for further
+/// details.
+pub fn pthread_sigmask(how: SigmaskHow, set: &SigSet) -> SysResult<SigSet> {
+    let mut old: sigset_t = unsafe { mem::zeroed() };
+    let status = unsafe {
+        ::libc::pthread_sigmask(how as c_int, &set.0, &mut old)
+    };
+    errnum_check!(status, SigSet(old))
+}
+
+/// The `sigpending()` system call.
+///
+/// Returns the set of signals that are pending for the calling
+/// thread or its process, having been raised while blocked.
+///
+/// Consult the man page (command `man 2 sigpending`) for further
+/// details.
+pub fn sigpending() -> SysResult<SigSet> {
+    let mut set: sigset_t = unsafe { mem::zeroed() };
+    let status = unsafe { ::libc::sigpending(&mut set) };
+    errno_check!(status, SigSet(set))
+}
+
+/// The `pause()` system call.
+///
+/// Suspends the calling thread until a signal is delivered to a
+/// handler. Only returns once the handler has, so the result is always
+/// an error: `EINTR`.
+///
+/// Consult the man page (command `man 2 pause`) for further details.
+pub fn pause() -> Errno {
+    unsafe { ::libc::pause() };
+    Errno::last()
+}
+
+/// The `sigsuspend()` system call.
+///
+/// Replaces the signal mask of the calling thread with `mask`, and
+/// suspends it until a signal is delivered to a handler, then restores
+/// the previous mask. Unblocking a signal and waiting for it happen
+/// atomically, so a signal that becomes pending in between can't be
+/// missed. Only returns once the handler has, so the result is always
+/// an error: `EINTR`.
+///
+/// Consult the man page (command `man 2 sigsuspend`) for further
+/// details.
+pub fn sigsuspend(mask: &SigSet) -> Errno {
+    unsafe { ::libc::sigsuspend(&mask.0) };
+    Errno::last()
+}
+
+/// An alternate signal stack, as set by `sigaltstack()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AltStack {
+    /// The lowest address of the stack.
+    pub addr: usize,
+    /// The size of the stack, in bytes.
+    pub size: usize,
+    /// Whether the thread is currently running on the stack, in a
+    /// handler (`SS_ONSTACK`).
+    pub on_stack: bool,
+    /// Whether the stack is disabled, so that every handler runs on
+    /// the normal stack (`SS_DISABLE`).
+    pub disabled: bool,
+}
+
+/// The `sigaltstack()` system call.
+///
+/// Establishes `stack` as the alternate signal stack of the calling
+/// thread, where handlers established with `SA_ONSTACK` run; this
+/// lets a handler run even when the normal stack has overflowed.
+/// Returns the previous alternate stack. Fails with `ENOMEM` if
+/// `stack` is smaller than `MINSIGSTKSZ`.
+///
+/// The stack must stay valid for as long as a handler may run on it,
+/// so it is leaked.
+///
+/// Consult the man page (command `man 2 sigaltstack`) for further
+/// details.
+pub fn sigaltstack(mut stack: Vec<u8>) -> SysResult<AltStack> {
+    let raw = RawStack {
+        sp: stack.as_mut_ptr() as *mut c_void,
+        flags: 0,
+        size: stack.len(),
+    };
+    let mut old = RawStack { sp: ptr::null_mut(), flags: 0, size: 0 };
+    let status = unsafe { sigaltstack_c(&raw, &mut old) };
+    try!(errno_check!(status, ()));
+    mem::forget(stack);
+    Ok(old.to_alt_stack())
+}
+
+/// Returns the alternate signal stack of the calling thread, using the
+/// `sigaltstack()` system call without changing it.
+pub fn current_altstack() -> SysResult<AltStack> {
+    let mut old = RawStack { sp: ptr::null_mut(), flags: 0, size: 0 };
+    let status = unsafe { sigaltstack_c(ptr::null(), &mut old) };
+    errno_check!(status, old.to_alt_stack())
+}
+
+/// The C `stack_t`.
+#[repr(C)]
+struct RawStack {
+    sp: *mut c_void,
+    flags: c_int,
+    size: usize,
+}
+
+impl RawStack {
+
+    /// Helper method; converts to the public representation.
+    fn to_alt_stack(&self) -> AltStack {
+        AltStack {
+            addr: self.sp as usize,
+            size: self.size,
+            on_stack: self.flags & SS_ONSTACK != 0,
+            disabled: self.flags & SS_DISABLE != 0,
+        }
+    }
+
+}
+
+/// The `sigwait()` library function.
+///
+/// Waits until one of the signals in `set` is pending, then accepts
+/// it and returns it. The signals should be blocked in all threads,
+/// so that none of them is delivered to a handler instead.
+///
+/// Consult the man page (command `man 3 sigwait`) for further
+/// details.
+pub fn sigwait(set: &SigSet) -> SysResult<Signal> {
+    let mut sig: c_int = 0;
+    let status = unsafe { ::libc::sigwait(&set.0, &mut sig) };
+    errnum_check!(status, Signal(sig))
+}
+
+/// A file descriptor for accepting signals, created with
+/// `signalfd()`.
+///
+/// The signals must be blocked in all threads, so that they stay
+/// pending until read; each read accepts one of them. As with
+/// `FileDescriptor`, does not implement `Copy` so that
+/// `SignalFd::close()` can take ownership.
+#[derive(Debug)]
+pub struct SignalFd(FileDescriptor);
+
+/// A signal read from a `SignalFd` (`struct signalfd_siginfo`).
+///
+/// Only the fields common to the signals sent by processes, and the
+/// status of a child for `SIGCHLD`, are provided.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignalFdInfo {
+    /// The signal number (`ssi_signo`).
+    pub signo: Signal,
+    /// Where the signal came from (`ssi_code`), such as `SI_USER` or
+    /// `SI_QUEUE`.
+    pub code: i32,
+    /// The process ID of the sender (`ssi_pid`).
+    pub pid: pid_t,
+    /// The real user ID of the sender (`ssi_uid`).
+    pub uid: uid_t,
+    /// The exit status or signal of the child, for `SIGCHLD`
+    /// (`ssi_status`).
+    pub status: i32,
+    /// The data accompanying a `sigqueue()` signal, as an integer
+    /// (`ssi_int`).
+    pub int: i32,
+    /// The same data, as a pointer-sized value (`ssi_ptr`).
+    pub ptr: u64,
+}
+
+impl SignalFd {
+
+    /// The `signalfd()` system call.
+    ///
+    /// Creates a file descriptor from which the signals in `mask` can
+    /// be read.
+    ///
+    /// Consult the man page (command `man 2 signalfd`) for further
+    /// details.
+    pub fn new(mask: &SigSet, flags: SfdFlags) -> SysResult<SignalFd> {
+        let fd = unsafe { signalfd_c(-1, &mask.0, flags.bits()) };
+        errno_check!(fd, SignalFd(FileDescriptor::from_raw(fd)))
+    }
+
+    /// Returns the file descriptor, for use with `select()`, `poll()`
+    /// and similar.
+    pub fn as_fd(&self) -> &FileDescriptor { &self.0 }
+
+    /// The `signalfd()` system call, for an existing file descriptor;
+    /// replaces the set of signals that can be read.
+    ///
+    /// Consult the man page (command `man 2 signalfd`) for further
+    /// details.
+    pub fn set_mask(&self, mask: &SigSet) -> SysResult<()> {
+        let fd = unsafe { signalfd_c(self.0.as_raw(), &mask.0, 0) };
+        errno_check!(fd, ())
+    }
+
+    /// Accepts one of the pending signals, blocking until there is one
+    /// unless `SFD_NONBLOCK` was given, in which case it fails with
+    /// `EAGAIN`.
+    pub fn read_signal(&self) -> SysResult<SignalFdInfo> {
+        let mut buf = [0u8; SIGNALFD_INFO_SIZE];
+        try!(self.0.read(&mut buf));
+
+        // The buffer has no particular alignment, so copy the fields
+        // out rather than referring to them in place
+        let mut raw: RawSignalFdInfo = unsafe { mem::zeroed() };
+        unsafe {
+            ptr::copy_nonoverlapping(
+                buf.as_ptr(), &mut raw as *mut _ as *mut u8,
+                SIGNALFD_INFO_SIZE
+            )
+        };
+        Ok(SignalFdInfo {
+            signo: Signal(raw.signo as c_int),
+            code: raw.code,
+            pid: raw.pid as pid_t,
+            uid: raw.uid,
+            status: raw.status,
+            int: raw.int,
+            ptr: raw.ptr,
+        })
+    }
+
+    /// The `close()` system call.
+    pub fn close(self) -> SysResult<()> {
+        self.0.close()
+    }
+
+}
+
+/// The C `struct signalfd_siginfo`, without the fields specific to
+/// signals raised by the kernel.
+#[repr(C)]
+struct RawSignalFdInfo {
+    signo: u32,
+    errno: i32,
+    code: i32,
+    pid: u32,
+    uid: u32,
+    fd: i32,
+    tid: u32,
+    band: u32,
+    overrun: u32,
+    trapno: u32,
+    status: i32,
+    int: i32,
+    ptr: u64,
+    rest: [u8; 72],
+}
+
+bitflags! {
+    #[doc = "Options for `SignalFd::new()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 signalfd` for details on each flag."]
+    #[doc = ""]
+    #[doc = "Taken from C header files on an x86-64 Linux system; not"]
+    #[doc = "intended to be portable!"]
+    flags SfdFlags: c_int {
+        #[doc = "don't block when reading with no signal pending"]
+        const SFD_NONBLOCK = 0o0004000,
+        #[doc = "close the file descriptor on a successful `execve()`"]
+        const SFD_CLOEXEC  = 0o2000000,
+    }
+}
+
+/// Helper function; preserves `errno` across a call to `body`, as
+/// signal handlers must.
+///
+/// Intended for the other modules of this crate.
+pub fn preserving_errno<F: FnOnce()>(body: F) {
+    let saved = Errno::last();
+    body();
+    Errno::set_last(saved);
+}
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const SA_SIGINFO: c_int = 0x00000004;
+const SS_ONSTACK: c_int = 1;
+const SS_DISABLE: c_int = 2;
+
+const SIGNALFD_INFO_SIZE: usize = 128;
+
+/// The minimum size of an alternate signal stack.
+pub const MINSIGSTKSZ: usize = 2048;
+
+/// The usual size of an alternate signal stack.
+pub const SIGSTKSZ: usize = 8192;
+
+/// `si_code` of a signal sent by `kill()` or `raise()`.
+pub const SI_USER: i32 = 0;
+
+/// `si_code` of a signal sent by `sigqueue()`.
+pub const SI_QUEUE: i32 = -1;
+
+extern {
+    fn __libc_current_sigrtmin() -> c_int;
+
+    fn __libc_current_sigrtmax() -> c_int;
+
+    // The `union sigval` argument is passed as a pointer would be
+    #[link_name = "sigqueue"]
+    fn sigqueue_c(pid: pid_t, sig: c_int, value: *mut c_void) -> c_int;
+
+    #[link_name = "signalfd"]
+    fn signalfd_c(fd: c_int, mask: *const sigset_t, flags: c_int) -> c_int;
+
+    #[link_name = "sigaltstack"]
+    fn sigaltstack_c(ss: *const RawStack, old_ss: *mut RawStack) -> c_int;
+}
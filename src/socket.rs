@@ -0,0 +1,989 @@
+
+//! Provides sockets and socket addresses.
+
+use std::fmt;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+use std::ptr;
+use std::slice;
+use libc::{c_char, c_int, c_void, size_t, sa_family_t, sockaddr};
+use libc::{sockaddr_in, sockaddr_in6, sockaddr_storage, sockaddr_un};
+use libc::{cmsghdr, iovec, msghdr, socklen_t, ucred};
+use libc::{gid_t, pid_t, uid_t};
+use libc::{AF_INET, AF_INET6, AF_UNIX, EAFNOSUPPORT, EINVAL, ENAMETOOLONG};
+use err::Errno;
+use fd::{FileDescriptor, SysResult};
+use inet::{getnameinfo, NI_NUMERICSERV};
+
+/// A socket; a file descriptor that is an endpoint for
+/// communication.
+///
+/// As with `FileDescriptor`, does not implement `Copy` so that
+/// `Socket::close()` can take ownership.
+pub struct Socket(FileDescriptor);
+
+/// Communication domains (address families) for sockets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Domain {
+    /// `AF_UNIX`: communication between processes on the same host.
+    Unix = 1,
+    /// `AF_INET`: communication over IPv4.
+    Inet = 2,
+    /// `AF_INET6`: communication over IPv6.
+    Inet6 = 10,
+}
+
+/// Types of socket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SockType {
+    /// `SOCK_STREAM`: reliable, bidirectional, connection-oriented
+    /// byte streams.
+    Stream = 1,
+    /// `SOCK_DGRAM`: unreliable, connectionless messages with
+    /// preserved boundaries.
+    Datagram = 2,
+    /// `SOCK_SEQPACKET`: like `Stream`, but with preserved message
+    /// boundaries.
+    SeqPacket = 5,
+}
+
+/// Directions of communication to close with `Socket::shutdown()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum How {
+    /// `SHUT_RD`: no further data can be received; subsequent reads
+    /// return end-of-file.
+    Read = 0,
+    /// `SHUT_WR`: no further data can be sent; the peer sees
+    /// end-of-file once it has read any data already sent.
+    Write = 1,
+    /// `SHUT_RDWR`: both of the above.
+    Both = 2,
+}
+
+bitflags! {
+    #[doc = "Options for `Socket::new()` and `Socket::accept4()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 socket` for details on each flag."]
+    flags SockFlags: c_int {
+        #[doc = "set the `O_NONBLOCK` flag on the new socket"]
+        const SOCK_NONBLOCK = 0o0004000,
+        #[doc = "set the close-on-exec flag on the new socket"]
+        const SOCK_CLOEXEC  = 0o2000000,
+    }
+}
+
+impl Socket {
+
+    /// The `socket()` system call.
+    ///
+    /// Creates an unbound, unconnected socket in the given domain,
+    /// using the default protocol for its type.
+    ///
+    /// Consult the man page (command `man 2 socket`) for further
+    /// details.
+    pub fn new(
+        domain: Domain, sock_type: SockType, flags: SockFlags
+    ) -> SysResult<Socket> {
+        let raw_type = sock_type as c_int | flags.bits();
+        let sfd = unsafe { ::libc::socket(domain as c_int, raw_type, 0) };
+        errno_check!(sfd, Socket(FileDescriptor::from_raw(sfd)))
+    }
+
+    /// Wraps an existing file descriptor that refers to a socket.
+    pub fn from_fd(fd: FileDescriptor) -> Socket { Socket(fd) }
+
+    /// The file descriptor of this socket, for use with the
+    /// operations of `FileDescriptor`.
+    pub fn as_fd(&self) -> &FileDescriptor { &self.0 }
+
+    /// Consumes the socket, returning its file descriptor.
+    pub fn into_fd(self) -> FileDescriptor { self.0 }
+
+    /// The `bind()` system call.
+    ///
+    /// Binds the socket to the address `addr`.
+    ///
+    /// Consult the man page (command `man 2 bind`) for further
+    /// details.
+    pub fn bind(&self, addr: &SockAddr) -> SysResult<()> {
+        let (storage, len) = addr.to_raw();
+        let addr_ptr = &storage as *const _ as *const sockaddr;
+        let status = unsafe { ::libc::bind(self.raw(), addr_ptr, len) };
+        errno_check!(status, ())
+    }
+
+    /// The `listen()` system call.
+    ///
+    /// Marks a stream socket as passive, so that it accepts incoming
+    /// connections; `backlog` limits the number of pending
+    /// connections.
+    ///
+    /// Consult the man page (command `man 2 listen`) for further
+    /// details.
+    pub fn listen(&self, backlog: i32) -> SysResult<()> {
+        let status = unsafe { ::libc::listen(self.raw(), backlog as c_int) };
+        errno_check!(status, ())
+    }
+
+    /// The `accept()` system call.
+    ///
+    /// Waits for an incoming connection on a listening socket,
+    /// returning a new socket connected to the peer along with the
+    /// peer's address.
+    ///
+    /// Consult the man page (command `man 2 accept`) for further
+    /// details.
+    pub fn accept(&self) -> SysResult<(Socket, SockAddr)> {
+        self.accept4(SockFlags::empty())
+    }
+
+    /// The `accept4()` system call.
+    ///
+    /// Like `accept()`, but applies `flags` to the new socket.
+    ///
+    /// Consult the man page (command `man 2 accept4`) for further
+    /// details.
+    pub fn accept4(
+        &self, flags: SockFlags
+    ) -> SysResult<(Socket, SockAddr)> {
+        let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+        let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
+        let addr_ptr = &mut storage as *mut _ as *mut sockaddr;
+        let cfd = unsafe {
+            ::libc::accept4(self.raw(), addr_ptr, &mut len, flags.bits())
+        };
+        try!(errno_check!(cfd, ()));
+
+        let socket = Socket(FileDescriptor::from_raw(cfd));
+        match SockAddr::from_raw(&storage, len) {
+            Ok(addr) => Ok((socket, addr)),
+            Err(errno) => {
+                let _ = socket.close();
+                Err(errno)
+            },
+        }
+    }
+
+    /// The `connect()` system call.
+    ///
+    /// Connects a stream socket to the listening socket at `addr`, or
+    /// sets the default peer of a datagram socket.
+    ///
+    /// Consult the man page (command `man 2 connect`) for further
+    /// details.
+    pub fn connect(&self, addr: &SockAddr) -> SysResult<()> {
+        let (storage, len) = addr.to_raw();
+        let addr_ptr = &storage as *const _ as *const sockaddr;
+        let status = unsafe { ::libc::connect(self.raw(), addr_ptr, len) };
+        errno_check!(status, ())
+    }
+
+    /// Reads from the socket; see `FileDescriptor::read()`.
+    pub fn read(&self, buf: &mut [u8]) -> SysResult<usize> {
+        self.0.read(buf)
+    }
+
+    /// Writes to the socket; see `FileDescriptor::write()`.
+    pub fn write(&self, buf: &[u8]) -> SysResult<usize> {
+        self.0.write(buf)
+    }
+
+    /// The `sendmsg()` system call.
+    ///
+    /// Sends the data gathered from `bufs`, along with the ancillary
+    /// data in `control`, to `addr`, or the connected peer if `addr`
+    /// is `None`. Returns the number of bytes of data sent.
+    ///
+    /// Consult the man page (command `man 2 sendmsg`) for further
+    /// details.
+    pub fn sendmsg(
+        &self, bufs: &[&[u8]], control: &[ControlMessage],
+        addr: Option<&SockAddr>, flags: MsgFlags
+    ) -> SysResult<usize> {
+        let mut iov: Vec<iovec> = bufs.iter().map(|buf| iovec {
+            iov_base: buf.as_ptr() as *mut c_void,
+            iov_len: buf.len() as size_t,
+        }).collect();
+        let mut control_buf = encode_control(control);
+        let mut raw_addr = addr.map(|addr| addr.to_raw());
+
+        let mut msg: msghdr = unsafe { mem::zeroed() };
+        if let Some((ref mut storage, len)) = raw_addr {
+            msg.msg_name = storage as *mut _ as *mut c_void;
+            msg.msg_namelen = len;
+        }
+        msg.msg_iov = iov.as_mut_ptr();
+        msg.msg_iovlen = iov.len() as size_t;
+        if !control_buf.is_empty() {
+            msg.msg_control = control_buf.as_mut_ptr() as *mut c_void;
+            msg.msg_controllen = control_len(control) as size_t;
+        }
+
+        let count = unsafe {
+            ::libc::sendmsg(self.raw(), &msg, flags.bits())
+        };
+        errno_check!(count, count as usize)
+    }
+
+    /// The `recvmsg()` system call.
+    ///
+    /// Receives data into `bufs`, in order, along with up to
+    /// `control_space` bytes of ancillary data; use `rights_space()`
+    /// and `credentials_space()` to calculate the space needed for the
+    /// expected messages.
+    ///
+    /// Any file descriptors received are owned by the caller. Pass
+    /// `MSG_CMSG_CLOEXEC` to set their close-on-exec flag.
+    ///
+    /// Consult the man page (command `man 2 recvmsg`) for further
+    /// details.
+    pub fn recvmsg(
+        &self, bufs: &mut [&mut [u8]], control_space: usize, flags: MsgFlags
+    ) -> SysResult<ReceivedMessage> {
+        let mut iov: Vec<iovec> = bufs.iter_mut().map(|buf| iovec {
+            iov_base: buf.as_mut_ptr() as *mut c_void,
+            iov_len: buf.len() as size_t,
+        }).collect();
+        let mut control_buf = control_buffer(control_space);
+        let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+
+        let mut msg: msghdr = unsafe { mem::zeroed() };
+        msg.msg_name = &mut storage as *mut _ as *mut c_void;
+        msg.msg_namelen = mem::size_of::<sockaddr_storage>() as socklen_t;
+        msg.msg_iov = iov.as_mut_ptr();
+        msg.msg_iovlen = iov.len() as size_t;
+        if control_space > 0 {
+            msg.msg_control = control_buf.as_mut_ptr() as *mut c_void;
+            msg.msg_controllen = control_space as size_t;
+        }
+
+        let count = unsafe {
+            ::libc::recvmsg(self.raw(), &mut msg, flags.bits())
+        };
+        try!(errno_check!(count, ()));
+
+        // Take ownership of any descriptors before anything else can
+        // fail, so that they aren't leaked
+        let control = decode_control(&msg);
+
+        // Connected sockets don't report the peer address
+        let addr = if msg.msg_namelen == 0 {
+            None
+        } else {
+            match SockAddr::from_raw(&storage, msg.msg_namelen) {
+                Ok(addr) => Some(addr),
+                Err(errno) => {
+                    close_received(control);
+                    return Err(errno);
+                },
+            }
+        };
+
+        Ok(ReceivedMessage {
+            bytes: count as usize,
+            addr: addr,
+            control: control,
+            flags: MsgFlags::from_bits_truncate(msg.msg_flags),
+        })
+    }
+
+    /// The `shutdown()` system call.
+    ///
+    /// Closes one or both directions of communication on a connected
+    /// socket. Unlike `close()`, this affects all file descriptors
+    /// referring to the socket, and allows a _half-close_; e.g. a
+    /// client can signal the end of its requests while still reading
+    /// the responses.
+    ///
+    /// Consult the man page (command `man 2 shutdown`) for further
+    /// details.
+    pub fn shutdown(&self, how: How) -> SysResult<()> {
+        let status = unsafe { ::libc::shutdown(self.raw(), how as c_int) };
+        errno_check!(status, ())
+    }
+
+    /// Enables or disables the `SO_PASSCRED` option, which makes
+    /// `recvmsg()` on a UNIX domain socket report the credentials of
+    /// the sender as an `SCM_CREDENTIALS` message.
+    ///
+    /// Consult `man 7 unix` for further details.
+    pub fn set_pass_credentials(&self, enable: bool) -> SysResult<()> {
+        let optval: c_int = if enable { 1 } else { 0 };
+        let status = unsafe {
+            ::libc::setsockopt(
+                self.raw(), SOL_SOCKET, SO_PASSCRED,
+                &optval as *const _ as *const c_void,
+                mem::size_of::<c_int>() as socklen_t
+            )
+        };
+        errno_check!(status, ())
+    }
+
+    /// Returns the credentials of the peer of a connected UNIX domain
+    /// socket, using the `SO_PEERCRED` option.
+    ///
+    /// These are the credentials the peer had when it called
+    /// `connect()` or `listen()`, or `socketpair()` was called.
+    ///
+    /// Consult `man 7 unix` for further details.
+    pub fn peer_credentials(&self) -> SysResult<Credentials> {
+        let mut raw: ucred = unsafe { mem::zeroed() };
+        let mut len = mem::size_of::<ucred>() as socklen_t;
+        let status = unsafe {
+            ::libc::getsockopt(
+                self.raw(), SOL_SOCKET, SO_PEERCRED,
+                &mut raw as *mut _ as *mut c_void, &mut len
+            )
+        };
+        errno_check!(status, Credentials::from_raw(&raw))
+    }
+
+    /// Closes the socket; see `FileDescriptor::close()`.
+    pub fn close(self) -> SysResult<()> {
+        self.0.close()
+    }
+
+    /// Helper method; the raw file descriptor of the socket.
+    fn raw(&self) -> c_int { self.0.as_raw() }
+
+}
+
+/// The `socketpair()` system call.
+///
+/// Creates a pair of connected sockets of the given type. Only the
+/// UNIX domain is supported on Linux. The sockets are not bound to
+/// any address, so they are mainly useful for communication between
+/// a parent and child process.
+///
+/// Consult the man page (command `man 2 socketpair`) for further
+/// details.
+pub fn socketpair(
+    domain: Domain, sock_type: SockType
+) -> SysResult<(Socket, Socket)> {
+    let mut sv: [c_int; 2] = [-1, -1];
+    let status = unsafe {
+        ::libc::socketpair(domain as c_int, sock_type as c_int, 0, &mut sv[0])
+    };
+    errno_check!(status, (
+        Socket(FileDescriptor::from_raw(sv[0])),
+        Socket(FileDescriptor::from_raw(sv[1]))
+    ))
+}
+
+bitflags! {
+    #[doc = "Options for `Socket::sendmsg()` and `Socket::recvmsg()`,"]
+    #[doc = "and the flags reported in `ReceivedMessage`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 recvmsg` for details on each flag."]
+    flags MsgFlags: c_int {
+        #[doc = "send or receive out-of-band data"]
+        const MSG_OOB          = 0x00000001,
+        #[doc = "receive data without removing it from the queue"]
+        const MSG_PEEK         = 0x00000002,
+        #[doc = "send without using a gateway"]
+        const MSG_DONTROUTE    = 0x00000004,
+        #[doc = "(reported) some ancillary data was discarded"]
+        const MSG_CTRUNC       = 0x00000008,
+        #[doc = "(reported) some datagram data was discarded"]
+        const MSG_TRUNC        = 0x00000020,
+        #[doc = "perform a nonblocking operation"]
+        const MSG_DONTWAIT     = 0x00000040,
+        #[doc = "(reported) end of a record"]
+        const MSG_EOR          = 0x00000080,
+        #[doc = "block until the full request is satisfied"]
+        const MSG_WAITALL      = 0x00000100,
+        #[doc = "don't raise `SIGPIPE` on a broken stream"]
+        const MSG_NOSIGNAL     = 0x00004000,
+        #[doc = "set close-on-exec on descriptors from `SCM_RIGHTS`"]
+        const MSG_CMSG_CLOEXEC = 0x40000000,
+    }
+}
+
+/// Ancillary data to send with `Socket::sendmsg()`.
+pub enum ControlMessage<'a> {
+    /// `SCM_RIGHTS`: duplicates the given file descriptors into the
+    /// receiving process, as if by `dup()`. Only UNIX domain sockets
+    /// support this.
+    Rights(&'a [&'a FileDescriptor]),
+    /// `SCM_CREDENTIALS`: the credentials of the sending process.
+    /// Unprivileged processes can only send their own IDs; see
+    /// `man 7 unix`.
+    Credentials(Credentials),
+}
+
+/// Ancillary data received by `Socket::recvmsg()`.
+#[derive(Debug)]
+pub enum ReceivedControl {
+    /// `SCM_RIGHTS`: file descriptors sent by the peer, now open in
+    /// this process.
+    Rights(Vec<FileDescriptor>),
+    /// `SCM_CREDENTIALS`: the credentials of the sender, verified by
+    /// the kernel. Only received if `SO_PASSCRED` is enabled.
+    Credentials(Credentials),
+    /// A message of a kind not interpreted by this module, given as
+    /// its level, type and raw data.
+    Other(i32, i32, Vec<u8>),
+}
+
+/// The result of `Socket::recvmsg()`.
+#[derive(Debug)]
+pub struct ReceivedMessage {
+    /// The number of bytes of data received.
+    pub bytes: usize,
+    /// The address of the sender, if the socket isn't connected.
+    pub addr: Option<SockAddr>,
+    /// The ancillary data received, in order.
+    pub control: Vec<ReceivedControl>,
+    /// Flags describing the received message, such as `MSG_CTRUNC`.
+    pub flags: MsgFlags,
+}
+
+/// The credentials of a process, in the form passed over UNIX domain
+/// sockets (`struct ucred`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Credentials {
+    /// Process ID.
+    pub pid: pid_t,
+    /// User ID.
+    pub uid: uid_t,
+    /// Group ID.
+    pub gid: gid_t,
+}
+
+impl Credentials {
+
+    /// The process ID, real user ID and real group ID of the calling
+    /// process.
+    pub fn current() -> Credentials {
+        unsafe {
+            Credentials {
+                pid: ::libc::getpid(),
+                uid: ::libc::getuid(),
+                gid: ::libc::getgid(),
+            }
+        }
+    }
+
+    /// Helper function; converts from the C representation.
+    fn from_raw(raw: &ucred) -> Credentials {
+        Credentials { pid: raw.pid, uid: raw.uid, gid: raw.gid }
+    }
+
+    /// Helper method; converts into the C representation.
+    fn to_raw(&self) -> ucred {
+        ucred { pid: self.pid, uid: self.uid, gid: self.gid }
+    }
+
+}
+
+/// Returns the number of bytes of ancillary data needed to receive
+/// an `SCM_RIGHTS` message carrying `num_fds` file descriptors.
+///
+/// The values for several messages can be added together.
+pub fn rights_space(num_fds: usize) -> usize {
+    cmsg_space(num_fds * mem::size_of::<c_int>())
+}
+
+/// Returns the number of bytes of ancillary data needed to receive
+/// an `SCM_CREDENTIALS` message.
+///
+/// The values for several messages can be added together.
+pub fn credentials_space() -> usize {
+    cmsg_space(mem::size_of::<ucred>())
+}
+
+/// Creates a UNIX domain stream socket bound to `path` and listening
+/// for connections.
+///
+/// The socket file must not already exist; servers conventionally
+/// remove it before calling this function.
+pub fn unix_stream_listen(path: &str, backlog: i32) -> SysResult<Socket> {
+    let addr = SockAddr::Unix(try!(UnixAddr::new(path)));
+    let socket = try!(Socket::new(
+        Domain::Unix, SockType::Stream, SockFlags::empty()
+    ));
+
+    match socket.bind(&addr).and_then(|_| socket.listen(backlog)) {
+        Ok(()) => Ok(socket),
+        Err(errno) => {
+            // The original error is more informative than any close
+            // error
+            let _ = socket.close();
+            Err(errno)
+        },
+    }
+}
+
+/// Creates a UNIX domain stream socket connected to the listening
+/// socket bound to `path`.
+pub fn unix_stream_connect(path: &str) -> SysResult<Socket> {
+    let addr = SockAddr::Unix(try!(UnixAddr::new(path)));
+    let socket = try!(Socket::new(
+        Domain::Unix, SockType::Stream, SockFlags::empty()
+    ));
+
+    match socket.connect(&addr) {
+        Ok(()) => Ok(socket),
+        Err(errno) => {
+            let _ = socket.close();
+            Err(errno)
+        },
+    }
+}
+
+/// A socket address in one of the supported domains.
+#[derive(Clone, Debug)]
+pub enum SockAddr {
+    /// An address in the UNIX domain.
+    Unix(UnixAddr),
+    /// An IPv4 address and port.
+    Inet(SocketAddrV4),
+    /// An IPv6 address and port.
+    Inet6(SocketAddrV6),
+}
+
+impl SockAddr {
+
+    /// The domain of this address.
+    pub fn domain(&self) -> Domain {
+        match *self {
+            SockAddr::Unix(_) => Domain::Unix,
+            SockAddr::Inet(_) => Domain::Inet,
+            SockAddr::Inet6(_) => Domain::Inet6,
+        }
+    }
+
+    /// Converts this address into the C representation expected by
+    /// system calls, along with its length.
+    ///
+    /// Intended for the other modules of this crate.
+    pub fn to_raw(&self) -> (sockaddr_storage, socklen_t) {
+        let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+        let len = match *self {
+            SockAddr::Unix(ref unix_addr) => {
+                unsafe {
+                    ptr::write(
+                        &mut storage as *mut _ as *mut sockaddr_un,
+                        unix_addr.addr
+                    );
+                }
+                unix_addr.len
+            },
+            SockAddr::Inet(ref inet_addr) => {
+                let mut raw: sockaddr_in = unsafe { mem::zeroed() };
+                raw.sin_family = AF_INET as sa_family_t;
+                raw.sin_port = inet_addr.port().to_be();
+                let octets = inet_addr.ip().octets();
+                raw.sin_addr.s_addr = unsafe {
+                    mem::transmute::<[u8; 4], u32>(octets)
+                };
+                unsafe {
+                    ptr::write(
+                        &mut storage as *mut _ as *mut sockaddr_in, raw
+                    );
+                }
+                mem::size_of::<sockaddr_in>() as socklen_t
+            },
+            SockAddr::Inet6(ref inet6_addr) => {
+                let mut raw: sockaddr_in6 = unsafe { mem::zeroed() };
+                raw.sin6_family = AF_INET6 as sa_family_t;
+                raw.sin6_port = inet6_addr.port().to_be();
+                raw.sin6_flowinfo = inet6_addr.flowinfo().to_be();
+                raw.sin6_scope_id = inet6_addr.scope_id();
+                let segments = inet6_addr.ip().segments();
+                for (i, segment) in segments.iter().enumerate() {
+                    raw.sin6_addr.s6_addr[2 * i] = (segment >> 8) as u8;
+                    raw.sin6_addr.s6_addr[2 * i + 1] = *segment as u8;
+                }
+                unsafe {
+                    ptr::write(
+                        &mut storage as *mut _ as *mut sockaddr_in6, raw
+                    );
+                }
+                mem::size_of::<sockaddr_in6>() as socklen_t
+            },
+        };
+        (storage, len)
+    }
+
+    /// Converts the C representation of an address, as filled in by a
+    /// system call, into a `SockAddr`.
+    ///
+    /// Fails with `EAFNOSUPPORT` if the address is in an unsupported
+    /// domain, or `EINVAL` if its length is inconsistent.
+    ///
+    /// Intended for the other modules of this crate.
+    pub fn from_raw(
+        storage: &sockaddr_storage, len: socklen_t
+    ) -> SysResult<SockAddr> {
+        let family_len = mem::size_of::<sa_family_t>() as socklen_t;
+        if len < family_len {
+            return Err(Errno::new(EINVAL));
+        }
+
+        match storage.ss_family as c_int {
+            AF_UNIX => {
+                let raw = unsafe {
+                    ptr::read(storage as *const _ as *const sockaddr_un)
+                };
+                if len as usize > mem::size_of::<sockaddr_un>() {
+                    return Err(Errno::new(EINVAL));
+                }
+                Ok(SockAddr::Unix(UnixAddr { addr: raw, len: len }))
+            },
+            AF_INET => {
+                if (len as usize) < mem::size_of::<sockaddr_in>() {
+                    return Err(Errno::new(EINVAL));
+                }
+                let raw = unsafe {
+                    ptr::read(storage as *const _ as *const sockaddr_in)
+                };
+                // The address is in network byte order, which is also
+                // the order of `octets`
+                let octets: [u8; 4] = unsafe {
+                    mem::transmute(raw.sin_addr.s_addr)
+                };
+                let ip = Ipv4Addr::new(
+                    octets[0], octets[1], octets[2], octets[3]
+                );
+                let addr = SocketAddrV4::new(ip, u16::from_be(raw.sin_port));
+                Ok(SockAddr::Inet(addr))
+            },
+            AF_INET6 => {
+                if (len as usize) < mem::size_of::<sockaddr_in6>() {
+                    return Err(Errno::new(EINVAL));
+                }
+                let raw = unsafe {
+                    ptr::read(storage as *const _ as *const sockaddr_in6)
+                };
+                let bytes = raw.sin6_addr.s6_addr;
+                let mut segments = [0u16; 8];
+                for (i, segment) in segments.iter_mut().enumerate() {
+                    *segment = (bytes[2 * i] as u16) << 8 |
+                        bytes[2 * i + 1] as u16;
+                }
+                let ip = Ipv6Addr::new(
+                    segments[0], segments[1], segments[2], segments[3],
+                    segments[4], segments[5], segments[6], segments[7]
+                );
+                let addr = SocketAddrV6::new(
+                    ip, u16::from_be(raw.sin6_port),
+                    u32::from_be(raw.sin6_flowinfo), raw.sin6_scope_id
+                );
+                Ok(SockAddr::Inet6(addr))
+            },
+            _ => Err(Errno::new(EAFNOSUPPORT)),
+        }
+    }
+
+}
+
+/// Formats the address for display, in the style of
+/// `inetAddressStr()` from _The Linux Programming Interface_.
+///
+/// Internet domain addresses are shown as `(host, port)`, where the
+/// host is resolved to a name if possible; note that this can involve
+/// a DNS lookup. UNIX domain addresses are shown as their pathname,
+/// `@name` for the abstract namespace, or `(unnamed)`.
+impl fmt::Display for SockAddr {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SockAddr::Unix(ref unix_addr) => {
+                if let Some(path) = unix_addr.path() {
+                    write!(f, "{}", path)
+                } else if let Some(name) = unix_addr.abstract_name() {
+                    write!(f, "@{}", String::from_utf8_lossy(name))
+                } else {
+                    write!(f, "(unnamed)")
+                }
+            },
+            _ => match getnameinfo(self, NI_NUMERICSERV) {
+                Ok((host, service)) => write!(f, "({}, {})", host, service),
+                Err(_) => write!(f, "(?UNKNOWN?)"),
+            },
+        }
+    }
+
+}
+
+/// An address in the UNIX domain.
+///
+/// Such addresses are normally pathnames in the file system. Linux
+/// also supports the _abstract_ namespace, whose names begin with a
+/// null byte and don't appear in the file system. Sockets that
+/// haven't been bound have _unnamed_ addresses.
+#[derive(Clone, Copy)]
+pub struct UnixAddr {
+    /// The raw address.
+    addr: sockaddr_un,
+
+    /// The number of meaningful bytes in `addr`.
+    len: socklen_t,
+}
+
+impl UnixAddr {
+
+    /// Creates an address for the pathname `path`.
+    ///
+    /// Fails with `ENAMETOOLONG` if `path` doesn't fit into
+    /// `sun_path` together with a terminating null byte, and `EINVAL`
+    /// if it is empty or contains null bytes.
+    pub fn new(path: &str) -> SysResult<UnixAddr> {
+        if path.is_empty() || path.as_bytes().contains(&0) {
+            return Err(Errno::new(EINVAL));
+        }
+        UnixAddr::from_name_bytes(path.as_bytes(), true)
+    }
+
+    /// Creates an address for `name` in the Linux-specific abstract
+    /// namespace.
+    ///
+    /// Fails with `ENAMETOOLONG` if `name` doesn't fit into
+    /// `sun_path` after the leading null byte.
+    pub fn new_abstract(name: &[u8]) -> SysResult<UnixAddr> {
+        let mut bytes = vec![0u8];
+        bytes.extend(name);
+        UnixAddr::from_name_bytes(&bytes, false)
+    }
+
+    /// The pathname of this address, or `None` if it's unnamed or in
+    /// the abstract namespace.
+    pub fn path(&self) -> Option<String> {
+        let bytes = self.name_bytes();
+        if bytes.is_empty() || bytes[0] == 0 {
+            return None;
+        }
+
+        // Pathnames obtained from the kernel may or may not be
+        // null-terminated
+        let nul_pos = bytes.iter().position(|&b| b == 0);
+        let end = nul_pos.unwrap_or(bytes.len());
+        Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+
+    /// The name of this address in the abstract namespace (without
+    /// the leading null byte), or `None` if it isn't in that
+    /// namespace.
+    pub fn abstract_name(&self) -> Option<&[u8]> {
+        let bytes = self.name_bytes();
+        if !bytes.is_empty() && bytes[0] == 0 {
+            Some(&bytes[1..])
+        } else {
+            None
+        }
+    }
+
+    /// Whether this address is unnamed, i.e. belongs to a socket that
+    /// wasn't bound.
+    pub fn is_unnamed(&self) -> bool {
+        self.name_bytes().is_empty()
+    }
+
+    /// Helper function; builds an address from the bytes of
+    /// `sun_path`, optionally followed by a terminating null byte.
+    fn from_name_bytes(
+        bytes: &[u8], terminate: bool
+    ) -> SysResult<UnixAddr> {
+        let mut addr: sockaddr_un = unsafe { mem::zeroed() };
+        let capacity = addr.sun_path.len();
+        let name_len = bytes.len() + if terminate { 1 } else { 0 };
+        if name_len > capacity {
+            return Err(Errno::new(ENAMETOOLONG));
+        }
+
+        addr.sun_family = AF_UNIX as sa_family_t;
+        for (dest, &src) in addr.sun_path.iter_mut().zip(bytes.iter()) {
+            *dest = src as c_char;
+        }
+
+        let len = UnixAddr::path_offset() + name_len;
+        Ok(UnixAddr { addr: addr, len: len as socklen_t })
+    }
+
+    /// Helper method; the meaningful bytes of `sun_path`.
+    fn name_bytes(&self) -> &[u8] {
+        let name_len = (self.len as usize).saturating_sub(
+            UnixAddr::path_offset()
+        );
+        let path_ptr = self.addr.sun_path.as_ptr() as *const u8;
+        unsafe { slice::from_raw_parts(path_ptr, name_len) }
+    }
+
+    /// Helper function; the offset of `sun_path` within `sockaddr_un`.
+    fn path_offset() -> usize {
+        mem::size_of::<sa_family_t>()
+    }
+
+}
+
+impl fmt::Debug for UnixAddr {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = self.path() {
+            write!(f, "UnixAddr({:?})", path)
+        } else if let Some(name) = self.abstract_name() {
+            write!(f, "UnixAddr(@{:?})", String::from_utf8_lossy(name))
+        } else {
+            write!(f, "UnixAddr(unnamed)")
+        }
+    }
+
+}
+
+/// `SOL_SOCKET`: the level of socket-independent options and control
+/// messages.
+const SOL_SOCKET: c_int = 1;
+
+/// `SCM_RIGHTS`: the type of control messages carrying descriptors.
+const SCM_RIGHTS: c_int = 1;
+
+/// `SCM_CREDENTIALS`: the type of control messages carrying process
+/// credentials.
+const SCM_CREDENTIALS: c_int = 2;
+
+/// `SO_PASSCRED`: option to receive `SCM_CREDENTIALS` messages.
+const SO_PASSCRED: c_int = 16;
+
+/// `SO_PEERCRED`: option to query the credentials of the peer.
+const SO_PEERCRED: c_int = 17;
+
+/// Helper function; the C macro `CMSG_ALIGN()`.
+fn cmsg_align(len: usize) -> usize {
+    let align = mem::size_of::<size_t>();
+    (len + align - 1) & !(align - 1)
+}
+
+/// Helper function; the C macro `CMSG_SPACE()`.
+fn cmsg_space(data_len: usize) -> usize {
+    cmsg_align(mem::size_of::<cmsghdr>()) + cmsg_align(data_len)
+}
+
+/// Helper function; the C macro `CMSG_LEN()`.
+fn cmsg_len(data_len: usize) -> usize {
+    cmsg_align(mem::size_of::<cmsghdr>()) + data_len
+}
+
+/// Helper function; allocates a zeroed buffer of at least `len`
+/// bytes, suitably aligned for `cmsghdr`.
+fn control_buffer(len: usize) -> Vec<size_t> {
+    let word_len = mem::size_of::<size_t>();
+    vec![0; (len + word_len - 1) / word_len]
+}
+
+/// Helper function; the level, type and data of a control message.
+fn control_parts(message: &ControlMessage) -> (c_int, c_int, Vec<u8>) {
+    match *message {
+        ControlMessage::Rights(fds) => {
+            let mut data = Vec::new();
+            for fd in fds {
+                let raw: [u8; 4] = unsafe { mem::transmute(fd.as_raw()) };
+                data.extend(&raw);
+            }
+            (SOL_SOCKET, SCM_RIGHTS, data)
+        },
+        ControlMessage::Credentials(ref credentials) => {
+            let raw = credentials.to_raw();
+            let data = unsafe {
+                let raw_ptr = &raw as *const _ as *const u8;
+                slice::from_raw_parts(raw_ptr, mem::size_of::<ucred>())
+            };
+            (SOL_SOCKET, SCM_CREDENTIALS, data.to_vec())
+        },
+    }
+}
+
+/// Helper function; the value of `msg_controllen` for `messages`.
+fn control_len(messages: &[ControlMessage]) -> usize {
+    messages.iter().map(|message| {
+        let (_, _, data) = control_parts(message);
+        cmsg_space(data.len())
+    }).fold(0, |total, space| total + space)
+}
+
+/// Helper function; lays out `messages` as the control buffer of a
+/// `msghdr`.
+fn encode_control(messages: &[ControlMessage]) -> Vec<size_t> {
+    let mut buf = control_buffer(control_len(messages));
+    let mut offset = 0;
+    for message in messages {
+        let (level, kind, data) = control_parts(message);
+        unsafe {
+            let base = (buf.as_mut_ptr() as *mut u8).offset(offset as isize);
+            let header = base as *mut cmsghdr;
+            (*header).cmsg_len = cmsg_len(data.len()) as size_t;
+            (*header).cmsg_level = level;
+            (*header).cmsg_type = kind;
+            let data_ptr = base.offset(cmsg_len(0) as isize);
+            ptr::copy_nonoverlapping(data.as_ptr(), data_ptr, data.len());
+        }
+        offset += cmsg_space(data.len());
+    }
+    buf
+}
+
+/// Helper function; parses the control buffer of a `msghdr` filled in
+/// by `recvmsg()`, taking ownership of any descriptors.
+fn decode_control(msg: &msghdr) -> Vec<ReceivedControl> {
+    let mut messages = Vec::new();
+    let base = msg.msg_control as *const u8;
+    let total_len = msg.msg_controllen as usize;
+    let header_len = mem::size_of::<cmsghdr>();
+
+    let mut offset = 0;
+    while !base.is_null() && offset + header_len <= total_len {
+        let header = unsafe {
+            ptr::read(base.offset(offset as isize) as *const cmsghdr)
+        };
+        let msg_len = header.cmsg_len as usize;
+        if msg_len < cmsg_len(0) || offset + msg_len > total_len {
+            break;
+        }
+
+        let data = unsafe {
+            let data_ptr = base.offset((offset + cmsg_len(0)) as isize);
+            slice::from_raw_parts(data_ptr, msg_len - cmsg_len(0))
+        };
+        let message = match (header.cmsg_level, header.cmsg_type) {
+            (SOL_SOCKET, SCM_RIGHTS) => {
+                let fds = data.chunks(mem::size_of::<c_int>())
+                    .filter(|chunk| chunk.len() == mem::size_of::<c_int>())
+                    .map(|chunk| {
+                        let raw = [chunk[0], chunk[1], chunk[2], chunk[3]];
+                        let fd: c_int = unsafe { mem::transmute(raw) };
+                        FileDescriptor::from_raw(fd)
+                    })
+                    .collect();
+                ReceivedControl::Rights(fds)
+            },
+            (SOL_SOCKET, SCM_CREDENTIALS)
+                if data.len() >= mem::size_of::<ucred>() =>
+            {
+                // The control buffer is aligned for `cmsghdr`, and so
+                // the data is suitably aligned too
+                let raw = unsafe { ptr::read(data.as_ptr() as *const ucred) };
+                ReceivedControl::Credentials(Credentials::from_raw(&raw))
+            },
+            (level, kind) => {
+                ReceivedControl::Other(level, kind, data.to_vec())
+            },
+        };
+        messages.push(message);
+
+        offset += cmsg_align(msg_len);
+    }
+
+    messages
+}
+
+/// Helper function; closes any descriptors in `messages`, for use when
+/// a received message can't be returned.
+fn close_received(messages: Vec<ReceivedControl>) {
+    for message in messages {
+        if let ReceivedControl::Rights(fds) = message {
+            for fd in fds {
+                let _ = fd.close();
+            }
+        }
+    }
+}
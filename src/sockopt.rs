@@ -0,0 +1,239 @@
+
+//! Provides typed access to socket options.
+//!
+//! Each option is exposed as a pair of methods on `Socket`: a setter
+//! named `set_<option>()` and a getter named after the option. The
+//! methods convert between Rust types and the representation expected
+//! by `setsockopt()` and `getsockopt()`, so that the size and type of
+//! each option value can't be got wrong.
+//!
+//! Consult `man 7 socket` and `man 7 tcp` for details on each option.
+
+use std::mem;
+use std::time::Duration;
+use libc::{c_int, c_void, linger, socklen_t, timeval};
+use libc::{IPPROTO_TCP, SOL_SOCKET, TCP_NODELAY};
+use libc::{SO_ERROR, SO_KEEPALIVE, SO_LINGER, SO_RCVBUF, SO_RCVTIMEO};
+use libc::{SO_REUSEADDR, SO_SNDBUF};
+use err::{Errno, EINVAL};
+use fd::SysResult;
+use select::timeout_timeval;
+use socket::Socket;
+
+impl Socket {
+
+    /// Enables or disables the `SO_REUSEADDR` option, which allows
+    /// binding to a local address that is still in use by connections
+    /// in the `TIME_WAIT` state.
+    pub fn set_reuse_addr(&self, enable: bool) -> SysResult<()> {
+        set_bool(self, SOL_SOCKET, SO_REUSEADDR, enable)
+    }
+
+    /// Whether the `SO_REUSEADDR` option is enabled.
+    pub fn reuse_addr(&self) -> SysResult<bool> {
+        get_bool(self, SOL_SOCKET, SO_REUSEADDR)
+    }
+
+    /// Sets the `SO_RCVBUF` option, the size of the socket receive
+    /// buffer in bytes.
+    ///
+    /// The kernel doubles the requested size to allow for bookkeeping
+    /// overhead, and the doubled value is what `recv_buffer_size()`
+    /// returns.
+    pub fn set_recv_buffer_size(&self, size: usize) -> SysResult<()> {
+        set_size(self, SO_RCVBUF, size)
+    }
+
+    /// The value of the `SO_RCVBUF` option.
+    pub fn recv_buffer_size(&self) -> SysResult<usize> {
+        get_opt::<c_int>(self, SOL_SOCKET, SO_RCVBUF).map(|size| {
+            size as usize
+        })
+    }
+
+    /// Sets the `SO_SNDBUF` option, the size of the socket send buffer
+    /// in bytes; doubled by the kernel like `SO_RCVBUF`.
+    pub fn set_send_buffer_size(&self, size: usize) -> SysResult<()> {
+        set_size(self, SO_SNDBUF, size)
+    }
+
+    /// The value of the `SO_SNDBUF` option.
+    pub fn send_buffer_size(&self) -> SysResult<usize> {
+        get_opt::<c_int>(self, SOL_SOCKET, SO_SNDBUF).map(|size| {
+            size as usize
+        })
+    }
+
+    /// Enables or disables the `SO_KEEPALIVE` option, which sends
+    /// keepalive probes on an idle connection-oriented socket.
+    pub fn set_keepalive(&self, enable: bool) -> SysResult<()> {
+        set_bool(self, SOL_SOCKET, SO_KEEPALIVE, enable)
+    }
+
+    /// Whether the `SO_KEEPALIVE` option is enabled.
+    pub fn keepalive(&self) -> SysResult<bool> {
+        get_bool(self, SOL_SOCKET, SO_KEEPALIVE)
+    }
+
+    /// Sets the `SO_LINGER` option.
+    ///
+    /// If `timeout` is given, `close()` and `shutdown()` block until
+    /// unsent data is transmitted or the timeout expires; a zero
+    /// timeout makes `close()` reset the connection. The kernel counts
+    /// in whole seconds, so a nonzero timeout is rounded up to the next
+    /// second, so as not to become a reset. If `None`, lingering is
+    /// disabled.
+    pub fn set_linger(&self, timeout: Option<Duration>) -> SysResult<()> {
+        let value = match timeout {
+            Some(duration) => {
+                linger { l_onoff: 1, l_linger: duration_to_secs(duration) }
+            },
+            None => linger { l_onoff: 0, l_linger: 0 },
+        };
+        set_opt(self, SOL_SOCKET, SO_LINGER, &value)
+    }
+
+    /// The value of the `SO_LINGER` option; `None` if disabled.
+    pub fn linger(&self) -> SysResult<Option<Duration>> {
+        get_opt::<linger>(self, SOL_SOCKET, SO_LINGER).map(|value| {
+            if value.l_onoff == 0 {
+                None
+            } else {
+                Some(Duration::from_secs(value.l_linger as u64))
+            }
+        })
+    }
+
+    /// Enables or disables the `TCP_NODELAY` option, which disables
+    /// the Nagle algorithm so that small segments are sent at once.
+    pub fn set_tcp_nodelay(&self, enable: bool) -> SysResult<()> {
+        set_bool(self, IPPROTO_TCP, TCP_NODELAY, enable)
+    }
+
+    /// Whether the `TCP_NODELAY` option is enabled.
+    pub fn tcp_nodelay(&self) -> SysResult<bool> {
+        get_bool(self, IPPROTO_TCP, TCP_NODELAY)
+    }
+
+    /// Sets the `SO_RCVTIMEO` option, the timeout for blocking
+    /// receive operations; they fail with `EAGAIN` when it expires.
+    /// If `None`, they block indefinitely.
+    ///
+    /// Fails with `EINVAL` if `timeout` is zero, since the kernel
+    /// would interpret that as no timeout. A nonzero timeout is
+    /// rounded up to the next microsecond for the same reason.
+    pub fn set_recv_timeout(
+        &self, timeout: Option<Duration>
+    ) -> SysResult<()> {
+        let value = match timeout {
+            Some(duration) => {
+                if duration.as_secs() == 0 && duration.subsec_nanos() == 0 {
+                    return Err(EINVAL);
+                }
+                timeout_timeval(duration)
+            },
+            None => timeval { tv_sec: 0, tv_usec: 0 },
+        };
+        set_opt(self, SOL_SOCKET, SO_RCVTIMEO, &value)
+    }
+
+    /// The value of the `SO_RCVTIMEO` option; `None` if receive
+    /// operations block indefinitely.
+    pub fn recv_timeout(&self) -> SysResult<Option<Duration>> {
+        get_opt::<timeval>(self, SOL_SOCKET, SO_RCVTIMEO).map(|value| {
+            if value.tv_sec == 0 && value.tv_usec == 0 {
+                None
+            } else {
+                let nanos = value.tv_usec as u32 * 1000;
+                Some(Duration::new(value.tv_sec as u64, nanos))
+            }
+        })
+    }
+
+    /// Retrieves and clears the pending error on the socket, using the
+    /// `SO_ERROR` option.
+    ///
+    /// This is how the outcome of a nonblocking `connect()` is
+    /// determined.
+    pub fn take_error(&self) -> SysResult<Option<Errno>> {
+        get_opt::<c_int>(self, SOL_SOCKET, SO_ERROR).map(|error| {
+            if error == 0 { None } else { Some(Errno::new(error)) }
+        })
+    }
+
+}
+
+/// Helper function; converts `duration` to whole seconds, rounding up
+/// and saturating at the largest representable value.
+fn duration_to_secs(duration: Duration) -> c_int {
+    let mut secs = duration.as_secs();
+    if duration.subsec_nanos() > 0 {
+        secs = secs.saturating_add(1);
+    }
+    if secs > c_int::max_value() as u64 {
+        c_int::max_value()
+    } else {
+        secs as c_int
+    }
+}
+
+/// Helper function; the `setsockopt()` system call, for an option
+/// whose value has type `T`.
+fn set_opt<T>(
+    socket: &Socket, level: c_int, name: c_int, value: &T
+) -> SysResult<()> {
+    let status = unsafe {
+        ::libc::setsockopt(
+            socket.as_fd().as_raw(), level, name,
+            value as *const _ as *const c_void,
+            mem::size_of::<T>() as socklen_t
+        )
+    };
+    errno_check!(status, ())
+}
+
+/// Helper function; the `getsockopt()` system call, for an option
+/// whose value has type `T`.
+///
+/// Fails with `EINVAL` if the kernel returns a value of a different
+/// size.
+fn get_opt<T: Copy>(
+    socket: &Socket, level: c_int, name: c_int
+) -> SysResult<T> {
+    let mut value: T = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<T>() as socklen_t;
+    let status = unsafe {
+        ::libc::getsockopt(
+            socket.as_fd().as_raw(), level, name,
+            &mut value as *mut _ as *mut c_void, &mut len
+        )
+    };
+    try!(errno_check!(status, ()));
+
+    if len as usize != mem::size_of::<T>() {
+        return Err(EINVAL);
+    }
+    Ok(value)
+}
+
+/// Helper function; sets a boolean option, represented as an `int`.
+fn set_bool(
+    socket: &Socket, level: c_int, name: c_int, enable: bool
+) -> SysResult<()> {
+    let value: c_int = if enable { 1 } else { 0 };
+    set_opt(socket, level, name, &value)
+}
+
+/// Helper function; gets a boolean option, represented as an `int`.
+fn get_bool(socket: &Socket, level: c_int, name: c_int) -> SysResult<bool> {
+    get_opt::<c_int>(socket, level, name).map(|value| value != 0)
+}
+
+/// Helper function; sets a buffer size option, failing with `EINVAL`
+/// if `size` doesn't fit into an `int`.
+fn set_size(socket: &Socket, name: c_int, size: usize) -> SysResult<()> {
+    if size > c_int::max_value() as usize {
+        return Err(EINVAL);
+    }
+    set_opt(socket, SOL_SOCKET, name, &(size as c_int))
+}
@@ -0,0 +1,412 @@
+
+//! Provides retrieval of file attributes held in i-nodes.
+//!
+//! `stat()`, `lstat()` and `fstat()` return the traditional set of
+//! attributes as a `FileStat`. `statx()` is the most recent of the
+//! stat family of system calls: the caller asks for the attributes it
+//! needs with a `StatxMask`, and the file system reports which ones
+//! it supplied. Unlike the older calls, it can also return the birth
+//! (creation) time of a file and some of its i-node flags.
+
+use std::ffi::CString;
+use std::mem;
+use libc::{c_char, c_long, size_t, time_t, tm};
+use dir::FileType;
+use fd::{FileDescriptor, FilePerms, SysResult};
+use fs::{self, AtFlags};
+
+/// A file timestamp, in seconds and nanoseconds since the Epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp {
+    /// Whole seconds since the Epoch; negative for earlier times.
+    pub secs: i64,
+    /// Nanoseconds past `secs`, less than 1000000000.
+    pub nanos: u32,
+}
+
+/// The attributes of a file, as returned by `stat()`, `lstat()` and
+/// `fstat()` (`struct stat`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileStat {
+    /// The major and minor numbers of the device holding the file.
+    pub dev: (u32, u32),
+    /// The i-node number.
+    pub ino: u64,
+    /// The type of the file.
+    pub file_type: FileType,
+    /// The permissions of the file, including the set-user-ID,
+    /// set-group-ID and sticky bits.
+    pub perms: FilePerms,
+    /// The number of hard links.
+    pub nlink: u64,
+    /// The user ID of the owner.
+    pub uid: u32,
+    /// The group ID of the owner.
+    pub gid: u32,
+    /// The major and minor numbers of the device, for a device file.
+    pub rdev: (u32, u32),
+    /// The size of the file in bytes; for a symbolic link, the length
+    /// of the pathname it holds.
+    pub size: u64,
+    /// The preferred block size for I/O.
+    pub blksize: u64,
+    /// The number of 512-byte blocks allocated to the file.
+    pub blocks: u64,
+    /// The last access time.
+    pub atime: Timestamp,
+    /// The last modification time.
+    pub mtime: Timestamp,
+    /// The last status change time.
+    pub ctime: Timestamp,
+}
+
+/// The attributes of a file, as returned by `statx()`.
+///
+/// Only the fields named by `mask` hold meaningful values; the others
+/// are zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Statx {
+    /// The attributes that were supplied, which may include more or
+    /// fewer than were asked for.
+    pub mask: StatxMask,
+    /// The preferred block size for I/O.
+    pub blksize: u32,
+    /// The i-node flags set on the file.
+    pub attributes: StatxAttributes,
+    /// The i-node flags that the file system supports, and so are
+    /// meaningful in `attributes`.
+    pub attributes_mask: StatxAttributes,
+    /// The number of hard links.
+    pub nlink: u32,
+    /// The user ID of the owner.
+    pub uid: u32,
+    /// The group ID of the owner.
+    pub gid: u32,
+    /// The type of the file.
+    pub file_type: FileType,
+    /// The permissions of the file, including the set-user-ID,
+    /// set-group-ID and sticky bits.
+    pub perms: FilePerms,
+    /// The i-node number.
+    pub ino: u64,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The number of 512-byte blocks allocated to the file.
+    pub blocks: u64,
+    /// The last access time.
+    pub atime: Timestamp,
+    /// The birth (creation) time.
+    pub btime: Timestamp,
+    /// The last status change time.
+    pub ctime: Timestamp,
+    /// The last modification time.
+    pub mtime: Timestamp,
+    /// The major and minor numbers of the device, for a device file.
+    pub rdev: (u32, u32),
+    /// The major and minor numbers of the device holding the file.
+    pub dev: (u32, u32),
+    /// The ID of the mount holding the file, as listed in
+    /// `/proc/self/mountinfo`.
+    pub mnt_id: u64,
+}
+
+impl Timestamp {
+
+    /// Formats the timestamp as a local time, using the
+    /// `localtime_r()` and `strftime()` library functions; pass
+    /// `"%c"`, for example, for the locale's usual representation.
+    ///
+    /// The nanoseconds are ignored, since `strftime()` has no
+    /// conversion for them.
+    ///
+    /// Consult the man page (command `man 3 strftime`) for the
+    /// conversion specifications.
+    pub fn format_local(&self, format: &str) -> String {
+        let secs = self.secs as time_t;
+        let mut fields: tm = unsafe { mem::zeroed() };
+        unsafe { ::libc::localtime_r(&secs, &mut fields) };
+
+        // Panic if `format` contains nul chars; crude but good enough
+        let c_format = CString::new(format).unwrap();
+        // `strftime()` returns zero both for an empty result and for
+        // one that doesn't fit, so leave room for some formats with
+        // long expansions before giving up
+        let mut buf = vec![0u8; 64 + 4 * format.len()];
+        let len = unsafe {
+            ::libc::strftime(
+                buf.as_mut_ptr() as *mut c_char, buf.len() as size_t,
+                c_format.as_ptr(), &fields
+            )
+        };
+        buf.truncate(len as usize);
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    /// Parses `text` as a local time in the given format, using the
+    /// `strptime()` and `mktime()` library functions; returns `None`
+    /// if `text` doesn't match the format in full.
+    ///
+    /// Fields missing from the format, such as the seconds in
+    /// `"%Y-%m-%d %H:%M"`, are taken as zero, or as the first day of
+    /// the month or year. Whether daylight saving time applies is
+    /// worked out from the date.
+    ///
+    /// Consult the man page (command `man 3 strptime`) for the
+    /// conversion specifications.
+    pub fn parse_local(text: &str, format: &str) -> Option<Timestamp> {
+        // Panic if the arguments contain nul chars; crude but good
+        // enough
+        let c_text = CString::new(text).unwrap();
+        let c_format = CString::new(format).unwrap();
+
+        let mut fields: tm = unsafe { mem::zeroed() };
+        fields.tm_mday = 1;
+        let end = unsafe {
+            ::libc::strptime(c_text.as_ptr(), c_format.as_ptr(), &mut fields)
+        };
+        // Trailing characters that the format doesn't cover are an error
+        if end.is_null() || unsafe { *end } != 0 {
+            return None;
+        }
+
+        fields.tm_isdst = -1;
+        let secs = unsafe { ::libc::mktime(&mut fields) };
+        if secs == -1 {
+            return None;
+        }
+        Some(Timestamp { secs: secs as i64, nanos: 0 })
+    }
+
+}
+
+/// The `stat()` system call.
+///
+/// Returns the attributes of the file `path`, following symbolic
+/// links.
+///
+/// Consult the man page (command `man 2 stat`) for further details.
+pub fn stat(path: &str) -> SysResult<FileStat> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = CString::new(path).unwrap();
+    let mut raw: ::libc::stat = unsafe { mem::zeroed() };
+    let status = unsafe { ::libc::stat(c_path.as_ptr(), &mut raw) };
+    errno_check!(status, to_file_stat(&raw))
+}
+
+/// The `lstat()` system call.
+///
+/// Like `stat()`, but if `path` is a symbolic link, returns the
+/// attributes of the link itself.
+///
+/// Consult the man page (command `man 2 lstat`) for further details.
+pub fn lstat(path: &str) -> SysResult<FileStat> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = CString::new(path).unwrap();
+    let mut raw: ::libc::stat = unsafe { mem::zeroed() };
+    let status = unsafe { ::libc::lstat(c_path.as_ptr(), &mut raw) };
+    errno_check!(status, to_file_stat(&raw))
+}
+
+/// The `fstat()` system call.
+///
+/// Returns the attributes of the file open on `fd`.
+///
+/// Consult the man page (command `man 2 fstat`) for further details.
+pub fn fstat(fd: &FileDescriptor) -> SysResult<FileStat> {
+    let mut raw: ::libc::stat = unsafe { mem::zeroed() };
+    let status = unsafe { ::libc::fstat(fd.as_raw(), &mut raw) };
+    errno_check!(status, to_file_stat(&raw))
+}
+
+/// The `statx()` system call.
+///
+/// Returns the attributes of the file `path`, interpreted relative to
+/// the directory `dirfd`, or the current working directory for
+/// `None`. With `AT_EMPTY_PATH` and an empty `path`, returns the
+/// attributes of the file `dirfd` itself. `mask` gives the
+/// attributes needed; the file system may supply others as well, if
+/// they come at no extra cost.
+///
+/// Consult the man page (command `man 2 statx`) for further details.
+pub fn statx(
+    dirfd: Option<&FileDescriptor>, path: &str, flags: AtFlags,
+    mask: StatxMask
+) -> SysResult<Statx> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = CString::new(path).unwrap();
+    let mut raw: RawStatx = unsafe { mem::zeroed() };
+    let status = unsafe {
+        ::libc::syscall(
+            SYS_STATX, fs::raw_dirfd(dirfd), c_path.as_ptr(), flags.bits(),
+            mask.bits(), &mut raw
+        )
+    };
+    try!(errno_check!(status, ()));
+
+    Ok(Statx {
+        mask: StatxMask::from_bits_truncate(raw.stx_mask),
+        blksize: raw.stx_blksize,
+        attributes: StatxAttributes::from_bits_truncate(raw.stx_attributes),
+        attributes_mask:
+            StatxAttributes::from_bits_truncate(raw.stx_attributes_mask),
+        nlink: raw.stx_nlink,
+        uid: raw.stx_uid,
+        gid: raw.stx_gid,
+        file_type: FileType::from_mode(raw.stx_mode as u32),
+        perms: FilePerms::from_bits_truncate(raw.stx_mode as u32),
+        ino: raw.stx_ino,
+        size: raw.stx_size,
+        blocks: raw.stx_blocks,
+        atime: raw.stx_atime.to_timestamp(),
+        btime: raw.stx_btime.to_timestamp(),
+        ctime: raw.stx_ctime.to_timestamp(),
+        mtime: raw.stx_mtime.to_timestamp(),
+        rdev: (raw.stx_rdev_major, raw.stx_rdev_minor),
+        dev: (raw.stx_dev_major, raw.stx_dev_minor),
+        mnt_id: raw.stx_mnt_id,
+    })
+}
+
+bitflags! {
+    #[doc = "The attributes asked for from, and supplied by, `statx()`."]
+    #[doc = ""]
+    #[doc = "Taken from C header files on an x86-64 Linux system; not"]
+    #[doc = "intended to be portable!"]
+    flags StatxMask: u32 {
+        #[doc = "`file_type`"]
+        const STATX_TYPE        = 0x0001,
+        #[doc = "`perms`"]
+        const STATX_MODE        = 0x0002,
+        #[doc = "`nlink`"]
+        const STATX_NLINK       = 0x0004,
+        #[doc = "`uid`"]
+        const STATX_UID         = 0x0008,
+        #[doc = "`gid`"]
+        const STATX_GID         = 0x0010,
+        #[doc = "`atime`"]
+        const STATX_ATIME       = 0x0020,
+        #[doc = "`mtime`"]
+        const STATX_MTIME       = 0x0040,
+        #[doc = "`ctime`"]
+        const STATX_CTIME       = 0x0080,
+        #[doc = "`ino`"]
+        const STATX_INO         = 0x0100,
+        #[doc = "`size`"]
+        const STATX_SIZE        = 0x0200,
+        #[doc = "`blocks`"]
+        const STATX_BLOCKS      = 0x0400,
+        #[doc = "everything that the older stat calls return"]
+        const STATX_BASIC_STATS = 0x07ff,
+        #[doc = "`btime`"]
+        const STATX_BTIME       = 0x0800,
+        #[doc = "`mnt_id` (since Linux 5.8)"]
+        const STATX_MNT_ID      = 0x1000,
+    }
+}
+
+bitflags! {
+    #[doc = "The i-node flags reported by `statx()`."]
+    #[doc = ""]
+    #[doc = "Consult `man 2 statx` for details on each flag."]
+    #[doc = ""]
+    #[doc = "Taken from C header files on an x86-64 Linux system; not"]
+    #[doc = "intended to be portable!"]
+    flags StatxAttributes: u64 {
+        #[doc = "the file is compressed by the file system"]
+        const STATX_ATTR_COMPRESSED = 0x0004,
+        #[doc = "the file cannot be modified, deleted or renamed"]
+        const STATX_ATTR_IMMUTABLE  = 0x0010,
+        #[doc = "the file can only be opened for appending"]
+        const STATX_ATTR_APPEND     = 0x0020,
+        #[doc = "the file is not backed up by `dump`"]
+        const STATX_ATTR_NODUMP     = 0x0040,
+        #[doc = "the file needs a key to be decrypted"]
+        const STATX_ATTR_ENCRYPTED  = 0x0800,
+        #[doc = "the directory is an automount trigger"]
+        const STATX_ATTR_AUTOMOUNT  = 0x1000,
+    }
+}
+
+/// Helper function; converts to the public type.
+fn to_file_stat(raw: &::libc::stat) -> FileStat {
+    FileStat {
+        dev: split_dev(raw.st_dev as u64),
+        ino: raw.st_ino as u64,
+        file_type: FileType::from_mode(raw.st_mode as u32),
+        perms: FilePerms::from_bits_truncate(raw.st_mode as u32),
+        nlink: raw.st_nlink as u64,
+        uid: raw.st_uid,
+        gid: raw.st_gid,
+        rdev: split_dev(raw.st_rdev as u64),
+        size: raw.st_size as u64,
+        blksize: raw.st_blksize as u64,
+        blocks: raw.st_blocks as u64,
+        atime: Timestamp {
+            secs: raw.st_atime as i64, nanos: raw.st_atime_nsec as u32
+        },
+        mtime: Timestamp {
+            secs: raw.st_mtime as i64, nanos: raw.st_mtime_nsec as u32
+        },
+        ctime: Timestamp {
+            secs: raw.st_ctime as i64, nanos: raw.st_ctime_nsec as u32
+        },
+    }
+}
+
+/// Helper function; splits a device number (`dev_t`) into its major
+/// and minor numbers, as the `major()` and `minor()` macros of glibc
+/// do.
+fn split_dev(dev: u64) -> (u32, u32) {
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    (major as u32, minor as u32)
+}
+
+/// Helper type; the buffer filled by `statx()` (`struct statx`).
+#[repr(C)]
+struct RawStatx {
+    stx_mask: u32,
+    stx_blksize: u32,
+    stx_attributes: u64,
+    stx_nlink: u32,
+    stx_uid: u32,
+    stx_gid: u32,
+    stx_mode: u16,
+    spare0: u16,
+    stx_ino: u64,
+    stx_size: u64,
+    stx_blocks: u64,
+    stx_attributes_mask: u64,
+    stx_atime: RawTimestamp,
+    stx_btime: RawTimestamp,
+    stx_ctime: RawTimestamp,
+    stx_mtime: RawTimestamp,
+    stx_rdev_major: u32,
+    stx_rdev_minor: u32,
+    stx_dev_major: u32,
+    stx_dev_minor: u32,
+    stx_mnt_id: u64,
+    spare3: [u64; 13],
+}
+
+/// Helper type; a timestamp in `struct statx`
+/// (`struct statx_timestamp`).
+#[repr(C)]
+struct RawTimestamp {
+    tv_sec: i64,
+    tv_nsec: u32,
+    reserved: i32,
+}
+
+impl RawTimestamp {
+
+    /// Helper method; converts to the public type.
+    fn to_timestamp(&self) -> Timestamp {
+        Timestamp { secs: self.tv_sec, nanos: self.tv_nsec }
+    }
+
+}
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const SYS_STATX: c_long = 332;
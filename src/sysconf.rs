@@ -0,0 +1,190 @@
+
+//! Provides run-time access to system limits and options.
+//!
+//! The wrappers in this module distinguish between the two reasons
+//! that the underlying libc functions can return -1: a genuine error,
+//! which sets `errno`, and a limit that is indeterminate, which
+//! leaves `errno` unchanged. The former produces `Err`; the latter
+//! produces `Ok(None)`.
+
+use std::ffi;
+use libc::{c_int, c_long};
+use err::Errno;
+use fd::{FileDescriptor, SysResult};
+
+/// Names of system limits and options that can be queried with
+/// `sysconf()`.
+///
+/// Only the most commonly needed names are provided. Consult
+/// `man 3 sysconf` for details on each one.
+///
+/// Taken from C header files on an x86-64 Linux system; not intended
+/// to be portable!
+#[derive(Clone, Copy, Debug)]
+pub enum SysconfName {
+    /// `_SC_ARG_MAX`: maximum length of the arguments to the `exec()`
+    /// functions, including the environment.
+    ArgMax = 0,
+    /// `_SC_CHILD_MAX`: maximum number of simultaneous processes per
+    /// user ID.
+    ChildMax = 1,
+    /// `_SC_CLK_TCK`: number of clock ticks per second, as used by
+    /// `times()`.
+    ClkTck = 2,
+    /// `_SC_NGROUPS_MAX`: maximum number of supplementary group IDs.
+    NgroupsMax = 3,
+    /// `_SC_OPEN_MAX`: maximum number of files that a process can
+    /// have open at any time.
+    OpenMax = 4,
+    /// `_SC_STREAM_MAX`: maximum number of simultaneously open stdio
+    /// streams.
+    StreamMax = 5,
+    /// `_SC_TZNAME_MAX`: maximum number of bytes in a timezone name.
+    TznameMax = 6,
+    /// `_SC_JOB_CONTROL`: whether job control is supported.
+    JobControl = 7,
+    /// `_SC_SAVED_IDS`: whether processes have saved set-user-IDs and
+    /// set-group-IDs.
+    SavedIds = 8,
+    /// `_SC_MQ_PRIO_MAX`: maximum priority for POSIX message queues.
+    MqPrioMax = 28,
+    /// `_SC_VERSION`: the year and month of the POSIX.1 standard that
+    /// the implementation conforms to.
+    Version = 29,
+    /// `_SC_PAGESIZE`: size of a virtual memory page, in bytes.
+    Pagesize = 30,
+    /// `_SC_RTSIG_MAX`: maximum number of distinct realtime signals.
+    RtsigMax = 31,
+    /// `_SC_SEM_NSEMS_MAX`: maximum number of POSIX semaphores a
+    /// process may have.
+    SemNsemsMax = 32,
+    /// `_SC_SIGQUEUE_MAX`: maximum number of queued realtime signals.
+    SigqueueMax = 34,
+    /// `_SC_TIMER_MAX`: maximum number of POSIX timers per process.
+    TimerMax = 35,
+    /// `_SC_LINE_MAX`: maximum length of an input line for text
+    /// utilities.
+    LineMax = 43,
+    /// `_SC_IOV_MAX`: maximum number of `iovec` structures for
+    /// `readv()` and `writev()`.
+    IovMax = 60,
+    /// `_SC_GETGR_R_SIZE_MAX`: suggested buffer size for
+    /// `getgrnam_r()` and `getgrgid_r()`.
+    GetgrRSizeMax = 69,
+    /// `_SC_GETPW_R_SIZE_MAX`: suggested buffer size for
+    /// `getpwnam_r()` and `getpwuid_r()`.
+    GetpwRSizeMax = 70,
+    /// `_SC_LOGIN_NAME_MAX`: maximum length of a login name,
+    /// including the terminating null byte.
+    LoginNameMax = 71,
+    /// `_SC_TTY_NAME_MAX`: maximum length of a terminal device name,
+    /// including the terminating null byte.
+    TtyNameMax = 72,
+    /// `_SC_NPROCESSORS_CONF`: number of processors configured.
+    NprocessorsConf = 83,
+    /// `_SC_NPROCESSORS_ONLN`: number of processors currently online.
+    NprocessorsOnln = 84,
+    /// `_SC_PHYS_PAGES`: number of pages of physical memory.
+    PhysPages = 85,
+    /// `_SC_HOST_NAME_MAX`: maximum length of a hostname, not
+    /// including the terminating null byte.
+    HostNameMax = 180,
+}
+
+/// Names of file-related limits and options that can be queried with
+/// `pathconf()` and `fpathconf()`.
+///
+/// Consult `man 3 fpathconf` for details on each one.
+///
+/// Taken from C header files on an x86-64 Linux system; not intended
+/// to be portable!
+#[derive(Clone, Copy, Debug)]
+pub enum PathconfName {
+    /// `_PC_LINK_MAX`: maximum number of links to the file.
+    LinkMax = 0,
+    /// `_PC_MAX_CANON`: maximum length of a formatted input line on a
+    /// terminal.
+    MaxCanon = 1,
+    /// `_PC_MAX_INPUT`: maximum length of an input line on a
+    /// terminal.
+    MaxInput = 2,
+    /// `_PC_NAME_MAX`: maximum length of a filename in the directory.
+    NameMax = 3,
+    /// `_PC_PATH_MAX`: maximum length of a relative pathname starting
+    /// from the directory.
+    PathMax = 4,
+    /// `_PC_PIPE_BUF`: number of bytes that can be written atomically
+    /// to a pipe or FIFO.
+    PipeBuf = 5,
+    /// `_PC_CHOWN_RESTRICTED`: whether `chown()` is restricted to
+    /// privileged processes.
+    ChownRestricted = 6,
+    /// `_PC_NO_TRUNC`: whether overlong filenames produce an error
+    /// rather than being truncated.
+    NoTrunc = 7,
+    /// `_PC_VDISABLE`: the character that disables special terminal
+    /// characters.
+    Vdisable = 8,
+    /// `_PC_SYNC_IO`: whether synchronized I/O is supported.
+    SyncIo = 9,
+    /// `_PC_FILESIZEBITS`: number of bits needed to represent the
+    /// maximum file size.
+    FileSizeBits = 13,
+    /// `_PC_SYMLINK_MAX`: maximum length of a symbolic link's
+    /// contents.
+    SymlinkMax = 19,
+}
+
+/// The `sysconf()` library function.
+///
+/// Returns the value of the given system limit or option, or `None`
+/// if it is indeterminate.
+///
+/// Consult the man page (command `man 3 sysconf`) for further
+/// details.
+pub fn sysconf(name: SysconfName) -> SysResult<Option<i64>> {
+    Errno::set_last(Errno::new(0));
+    let value = unsafe { ::libc::sysconf(name as c_int) };
+    limit_result(value)
+}
+
+/// The `pathconf()` library function.
+///
+/// Returns the value of the given limit or option for the file at
+/// `path`, or `None` if it is indeterminate.
+///
+/// Consult the man page (command `man 3 pathconf`) for further
+/// details.
+pub fn pathconf(path: &str, name: PathconfName) -> SysResult<Option<i64>> {
+    // Panic if `path` contains nul chars; crude but good enough
+    let c_path = ffi::CString::new(path).unwrap();
+    Errno::set_last(Errno::new(0));
+    let value = unsafe { ::libc::pathconf(c_path.as_ptr(), name as c_int) };
+    limit_result(value)
+}
+
+/// The `fpathconf()` library function.
+///
+/// Returns the value of the given limit or option for the open file
+/// `fd`, or `None` if it is indeterminate.
+///
+/// Consult the man page (command `man 3 fpathconf`) for further
+/// details.
+pub fn fpathconf(
+    fd: &FileDescriptor, name: PathconfName
+) -> SysResult<Option<i64>> {
+    Errno::set_last(Errno::new(0));
+    let value = unsafe { ::libc::fpathconf(fd.as_raw(), name as c_int) };
+    limit_result(value)
+}
+
+/// Helper function; interprets the return value of the `*conf()`
+/// functions, assuming `errno` was cleared before the call.
+fn limit_result(value: c_long) -> SysResult<Option<i64>> {
+    if value != -1 {
+        Ok(Some(value as i64))
+    } else {
+        let errno = Errno::last();
+        if errno.value() == 0 { Ok(None) } else { Err(errno) }
+    }
+}
@@ -0,0 +1,143 @@
+
+//! Provides information about the system and its identity.
+
+use std::ffi::CStr;
+use std::mem;
+use libc::{c_char, c_int, c_long, size_t, utsname, ENAMETOOLONG};
+use fd::SysResult;
+use sysconf::{sysconf, SysconfName};
+
+/// System identification returned by `uname()`.
+///
+/// Each field is copied out of the kernel-supplied `struct utsname`,
+/// with any bytes that are not valid UTF-8 replaced by U+FFFD.
+#[derive(Clone, Debug)]
+pub struct UtsName {
+    /// Name of the implementation of the operating system, e.g.
+    /// `"Linux"`.
+    pub sysname: String,
+    /// Name of this node on the network.
+    pub nodename: String,
+    /// Release level of the operating system, e.g. `"3.18.6-1"`.
+    pub release: String,
+    /// Version level of the operating system (often the build date).
+    pub version: String,
+    /// Hardware type identifier, e.g. `"x86_64"`.
+    pub machine: String,
+    /// NIS/YP domain name of the host; a GNU extension.
+    pub domainname: String,
+}
+
+/// The `uname()` system call.
+///
+/// Returns identifying information about the host system.
+///
+/// Consult the man page (command `man 2 uname`) for further details.
+pub fn uname() -> SysResult<UtsName> {
+    let mut buf: utsname = unsafe { mem::zeroed() };
+    let status = unsafe { ::libc::uname(&mut buf) };
+    errno_check!(status, UtsName {
+        sysname: field_to_string(&buf.sysname),
+        nodename: field_to_string(&buf.nodename),
+        release: field_to_string(&buf.release),
+        version: field_to_string(&buf.version),
+        machine: field_to_string(&buf.machine),
+        domainname: field_to_string(&buf.domainname),
+    })
+}
+
+/// The `gethostname()` system call.
+///
+/// Returns the hostname of the system. The buffer used to retrieve it
+/// starts at the size given by `_SC_HOST_NAME_MAX` and grows as needed
+/// if the name is truncated.
+///
+/// Consult the man page (command `man 2 gethostname`) for further
+/// details.
+pub fn gethostname() -> SysResult<String> {
+    let initial_len = match sysconf(SysconfName::HostNameMax) {
+        Ok(Some(max)) => max as usize + 1,
+        _ => HOST_NAME_GUESS,
+    };
+
+    let mut buf = vec![0u8; initial_len];
+    loop {
+        let buf_ptr = buf.as_mut_ptr() as *mut c_char;
+        let status = unsafe {
+            ::libc::gethostname(buf_ptr, buf.len() as size_t)
+        };
+
+        // Truncation is not always reported as an error; a missing
+        // terminator also means the buffer was too small
+        let truncated = match errno_check!(status, ()) {
+            Ok(()) => !buf.contains(&0),
+            Err(errno) if errno.value() == ENAMETOOLONG => true,
+            Err(errno) => return Err(errno),
+        };
+
+        if !truncated {
+            let name_ptr = buf.as_ptr() as *const c_char;
+            let c_str = unsafe { CStr::from_ptr(name_ptr) };
+            return Ok(c_str.to_string_lossy().into_owned());
+        }
+
+        let new_len = buf.len() * 2;
+        buf = vec![0u8; new_len];
+    }
+}
+
+/// The `sethostname()` system call.
+///
+/// Changes the hostname of the system to `name`; requires privilege.
+///
+/// Consult the man page (command `man 2 sethostname`) for further
+/// details.
+pub fn sethostname(name: &str) -> SysResult<()> {
+    let name_ptr = name.as_ptr() as *const c_char;
+    let status = unsafe {
+        ::libc::sethostname(name_ptr, name.len() as size_t)
+    };
+    errno_check!(status, ())
+}
+
+/// The `gethostid()` library function.
+///
+/// Returns the 32-bit identifier of the current host. This function
+/// cannot fail.
+///
+/// Consult the man page (command `man 3 gethostid`) for further
+/// details.
+pub fn gethostid() -> i64 {
+    unsafe { gethostid_c() as i64 }
+}
+
+/// The `sethostid()` library function.
+///
+/// Records `host_id` as the identifier of the current host, in the
+/// file `/etc/hostid`; requires privilege.
+///
+/// Consult the man page (command `man 3 sethostid`) for further
+/// details.
+pub fn sethostid(host_id: i64) -> SysResult<()> {
+    let status = unsafe { sethostid_c(host_id as c_long) };
+    errno_check!(status, ())
+}
+
+/// Starting buffer size for `gethostname()` if `sysconf()` can't
+/// provide one.
+const HOST_NAME_GUESS: usize = 256;
+
+extern {
+    #[link_name = "gethostid"]
+    fn gethostid_c() -> c_long;
+
+    #[link_name = "sethostid"]
+    fn sethostid_c(hostid: c_long) -> c_int;
+}
+
+/// Helper function; converts a nul-terminated `utsname` field into an
+/// owned string.
+fn field_to_string(field: &[c_char]) -> String {
+    let c_str = unsafe { CStr::from_ptr(field.as_ptr()) };
+    c_str.to_string_lossy().into_owned()
+}
@@ -0,0 +1,157 @@
+
+//! Provides helpers for tests of the system call wrappers.
+//!
+//! `assert_errno!` checks that a call failed in the expected way, and
+//! `TempDir` gives each test a private directory in which to create
+//! the files it needs, with exactly the permissions it asks for.
+//!
+//! Only compiled for the crate's own tests.
+
+use dir::FileType;
+use err::{EEXIST, ENOENT};
+use fd::{FileDescriptor, FilePerms, SysResult, O_CREAT, O_EXCL, O_WRONLY};
+use fd::{S_IRUSR, S_IRWXU, S_IWGRP, S_IWOTH, S_IWUSR};
+use fs::{chmod, mkdir, mkdtemp, mkfifo};
+use stat::stat;
+
+/// Asserts that an expression of type `SysResult` is an `Err` with
+/// the given `Errno`, such as `ENOENT`.
+///
+/// Panics with the actual result otherwise, which must implement
+/// `Debug`.
+macro_rules! assert_errno {
+    ($result:expr, $errno:expr) => (
+        match $result {
+            Err(errno) => assert!(
+                errno == $errno, "expected {:?}, got {:?}", $errno, errno
+            ),
+            Ok(value) => panic!("expected {:?}, got Ok({:?})", $errno, value),
+        }
+    )
+}
+
+/// A uniquely named directory under `/tmp`, removed along with its
+/// contents when dropped.
+///
+/// Errors while removing it are ignored, since a test has no way of
+/// reporting them from `drop()`. A subdirectory whose permissions
+/// deny its owner access can't be emptied, except by a privileged
+/// process.
+#[derive(Debug)]
+pub struct TempDir {
+    path: String,
+}
+
+impl TempDir {
+
+    /// Creates the directory, with a name starting with `prefix`.
+    pub fn new(prefix: &str) -> SysResult<TempDir> {
+        let path = try!(mkdtemp(&format!("/tmp/{}.XXXXXX", prefix)));
+        Ok(TempDir { path: path })
+    }
+
+    /// The pathname of the directory.
+    pub fn path(&self) -> &str { &self.path }
+
+    /// The pathname of `name` within the directory.
+    pub fn join(&self, name: &str) -> String {
+        format!("{}/{}", self.path, name)
+    }
+
+    /// Creates the empty regular file `name` within the directory, with
+    /// exactly the permissions `perms`, and returns its pathname.
+    pub fn create_file(
+        &self, name: &str, perms: FilePerms
+    ) -> SysResult<String> {
+        let path = self.join(name);
+        let fd = try!(FileDescriptor::open(
+            path.clone(), O_CREAT | O_EXCL | O_WRONLY, perms
+        ));
+        try!(fd.close());
+        set_exact_perms(path, perms)
+    }
+
+    /// Creates the subdirectory `name`, with exactly the permissions
+    /// `perms`, and returns its pathname.
+    pub fn create_dir(
+        &self, name: &str, perms: FilePerms
+    ) -> SysResult<String> {
+        let path = self.join(name);
+        try!(mkdir(&path, perms));
+        set_exact_perms(path, perms)
+    }
+
+    /// Creates the FIFO `name` within the directory, with exactly the
+    /// permissions `perms`, and returns its pathname.
+    pub fn create_fifo(
+        &self, name: &str, perms: FilePerms
+    ) -> SysResult<String> {
+        let path = self.join(name);
+        try!(mkfifo(&path, perms));
+        set_exact_perms(path, perms)
+    }
+
+}
+
+impl Drop for TempDir {
+
+    fn drop(&mut self) {
+        let _ = ::std::fs::remove_dir_all(&self.path);
+    }
+
+}
+
+/// Gives the newly created file at `path` exactly the permissions
+/// `perms`, and returns `path`.
+fn set_exact_perms(path: String, perms: FilePerms) -> SysResult<String> {
+    // The permissions given at creation were modified by the umask
+    try!(chmod(&path, perms));
+    Ok(path)
+}
+
+#[test]
+fn create_file_ignores_umask() {
+    let dir = TempDir::new("tlpi_testing").unwrap();
+    let perms = S_IRUSR | S_IWUSR | S_IWGRP | S_IWOTH;
+    let path = dir.create_file("file", perms).unwrap();
+
+    let st = stat(&path).unwrap();
+    assert_eq!(st.file_type, FileType::Regular);
+    assert_eq!(st.perms, perms);
+    assert_errno!(dir.create_file("file", perms), EEXIST);
+}
+
+#[test]
+fn create_dir_ignores_umask() {
+    let dir = TempDir::new("tlpi_testing").unwrap();
+    let perms = S_IRWXU | S_IWGRP | S_IWOTH;
+    let path = dir.create_dir("dir", perms).unwrap();
+
+    let st = stat(&path).unwrap();
+    assert_eq!(st.file_type, FileType::Directory);
+    assert_eq!(st.perms, perms);
+    assert_errno!(dir.create_dir("dir", perms), EEXIST);
+}
+
+#[test]
+fn create_fifo_ignores_umask() {
+    let dir = TempDir::new("tlpi_testing").unwrap();
+    let perms = S_IRUSR | S_IWUSR | S_IWGRP | S_IWOTH;
+    let path = dir.create_fifo("fifo", perms).unwrap();
+
+    let st = stat(&path).unwrap();
+    assert_eq!(st.file_type, FileType::Fifo);
+    assert_eq!(st.perms, perms);
+    assert_errno!(dir.create_fifo("fifo", perms), EEXIST);
+}
+
+#[test]
+fn temp_dir_removed_on_drop() {
+    let path = {
+        let dir = TempDir::new("tlpi_testing").unwrap();
+        dir.create_file("file", S_IRUSR).unwrap();
+        String::from(dir.path())
+    };
+    assert_errno!(stat(&path), ENOENT);
+}
+
@@ -0,0 +1,539 @@
+
+//! Provides POSIX threads (Pthreads).
+//!
+//! Unlike `std::thread`, these are thin wrappers over the C API, as
+//! used by the threads chapters of _The Linux Programming Interface_.
+//! The Pthreads functions return an error number instead of setting
+//! `errno`; it is reported as an `Errno` all the same.
+//!
+//! Threads are synchronized with the `Mutex` and `Cond` types, which
+//! likewise follow the C API: a mutex is locked and unlocked
+//! explicitly, and doesn't own the data it protects. Also provided
+//! are one-time initialization (`Once`) and thread-specific data
+//! (`Key`).
+
+use std::cell::{Cell, UnsafeCell};
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::time::Duration;
+use libc::{c_int, c_long, c_void, pthread_t, time_t, timespec};
+use libc::{pthread_cond_t, pthread_mutex_t, pthread_mutexattr_t};
+use libc::{pthread_key_t, pthread_once_t};
+use libc::CLOCK_REALTIME;
+use libc::PTHREAD_COND_INITIALIZER as RAW_COND_INITIALIZER;
+use libc::PTHREAD_MUTEX_INITIALIZER as RAW_MUTEX_INITIALIZER;
+use libc::PTHREAD_ONCE_INIT as RAW_ONCE_INIT;
+use err::Errno;
+use fd::SysResult;
+use signal::{pthread_sigmask, sigwait, SigSet, Signal, SigmaskHow};
+
+/// The ID of a thread (`pthread_t`).
+///
+/// Thread IDs are compared with `pthread_equal()`.
+#[derive(Clone, Copy, Debug)]
+pub struct Thread(pthread_t);
+
+impl PartialEq for Thread {
+
+    fn eq(&self, other: &Thread) -> bool {
+        unsafe { ::libc::pthread_equal(self.0, other.0) != 0 }
+    }
+
+}
+
+impl Eq for Thread {}
+
+impl Thread {
+
+    /// The C representation of this thread ID.
+    ///
+    /// Intended for the other modules of this crate.
+    pub fn as_raw(&self) -> pthread_t { self.0 }
+
+}
+
+/// The `pthread_create()` library function.
+///
+/// Starts a new thread that calls `start`; the thread terminates when
+/// `start` returns, and its result becomes the exit value of the
+/// thread, as retrieved by `pthread_join()`. Returns the ID of the
+/// new thread.
+///
+/// Consult the man page (command `man 3 pthread_create`) for further
+/// details.
+pub fn pthread_create<F>(start: F) -> SysResult<Thread>
+    where F: FnOnce() -> usize + Send + 'static
+{
+    // A boxed `FnOnce` can't be called, so take it out of an `Option`
+    // from a boxed `FnMut` instead
+    let mut start = Some(start);
+    let body: Box<FnMut() -> usize + Send> = Box::new(move || {
+        start.take().unwrap()()
+    });
+    let arg = Box::into_raw(Box::new(body)) as *mut c_void;
+
+    let mut raw: pthread_t = unsafe { mem::zeroed() };
+    let status = unsafe {
+        ::libc::pthread_create(&mut raw, ptr::null(), start_thread, arg)
+    };
+    if status != 0 {
+        // The thread didn't start, so the closure is still ours
+        let _ = unsafe {
+            Box::from_raw(arg as *mut Box<FnMut() -> usize + Send>)
+        };
+    }
+    errnum_check!(status, Thread(raw))
+}
+
+/// The `pthread_join()` library function.
+///
+/// Waits for `thread` to terminate, returning its exit value. Fails
+/// with `EINVAL` if `thread` is detached, or `EDEADLK` if it is the
+/// calling thread.
+///
+/// Consult the man page (command `man 3 pthread_join`) for further
+/// details.
+pub fn pthread_join(thread: Thread) -> SysResult<usize> {
+    let mut value: *mut c_void = ptr::null_mut();
+    let status = unsafe { ::libc::pthread_join(thread.0, &mut value) };
+    errnum_check!(status, value as usize)
+}
+
+/// The `pthread_detach()` library function.
+///
+/// Marks `thread` as detached, so that it is cleaned up automatically
+/// when it terminates, and can't be joined.
+///
+/// Consult the man page (command `man 3 pthread_detach`) for further
+/// details.
+pub fn pthread_detach(thread: Thread) -> SysResult<()> {
+    let status = unsafe { ::libc::pthread_detach(thread.0) };
+    errnum_check!(status, ())
+}
+
+/// The `pthread_self()` library function.
+///
+/// Returns the ID of the calling thread. This function cannot fail.
+///
+/// Consult the man page (command `man 3 pthread_self`) for further
+/// details.
+pub fn pthread_self() -> Thread {
+    Thread(unsafe { ::libc::pthread_self() })
+}
+
+/// The `pthread_exit()` library function.
+///
+/// Terminates the calling thread with the exit value `value`.
+///
+/// In a thread started by `pthread_create()`, the stack of the thread
+/// is unwound as for a panic, so that destructors are run; the
+/// unwinding is caught when it reaches the start of the thread. Other
+/// threads are terminated by the C function, which unwinds the stack
+/// itself. This can't be done in the main thread of a Rust program,
+/// whose runtime would catch the unwinding and abort the process;
+/// join the other threads and return from `main()` instead.
+///
+/// Consult the man page (command `man 3 pthread_exit`) for further
+/// details.
+pub fn pthread_exit(value: usize) -> ! {
+    if CREATED_THREAD.with(|created| created.get()) {
+        panic::resume_unwind(Box::new(ThreadExit(value)))
+    } else {
+        unsafe { ::libc::pthread_exit(value as *mut c_void) }
+    }
+}
+
+/// The `pthread_kill()` library function.
+///
+/// Sends `sig` to `thread`, which must belong to the calling process.
+///
+/// Consult the man page (command `man 3 pthread_kill`) for further
+/// details.
+pub fn pthread_kill(thread: Thread, sig: Signal) -> SysResult<()> {
+    let status = unsafe { ::libc::pthread_kill(thread.0, sig.value()) };
+    errnum_check!(status, ())
+}
+
+/// Starts a thread dedicated to handling the signals in `set`, using
+/// `sigwait()`.
+///
+/// The signals are first blocked in the calling thread, so this should
+/// be called in the main thread before any other threads are created:
+/// they inherit the mask, so the signals are only ever accepted by the
+/// new thread. That thread calls `handler` with each signal accepted,
+/// as an ordinary function rather than a signal handler, until
+/// `handler` returns `false`; its exit value is then zero, or the raw
+/// `Errno` value if `sigwait()` failed.
+///
+/// This is the design recommended by _The Linux Programming
+/// Interface_ for programs that combine threads and signals.
+pub fn spawn_signal_thread<F>(set: SigSet, mut handler: F) -> SysResult<Thread>
+    where F: FnMut(Signal) -> bool + Send + 'static
+{
+    try!(pthread_sigmask(SigmaskHow::Block, &set));
+    pthread_create(move || {
+        loop {
+            match sigwait(&set) {
+                Ok(sig) => if !handler(sig) { return 0 },
+                Err(errno) => return errno.value() as usize,
+            };
+        }
+    })
+}
+
+/// A Pthreads mutex (`pthread_mutex_t`).
+///
+/// A mutex must not be moved once it has been used, so a statically
+/// initialized mutex is stored in a `static`, and a dynamically
+/// initialized one is boxed. As with `FileDescriptor`, a dynamically
+/// initialized mutex is destroyed explicitly, with `destroy()`.
+pub struct Mutex(UnsafeCell<pthread_mutex_t>);
+
+unsafe impl Send for Mutex {}
+unsafe impl Sync for Mutex {}
+
+/// A statically initialized mutex of the default type, for use as the
+/// initializer of a `static`.
+pub const PTHREAD_MUTEX_INITIALIZER: Mutex =
+    Mutex(UnsafeCell::new(RAW_MUTEX_INITIALIZER));
+
+/// The type of a mutex, which determines what happens when it is
+/// misused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MutexType {
+    /// `PTHREAD_MUTEX_NORMAL`: relocking deadlocks, and unlocking
+    /// a mutex not locked by the thread is undefined.
+    Normal = 0,
+    /// `PTHREAD_MUTEX_RECURSIVE`: the owner may lock the mutex again,
+    /// and it is released once unlocked as many times.
+    Recursive = 1,
+    /// `PTHREAD_MUTEX_ERRORCHECK`: relocking fails with `EDEADLK`, and
+    /// unlocking a mutex not locked by the thread fails with `EPERM`.
+    ErrorCheck = 2,
+}
+
+impl Mutex {
+
+    /// The `pthread_mutex_init()` library function.
+    ///
+    /// Creates a dynamically initialized mutex of the given type;
+    /// `None` means the default type, which behaves as `Normal` on
+    /// Linux.
+    ///
+    /// Consult the man page (command `man 3 pthread_mutex_init`) for
+    /// further details.
+    pub fn new(kind: Option<MutexType>) -> SysResult<Box<Mutex>> {
+        let mutex = Box::new(PTHREAD_MUTEX_INITIALIZER);
+        let mut attr: pthread_mutexattr_t = unsafe { mem::zeroed() };
+        let attr_ptr = match kind {
+            Some(kind) => {
+                try!(errnum_check!(
+                    unsafe { ::libc::pthread_mutexattr_init(&mut attr) }, ()
+                ));
+                let status = unsafe {
+                    ::libc::pthread_mutexattr_settype(&mut attr, kind as c_int)
+                };
+                if status != 0 {
+                    unsafe { ::libc::pthread_mutexattr_destroy(&mut attr) };
+                    return Err(Errno::new(status));
+                }
+                &attr as *const pthread_mutexattr_t
+            },
+            None => ptr::null(),
+        };
+
+        let status = unsafe {
+            ::libc::pthread_mutex_init(mutex.0.get(), attr_ptr)
+        };
+        if kind.is_some() {
+            unsafe { ::libc::pthread_mutexattr_destroy(&mut attr) };
+        }
+        errnum_check!(status, mutex)
+    }
+
+    /// The `pthread_mutex_lock()` library function.
+    ///
+    /// Locks the mutex, blocking until it is available.
+    ///
+    /// Consult the man page (command `man 3 pthread_mutex_lock`) for
+    /// further details.
+    pub fn lock(&self) -> SysResult<()> {
+        let status = unsafe { ::libc::pthread_mutex_lock(self.0.get()) };
+        errnum_check!(status, ())
+    }
+
+    /// The `pthread_mutex_trylock()` library function.
+    ///
+    /// Locks the mutex if it is available, failing with `EBUSY`
+    /// otherwise.
+    ///
+    /// Consult the man page (command `man 3 pthread_mutex_trylock`)
+    /// for further details.
+    pub fn trylock(&self) -> SysResult<()> {
+        let status = unsafe { ::libc::pthread_mutex_trylock(self.0.get()) };
+        errnum_check!(status, ())
+    }
+
+    /// The `pthread_mutex_unlock()` library function.
+    ///
+    /// Unlocks the mutex, which must be locked by the calling thread.
+    ///
+    /// Consult the man page (command `man 3 pthread_mutex_unlock`) for
+    /// further details.
+    pub fn unlock(&self) -> SysResult<()> {
+        let status = unsafe { ::libc::pthread_mutex_unlock(self.0.get()) };
+        errnum_check!(status, ())
+    }
+
+    /// The `pthread_mutex_destroy()` library function.
+    ///
+    /// Destroys a dynamically initialized mutex, which must be
+    /// unlocked; fails with `EBUSY` otherwise. Statically initialized
+    /// mutexes need not be destroyed.
+    ///
+    /// Consult the man page (command `man 3 pthread_mutex_destroy`)
+    /// for further details.
+    pub fn destroy(self: Box<Mutex>) -> SysResult<()> {
+        let status = unsafe { ::libc::pthread_mutex_destroy(self.0.get()) };
+        errnum_check!(status, ())
+    }
+
+}
+
+/// A Pthreads condition variable (`pthread_cond_t`).
+///
+/// As with `Mutex`, a condition variable must not be moved once it
+/// has been used.
+pub struct Cond(UnsafeCell<pthread_cond_t>);
+
+unsafe impl Send for Cond {}
+unsafe impl Sync for Cond {}
+
+/// A statically initialized condition variable, for use as the
+/// initializer of a `static`.
+pub const PTHREAD_COND_INITIALIZER: Cond =
+    Cond(UnsafeCell::new(RAW_COND_INITIALIZER));
+
+impl Cond {
+
+    /// The `pthread_cond_init()` library function.
+    ///
+    /// Creates a dynamically initialized condition variable with the
+    /// default attributes.
+    ///
+    /// Consult the man page (command `man 3 pthread_cond_init`) for
+    /// further details.
+    pub fn new() -> SysResult<Box<Cond>> {
+        let cond = Box::new(PTHREAD_COND_INITIALIZER);
+        let status = unsafe {
+            ::libc::pthread_cond_init(cond.0.get(), ptr::null())
+        };
+        errnum_check!(status, cond)
+    }
+
+    /// The `pthread_cond_signal()` library function.
+    ///
+    /// Wakes at least one of the threads waiting on the condition
+    /// variable, if there are any.
+    ///
+    /// Consult the man page (command `man 3 pthread_cond_signal`) for
+    /// further details.
+    pub fn signal(&self) -> SysResult<()> {
+        let status = unsafe { ::libc::pthread_cond_signal(self.0.get()) };
+        errnum_check!(status, ())
+    }
+
+    /// The `pthread_cond_broadcast()` library function.
+    ///
+    /// Wakes all of the threads waiting on the condition variable.
+    ///
+    /// Consult the man page (command `man 3 pthread_cond_broadcast`)
+    /// for further details.
+    pub fn broadcast(&self) -> SysResult<()> {
+        let status = unsafe { ::libc::pthread_cond_broadcast(self.0.get()) };
+        errnum_check!(status, ())
+    }
+
+    /// The `pthread_cond_wait()` library function.
+    ///
+    /// Unlocks `mutex`, which must be locked by the calling thread,
+    /// and waits to be woken; `mutex` is locked again before
+    /// returning. Wakeups may be spurious, so the caller should check
+    /// the condition it is waiting for in a loop.
+    ///
+    /// Consult the man page (command `man 3 pthread_cond_wait`) for
+    /// further details.
+    pub fn wait(&self, mutex: &Mutex) -> SysResult<()> {
+        let status = unsafe {
+            ::libc::pthread_cond_wait(self.0.get(), mutex.0.get())
+        };
+        errnum_check!(status, ())
+    }
+
+    /// The `pthread_cond_timedwait()` library function.
+    ///
+    /// As `wait()`, but fails with `ETIMEDOUT` if not woken within
+    /// `timeout`; `mutex` is locked again in either case.
+    ///
+    /// Consult the man page (command `man 3 pthread_cond_timedwait`)
+    /// for further details.
+    pub fn timedwait(
+        &self, mutex: &Mutex, timeout: Duration
+    ) -> SysResult<()> {
+        // The C function takes an absolute time on the realtime clock
+        let mut abstime: timespec = unsafe { mem::zeroed() };
+        let status = unsafe {
+            ::libc::clock_gettime(CLOCK_REALTIME, &mut abstime)
+        };
+        try!(errno_check!(status, ()));
+        let nanos = abstime.tv_nsec as u64 + timeout.subsec_nanos() as u64;
+        let secs = timeout.as_secs().saturating_add(nanos / 1_000_000_000);
+
+        // Saturate at the latest representable time, rather than
+        // overflowing into a deadline in the past
+        let max_secs = (time_t::max_value() - abstime.tv_sec) as u64;
+        if secs > max_secs {
+            abstime.tv_sec = time_t::max_value();
+            abstime.tv_nsec = 999_999_999;
+        } else {
+            abstime.tv_sec += secs as time_t;
+            abstime.tv_nsec = (nanos % 1_000_000_000) as c_long;
+        }
+
+        let status = unsafe {
+            ::libc::pthread_cond_timedwait(
+                self.0.get(), mutex.0.get(), &abstime
+            )
+        };
+        errnum_check!(status, ())
+    }
+
+    /// The `pthread_cond_destroy()` library function.
+    ///
+    /// Destroys a dynamically initialized condition variable, on which
+    /// no threads may be waiting. Statically initialized condition
+    /// variables need not be destroyed.
+    ///
+    /// Consult the man page (command `man 3 pthread_cond_destroy`) for
+    /// further details.
+    pub fn destroy(self: Box<Cond>) -> SysResult<()> {
+        let status = unsafe { ::libc::pthread_cond_destroy(self.0.get()) };
+        errnum_check!(status, ())
+    }
+
+}
+
+/// A control for one-time initialization (`pthread_once_t`).
+pub struct Once(UnsafeCell<pthread_once_t>);
+
+unsafe impl Send for Once {}
+unsafe impl Sync for Once {}
+
+/// A control whose initialization has not yet been done, for use as
+/// the initializer of a `static`.
+pub const PTHREAD_ONCE_INIT: Once = Once(UnsafeCell::new(RAW_ONCE_INIT));
+
+impl Once {
+
+    /// The `pthread_once()` library function.
+    ///
+    /// Calls `init` if no thread has yet called this method on this
+    /// control; otherwise waits until that first call has returned.
+    ///
+    /// Consult the man page (command `man 3 pthread_once`) for further
+    /// details.
+    pub fn call(&self, init: extern "C" fn()) -> SysResult<()> {
+        let status = unsafe { ::libc::pthread_once(self.0.get(), init) };
+        errnum_check!(status, ())
+    }
+
+}
+
+/// A key for thread-specific data (`pthread_key_t`).
+///
+/// Each thread has its own value for the key, initially null.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key(pthread_key_t);
+
+impl Key {
+
+    /// The `pthread_key_create()` library function.
+    ///
+    /// Creates a new key. When a thread terminates with a non-null
+    /// value for the key, `destructor` (if given) is called with that
+    /// value.
+    ///
+    /// Consult the man page (command `man 3 pthread_key_create`) for
+    /// further details.
+    pub fn new(
+        destructor: Option<unsafe extern "C" fn(*mut c_void)>
+    ) -> SysResult<Key> {
+        let mut raw: pthread_key_t = 0;
+        let status = unsafe {
+            ::libc::pthread_key_create(&mut raw, destructor)
+        };
+        errnum_check!(status, Key(raw))
+    }
+
+    /// The `pthread_setspecific()` library function.
+    ///
+    /// Sets the value of the key for the calling thread.
+    ///
+    /// Consult the man page (command `man 3 pthread_setspecific`) for
+    /// further details.
+    pub fn set_specific(&self, value: *mut c_void) -> SysResult<()> {
+        let status = unsafe { ::libc::pthread_setspecific(self.0, value) };
+        errnum_check!(status, ())
+    }
+
+    /// The `pthread_getspecific()` library function.
+    ///
+    /// Returns the value of the key for the calling thread, which is
+    /// null if it hasn't been set. This function cannot fail.
+    ///
+    /// Consult the man page (command `man 3 pthread_getspecific`) for
+    /// further details.
+    pub fn get_specific(&self) -> *mut c_void {
+        unsafe { ::libc::pthread_getspecific(self.0) }
+    }
+
+    /// The `pthread_key_delete()` library function.
+    ///
+    /// Deletes the key. Destructors are not called for the values that
+    /// threads still have for it.
+    ///
+    /// Consult the man page (command `man 3 pthread_key_delete`) for
+    /// further details.
+    pub fn delete(self) -> SysResult<()> {
+        let status = unsafe { ::libc::pthread_key_delete(self.0) };
+        errnum_check!(status, ())
+    }
+
+}
+
+/// The payload of the unwinding started by `pthread_exit()`.
+struct ThreadExit(usize);
+
+thread_local! {
+    /// Whether the current thread was started by `pthread_create()`.
+    static CREATED_THREAD: Cell<bool> = Cell::new(false)
+}
+
+/// Helper function; the start function of every thread, which calls
+/// the closure passed to `pthread_create()`.
+extern "C" fn start_thread(arg: *mut c_void) -> *mut c_void {
+    let mut body = unsafe {
+        Box::from_raw(arg as *mut Box<FnMut() -> usize + Send>)
+    };
+    CREATED_THREAD.with(|created| created.set(true));
+
+    match panic::catch_unwind(AssertUnwindSafe(|| body())) {
+        Ok(value) => value as *mut c_void,
+        Err(payload) => match payload.downcast::<ThreadExit>() {
+            Ok(exit) => exit.0 as *mut c_void,
+            // A panic can't unwind into the C code that called us
+            Err(_) => unsafe { ::libc::abort() },
+        },
+    }
+}
@@ -0,0 +1,454 @@
+
+//! Provides terminal attributes (`struct termios`).
+//!
+//! The attributes of a terminal are retrieved with `tcgetattr()`,
+//! modified through the fields of `Termios`, and applied with
+//! `tcsetattr()`. The helpers `set_cbreak()` and `set_raw()` switch a
+//! terminal to the two most common noncanonical modes.
+//!
+//! Also provides the functions that identify terminals, such as
+//! `isatty()`.
+
+use std::ffi::CStr;
+use std::mem;
+use libc::{c_char, cc_t, tcflag_t, termios, winsize, NCCS};
+use libc::{TIOCGWINSZ, TIOCSWINSZ};
+use err::{Errno, ERANGE};
+use fd::{FileDescriptor, SysResult};
+
+/// The attributes of a terminal.
+///
+/// The flag fields and control characters are exposed for
+/// modification; other attributes, such as the line speed, are kept
+/// as they were retrieved.
+#[derive(Clone, Copy)]
+pub struct Termios {
+    /// Input flags (`c_iflag`).
+    pub input_flags: InputFlags,
+    /// Output flags (`c_oflag`).
+    pub output_flags: OutputFlags,
+    /// Control flags (`c_cflag`).
+    pub control_flags: ControlFlags,
+    /// Local flags (`c_lflag`).
+    pub local_flags: LocalFlags,
+    /// The terminal special characters (`c_cc`), indexed by `VINTR`,
+    /// `VMIN`, etc.
+    pub control_chars: [cc_t; NCCS],
+
+    /// The C representation, holding the remaining attributes.
+    raw: termios,
+}
+
+/// The size of a terminal window (`struct winsize`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowSize {
+    /// The number of rows of characters.
+    pub rows: u16,
+    /// The number of columns of characters.
+    pub cols: u16,
+    /// The width in pixels; unused on Linux.
+    pub x_pixels: u16,
+    /// The height in pixels; unused on Linux.
+    pub y_pixels: u16,
+}
+
+/// When `tcsetattr()` applies the new attributes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum When {
+    /// `TCSANOW`: immediately.
+    Now = 0,
+    /// `TCSADRAIN`: once all pending output has been transmitted.
+    Drain = 1,
+    /// `TCSAFLUSH`: as with `Drain`, also discarding pending input.
+    Flush = 2,
+}
+
+impl Termios {
+
+    /// Wraps the C representation of the attributes.
+    ///
+    /// Intended for the other modules of this crate.
+    pub fn from_raw(raw: termios) -> Termios {
+        Termios {
+            input_flags: InputFlags::from_bits_truncate(raw.c_iflag),
+            output_flags: OutputFlags::from_bits_truncate(raw.c_oflag),
+            control_flags: ControlFlags::from_bits_truncate(raw.c_cflag),
+            local_flags: LocalFlags::from_bits_truncate(raw.c_lflag),
+            control_chars: raw.c_cc,
+            raw: raw,
+        }
+    }
+
+    /// The C representation of the attributes, including any changes
+    /// made to the fields.
+    ///
+    /// Intended for the other modules of this crate.
+    pub fn to_raw(&self) -> termios {
+        let mut raw = self.raw;
+        // Keep any bits this module has no flags for
+        raw.c_iflag = (raw.c_iflag & !InputFlags::all().bits()) |
+            self.input_flags.bits();
+        raw.c_oflag = (raw.c_oflag & !OutputFlags::all().bits()) |
+            self.output_flags.bits();
+        raw.c_cflag = (raw.c_cflag & !ControlFlags::all().bits()) |
+            self.control_flags.bits();
+        raw.c_lflag = (raw.c_lflag & !LocalFlags::all().bits()) |
+            self.local_flags.bits();
+        raw.c_cc = self.control_chars;
+        raw
+    }
+
+    /// Modifies the attributes for cbreak mode: input is available a
+    /// character at a time without echoing, but the signal-generating
+    /// characters are still interpreted.
+    pub fn make_cbreak(&mut self) {
+        self.local_flags.remove(ICANON | ECHO);
+        self.local_flags.insert(ISIG);
+        self.input_flags.remove(ICRNL);
+        self.control_chars[VMIN] = 1;
+        self.control_chars[VTIME] = 0;
+    }
+
+    /// Modifies the attributes for raw mode: input is available a
+    /// character at a time without echoing, and no special processing
+    /// is done on input or output.
+    pub fn make_raw(&mut self) {
+        self.local_flags.remove(ICANON | ISIG | IEXTEN | ECHO);
+        self.input_flags.remove(
+            BRKINT | ICRNL | IGNBRK | IGNCR | INLCR | INPCK | ISTRIP |
+            IXON | PARMRK
+        );
+        self.output_flags.remove(OPOST);
+        self.control_chars[VMIN] = 1;
+        self.control_chars[VTIME] = 0;
+    }
+
+}
+
+/// The `tcgetattr()` library function.
+///
+/// Returns the attributes of the terminal `fd`. Fails with `ENOTTY`
+/// if `fd` is not a terminal.
+///
+/// Consult the man page (command `man 3 tcgetattr`) for further
+/// details.
+pub fn tcgetattr(fd: &FileDescriptor) -> SysResult<Termios> {
+    let mut raw: termios = unsafe { mem::zeroed() };
+    let status = unsafe { ::libc::tcgetattr(fd.as_raw(), &mut raw) };
+    errno_check!(status, Termios::from_raw(raw))
+}
+
+/// The `tcsetattr()` library function.
+///
+/// Applies `attrs` to the terminal `fd`, at the time given by `when`.
+/// Note that success is reported if any of the changes could be made,
+/// so check with `tcgetattr()` if all of them are essential.
+///
+/// Consult the man page (command `man 3 tcsetattr`) for further
+/// details.
+pub fn tcsetattr(
+    fd: &FileDescriptor, when: When, attrs: &Termios
+) -> SysResult<()> {
+    let raw = attrs.to_raw();
+    let status = unsafe {
+        ::libc::tcsetattr(fd.as_raw(), when as i32, &raw)
+    };
+    errno_check!(status, ())
+}
+
+/// Places the terminal `fd` in cbreak mode (see
+/// `Termios::make_cbreak()`), returning its previous attributes so
+/// that they can be restored later.
+///
+/// This is `ttySetCbreak()` from _The Linux Programming Interface_.
+pub fn set_cbreak(fd: &FileDescriptor) -> SysResult<Termios> {
+    let previous = try!(tcgetattr(fd));
+    let mut attrs = previous;
+    attrs.make_cbreak();
+    try!(tcsetattr(fd, When::Flush, &attrs));
+    Ok(previous)
+}
+
+/// Places the terminal `fd` in raw mode (see `Termios::make_raw()`),
+/// returning its previous attributes so that they can be restored
+/// later.
+///
+/// This is `ttySetRaw()` from _The Linux Programming Interface_.
+pub fn set_raw(fd: &FileDescriptor) -> SysResult<Termios> {
+    let previous = try!(tcgetattr(fd));
+    let mut attrs = previous;
+    attrs.make_raw();
+    try!(tcsetattr(fd, When::Flush, &attrs));
+    Ok(previous)
+}
+
+/// The `TIOCGWINSZ` operation of the `ioctl()` system call.
+///
+/// Returns the window size of the terminal `fd`.
+///
+/// Consult the man page (command `man 4 tty_ioctl`) for further
+/// details.
+pub fn get_window_size(fd: &FileDescriptor) -> SysResult<WindowSize> {
+    let mut raw: winsize = unsafe { mem::zeroed() };
+    let status = unsafe {
+        ::libc::ioctl(fd.as_raw(), TIOCGWINSZ, &mut raw)
+    };
+    errno_check!(status, WindowSize {
+        rows: raw.ws_row,
+        cols: raw.ws_col,
+        x_pixels: raw.ws_xpixel,
+        y_pixels: raw.ws_ypixel,
+    })
+}
+
+/// The `TIOCSWINSZ` operation of the `ioctl()` system call.
+///
+/// Sets the window size of the terminal `fd`; if it changes, the
+/// foreground process group of the terminal is sent `SIGWINCH`.
+///
+/// Consult the man page (command `man 4 tty_ioctl`) for further
+/// details.
+pub fn set_window_size(
+    fd: &FileDescriptor, size: &WindowSize
+) -> SysResult<()> {
+    let raw = winsize {
+        ws_row: size.rows,
+        ws_col: size.cols,
+        ws_xpixel: size.x_pixels,
+        ws_ypixel: size.y_pixels,
+    };
+    let status = unsafe { ::libc::ioctl(fd.as_raw(), TIOCSWINSZ, &raw) };
+    errno_check!(status, ())
+}
+
+/// The `isatty()` library function.
+///
+/// Whether `fd` refers to a terminal; useful for programs that
+/// behave differently when, for example, their output is redirected
+/// to a file.
+///
+/// Consult the man page (command `man 3 isatty`) for further details.
+pub fn isatty(fd: &FileDescriptor) -> bool {
+    unsafe { ::libc::isatty(fd.as_raw()) == 1 }
+}
+
+/// The `ttyname_r()` library function.
+///
+/// Returns the pathname of the terminal device that `fd` refers to.
+/// Fails with `ENOTTY` if `fd` is not a terminal.
+///
+/// Consult the man page (command `man 3 ttyname_r`) for further
+/// details.
+pub fn ttyname(fd: &FileDescriptor) -> SysResult<String> {
+    let mut buf: Vec<c_char> = vec![0; 64];
+    loop {
+        let status = unsafe {
+            ::libc::ttyname_r(fd.as_raw(), buf.as_mut_ptr(), buf.len())
+        };
+        match status {
+            0 => return Ok(c_buf_to_string(&buf)),
+            // Pathname didn't fit; try again with a larger buffer
+            _ if status == ERANGE.value() => {
+                buf = vec![0; buf.len() * 2];
+            },
+            _ => return Err(Errno::new(status)),
+        }
+    }
+}
+
+/// The `ctermid()` library function.
+///
+/// Returns the pathname of the controlling terminal of the process;
+/// on Linux, this is always `/dev/tty`. The process may not have a
+/// controlling terminal, or access to it.
+///
+/// Consult the man page (command `man 3 ctermid`) for further
+/// details.
+pub fn ctermid() -> String {
+    let mut buf: [c_char; L_CTERMID] = [0; L_CTERMID];
+    unsafe { ctermid_c(buf.as_mut_ptr()) };
+    c_buf_to_string(&buf)
+}
+
+/// Helper function; converts a nul-terminated buffer filled in by a
+/// library function into an owned string.
+fn c_buf_to_string(buf: &[c_char]) -> String {
+    let c_str = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    c_str.to_string_lossy().into_owned()
+}
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const L_CTERMID: usize = 9;
+
+extern {
+    #[link_name = "ctermid"]
+    fn ctermid_c(s: *mut c_char) -> *mut c_char;
+}
+
+bitflags! {
+    #[doc = "Terminal input flags (`c_iflag`)."]
+    #[doc = ""]
+    #[doc = "Consult `man 3 termios` for details on each flag."]
+    flags InputFlags: tcflag_t {
+        #[doc = "ignore BREAK conditions"]
+        const IGNBRK  = 0x0001,
+        #[doc = "signal interrupt on BREAK"]
+        const BRKINT  = 0x0002,
+        #[doc = "ignore characters with parity errors"]
+        const IGNPAR  = 0x0004,
+        #[doc = "mark parity errors"]
+        const PARMRK  = 0x0008,
+        #[doc = "enable input parity checking"]
+        const INPCK   = 0x0010,
+        #[doc = "strip the high bit of input characters"]
+        const ISTRIP  = 0x0020,
+        #[doc = "map NL to CR on input"]
+        const INLCR   = 0x0040,
+        #[doc = "ignore CR on input"]
+        const IGNCR   = 0x0080,
+        #[doc = "map CR to NL on input"]
+        const ICRNL   = 0x0100,
+        #[doc = "map uppercase to lowercase on input"]
+        const IUCLC   = 0x0200,
+        #[doc = "enable start/stop output flow control"]
+        const IXON    = 0x0400,
+        #[doc = "any character restarts stopped output"]
+        const IXANY   = 0x0800,
+        #[doc = "enable start/stop input flow control"]
+        const IXOFF   = 0x1000,
+        #[doc = "ring the bell when the input queue is full"]
+        const IMAXBEL = 0x2000,
+        #[doc = "input is UTF-8"]
+        const IUTF8   = 0x4000,
+    }
+}
+
+bitflags! {
+    #[doc = "Terminal output flags (`c_oflag`)."]
+    #[doc = ""]
+    #[doc = "Consult `man 3 termios` for details on each flag."]
+    flags OutputFlags: tcflag_t {
+        #[doc = "enable output processing"]
+        const OPOST  = 0x0001,
+        #[doc = "map lowercase to uppercase on output"]
+        const OLCUC  = 0x0002,
+        #[doc = "map NL to CR-NL on output"]
+        const ONLCR  = 0x0004,
+        #[doc = "map CR to NL on output"]
+        const OCRNL  = 0x0008,
+        #[doc = "don't output CR at column 0"]
+        const ONOCR  = 0x0010,
+        #[doc = "NL performs the CR function"]
+        const ONLRET = 0x0020,
+        #[doc = "use fill characters for delays"]
+        const OFILL  = 0x0040,
+        #[doc = "the fill character is DEL rather than NUL"]
+        const OFDEL  = 0x0080,
+    }
+}
+
+bitflags! {
+    #[doc = "Terminal control flags (`c_cflag`)."]
+    #[doc = ""]
+    #[doc = "The character size is the bits of `CSIZE`, one of `CS5`"]
+    #[doc = "through `CS8`. Consult `man 3 termios` for details on each"]
+    #[doc = "flag."]
+    flags ControlFlags: tcflag_t {
+        #[doc = "mask for the character size"]
+        const CSIZE   = 0x0030,
+        #[doc = "5-bit characters"]
+        const CS5     = 0x0000,
+        #[doc = "6-bit characters"]
+        const CS6     = 0x0010,
+        #[doc = "7-bit characters"]
+        const CS7     = 0x0020,
+        #[doc = "8-bit characters"]
+        const CS8     = 0x0030,
+        #[doc = "use two stop bits rather than one"]
+        const CSTOPB  = 0x0040,
+        #[doc = "allow input to be received"]
+        const CREAD   = 0x0080,
+        #[doc = "enable parity generation and checking"]
+        const PARENB  = 0x0100,
+        #[doc = "use odd rather than even parity"]
+        const PARODD  = 0x0200,
+        #[doc = "hang up when the last process closes the device"]
+        const HUPCL   = 0x0400,
+        #[doc = "ignore modem status lines"]
+        const CLOCAL  = 0x0800,
+        #[doc = "enable hardware flow control"]
+        const CRTSCTS = 0x80000000,
+    }
+}
+
+bitflags! {
+    #[doc = "Terminal local flags (`c_lflag`)."]
+    #[doc = ""]
+    #[doc = "Consult `man 3 termios` for details on each flag."]
+    flags LocalFlags: tcflag_t {
+        #[doc = "generate signals from the INTR, QUIT and SUSP characters"]
+        const ISIG    = 0x00001,
+        #[doc = "canonical mode: input is available a line at a time"]
+        const ICANON  = 0x00002,
+        #[doc = "echo input characters"]
+        const ECHO    = 0x00008,
+        #[doc = "echo ERASE visually"]
+        const ECHOE   = 0x00010,
+        #[doc = "echo KILL visually"]
+        const ECHOK   = 0x00020,
+        #[doc = "echo NL even if `ECHO` is not set"]
+        const ECHONL  = 0x00040,
+        #[doc = "don't flush the queues when generating signals"]
+        const NOFLSH  = 0x00080,
+        #[doc = "send SIGTTOU for background output"]
+        const TOSTOP  = 0x00100,
+        #[doc = "echo control characters as ^X"]
+        const ECHOCTL = 0x00200,
+        #[doc = "echo erased characters in printing terminal style"]
+        const ECHOPRT = 0x00400,
+        #[doc = "echo KILL by erasing each character of the line"]
+        const ECHOKE  = 0x00800,
+        #[doc = "output is being flushed"]
+        const FLUSHO  = 0x01000,
+        #[doc = "reprint pending input at the next read"]
+        const PENDIN  = 0x04000,
+        #[doc = "enable implementation-defined input processing"]
+        const IEXTEN  = 0x08000,
+        #[doc = "external processing of input"]
+        const EXTPROC = 0x10000,
+    }
+}
+
+/// Index of the interrupt character (normally `^C`).
+pub const VINTR: usize = 0;
+/// Index of the quit character (normally `^\`).
+pub const VQUIT: usize = 1;
+/// Index of the erase character (normally DEL).
+pub const VERASE: usize = 2;
+/// Index of the kill character (normally `^U`).
+pub const VKILL: usize = 3;
+/// Index of the end-of-file character (normally `^D`).
+pub const VEOF: usize = 4;
+/// Index of the timeout for noncanonical reads, in tenths of a second.
+pub const VTIME: usize = 5;
+/// Index of the minimum number of bytes for noncanonical reads.
+pub const VMIN: usize = 6;
+/// Index of the start character (normally `^Q`).
+pub const VSTART: usize = 8;
+/// Index of the stop character (normally `^S`).
+pub const VSTOP: usize = 9;
+/// Index of the suspend character (normally `^Z`).
+pub const VSUSP: usize = 10;
+/// Index of the additional end-of-line character.
+pub const VEOL: usize = 11;
+/// Index of the reprint character (normally `^R`).
+pub const VREPRINT: usize = 12;
+/// Index of the discard character (normally `^O`).
+pub const VDISCARD: usize = 13;
+/// Index of the word erase character (normally `^W`).
+pub const VWERASE: usize = 14;
+/// Index of the literal next character (normally `^V`).
+pub const VLNEXT: usize = 15;
+/// Index of the second additional end-of-line character.
+pub const VEOL2: usize = 16;
@@ -0,0 +1,415 @@
+
+//! Provides batched asynchronous I/O with Linux `io_uring`.
+//!
+//! An `IoUring` consists of a submission queue and a completion queue
+//! shared with the kernel. Requests are pushed onto the submission
+//! queue as `SubmissionEntry` values, handed to the kernel in batches
+//! with `IoUring::submit()`, and their results collected from the
+//! completion queue with `IoUring::completion()`.
+//!
+//! This module is only built with the `uring` feature, and requires
+//! Linux version 5.6 or later.
+
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{fence, Ordering};
+use libc::{c_int, c_long, c_void};
+use err::{Errno, EBUSY};
+use fd::{FileDescriptor, SysResult};
+use memory::{mmap, MappedRegion, MAP_POPULATE, MAP_SHARED};
+use memory::{PROT_READ, PROT_WRITE};
+
+/// An `io_uring` instance.
+///
+/// As with `FileDescriptor`, its file descriptor must be closed
+/// explicitly, with `IoUring::close()`.
+pub struct IoUring {
+    /// The file descriptor returned by `io_uring_setup()`.
+    fd: FileDescriptor,
+
+    /// Mapping of the submission queue ring.
+    sq_ring: MappedRegion,
+
+    /// Mapping of the completion queue ring.
+    cq_ring: MappedRegion,
+
+    /// Mapping of the array of submission queue entries.
+    sqes: MappedRegion,
+
+    /// Offsets of the fields of `sq_ring`.
+    sq_off: SqOffsets,
+
+    /// Offsets of the fields of `cq_ring`.
+    cq_off: CqOffsets,
+
+    /// The mask applied to ring indexes of the submission queue.
+    sq_mask: u32,
+
+    /// The number of entries in the submission queue.
+    sq_entries: u32,
+
+    /// The mask applied to ring indexes of the completion queue.
+    cq_mask: u32,
+
+    /// The number of entries pushed but not yet submitted.
+    pending: u32,
+}
+
+/// A request to be pushed onto the submission queue of an `IoUring`
+/// (`struct io_uring_sqe`).
+#[derive(Clone, Copy)]
+pub struct SubmissionEntry(RawSqe);
+
+/// The result of a request, taken from the completion queue.
+#[derive(Clone, Copy, Debug)]
+pub struct Completion {
+    /// The value given to `SubmissionEntry::user_data()`, identifying
+    /// the request.
+    pub user_data: u64,
+    /// The result of the request; for reads and writes, the number of
+    /// bytes transferred.
+    pub result: SysResult<usize>,
+    /// Flags describing the completion.
+    pub flags: u32,
+}
+
+impl SubmissionEntry {
+
+    /// `IORING_OP_NOP`: a request that does nothing, mainly useful for
+    /// measuring the overhead of the rings.
+    pub fn nop() -> SubmissionEntry {
+        SubmissionEntry::new(IORING_OP_NOP, -1)
+    }
+
+    /// `IORING_OP_READ`: like `pread()`, reads into `buf` from `offset`
+    /// in the file `fd`.
+    pub fn read(
+        fd: &FileDescriptor, buf: &mut [u8], offset: u64
+    ) -> SubmissionEntry {
+        let mut entry = SubmissionEntry::new(IORING_OP_READ, fd.as_raw());
+        entry.0.addr = buf.as_mut_ptr() as u64;
+        entry.0.len = buf.len() as u32;
+        entry.0.off = offset;
+        entry
+    }
+
+    /// `IORING_OP_WRITE`: like `pwrite()`, writes `buf` at `offset` in
+    /// the file `fd`.
+    pub fn write(
+        fd: &FileDescriptor, buf: &[u8], offset: u64
+    ) -> SubmissionEntry {
+        let mut entry = SubmissionEntry::new(IORING_OP_WRITE, fd.as_raw());
+        entry.0.addr = buf.as_ptr() as u64;
+        entry.0.len = buf.len() as u32;
+        entry.0.off = offset;
+        entry
+    }
+
+    /// `IORING_OP_FSYNC`: like `fsync()` on the file `fd`, or
+    /// `fdatasync()` if `data_only` is set.
+    pub fn fsync(fd: &FileDescriptor, data_only: bool) -> SubmissionEntry {
+        let mut entry = SubmissionEntry::new(IORING_OP_FSYNC, fd.as_raw());
+        if data_only {
+            entry.0.op_flags = IORING_FSYNC_DATASYNC;
+        }
+        entry
+    }
+
+    /// Sets the value that identifies the request in its `Completion`.
+    pub fn user_data(mut self, user_data: u64) -> SubmissionEntry {
+        self.0.user_data = user_data;
+        self
+    }
+
+    /// Helper function; creates an entry for `opcode` on `fd`.
+    fn new(opcode: u8, fd: c_int) -> SubmissionEntry {
+        let mut raw: RawSqe = unsafe { mem::zeroed() };
+        raw.opcode = opcode;
+        raw.fd = fd;
+        SubmissionEntry(raw)
+    }
+
+}
+
+impl IoUring {
+
+    /// The `io_uring_setup()` system call.
+    ///
+    /// Creates an instance whose submission queue has room for at
+    /// least `entries` requests (rounded up to a power of two), and
+    /// maps its rings into memory.
+    ///
+    /// Consult the man page (command `man 2 io_uring_setup`) for
+    /// further details.
+    pub fn new(entries: u32) -> SysResult<IoUring> {
+        let mut params: RawParams = unsafe { mem::zeroed() };
+        let raw_fd = unsafe {
+            ::libc::syscall(SYS_IO_URING_SETUP, entries, &mut params)
+        };
+        try!(errno_check!(raw_fd, ()));
+        let fd = FileDescriptor::from_raw(raw_fd as c_int);
+
+        match IoUring::map_rings(fd, &params) {
+            Ok(uring) => Ok(uring),
+            Err((fd, errno)) => {
+                // The mapping error is more informative than any close
+                // error
+                let _ = fd.close();
+                Err(errno)
+            },
+        }
+    }
+
+    /// Pushes `entry` onto the submission queue, without submitting
+    /// it to the kernel. Fails with `EBUSY` if the queue is full.
+    ///
+    /// This method is unsafe because the kernel accesses the buffer of
+    /// a read or write request asynchronously: it must remain valid,
+    /// and not otherwise be accessed, until the request's completion
+    /// has been taken from the completion queue.
+    pub unsafe fn push(&mut self, entry: &SubmissionEntry) -> SysResult<()> {
+        let head = ptr::read_volatile(self.sq_field(self.sq_off.head));
+        fence(Ordering::Acquire);
+        let tail_ptr = self.sq_field(self.sq_off.tail);
+        let tail = *tail_ptr;
+        if tail.wrapping_sub(head) == self.sq_entries {
+            return Err(EBUSY);
+        }
+
+        let index = tail & self.sq_mask;
+        let sqes = self.sqes.addr() as *mut RawSqe;
+        ptr::write(sqes.offset(index as isize), entry.0);
+        let array = self.sq_field(self.sq_off.array);
+        *array.offset(index as isize) = index;
+
+        // The entry must be visible before the new tail
+        fence(Ordering::Release);
+        ptr::write_volatile(tail_ptr, tail.wrapping_add(1));
+        self.pending += 1;
+        Ok(())
+    }
+
+    /// The `io_uring_enter()` system call.
+    ///
+    /// Submits the pushed requests to the kernel, returning the number
+    /// submitted.
+    ///
+    /// Consult the man page (command `man 2 io_uring_enter`) for
+    /// further details.
+    pub fn submit(&mut self) -> SysResult<usize> {
+        self.enter(0, 0)
+    }
+
+    /// The `io_uring_enter()` system call, with
+    /// `IORING_ENTER_GETEVENTS`.
+    ///
+    /// Submits the pushed requests to the kernel, then waits until at
+    /// least `min_complete` completions are available. Returns the
+    /// number of requests submitted.
+    ///
+    /// Consult the man page (command `man 2 io_uring_enter`) for
+    /// further details.
+    pub fn submit_and_wait(&mut self, min_complete: u32) -> SysResult<usize> {
+        self.enter(min_complete, IORING_ENTER_GETEVENTS)
+    }
+
+    /// Takes the next result from the completion queue, or returns
+    /// `None` if it is empty.
+    pub fn completion(&mut self) -> Option<Completion> {
+        unsafe {
+            let head_ptr = self.cq_field(self.cq_off.head);
+            let head = *head_ptr;
+            let tail = ptr::read_volatile(self.cq_field(self.cq_off.tail));
+            fence(Ordering::Acquire);
+            if head == tail {
+                return None;
+            }
+
+            let cqes = self.cq_field(self.cq_off.cqes) as *const RawCqe;
+            let raw = ptr::read(cqes.offset((head & self.cq_mask) as isize));
+
+            // The entry must be read before its slot is released
+            fence(Ordering::Release);
+            ptr::write_volatile(head_ptr, head.wrapping_add(1));
+
+            Some(Completion {
+                user_data: raw.user_data,
+                result: if raw.res < 0 {
+                    Err(Errno::new(-raw.res))
+                } else {
+                    Ok(raw.res as usize)
+                },
+                flags: raw.flags,
+            })
+        }
+    }
+
+    /// Unmaps the rings and closes the file descriptor of the
+    /// instance. Requests still in progress are canceled.
+    pub fn close(self) -> SysResult<()> {
+        let IoUring { fd, sq_ring, cq_ring, sqes, .. } = self;
+        let _ = sqes.munmap();
+        let _ = cq_ring.munmap();
+        let _ = sq_ring.munmap();
+        fd.close()
+    }
+
+    /// Helper function; maps the rings described by `params`,
+    /// returning `fd` along with the error on failure.
+    fn map_rings(
+        fd: FileDescriptor, params: &RawParams
+    ) -> Result<IoUring, (FileDescriptor, Errno)> {
+        let prot = PROT_READ | PROT_WRITE;
+        let flags = MAP_SHARED | MAP_POPULATE;
+        let sq_len = params.sq_off.array as usize +
+            params.sq_entries as usize * mem::size_of::<u32>();
+        let cq_len = params.cq_off.cqes as usize +
+            params.cq_entries as usize * mem::size_of::<RawCqe>();
+        let sqes_len = params.sq_entries as usize * mem::size_of::<RawSqe>();
+
+        let regions = mmap(sq_len, prot, flags, Some(&fd), IORING_OFF_SQ_RING)
+            .and_then(|sq_ring| {
+                mmap(cq_len, prot, flags, Some(&fd), IORING_OFF_CQ_RING)
+                    .map(|cq_ring| (sq_ring, cq_ring))
+            })
+            .and_then(|(sq_ring, cq_ring)| {
+                mmap(sqes_len, prot, flags, Some(&fd), IORING_OFF_SQES)
+                    .map(|sqes| (sq_ring, cq_ring, sqes))
+            });
+        let (sq_ring, cq_ring, sqes) = match regions {
+            Ok(regions) => regions,
+            Err(errno) => return Err((fd, errno)),
+        };
+
+        let mut uring = IoUring {
+            fd: fd,
+            sq_ring: sq_ring,
+            cq_ring: cq_ring,
+            sqes: sqes,
+            sq_off: params.sq_off,
+            cq_off: params.cq_off,
+            sq_mask: 0,
+            sq_entries: 0,
+            cq_mask: 0,
+            pending: 0,
+        };
+        unsafe {
+            uring.sq_mask = *uring.sq_field(params.sq_off.ring_mask);
+            uring.sq_entries = *uring.sq_field(params.sq_off.ring_entries);
+            uring.cq_mask = *uring.cq_field(params.cq_off.ring_mask);
+        }
+        Ok(uring)
+    }
+
+    /// Helper method; calls `io_uring_enter()` for the pending
+    /// requests.
+    fn enter(&mut self, min_complete: u32, flags: u32) -> SysResult<usize> {
+        let submitted = unsafe {
+            ::libc::syscall(
+                SYS_IO_URING_ENTER, self.fd.as_raw(), self.pending,
+                min_complete, flags, ptr::null::<c_void>(), 0
+            )
+        };
+        try!(errno_check!(submitted, ()));
+        self.pending -= submitted as u32;
+        Ok(submitted as usize)
+    }
+
+    /// Helper method; the field at `offset` in the submission queue
+    /// ring.
+    unsafe fn sq_field(&self, offset: u32) -> *mut u32 {
+        (self.sq_ring.addr() as *mut u8).offset(offset as isize) as *mut u32
+    }
+
+    /// Helper method; the field at `offset` in the completion queue
+    /// ring.
+    unsafe fn cq_field(&self, offset: u32) -> *mut u32 {
+        (self.cq_ring.addr() as *mut u8).offset(offset as isize) as *mut u32
+    }
+
+}
+
+/// The C `struct io_sqring_offsets`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SqOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    flags: u32,
+    dropped: u32,
+    array: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+/// The C `struct io_cqring_offsets`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CqOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    overflow: u32,
+    cqes: u32,
+    flags: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+/// The C `struct io_uring_params`.
+#[repr(C)]
+struct RawParams {
+    sq_entries: u32,
+    cq_entries: u32,
+    flags: u32,
+    sq_thread_cpu: u32,
+    sq_thread_idle: u32,
+    features: u32,
+    wq_fd: u32,
+    resv: [u32; 3],
+    sq_off: SqOffsets,
+    cq_off: CqOffsets,
+}
+
+/// The C `struct io_uring_sqe`, with its unions reduced to the
+/// members used by this module.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RawSqe {
+    opcode: u8,
+    flags: u8,
+    ioprio: u16,
+    fd: i32,
+    off: u64,
+    addr: u64,
+    len: u32,
+    op_flags: u32,
+    user_data: u64,
+    pad: [u64; 3],
+}
+
+/// The C `struct io_uring_cqe`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RawCqe {
+    user_data: u64,
+    res: i32,
+    flags: u32,
+}
+
+// Taken from C header files on an x86-64 Linux system; not intended
+// to be portable!
+const SYS_IO_URING_SETUP: c_long = 425;
+const SYS_IO_URING_ENTER: c_long = 426;
+const IORING_OFF_SQ_RING: u64 = 0;
+const IORING_OFF_CQ_RING: u64 = 0x8000000;
+const IORING_OFF_SQES: u64 = 0x10000000;
+const IORING_ENTER_GETEVENTS: u32 = 1;
+const IORING_OP_NOP: u8 = 0;
+const IORING_OP_FSYNC: u8 = 3;
+const IORING_OP_READ: u8 = 22;
+const IORING_OP_WRITE: u8 = 23;
+const IORING_FSYNC_DATASYNC: u32 = 1;
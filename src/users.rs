@@ -0,0 +1,236 @@
+
+//! Provides lookup of the user and group databases.
+//!
+//! These are usually `/etc/passwd` and `/etc/group`, but may also
+//! come from a network service such as LDAP, depending on the
+//! configuration of the C library. The wrappers use the reentrant
+//! `*_r()` forms of the library functions, so that the results are
+//! copied out before another lookup can overwrite them.
+
+use std::ffi::{CStr, CString};
+use std::mem;
+use std::ptr;
+use libc::{c_char, c_int, gid_t, group, passwd, size_t, uid_t};
+use err::{Errno, ERANGE};
+use fd::SysResult;
+use sysconf::{sysconf, SysconfName};
+
+/// A user account, as recorded in the password file (`struct passwd`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Passwd {
+    /// The login name.
+    pub name: String,
+    /// The encrypted password; usually `x`, meaning that it is kept
+    /// in the shadow password file instead.
+    pub passwd: String,
+    /// The user ID.
+    pub uid: uid_t,
+    /// The ID of the first group that the user belongs to.
+    pub gid: gid_t,
+    /// The comment field, usually holding the full name of the user.
+    pub gecos: String,
+    /// The home directory.
+    pub dir: String,
+    /// The login shell.
+    pub shell: String,
+}
+
+/// A group, as recorded in the group file (`struct group`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Group {
+    /// The name of the group.
+    pub name: String,
+    /// The encrypted group password, if any.
+    pub passwd: String,
+    /// The group ID.
+    pub gid: gid_t,
+    /// The login names of the users listed as members of the group;
+    /// this doesn't include the users whose password file entry names
+    /// the group.
+    pub members: Vec<String>,
+}
+
+/// The `getpwnam_r()` library function.
+///
+/// Returns the password file entry for the login name `name`, or
+/// `None` if there is no such user.
+///
+/// Consult the man page (command `man 3 getpwnam_r`) for further
+/// details.
+pub fn getpwnam(name: &str) -> SysResult<Option<Passwd>> {
+    // Panic if `name` contains nul chars; crude but good enough
+    let c_name = CString::new(name).unwrap();
+    let mut pwd: passwd = unsafe { mem::zeroed() };
+    let mut result = ptr::null_mut();
+    let name_ptr = c_name.as_ptr();
+    let _buf = try!(with_buffer(SysconfName::GetpwRSizeMax, |buf, len| {
+        unsafe {
+            ::libc::getpwnam_r(name_ptr, &mut pwd, buf, len, &mut result)
+        }
+    }));
+    Ok(if result.is_null() { None } else { Some(to_passwd(&pwd)) })
+}
+
+/// The `getpwuid_r()` library function.
+///
+/// Returns the password file entry for the user ID `uid`, or `None`
+/// if there is no such user.
+///
+/// Consult the man page (command `man 3 getpwuid_r`) for further
+/// details.
+pub fn getpwuid(uid: uid_t) -> SysResult<Option<Passwd>> {
+    let mut pwd: passwd = unsafe { mem::zeroed() };
+    let mut result = ptr::null_mut();
+    let _buf = try!(with_buffer(SysconfName::GetpwRSizeMax, |buf, len| {
+        unsafe { ::libc::getpwuid_r(uid, &mut pwd, buf, len, &mut result) }
+    }));
+    Ok(if result.is_null() { None } else { Some(to_passwd(&pwd)) })
+}
+
+/// The `getgrnam_r()` library function.
+///
+/// Returns the group file entry for the group `name`, or `None` if
+/// there is no such group.
+///
+/// Consult the man page (command `man 3 getgrnam_r`) for further
+/// details.
+pub fn getgrnam(name: &str) -> SysResult<Option<Group>> {
+    // Panic if `name` contains nul chars; crude but good enough
+    let c_name = CString::new(name).unwrap();
+    let mut grp: group = unsafe { mem::zeroed() };
+    let mut result = ptr::null_mut();
+    let name_ptr = c_name.as_ptr();
+    let _buf = try!(with_buffer(SysconfName::GetgrRSizeMax, |buf, len| {
+        unsafe {
+            ::libc::getgrnam_r(name_ptr, &mut grp, buf, len, &mut result)
+        }
+    }));
+    Ok(if result.is_null() { None } else { Some(to_group(&grp)) })
+}
+
+/// The `getgrgid_r()` library function.
+///
+/// Returns the group file entry for the group ID `gid`, or `None` if
+/// there is no such group.
+///
+/// Consult the man page (command `man 3 getgrgid_r`) for further
+/// details.
+pub fn getgrgid(gid: gid_t) -> SysResult<Option<Group>> {
+    let mut grp: group = unsafe { mem::zeroed() };
+    let mut result = ptr::null_mut();
+    let _buf = try!(with_buffer(SysconfName::GetgrRSizeMax, |buf, len| {
+        unsafe { ::libc::getgrgid_r(gid, &mut grp, buf, len, &mut result) }
+    }));
+    Ok(if result.is_null() { None } else { Some(to_group(&grp)) })
+}
+
+/// Returns the login name of the user `uid`, or `None` if there is no
+/// such user.
+pub fn user_name_from_id(uid: uid_t) -> SysResult<Option<String>> {
+    getpwuid(uid).map(|entry| entry.map(|pwd| pwd.name))
+}
+
+/// Returns the user ID for `name`, which may be either a login name or
+/// a numeric ID, or `None` if there is no such user.
+///
+/// A numeric `name` is accepted without checking that the password
+/// file has an entry for it, as is usual for commands such as
+/// `chown`.
+pub fn user_id_from_name(name: &str) -> SysResult<Option<uid_t>> {
+    if name.is_empty() {
+        return Ok(None);
+    }
+    match name.parse() {
+        Ok(uid) => Ok(Some(uid)),
+        Err(_) => getpwnam(name).map(|entry| entry.map(|pwd| pwd.uid)),
+    }
+}
+
+/// Returns the name of the group `gid`, or `None` if there is no such
+/// group.
+pub fn group_name_from_id(gid: gid_t) -> SysResult<Option<String>> {
+    getgrgid(gid).map(|entry| entry.map(|grp| grp.name))
+}
+
+/// Returns the group ID for `name`, which may be either a group name
+/// or a numeric ID, or `None` if there is no such group.
+///
+/// As with `user_id_from_name()`, a numeric `name` is accepted as is.
+pub fn group_id_from_name(name: &str) -> SysResult<Option<gid_t>> {
+    if name.is_empty() {
+        return Ok(None);
+    }
+    match name.parse() {
+        Ok(gid) => Ok(Some(gid)),
+        Err(_) => getgrnam(name).map(|entry| entry.map(|grp| grp.gid)),
+    }
+}
+
+/// Helper function; calls `lookup` with a buffer for the strings of
+/// the entry, growing it for as long as the lookup reports that it is
+/// too small.
+///
+/// The buffer is returned, since the entry filled in by `lookup`
+/// points into it and so is only valid for the buffer's lifetime.
+fn with_buffer<F>(
+    size_name: SysconfName, mut lookup: F
+) -> SysResult<Vec<c_char>>
+    where F: FnMut(*mut c_char, size_t) -> c_int
+{
+    // The suggested size is only a hint, and may be indeterminate
+    let mut size = match sysconf(size_name) {
+        Ok(Some(size)) if size > 0 => size as usize,
+        _ => DEFAULT_BUFFER_SIZE,
+    };
+    loop {
+        let mut buf = vec![0 as c_char; size];
+        match lookup(buf.as_mut_ptr(), size as size_t) {
+            0 => return Ok(buf),
+            status if Errno::new(status) == ERANGE => size *= 2,
+            status => return Err(Errno::new(status)),
+        }
+    }
+}
+
+/// Helper function; copies a C string, which may be null.
+fn to_string(s: *const c_char) -> String {
+    if s.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned()
+    }
+}
+
+/// Helper function; converts to the public type.
+fn to_passwd(pwd: &passwd) -> Passwd {
+    Passwd {
+        name: to_string(pwd.pw_name),
+        passwd: to_string(pwd.pw_passwd),
+        uid: pwd.pw_uid,
+        gid: pwd.pw_gid,
+        gecos: to_string(pwd.pw_gecos),
+        dir: to_string(pwd.pw_dir),
+        shell: to_string(pwd.pw_shell),
+    }
+}
+
+/// Helper function; converts to the public type.
+fn to_group(grp: &group) -> Group {
+    let mut members = Vec::new();
+    // `gr_mem` is an array of strings terminated by a null pointer
+    let mut member = grp.gr_mem;
+    while !member.is_null() && !unsafe { *member }.is_null() {
+        members.push(to_string(unsafe { *member }));
+        member = unsafe { member.offset(1) };
+    }
+
+    Group {
+        name: to_string(grp.gr_name),
+        passwd: to_string(grp.gr_passwd),
+        gid: grp.gr_gid,
+        members: members,
+    }
+}
+
+/// Buffer size to start with when `sysconf()` doesn't suggest one.
+const DEFAULT_BUFFER_SIZE: usize = 1024;
@@ -0,0 +1,272 @@
+
+//! Provides traversal of directory trees.
+//!
+//! `walk_tree()` works like the `nftw()` library function, calling a
+//! closure for each file in the tree; since `nftw()` gives its
+//! callback no way to reach the caller's state, the traversal is
+//! implemented here in terms of `Dir` and `stat()` instead.
+
+use dir::{Dir, FileType};
+use fd::SysResult;
+use stat::{lstat, stat, FileStat};
+
+/// A file visited by `walk_tree()`.
+#[derive(Clone, Copy, Debug)]
+pub struct WalkEntry<'a> {
+    /// The pathname of the file, starting with the root of the walk.
+    pub path: &'a str,
+    /// The offset in `path` of the filename, after the last slash.
+    pub base: usize,
+    /// The depth of the file in the tree; the root is at level 0.
+    pub level: usize,
+    /// What kind of file this is, and how it is being visited.
+    pub kind: WalkKind,
+    /// The attributes of the file, unless `kind` is `NoStat`.
+    ///
+    /// These come from `stat()`, unless `WALK_PHYSICAL` was passed or
+    /// `kind` is `DanglingSymlink`, in which case they come from
+    /// `lstat()`.
+    pub stat: Option<FileStat>,
+}
+
+/// The kinds of entry visited by `walk_tree()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WalkKind {
+    /// `FTW_F`: a file other than a directory or symbolic link.
+    File,
+    /// `FTW_D`: a directory, visited before its contents.
+    Directory,
+    /// `FTW_DP`: a directory, visited after its contents, when
+    /// `WALK_DEPTH` is given.
+    DirectoryPost,
+    /// `FTW_DNR`: a directory that can't be read, and so whose
+    /// contents aren't visited.
+    Unreadable,
+    /// `FTW_NS`: a file whose attributes couldn't be retrieved.
+    NoStat,
+    /// `FTW_SL`: a symbolic link, when `WALK_PHYSICAL` is given.
+    Symlink,
+    /// `FTW_SLN`: a symbolic link whose target doesn't exist, when
+    /// `WALK_PHYSICAL` isn't given.
+    DanglingSymlink,
+}
+
+/// What `walk_tree()` does after visiting an entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WalkControl {
+    /// Carry on with the walk.
+    Continue,
+    /// Don't visit the contents of this entry, a `Directory`; for
+    /// other kinds, the same as `Continue`.
+    SkipSubtree,
+    /// End the walk immediately.
+    Stop,
+}
+
+/// Helper type; the state shared by every level of a walk.
+struct Walker<'a, F: 'a> {
+    flags: WalkFlags,
+    visit: &'a mut F,
+    /// The device holding the root of the walk, for `WALK_MOUNT`.
+    root_dev: Option<(u32, u32)>,
+    /// The device and i-node numbers of the directories being
+    /// visited, for detecting cycles formed by symbolic links.
+    ancestors: Vec<((u32, u32), u64)>,
+}
+
+/// Walks the tree of files rooted at `root`, calling `visit` for each
+/// one, including `root` itself.
+///
+/// The entries of each directory are visited in the order that
+/// `readdir()` returns them, without `.` and `..`. Symbolic links are
+/// followed unless `WALK_PHYSICAL` is given, but a directory that is
+/// one of its own ancestors is not entered again.
+///
+/// Failing to read the attributes of a file or the entries of a
+/// directory is reported to `visit` as a `NoStat` or `Unreadable`
+/// entry. Only an error partway through reading a directory ends the
+/// walk with `Err`.
+///
+/// Consult the man page (command `man 3 nftw`) for further details.
+pub fn walk_tree<F>(
+    root: &str, flags: WalkFlags, mut visit: F
+) -> SysResult<()>
+    where F: FnMut(&WalkEntry) -> WalkControl
+{
+    let mut walker = Walker {
+        flags: flags,
+        visit: &mut visit,
+        root_dev: None,
+        ancestors: Vec::new(),
+    };
+    walker.walk(root, base_of(root), 0).map(|_| ())
+}
+
+impl<'a, F> Walker<'a, F> where F: FnMut(&WalkEntry) -> WalkControl {
+
+    /// Helper method; visits `path` and, if it is a directory,
+    /// everything below it. Returns `Stop` if the walk should end.
+    fn walk(
+        &mut self, path: &str, base: usize, level: usize
+    ) -> SysResult<WalkControl> {
+        let (kind, sb) = self.classify(path);
+        let sb = match sb {
+            Some(sb) if kind == WalkKind::Directory => sb,
+            _ => return Ok(self.report(path, base, level, kind, sb)),
+        };
+
+        // A directory on another file system is left out entirely
+        match self.root_dev {
+            Some(dev) if self.flags.contains(WALK_MOUNT) && dev != sb.dev => {
+                return Ok(WalkControl::Continue)
+            },
+            Some(_) => {},
+            None => self.root_dev = Some(sb.dev),
+        }
+
+        let dir = match Dir::open(path) {
+            Ok(dir) => dir,
+            Err(_) => {
+                let kind = WalkKind::Unreadable;
+                return Ok(self.report(path, base, level, kind, Some(sb)));
+            },
+        };
+
+        if !self.flags.contains(WALK_DEPTH) {
+            let kind = WalkKind::Directory;
+            match self.report(path, base, level, kind, Some(sb)) {
+                WalkControl::Continue => {},
+                WalkControl::SkipSubtree => return Ok(WalkControl::Continue),
+                WalkControl::Stop => return Ok(WalkControl::Stop),
+            }
+        }
+
+        let id = (sb.dev, sb.ino);
+        if !self.ancestors.contains(&id) {
+            self.ancestors.push(id);
+            let result = self.walk_entries(dir, path, level);
+            self.ancestors.pop();
+            if try!(result) == WalkControl::Stop {
+                return Ok(WalkControl::Stop);
+            }
+        }
+
+        if self.flags.contains(WALK_DEPTH) {
+            let kind = WalkKind::DirectoryPost;
+            Ok(self.report(path, base, level, kind, Some(sb)))
+        } else {
+            Ok(WalkControl::Continue)
+        }
+    }
+
+    /// Helper method; walks each entry of `dir`, which is open on
+    /// `path`.
+    fn walk_entries(
+        &mut self, dir: Dir, path: &str, level: usize
+    ) -> SysResult<WalkControl> {
+        // Don't double up the slash after a root such as `/`
+        let prefix = if path.ends_with('/') {
+            path.to_string()
+        } else {
+            format!("{}/", path)
+        };
+
+        for entry in dir {
+            let entry = try!(entry);
+            if entry.name == "." || entry.name == ".." {
+                continue;
+            }
+            let child = format!("{}{}", prefix, entry.name);
+            let control = try!(self.walk(&child, prefix.len(), level + 1));
+            if control == WalkControl::Stop {
+                return Ok(WalkControl::Stop);
+            }
+        }
+        Ok(WalkControl::Continue)
+    }
+
+    /// Helper method; retrieves the attributes of `path`, and
+    /// determines the kind of entry it is.
+    fn classify(&self, path: &str) -> (WalkKind, Option<FileStat>) {
+        let result = if self.flags.contains(WALK_PHYSICAL) {
+            lstat(path)
+        } else {
+            stat(path)
+        };
+
+        match result {
+            Ok(sb) => {
+                let kind = match sb.file_type {
+                    FileType::Directory => WalkKind::Directory,
+                    FileType::Symlink => WalkKind::Symlink,
+                    _ => WalkKind::File,
+                };
+                (kind, Some(sb))
+            },
+            // A link that can't be followed may be dangling
+            Err(_) => match lstat(path) {
+                Ok(sb) if sb.file_type == FileType::Symlink => {
+                    (WalkKind::DanglingSymlink, Some(sb))
+                },
+                _ => (WalkKind::NoStat, None),
+            },
+        }
+    }
+
+    /// Helper method; passes an entry to the closure.
+    fn report(
+        &mut self, path: &str, base: usize, level: usize, kind: WalkKind,
+        sb: Option<FileStat>
+    ) -> WalkControl {
+        let entry = WalkEntry {
+            path: path,
+            base: base,
+            level: level,
+            kind: kind,
+            stat: sb,
+        };
+        (self.visit)(&entry)
+    }
+
+}
+
+impl<'a> WalkEntry<'a> {
+
+    /// The filename of the entry: the last component of `path`,
+    /// without any trailing slashes the root was given with (unless
+    /// the root is `/` itself).
+    pub fn name(&self) -> &'a str {
+        let name = &self.path[self.base..];
+        match name.trim_right_matches('/') {
+            "" => name,
+            trimmed => trimmed,
+        }
+    }
+
+}
+
+/// Helper function; finds where the last component of the root
+/// pathname starts, ignoring any trailing slashes.
+fn base_of(root: &str) -> usize {
+    let trimmed = root.trim_right_matches('/');
+    match trimmed.rfind('/') {
+        Some(index) => index + 1,
+        None => 0,
+    }
+}
+
+bitflags! {
+    #[doc = "Flags for `walk_tree()`."]
+    #[doc = ""]
+    #[doc = "The values match those that `nftw()` uses for the same"]
+    #[doc = "behavior."]
+    flags WalkFlags: u32 {
+        #[doc = "`FTW_PHYS`: don't follow symbolic links"]
+        const WALK_PHYSICAL = 0x01,
+        #[doc = "`FTW_MOUNT`: stay within the file system of the root"]
+        const WALK_MOUNT    = 0x02,
+        #[doc = "`FTW_DEPTH`: visit each directory after its contents,"]
+        #[doc = "rather than before"]
+        const WALK_DEPTH    = 0x08,
+    }
+}